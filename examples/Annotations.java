@@ -0,0 +1,210 @@
+import java.lang.annotation.Annotation;
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Inherited;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+import java.lang.reflect.Field;
+import java.lang.reflect.Method;
+
+@Retention(RetentionPolicy.RUNTIME)
+@interface Range {
+  int min() default 0;
+
+  int max();
+
+  String message() default "out of range";
+}
+
+@Retention(RetentionPolicy.RUNTIME)
+@Target({ElementType.TYPE, ElementType.METHOD})
+@Inherited
+@interface Entity {
+  String value();
+
+  Class<?>[] related() default {};
+
+  Level level() default Level.NORMAL;
+}
+
+@Retention(RetentionPolicy.RUNTIME)
+@interface Test {
+  long timeout() default 1000L;
+
+  double weight() default 1.5;
+
+  char tag() default 'T';
+
+  boolean enabled() default true;
+
+  Range range() default @Range(max = 10);
+
+  int[] sizes() default {1, 2, 3};
+}
+
+@Retention(RetentionPolicy.RUNTIME)
+@interface NotNull {}
+
+// Not visible at runtime
+@interface Note {}
+
+enum Level {
+  LOW,
+  NORMAL,
+  HIGH
+}
+
+@Entity(value = "item", related = {String.class, int.class}, level = Level.HIGH)
+@Note
+class Item {
+  @Range(min = 1, max = 100)
+  public int quantity;
+
+  @Range(max = 5, message = "too heavy")
+  public int weight;
+
+  public String label;
+
+  Item(int quantity, int weight) {
+    this.quantity = quantity;
+    this.weight = weight;
+  }
+
+  @Test
+  public void check() {}
+
+  @Test(timeout = 5L, sizes = {}, range = @Range(min = -1, max = 1), enabled = false)
+  public void slow() {}
+
+  public void rename(@NotNull String name, int times, @NotNull @Range(max = 3) String other) {}
+}
+
+class SpecialItem extends Item {
+  SpecialItem() {
+    super(0, 9);
+  }
+}
+
+@Entity("plain")
+class Plain {}
+
+// Equal to the @Range of Item.quantity
+class QuantityRange implements Range {
+  public int min() {
+    return 1;
+  }
+
+  public int max() {
+    return 100;
+  }
+
+  public String message() {
+    return "out of range";
+  }
+
+  public Class<? extends Annotation> annotationType() {
+    return Range.class;
+  }
+}
+
+public class Annotations {
+  // Returns the number of fields of ``object`` whose values are not in their @Range
+  static int validate(Object object) throws Exception {
+    int errors = 0;
+    for (Class<?> c = object.getClass(); c != null; c = c.getSuperclass()) {
+      for (Field field : c.getDeclaredFields()) {
+        Range range = field.getAnnotation(Range.class);
+        if (range == null) {
+          continue;
+        }
+        int value = field.getInt(object);
+        if (value < range.min() || value > range.max()) {
+          System.out.println(field.getName() + ": " + range.message());
+          errors++;
+        }
+      }
+    }
+    return errors;
+  }
+
+  public static void main(String[] args) throws Exception {
+    Entity entity = Item.class.getAnnotation(Entity.class);
+    System.out.println(entity.value());
+    System.out.println(entity.level().name());
+    System.out.println(entity.related().length);
+    System.out.println(entity.related()[0].getName());
+    System.out.println(entity.related()[1].getName());
+    System.out.println(entity.annotationType().getName());
+    System.out.println(Item.class.isAnnotationPresent(Entity.class));
+    System.out.println(Item.class.isAnnotationPresent(Note.class));
+    System.out.println(Item.class.getAnnotations().length);
+
+    // @Entity is @Inherited
+    System.out.println(SpecialItem.class.getAnnotation(Entity.class).value());
+    System.out.println(SpecialItem.class.getDeclaredAnnotations().length);
+    System.out.println(Plain.class.getAnnotation(Entity.class).level().name());
+    System.out.println(Plain.class.getAnnotation(Entity.class).related().length);
+    System.out.println(Annotations.class.getAnnotations().length);
+
+    Range range = Item.class.getField("quantity").getAnnotation(Range.class);
+    System.out.println(range.min());
+    System.out.println(range.max());
+    System.out.println(range.message());
+    System.out.println(range.toString());
+    System.out.println(Item.class.getField("label").getAnnotations().length);
+
+    System.out.println(validate(new Item(50, 3)));
+    System.out.println(validate(new Item(0, 7)));
+    System.out.println(validate(new SpecialItem()));
+
+    Test check = Item.class.getMethod("check").getAnnotation(Test.class);
+    System.out.println(check.timeout());
+    System.out.println(check.weight());
+    System.out.println(check.tag());
+    System.out.println(check.enabled());
+    System.out.println(check.range().max());
+    System.out.println(check.sizes().length);
+    System.out.println(check.sizes()[2]);
+
+    Test slow = Item.class.getMethod("slow").getAnnotation(Test.class);
+    System.out.println(slow.timeout());
+    System.out.println(slow.enabled());
+    System.out.println(slow.range().min());
+    System.out.println(slow.sizes().length);
+    System.out.println(slow.toString());
+
+    // Annotations are equal if their elements are
+    Range same = Item.class.getField("quantity").getAnnotation(Range.class);
+    System.out.println(range.equals(same));
+    System.out.println(range.hashCode() == same.hashCode());
+    System.out.println(range.hashCode());
+    System.out.println(range.equals(Item.class.getField("weight").getAnnotation(Range.class)));
+    System.out.println(range.equals(new QuantityRange()));
+    System.out.println(range.equals(null));
+    System.out.println(range.equals(range.toString()));
+    System.out.println(check.equals(Item.class.getMethod("check").getAnnotation(Test.class)));
+    System.out.println(check.equals(slow));
+    System.out.println(check.hashCode());
+    System.out.println(slow.hashCode());
+    System.out.println(entity.equals(SpecialItem.class.getAnnotation(Entity.class)));
+    System.out.println(entity.equals(Plain.class.getAnnotation(Entity.class)));
+
+    Method rename = Item.class.getMethod("rename", String.class, int.class, String.class);
+    System.out.println(rename.isAnnotationPresent(Test.class));
+    Annotation[][] parameters = rename.getParameterAnnotations();
+    System.out.println(parameters.length);
+    for (Annotation[] annotations : parameters) {
+      System.out.println(annotations.length);
+      for (Annotation annotation : annotations) {
+        System.out.println(annotation.toString());
+      }
+    }
+
+    System.out.println(Level.valueOf("LOW").ordinal());
+    try {
+      Level.valueOf("EXTREME");
+    } catch (IllegalArgumentException e) {
+      System.out.println(e.getMessage());
+    }
+  }
+}
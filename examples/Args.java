@@ -0,0 +1,20 @@
+class Args {
+  static int count;
+  static String last;
+
+  public static void main(String args[]) {
+    count = args.length;
+    for (int i = 0; i < args.length; i++) {
+      System.out.println("args[" + i + "] = " + args[i]);
+      last = args[i];
+    }
+  }
+
+  static int argCount() {
+    return count;
+  }
+
+  static String lastArg() {
+    return last;
+  }
+}
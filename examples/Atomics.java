@@ -0,0 +1,100 @@
+import java.util.concurrent.atomic.AtomicInteger;
+import java.util.concurrent.atomic.AtomicLong;
+import java.util.concurrent.atomic.AtomicReference;
+import sun.misc.Unsafe;
+
+class StackNode {
+  int item;
+  StackNode next;
+
+  StackNode(int item) {
+    this.item = item;
+  }
+}
+
+// Treiber stack built on Unsafe.compareAndSwapObject
+class LockFreeStack {
+  static final Unsafe U = Unsafe.getUnsafe();
+  static final long TOP = U.objectFieldOffset("LockFreeStack", "top");
+
+  StackNode top;
+
+  public void push(int item) {
+    StackNode node = new StackNode(item);
+    do {
+      node.next = (StackNode) U.getObjectVolatile(this, TOP);
+    } while (!U.compareAndSwapObject(this, TOP, node.next, node));
+  }
+
+  public int sum() {
+    int sum = 0;
+    for (StackNode node = top; node != null; node = node.next) {
+      sum += node.item;
+    }
+    return sum;
+  }
+}
+
+class AtomicWorker extends Thread {
+  AtomicInteger counter;
+  LockFreeStack stack;
+
+  AtomicWorker(AtomicInteger counter, LockFreeStack stack) {
+    this.counter = counter;
+    this.stack = stack;
+  }
+
+  public void run() {
+    for (int i = 0; i < 1000; i++) {
+      counter.incrementAndGet();
+      stack.push(i);
+    }
+  }
+}
+
+class Atomics {
+  public static void main(String[] args) throws InterruptedException {
+    AtomicInteger counter = new AtomicInteger();
+    LockFreeStack stack = new LockFreeStack();
+
+    AtomicWorker workers[] = new AtomicWorker[4];
+    for (int i = 0; i < 4; i++) {
+      workers[i] = new AtomicWorker(counter, stack);
+      workers[i].start();
+    }
+    for (int i = 0; i < 4; i++) {
+      workers[i].join();
+    }
+    System.out.println("counter = " + counter.get());
+    System.out.println("stack sum = " + stack.sum());
+
+    System.out.println(counter.compareAndSet(4000, 1));
+    System.out.println(counter.compareAndSet(4000, 2));
+    System.out.println(counter.getAndSet(10));
+    System.out.println(counter.decrementAndGet());
+
+    AtomicReference<String> ref = new AtomicReference<String>("a");
+    String b = "b";
+    System.out.println(ref.compareAndSet("a", b));
+    System.out.println(ref.compareAndSet("a", "c"));
+    String cur = ref.get();
+    System.out.println(cur);
+
+    AtomicLong big = new AtomicLong(1L << 40);
+    System.out.println(big.compareAndSet(1L << 40, (1L << 40) + 5));
+    System.out.println(big.getAndAdd(1L << 33));
+    System.out.println(big.get());
+
+    AtomicInteger missing = null;
+    try {
+      missing.incrementAndGet();
+    } catch (NullPointerException e) {
+      System.out.println("caught NullPointerException");
+    }
+    try {
+      LockFreeStack.U.compareAndSwapInt(new int[1], 8, 0, 1);
+    } catch (IllegalArgumentException e) {
+      System.out.println(e.getMessage());
+    }
+  }
+}
@@ -0,0 +1,219 @@
+public class Boxing {
+  static int sum(Integer a, Integer b) {
+    return a + b;
+  }
+
+  static Object box(Object obj) {
+    return obj;
+  }
+
+  static void parseInt(String s, int radix) {
+    try {
+      System.out.println(Integer.parseInt(s, radix));
+    } catch (NumberFormatException e) {
+      System.out.println("NumberFormatException: " + e.getMessage());
+    }
+  }
+
+  static void parseLong(String s) {
+    try {
+      System.out.println(Long.parseLong(s));
+    } catch (NumberFormatException e) {
+      System.out.println("NumberFormatException: " + e.getMessage());
+    }
+  }
+
+  static void parseDouble(String s) {
+    try {
+      System.out.println(Double.parseDouble(s));
+    } catch (NumberFormatException e) {
+      System.out.println("NumberFormatException: " + e.getMessage());
+    }
+  }
+
+  static void parseFloat(String s) {
+    try {
+      System.out.println(Float.parseFloat(s));
+    } catch (NumberFormatException e) {
+      System.out.println("NumberFormatException: " + e.getMessage());
+    }
+  }
+
+  public static void main(String[] args) {
+    // Autoboxing and unboxing
+    Integer boxed = 42;
+    int unboxed = boxed;
+    System.out.println(unboxed);
+    System.out.println(sum(3, 4));
+    Long l = 10L;
+    Double d = 2.5;
+    Float f = 1.5f;
+    Short s = (short) 7;
+    Byte b = (byte) -3;
+    Character c = 'x';
+    Boolean z = true;
+    System.out.println(l + d + f + s + b);
+    System.out.println(c);
+    System.out.println(z);
+    System.out.println(box(123));
+    System.out.println(box('q'));
+
+    // valueOf returns cached instances for -128..127
+    Integer i1 = 127;
+    Integer i2 = 127;
+    Integer i3 = 128;
+    Integer i4 = 128;
+    System.out.println(i1 == i2);
+    System.out.println(i3 == i4);
+    System.out.println(i3.equals(i4));
+    System.out.println(Integer.valueOf(-128) == Integer.valueOf(-128));
+    System.out.println(Integer.valueOf(-129) == Integer.valueOf(-129));
+    System.out.println(Long.valueOf(100L) == Long.valueOf(100L));
+    System.out.println(Long.valueOf(1000L) == Long.valueOf(1000L));
+    System.out.println(Short.valueOf((short) 5) == Short.valueOf((short) 5));
+    System.out.println(Byte.valueOf((byte) -128) == Byte.valueOf((byte) -128));
+    System.out.println(Character.valueOf('a') == Character.valueOf('a'));
+    System.out.println(Character.valueOf('\u00e9') == Character.valueOf('\u00e9'));
+    System.out.println(Boolean.valueOf(true) == Boolean.TRUE);
+    System.out.println(Boolean.valueOf("TRUE") == Boolean.TRUE);
+    System.out.println(Boolean.valueOf("yes"));
+    System.out.println(Double.valueOf(1.0) == Double.valueOf(1.0));
+
+    // Limits
+    System.out.println(Integer.MIN_VALUE);
+    System.out.println(Integer.MAX_VALUE);
+    System.out.println(Long.MIN_VALUE);
+    System.out.println(Long.MAX_VALUE);
+    System.out.println(Short.MIN_VALUE);
+    System.out.println(Short.MAX_VALUE);
+    System.out.println(Byte.MIN_VALUE);
+    System.out.println(Byte.MAX_VALUE);
+    System.out.println((int) Character.MAX_VALUE);
+    System.out.println(Double.MAX_VALUE);
+    System.out.println(Double.MIN_VALUE);
+    System.out.println(Double.MIN_NORMAL);
+    System.out.println(Float.MAX_VALUE);
+    System.out.println(Float.MIN_VALUE);
+    System.out.println(Double.POSITIVE_INFINITY);
+    System.out.println(Float.NEGATIVE_INFINITY);
+    System.out.println(Double.NaN);
+
+    // Parsing
+    parseInt("12345", 10);
+    parseInt("-2147483648", 10);
+    parseInt("2147483648", 10);
+    parseInt("+7", 10);
+    parseInt("ff", 16);
+    parseInt("-Zz", 36);
+    parseInt("12a", 10);
+    parseInt("12a", 8);
+    parseInt("", 10);
+    parseInt("-", 10);
+    parseInt(null, 10);
+    parseInt("1", 37);
+    parseInt("1", 1);
+    parseLong("-9223372036854775808");
+    parseLong("9223372036854775808");
+    parseLong(" 1");
+    System.out.println(Short.parseShort("-32768"));
+    try {
+      Short.parseShort("40000");
+    } catch (NumberFormatException e) {
+      System.out.println("NumberFormatException: " + e.getMessage());
+    }
+    try {
+      Byte.parseByte("80", 16);
+    } catch (NumberFormatException e) {
+      System.out.println("NumberFormatException: " + e.getMessage());
+    }
+    System.out.println(Integer.valueOf("-99"));
+    System.out.println(Boolean.parseBoolean("True"));
+    parseDouble("3.14");
+    parseDouble("  -1e10  ");
+    parseDouble("1e400");
+    parseDouble(".5");
+    parseDouble("5.");
+    parseDouble("1.5f");
+    parseDouble("2D");
+    parseDouble("+Infinity");
+    parseDouble("-Infinity");
+    parseDouble("NaN");
+    parseDouble("4.9e-324");
+    parseDouble("2.2250738585072014E-308");
+    parseDouble("0.1e+2");
+    parseDouble("1e");
+    parseDouble("abc");
+    parseDouble(".");
+    parseDouble("1.2.3");
+    parseDouble("infinity");
+    parseDouble("");
+    parseDouble("   ");
+    parseFloat("3.4028235e38");
+    parseFloat("3.4028236e38");
+    parseFloat("1.00000017881393421514957253748434595763683319091796875001");
+    parseFloat("0.1");
+    parseFloat("x");
+    try {
+      Double.parseDouble(null);
+    } catch (NullPointerException e) {
+      System.out.println("NullPointerException");
+    }
+
+    // Formatting
+    System.out.println(Integer.toString(255, 16));
+    System.out.println(Integer.toString(-255, 2));
+    System.out.println(Integer.toString(Integer.MIN_VALUE, 36));
+    System.out.println(Integer.toString(10, 99));
+    System.out.println(Long.toString(Long.MIN_VALUE, 16));
+    System.out.println(Long.toString(123456789012345L, 36));
+    System.out.println(Integer.toString(-42));
+    System.out.println(Long.toString(42L));
+    System.out.println(Short.toString((short) -5));
+    System.out.println(Character.toString('k'));
+    System.out.println(Boolean.toString(false));
+    System.out.println(Integer.valueOf(77).toString());
+    System.out.println(Character.forDigit(11, 16));
+    System.out.println(Character.digit('Z', 36));
+    System.out.println(Character.digit('9', 8));
+
+    // Comparison and hashing
+    System.out.println(Integer.compare(1, 2));
+    System.out.println(Integer.compare(Integer.MIN_VALUE, Integer.MAX_VALUE));
+    System.out.println(Long.compare(5L, 5L));
+    System.out.println(Character.compare('b', 'a'));
+    System.out.println(Boolean.compare(true, false));
+    System.out.println(Short.compare((short) 1, (short) 3));
+    System.out.println(Byte.compare((byte) 3, (byte) 1));
+    System.out.println(Double.compare(0.0, -0.0));
+    System.out.println(Double.compare(Double.NaN, Double.POSITIVE_INFINITY));
+    System.out.println(Double.compare(1.0, 2.0));
+    System.out.println(Float.compare(Float.NaN, Float.NaN));
+    System.out.println(Integer.valueOf(5).compareTo(9));
+    System.out.println(Double.valueOf(Double.NaN).equals(Double.NaN));
+    System.out.println(Double.valueOf(0.0).equals(-0.0));
+    System.out.println(Float.valueOf(2.5f).equals(2.5f));
+    System.out.println(Integer.valueOf(5).equals(5L));
+    System.out.println(Long.valueOf(-1L).hashCode());
+    System.out.println(Long.valueOf(1L << 40).hashCode());
+    System.out.println(Double.valueOf(1.5).hashCode());
+    System.out.println(Float.valueOf(1.5f).hashCode());
+    System.out.println(Boolean.TRUE.hashCode());
+    System.out.println(Boolean.FALSE.hashCode());
+    System.out.println(Character.valueOf('A').hashCode());
+    System.out.println(Double.isNaN(0.0 / 0.0));
+    System.out.println(Double.isInfinite(-1.0 / 0.0));
+    System.out.println(Double.isFinite(Double.MAX_VALUE));
+
+    // Bits
+    System.out.println(Double.doubleToLongBits(1.0));
+    System.out.println(Double.doubleToLongBits(-0.0));
+    System.out.println(Double.doubleToLongBits(Double.longBitsToDouble(0x7ff0000000000001L)));
+    System.out.println(Double.doubleToRawLongBits(Double.longBitsToDouble(0x7ff0000000000001L)));
+    System.out.println(Double.longBitsToDouble(0x400921fb54442d18L));
+    System.out.println(Float.floatToIntBits(1.0f));
+    System.out.println(Float.floatToIntBits(Float.intBitsToFloat(0x7f800001)));
+    System.out.println(Float.floatToRawIntBits(Float.intBitsToFloat(0x7f800001)));
+    System.out.println(Float.intBitsToFloat(0x3f800000));
+    System.out.println(Float.intBitsToFloat(1));
+  }
+}
@@ -0,0 +1,137 @@
+interface Shape {
+  int area();
+}
+
+interface Polygon extends Shape {
+  int sides();
+}
+
+class Rect implements Polygon {
+  int w, h;
+
+  Rect(int w, int h) {
+    this.w = w;
+    this.h = h;
+  }
+
+  public int area() {
+    return w * h;
+  }
+
+  public int sides() {
+    return 4;
+  }
+}
+
+class Square extends Rect {
+  Square(int n) {
+    super(n, n);
+  }
+}
+
+class Circle implements Shape {
+  int r;
+
+  Circle(int r) {
+    this.r = r;
+  }
+
+  public int area() {
+    return 3 * r * r;
+  }
+}
+
+class Unused {
+  static {
+    System.out.println("Unused initialized");
+  }
+}
+
+class Casts {
+  static int totalSides(Object[] objects) {
+    int sides = 0;
+    for (int i = 0; i < objects.length; i++) {
+      if (objects[i] instanceof Polygon) {
+        sides += ((Polygon) objects[i]).sides();
+      }
+    }
+    return sides;
+  }
+
+  static int count(Object[] objects, boolean squares) {
+    int n = 0;
+    for (int i = 0; i < objects.length; i++) {
+      if (squares ? objects[i] instanceof Square : objects[i] instanceof Circle) {
+        n++;
+      }
+    }
+    return n;
+  }
+
+  public static void main(String[] args) {
+    Object[] objects = {new Rect(2, 3), new Square(4), new Circle(1), "shape", null, new int[2]};
+    System.out.println(totalSides(objects));
+    System.out.println(count(objects, true) + " " + count(objects, false));
+
+    int area = 0;
+    for (int i = 0; i < 3; i++) {
+      area += ((Shape) objects[i]).area();
+    }
+    System.out.println(area);
+
+    Object nothing = null;
+    System.out.println(nothing instanceof Object);
+    System.out.println((String) nothing == null);
+
+    Object ints = objects[5];
+    System.out.println(ints instanceof int[]);
+    System.out.println(ints instanceof Object[]);
+    System.out.println(ints instanceof Object);
+    System.out.println(ints instanceof java.io.Serializable);
+    System.out.println(objects[0] instanceof java.io.Serializable);
+    System.out.println(objects[0] instanceof Unused);
+
+    Object[][] grid = new Square[2][3];
+    System.out.println(grid instanceof Rect[][]);
+    System.out.println(grid instanceof Shape[][]);
+    System.out.println(grid instanceof Object[]);
+    System.out.println(grid[0] instanceof Circle[]);
+    int[][] matrix = new int[2][];
+    System.out.println(matrix instanceof Object[]);
+    System.out.println(grid instanceof java.io.Serializable[]);
+    System.out.println(matrix instanceof Cloneable[]);
+
+    try {
+      Circle c = (Circle) objects[1];
+      System.out.println(c.r);
+    } catch (ClassCastException e) {
+      System.out.println("ClassCastException");
+    }
+    try {
+      System.out.println(((int[]) objects[3]).length);
+    } catch (ClassCastException e) {
+      System.out.println("ClassCastException");
+    }
+
+    Shape[] shapes = new Rect[2];
+    shapes[0] = new Square(5);
+    System.out.println(shapes[0].area());
+    try {
+      shapes[1] = new Circle(2);
+    } catch (ArrayStoreException e) {
+      System.out.println("ArrayStoreException: " + e.getMessage());
+    }
+    Object[] rows = new int[3][];
+    rows[0] = new int[1];
+    try {
+      rows[1] = new long[1];
+    } catch (ArrayStoreException e) {
+      System.out.println("ArrayStoreException: " + e.getMessage());
+    }
+    try {
+      grid[1] = new Rect[1];
+    } catch (ArrayStoreException e) {
+      System.out.println("ArrayStoreException: " + e.getMessage());
+    }
+  }
+}
@@ -0,0 +1,66 @@
+class Temperature {
+  int degrees;
+
+  Temperature(int degrees) {
+    this.degrees = degrees;
+  }
+
+  public String toString() {
+    return degrees + "C";
+  }
+}
+
+class Concat {
+  // Nested classes have ``NestHost`` and ``NestMembers`` attributes since Java 11
+  static class Counter {
+    private int count;
+  }
+
+  static String describe(String name, int age, boolean student) {
+    return name + " is " + age + (student ? " and studies" : "") + ".";
+  }
+
+  public static void main(String[] args) {
+    System.out.println(describe("Alice", 30, false));
+    System.out.println(describe("Bob", 20, true));
+
+    long big = 1L << 40;
+    char c = 'x';
+    short s = -3;
+    byte b = 7;
+    float f = 2.5f;
+    double d = -0.125;
+    System.out.println("long " + big + ", char " + c + ", short " + s + ", byte " + b);
+    System.out.println("float " + f + ", double " + d + ", " + true);
+
+    String nothing = null;
+    Object temperature = new Temperature(21);
+    System.out.println("null: " + nothing + ", object: " + temperature);
+    System.out.println(temperature + "" + 'a' + 1 + 2L);
+
+    // Literals containing the tag characters of the recipe are passed as constants
+    String tags = "\u0001\u0002";
+    System.out.println(("[" + tags + "]" + c + "\u0001").length());
+
+    String line = "";
+    for (int i = 0; i < 20; i++) {
+      line = line + i % 10;
+    }
+    System.out.println(line);
+    int sum = 0;
+    for (int i = 0; i < 1000; i++) {
+      sum += ("n" + i + ":" + (i * 3L)).length();
+    }
+    System.out.println(sum);
+
+    // Surrogates stay UTF-16 code units even when they come in separate arguments
+    char high = '\uD83D';
+    char low = '\uDE00';
+    String emoji = "" + high + low;
+    System.out.println(emoji.length() + " " + (int) emoji.charAt(0) + " " + (int) emoji.charAt(1));
+
+    Counter counter = new Counter();
+    counter.count += 3;
+    System.out.println("counter " + counter.count);
+  }
+}
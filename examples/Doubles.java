@@ -0,0 +1,43 @@
+public class Doubles {
+  public static void main(String[] args) {
+    double[] doubles = {
+      0.0, -0.0, 1.0, -1.0, 0.1, 0.2, 0.1 + 0.2, 1.0 / 3.0, 2.0 / 3.0, 100.0, 1234567.0, 9999999.0,
+      1.0E7, 12345678.9, 1.0E10, 1.0E-3, 0.001234, 9.99E-4, 1.0E-5, 123.456, 3.141592653589793,
+      2.718281828459045, 1.0E23, 2.0E23, 1.0E21, 1.0E22, 4.35, 0.5, 100.5, 1.0E100, 1.7976931348623157E308,
+      4.9E-324, 2.2250738585072014E-308, 0.0 / 0.0, 1.0 / 0.0, -1.0 / 0.0, 6.02214076E23,
+      1.602176634E-19, 299792458.0, 0.3, 1.1, 2.5E-5, 8.41E21, 5.0E-324, 1.0E-322, 123456789012345.0,
+      9007199254740993.0, 0.30000000000000004
+    };
+    for (double d : doubles) {
+      System.out.println(d);
+    }
+    float[] floats = {
+      0.0f, -0.0f, 1.0f, 0.1f, 0.2f, 0.1f + 0.2f, 1.0f / 3.0f, 1.0E7f, 1.0E-3f, 3.4028235E38f,
+      1.4E-45f, 1.17549435E-38f, 16777216.0f, 3.14159265f, 123.456f, 1.0E10f, 9.999999E-4f, 0.5f,
+      0.0f / 0.0f, 1.0f / 0.0f
+    };
+    for (float f : floats) {
+      System.out.println(f);
+    }
+
+    // Everywhere doubles and floats become text
+    double third = 1.0 / 3.0;
+    float half = 0.5f;
+    String concatenated = "third=" + third + ", half=" + half + ", big=" + 1.0E10;
+    System.out.println(concatenated);
+    System.out.println(new StringBuilder().append(2.0).append(' ').append(1.5f).toString());
+    System.out.println(String.valueOf(1.0E-5) + " " + String.valueOf(100.0f));
+    System.out.println(Double.toString(12.0) + " " + Float.toString(-0.25f));
+    System.out.println(Double.valueOf(7.0).toString() + " " + Float.valueOf(7.0f).toString());
+
+    // Pseudo-random values in [0, 1000)
+    long seed = 42;
+    double sum = 0;
+    for (int i = 0; i < 2000; i++) {
+      seed = seed * 6364136223846793005L + 1442695040888963407L;
+      double d = (double) (seed >>> 11) / (double) (1L << 53) * 1000.0;
+      sum += String.valueOf(d).length();
+    }
+    System.out.println(sum);
+  }
+}
@@ -0,0 +1,51 @@
+class Embedding {
+  public static int sum(int xs[]) {
+    int sum = 0;
+    for (int i = 0; i < xs.length; i++) sum += xs[i];
+    return sum;
+  }
+
+  public static int[] range(int n) {
+    int xs[] = new int[n];
+    for (int i = 0; i < n; i++) xs[i] = i;
+    return xs;
+  }
+
+  public static String greet(String name) {
+    return "Hello, " + name;
+  }
+
+  public static String first(String names[]) {
+    if (names.length == 0) return null;
+    return names[0];
+  }
+
+  public static boolean isNull(String s) {
+    return s == null;
+  }
+
+  // Implemented in Rust
+  public static native int add(int x, int y);
+  public static native String decorate(String s);
+
+  public static int addTwice(int x, int y) {
+    return add(add(x, y), y);
+  }
+
+  // Calls addTwice from Java, where it can be compiled
+  public static int addTwiceFromJava(int x, int y) {
+    return addTwice(x, y);
+  }
+
+  public static String shout(String s) {
+    return decorate(s) + "!";
+  }
+
+  public static String tryDecorate(String s) {
+    try {
+      return decorate(s);
+    } catch (NullPointerException e) {
+      return "NullPointerException";
+    }
+  }
+}
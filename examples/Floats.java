@@ -0,0 +1,79 @@
+class Vec2 {
+  float x, y;
+
+  Vec2(float x, float y) {
+    this.x = x;
+    this.y = y;
+  }
+
+  float dot(Vec2 v) {
+    return x * v.x + y * v.y;
+  }
+}
+
+class Floats {
+  static float scale = 0.5f;
+
+  static float lerp(float a, float b, float t) {
+    return a + (b - a) * t;
+  }
+
+  // Mandelbrot set in single precision
+  static int iterations(float cr, float ci) {
+    float zr = 0, zi = 0;
+    int i = 0;
+    while (i < 50 && zr * zr + zi * zi <= 4.0f) {
+      float t = zr * zr - zi * zi + cr;
+      zi = 2 * zr * zi + ci;
+      zr = t;
+      i++;
+    }
+    return i;
+  }
+
+  public static void main(String[] args) {
+    for (int y = 0; y < 12; y++) {
+      String line = "";
+      for (int x = 0; x < 40; x++) {
+        int n = iterations(x / 16.0f - 2.0f, y / 6.0f - 1.0f);
+        if (n == 50) {
+          line += "#";
+        } else if (n > 5) {
+          line += "+";
+        } else {
+          line += ".";
+        }
+      }
+      System.out.println(line);
+    }
+
+    float third = 1.0f / 3;
+    System.out.println(third);
+    System.out.println(0.1f + 0.2f);
+    System.out.println(lerp(1.5f, 4.25f, scale));
+    System.out.println(new Vec2(1.5f, -2.25f).dot(new Vec2(0.5f, 3.5f)));
+    System.out.println(-third * 7 % 2);
+    System.out.println((double) third);
+    System.out.println((float) (third * 0.3));
+    float big = 2e10f * scale;
+    System.out.println((int) big + " " + (int) (scale - 3.25f) + " " + (long) big);
+    long l = 1L << 40;
+    System.out.println((float) l / 123456789);
+
+    float nan = 0.0f / 0;
+    System.out.println(nan < 1 || nan > 1 || nan == nan);
+    System.out.println((int) nan);
+    System.out.println(1 / (nan - nan) != 1 / (scale - scale));
+
+    float[] samples = new float[8];
+    for (int i = 0; i < samples.length; i++) {
+      samples[i] = i * 0.375f;
+    }
+    float sum = 0;
+    for (int i = 0; i < samples.length; i++) {
+      sum += samples[i];
+    }
+    System.out.println(sum);
+    System.out.println("scale = " + scale);
+  }
+}
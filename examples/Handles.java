@@ -0,0 +1,88 @@
+import java.lang.invoke.MethodHandle;
+import java.lang.invoke.MethodHandles;
+import java.lang.invoke.MethodType;
+import java.lang.invoke.WrongMethodTypeException;
+
+class Account {
+  int balance;
+
+  Account(int balance) {
+    this.balance = balance;
+  }
+
+  int deposit(int amount) {
+    balance += amount;
+    return balance;
+  }
+}
+
+class Handles {
+  static int add(int a, int b) {
+    return a + b;
+  }
+
+  static long scale(long x, double factor) {
+    return (long) (x * factor);
+  }
+
+  static String greet(String name) {
+    return "Hello, " + name;
+  }
+
+  public static void main(String[] args) throws Throwable {
+    MethodHandles.Lookup lookup = MethodHandles.lookup();
+
+    MethodType intBinary = MethodType.methodType(int.class, int.class, int.class);
+    System.out.println(intBinary.toString());
+    System.out.println(intBinary.parameterCount());
+    System.out.println(intBinary == MethodType.methodType(int.class, int.class, int.class));
+
+    MethodHandle add = lookup.findStatic(Handles.class, "add", intBinary);
+    System.out.println((int) add.invokeExact(3, 4));
+    System.out.println(add.type().toString());
+
+    // invoke adapts the arguments and the return value
+    Integer boxed = (Integer) add.invoke(10, (Object) 20);
+    System.out.println(boxed.intValue());
+    long widened = (long) add.invoke((byte) 1, (short) 2);
+    System.out.println(widened);
+
+    MethodHandle scale =
+        lookup.findStatic(
+            Handles.class, "scale", MethodType.methodType(long.class, long.class, double.class));
+    System.out.println((long) scale.invokeExact(10L, 2.5));
+    System.out.println((long) scale.invoke(4, 0.5f));
+
+    MethodHandle greet =
+        lookup.findStatic(
+            Handles.class, "greet", MethodType.methodType(String.class, String.class));
+    System.out.println((String) greet.invokeExact("handles"));
+
+    MethodHandle deposit =
+        lookup.findVirtual(
+            Account.class, "deposit", MethodType.methodType(int.class, int.class));
+    Account account = new Account(100);
+    System.out.println((int) deposit.invokeExact(account, 50));
+    System.out.println(deposit.type().toString());
+
+    MethodHandle balance = lookup.findGetter(Account.class, "balance", int.class);
+    System.out.println((int) balance.invokeExact(account));
+
+    try {
+      int wrong = (int) add.invokeExact(1L, 2L);
+      System.out.println(wrong);
+    } catch (WrongMethodTypeException e) {
+      System.out.println("wrong method type");
+    }
+    try {
+      lookup.findStatic(Handles.class, "subtract", intBinary);
+    } catch (NoSuchMethodException e) {
+      System.out.println("no such method");
+    }
+    try {
+      lookup.findVirtual(Handles.class, "add", intBinary);
+    } catch (IllegalAccessException e) {
+      System.out.println("not virtual");
+    }
+  }
+}
@@ -0,0 +1,60 @@
+class JniExample {
+  static {
+    // Built from examples/jni/jniexample.c
+    System.loadLibrary("jniexample");
+  }
+
+  static String greeting = "Hello";
+  private int count;
+
+  // Implemented in C
+  static native int add(int x, int y);
+  static native double hypot(double x, double y);
+  static native String greet(String name);
+  static native int sum(int xs[]);
+  static native int[] squares(int n);
+  static native int applyTwice(int x);
+  static native void fail(String message);
+  static native int triple(int x);
+  static native double mix(int a, long b, float c, double d, byte e, short f, char g, boolean h,
+                           int i, long j, float k, double l, int m, long n, float o, double p);
+  static native int missing();
+  native void increment();
+
+  static int twice(int x) {
+    return x * 2;
+  }
+
+  static int count(int n) {
+    JniExample example = new JniExample();
+    for (int i = 0; i < n; i++) example.increment();
+    return example.count;
+  }
+
+  static double mixAll() {
+    return mix(1, 2L, 3.0f, 4.0, (byte) 5, (short) 6, (char) 7, true,
+               9, 10L, 11.0f, 12.0, 13, 14L, 15.0f, 16.0);
+  }
+
+  static int sumOfSquares(int n) {
+    return sum(squares(n));
+  }
+
+  static String catchFailure(String message) {
+    try {
+      fail(message);
+      return "not thrown";
+    } catch (IllegalArgumentException e) {
+      return e.getMessage();
+    }
+  }
+
+  static boolean isMissing() {
+    try {
+      missing();
+      return false;
+    } catch (UnsatisfiedLinkError e) {
+      return true;
+    }
+  }
+}
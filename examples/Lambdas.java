@@ -0,0 +1,105 @@
+interface IntOp {
+  int apply(int x);
+}
+
+interface LongOp {
+  long apply(long a, long b);
+}
+
+interface Fn<A, B> {
+  B apply(A a);
+}
+
+interface Measure {
+  int measure(String s);
+}
+
+interface Factory {
+  Tally make(int start);
+}
+
+interface Sized {
+  int area();
+}
+
+class Tally implements Sized {
+  int count;
+
+  Tally(int start) {
+    count = start;
+  }
+
+  int add(int n) {
+    count += n;
+    return count;
+  }
+
+  public int area() {
+    return count * count;
+  }
+}
+
+class Lambdas {
+  int base = 100;
+
+  static int square(int x) {
+    return x * x;
+  }
+
+  static int applyTwice(IntOp op, int x) {
+    return op.apply(op.apply(x));
+  }
+
+  IntOp offset() {
+    return x -> x + base;
+  }
+
+  public static void main(String[] args) {
+    Runnable hello = () -> System.out.println("hello from a lambda");
+    hello.run();
+
+    int n = 7;
+    IntOp addN = x -> x + n;
+    System.out.println(applyTwice(addN, 1));
+    System.out.println(applyTwice(Lambdas::square, 3));
+
+    IntOp[] adders = new IntOp[5];
+    for (int i = 0; i < adders.length; i++) {
+      int k = i * 10;
+      adders[i] = x -> x + k;
+    }
+    int sum = 0;
+    for (int i = 0; i < adders.length; i++) {
+      sum += adders[i].apply(i);
+    }
+    System.out.println(sum);
+
+    System.out.println(new Lambdas().offset().apply(5));
+
+    long big = 10000000000L;
+    double scale = 0.5;
+    LongOp mix = (a, b) -> a * b + big + (long) (scale * 4);
+    System.out.println(mix.apply(3L, 4L));
+
+    Fn<String, String> twice = s -> s + s;
+    System.out.println(twice.apply("ab"));
+
+    Measure length = String::length;
+    System.out.println(length.measure("ferrugo"));
+
+    Tally tally = new Tally(1);
+    IntOp bound = tally::add;
+    bound.apply(2);
+    bound.apply(3);
+    System.out.println(tally.count);
+
+    Factory factory = Tally::new;
+    Sized shape = factory.make(6);
+    IntOp area = x -> x * shape.area();
+    System.out.println(area.apply(2));
+
+    Object o = addN;
+    System.out.println(o instanceof IntOp);
+    System.out.println(o instanceof Runnable);
+  }
+}
@@ -0,0 +1,93 @@
+class Counter {
+  long total;
+
+  void add(long n) {
+    total += n;
+  }
+}
+
+class Longs {
+  static long calls;
+
+  // 64-bit FNV-1a
+  static long fnv1a(byte[] data) {
+    long hash = 0xcbf29ce484222325L;
+    for (int i = 0; i < data.length; i++) {
+      hash ^= data[i];
+      hash *= 0x100000001b3L;
+    }
+    calls++;
+    return hash;
+  }
+
+  static long factorial(int n) {
+    long f = 1;
+    for (int i = 2; i <= n; i++) {
+      f *= i;
+    }
+    return f;
+  }
+
+  static long gcd(long a, long b) {
+    while (b != 0) {
+      long t = a % b;
+      a = b;
+      b = t;
+    }
+    return a;
+  }
+
+  static int compare(long a, long b) {
+    if (a < b) {
+      return -1;
+    } else if (a > b) {
+      return 1;
+    }
+    return 0;
+  }
+
+  public static void main(String[] args) {
+    byte[] ferrugo = {'f', 'e', 'r', 'r', 'u', 'g', 'o'};
+    System.out.println(fnv1a(ferrugo));
+    System.out.println(factorial(20));
+    System.out.println(factorial(21));
+    System.out.println(gcd(1071L * 1000000007L, 462L * 1000000007L));
+
+    long x = -12345678901L;
+    System.out.println(x / 1000);
+    System.out.println(x % 1000);
+    System.out.println(-x);
+    System.out.println(x << 3);
+    System.out.println(x >> 3);
+    System.out.println(x >>> 60);
+    System.out.println((x & 0xffff) | (x ^ 0xff00L));
+    System.out.println((int) x);
+    System.out.println((long) (x / 1e10) + (long) (x * 1e10));
+    System.out.println((long) (double) (x * 1000000 - 1));
+    long max = 0x7fffffffffffffffL;
+    long min = 0x8000000000000000L;
+    System.out.println(compare(max, min) + compare(3L, 3L));
+    System.out.println(max + 1 == min);
+
+    long[] squares = new long[4];
+    for (int i = 0; i < squares.length; i++) {
+      squares[i] = (long) i * 3000000000L;
+    }
+    System.out.println(squares[1] + squares[3]);
+
+    Counter counter = new Counter();
+    for (int i = 0; i < 100; i++) {
+      counter.add(i * 100000000L);
+    }
+    System.out.println("total = " + counter.total + ", calls = " + calls);
+
+    try {
+      System.out.println(x / (calls - 1));
+    } catch (ArithmeticException e) {
+      System.out.println("ArithmeticException: " + e.getMessage());
+    }
+
+    long start = System.currentTimeMillis();
+    System.out.println(start > 0 && System.nanoTime() > 0);
+  }
+}
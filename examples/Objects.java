@@ -0,0 +1,126 @@
+class Pixel implements Cloneable {
+  int x;
+  int y;
+  int[] history;
+
+  Pixel(int x, int y) {
+    this.x = x;
+    this.y = y;
+    this.history = new int[] {x, y};
+  }
+
+  public boolean equals(Object obj) {
+    if (!(obj instanceof Pixel)) {
+      return false;
+    }
+    Pixel other = (Pixel) obj;
+    return x == other.x && y == other.y;
+  }
+
+  public int hashCode() {
+    return 31 * x + y;
+  }
+
+  public String toString() {
+    return "(" + x + ", " + y + ")";
+  }
+
+  public Pixel clone() throws CloneNotSupportedException {
+    return (Pixel) super.clone();
+  }
+}
+
+class Opaque {
+  int value = 7;
+
+  Opaque copy() throws CloneNotSupportedException {
+    return (Opaque) clone();
+  }
+}
+
+public class Objects {
+  static String hex(Object obj) {
+    return Integer.toHexString(obj.hashCode());
+  }
+
+  public static void main(String[] args) throws Exception {
+    Object a = new Object();
+    Object b = new Object();
+    System.out.println(a.hashCode() == a.hashCode());
+    System.out.println(a.hashCode() != 0);
+    System.out.println(a.hashCode() >= 0);
+    System.out.println(a.equals(a));
+    System.out.println(a.equals(b));
+    System.out.println(a.equals(null));
+    System.out.println(a.toString() == a.toString());
+    System.out.println(System.identityHashCode(a) == a.hashCode());
+    System.out.println(System.identityHashCode(null));
+
+    // The identity hash code doesn't change while objects are allocated and collected
+    int before = b.hashCode();
+    for (int i = 0; i < 100000; i++) {
+      new int[16].hashCode();
+    }
+    System.out.println(before == b.hashCode());
+
+    // Object.toString is ``Class@hex``
+    Opaque plain = new Opaque();
+    String expected = "Opaque@" + hex(plain);
+    System.out.println(plain.toString().length() == expected.length());
+    System.out.println(String.valueOf(plain).length() == expected.length());
+    int[] ints = new int[3];
+    System.out.println(("[I@" + hex(ints)).length() == ints.toString().length());
+    System.out.println(ints.getClass().getName());
+
+    // println(Object) and String.valueOf(Object) call toString virtually
+    Pixel p = new Pixel(3, 4);
+    System.out.println(p);
+    System.out.println(String.valueOf(p));
+    Object o = p;
+    System.out.println(o);
+    System.out.println((Object) "a string");
+    Object nothing = null;
+    System.out.println(nothing);
+    System.out.println(String.valueOf(nothing));
+    System.out.println(Pixel.class);
+
+    System.out.println(p.equals(new Pixel(3, 4)));
+    System.out.println(p.equals(new Pixel(4, 3)));
+    System.out.println(p.hashCode());
+    System.out.println(System.identityHashCode(p) == p.hashCode());
+
+    // Shallow copies
+    Pixel q = p.clone();
+    System.out.println(q);
+    System.out.println(q == p);
+    System.out.println(q.equals(p));
+    System.out.println(q.history == p.history);
+    System.out.println(System.identityHashCode(q) == System.identityHashCode(p));
+    q.x = 10;
+    System.out.println(p.x);
+
+    int[] copy = p.history.clone();
+    copy[0] = 99;
+    System.out.println(p.history[0]);
+    System.out.println(copy[0]);
+    System.out.println(copy.length);
+
+    long[] longs = {1L, 2L, 3L};
+    long[] longsCopy = longs.clone();
+    System.out.println(longsCopy[2]);
+    String[] strings = {"x", "y"};
+    String[] stringsCopy = strings.clone();
+    System.out.println(stringsCopy[1]);
+    System.out.println(stringsCopy == strings);
+    int[][] grid = {{1, 2}, {3, 4}};
+    int[][] gridCopy = grid.clone();
+    System.out.println(gridCopy[1] == grid[1]);
+
+    try {
+      plain.copy();
+      System.out.println("cloned");
+    } catch (CloneNotSupportedException e) {
+      System.out.println("CloneNotSupportedException: " + e.getMessage());
+    }
+  }
+}
@@ -0,0 +1,102 @@
+class Opcodes {
+  // Java's String.hashCode()
+  static int hash(char[] s) {
+    int h = 0;
+    for (int i = 0; i < s.length; i++) {
+      h = 31 * h + s[i];
+    }
+    return h;
+  }
+
+  static int countdown(int n) {
+    int sum = 0;
+    for (int i = n; i > 0; i--) {
+      sum += i;
+    }
+    for (int i = 0; i < 100000; i += 1000) {
+      sum -= 1;
+    }
+    return sum;
+  }
+
+  public static void main(String[] args) {
+    char[] word = {'f', 'e', 'r', 'r', 'u', 'g', 'o'};
+    System.out.println(hash(word));
+    word[0] = (char) (word[0] - 32);
+    System.out.println((int) word[0] + " " + (int) (char) -1);
+
+    short[] shorts = new short[3];
+    shorts[0] = (short) 40000;
+    shorts[1] = -2;
+    System.out.println(shorts[0] + shorts[1] + shorts[2]);
+
+    byte[] bytes = {(byte) 200, -1, 127};
+    System.out.println(bytes[0] + bytes[1] + bytes[2]);
+    int wider = 300;
+    System.out.println((byte) wider + " " + (byte) -(wider - 171));
+
+    System.out.println(countdown(100));
+
+    int x = -20;
+    System.out.println(-x + " " + (x >>> 28) + " " + (x >> 2) + " " + (x << 30) + " " + (x | 5));
+    System.out.println((x >>> 33) + " " + (1 << 35));
+    double d = 5.5;
+    System.out.println(d % 2);
+    System.out.println((d - 12.75) % 3);
+    System.out.println(-2147483648 / (x / x - 2) + " " + -2147483648 % (x / x - 2));
+
+    int[] ints = new int[2];
+    int y = ints[1] = 7;
+    long[] longs = new long[2];
+    long z = longs[0] = -9L;
+    System.out.println(y + ints[1] + " " + (z + longs[0]));
+
+    Object a = word;
+    Object b = shorts;
+    if (a != b) {
+      System.out.println("different");
+    }
+
+    try {
+      System.out.println(x / (x - x));
+    } catch (ArithmeticException e) {
+      System.out.println("ArithmeticException: " + e.getMessage());
+    }
+    try {
+      System.out.println(x % (x - x));
+    } catch (ArithmeticException e) {
+      System.out.println("ArithmeticException: " + e.getMessage());
+    }
+    try {
+      System.out.println(ints[2]);
+    } catch (ArrayIndexOutOfBoundsException e) {
+      System.out.println("ArrayIndexOutOfBoundsException: " + e.getMessage());
+    }
+    try {
+      longs[x] = 1;
+    } catch (ArrayIndexOutOfBoundsException e) {
+      System.out.println("ArrayIndexOutOfBoundsException: " + e.getMessage());
+    }
+    try {
+      int[] none = null;
+      System.out.println(none.length);
+    } catch (NullPointerException e) {
+      System.out.println("NullPointerException");
+    }
+    try {
+      System.out.println(new byte[x].length);
+    } catch (NegativeArraySizeException e) {
+      System.out.println("NegativeArraySizeException: " + e.getMessage());
+    }
+    try {
+      System.out.println(new String[x + 19].length);
+    } catch (NegativeArraySizeException e) {
+      System.out.println("NegativeArraySizeException: " + e.getMessage());
+    }
+    try {
+      System.out.println(new int[2][x][3].length);
+    } catch (NegativeArraySizeException e) {
+      System.out.println("NegativeArraySizeException: " + e.getMessage());
+    }
+  }
+}
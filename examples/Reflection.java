@@ -0,0 +1,140 @@
+import java.lang.reflect.Field;
+import java.lang.reflect.InvocationTargetException;
+import java.lang.reflect.Method;
+import java.lang.reflect.Modifier;
+
+interface Greeter {
+  String greet(String name);
+}
+
+class Animal {
+  public String kind = "animal";
+
+  public int legs() {
+    return 4;
+  }
+}
+
+class Bird extends Animal implements Greeter {
+  public static int count;
+  private String name;
+  long wingspan;
+
+  public Bird() {
+    this.name = "bird";
+    this.wingspan = 30L;
+    count++;
+  }
+
+  public int legs() {
+    return 2;
+  }
+
+  public String greet(String other) {
+    return "Tweet, " + other;
+  }
+
+  public static long scale(long x, double factor) {
+    return (long) (x * factor);
+  }
+
+  private void fail(int code) {
+    throw new BirdError("failed with " + code);
+  }
+}
+
+class BirdError extends RuntimeException {
+  BirdError(String message) {
+    super(message);
+  }
+}
+
+class Reflection {
+  public static void main(String[] args) throws Exception {
+    Class<?> birdClass = Class.forName("Bird");
+    System.out.println(birdClass.getName());
+    System.out.println(birdClass == Bird.class);
+    System.out.println(new Bird().getClass() == birdClass);
+    System.out.println(birdClass.getSuperclass().getName());
+    System.out.println(birdClass.getInterfaces()[0].toString());
+    System.out.println(birdClass.isInstance(new Bird()));
+    System.out.println(birdClass.isInstance(new Animal()));
+    System.out.println(new int[0].getClass().getName());
+    System.out.println(new String[0].getClass().getSimpleName());
+    System.out.println(Class.forName("[Ljava.lang.String;") == String[].class);
+    System.out.println(int.class.getName());
+    System.out.println(Modifier.isPublic(Greeter.class.getModifiers()));
+
+    Method[] methods = birdClass.getDeclaredMethods();
+    for (int i = 0; i < methods.length; i++) {
+      System.out.println(methods[i].toString());
+    }
+    Field[] fields = birdClass.getDeclaredFields();
+    for (int i = 0; i < fields.length; i++) {
+      System.out.println(fields[i].toString());
+    }
+
+    Bird bird = (Bird) birdClass.newInstance();
+    Method legs = birdClass.getMethod("legs");
+    System.out.println(((Integer) legs.invoke(bird)).intValue());
+    Method animalLegs = Animal.class.getMethod("legs");
+    System.out.println(((Integer) animalLegs.invoke(bird)).intValue());
+    Method greet = birdClass.getMethod("greet", String.class);
+    System.out.println((String) greet.invoke(bird, "reflection"));
+    Method scale = birdClass.getDeclaredMethod("scale", long.class, double.class);
+    System.out.println(((Long) scale.invoke(null, 10, 2.5f)).longValue());
+
+    Field count = birdClass.getField("count");
+    System.out.println(count.getInt(null));
+    count.set(null, 10);
+    System.out.println(Bird.count);
+    Field name = birdClass.getDeclaredField("name");
+    name.setAccessible(true);
+    System.out.println((String) name.get(bird));
+    name.set(bird, "robin");
+    System.out.println((String) name.get(bird));
+    Field wingspan = birdClass.getDeclaredField("wingspan");
+    System.out.println(wingspan.getLong(bird));
+    Field kind = birdClass.getField("kind");
+    System.out.println((String) kind.get(bird));
+    System.out.println(kind.getDeclaringClass().getName());
+
+    try {
+      Method fail = birdClass.getDeclaredMethod("fail", int.class);
+      fail.setAccessible(true);
+      fail.invoke(bird, 7);
+    } catch (InvocationTargetException e) {
+      System.out.println(e.getTargetException().getMessage());
+    }
+    try {
+      legs.invoke(new Animal());
+    } catch (IllegalArgumentException e) {
+      System.out.println(e.getMessage());
+    }
+    try {
+      greet.invoke(bird, 1);
+    } catch (IllegalArgumentException e) {
+      System.out.println(e.getMessage());
+    }
+    try {
+      birdClass.getMethod("fail", int.class);
+    } catch (NoSuchMethodException e) {
+      System.out.println(e.getMessage());
+    }
+    try {
+      birdClass.getDeclaredField("legs");
+    } catch (NoSuchFieldException e) {
+      System.out.println(e.getMessage());
+    }
+    try {
+      Class.forName("no.such.Class");
+    } catch (ClassNotFoundException e) {
+      System.out.println(e.getMessage());
+    }
+    try {
+      Greeter.class.newInstance();
+    } catch (InstantiationException e) {
+      System.out.println(e.getMessage());
+    }
+  }
+}
@@ -0,0 +1,77 @@
+public class StringBuilders {
+  static class Tag {
+    public String toString() {
+      return "<tag>";
+    }
+  }
+
+  public static void main(String[] args) {
+    StringBuilder sb = new StringBuilder();
+    sb.append("int ").append(42).append(' ').append(true).append(' ').append(123456789012L);
+    sb.append(' ').append(new Tag()).append(' ').append((Object) null).append(' ');
+    sb.append((String) null).append(' ').append(new char[] {'x', 'y'});
+    System.out.println(sb.toString());
+    System.out.println(sb.length());
+    System.out.println(String.valueOf(sb.charAt(4)));
+
+    // Implicit concatenation of chars and booleans
+    char c = 'c';
+    boolean b = false;
+    System.out.println("char " + c + ", boolean " + b);
+
+    StringBuilder word = new StringBuilder("hello");
+    word.insert(0, "[").append(']');
+    word.insert(1, 'X').insert(2, 7).insert(3, false).insert(4, 99L);
+    System.out.println(word);
+    word.delete(1, 4);
+    System.out.println(word);
+    word.delete(6, 100);
+    System.out.println(word);
+    word.deleteCharAt(0);
+    System.out.println(word);
+    word.setCharAt(0, 'J');
+    System.out.println(word);
+    word.reverse();
+    System.out.println(word);
+
+    StringBuilder pairs = new StringBuilder("a\ud83d\ude00b");
+    System.out.println((int) pairs.reverse().charAt(1));
+    System.out.println((int) pairs.charAt(2));
+
+    StringBuilder sized = new StringBuilder(2);
+    for (int i = 0; i < 20; i++) {
+      sized.append(i % 10);
+    }
+    System.out.println(sized);
+    sized.setLength(5);
+    System.out.println(sized);
+    sized.setLength(7);
+    System.out.println(sized.length());
+    System.out.println((int) sized.charAt(6));
+    sized.setLength(0);
+    System.out.println(sized.append("reused"));
+
+    // toString copies
+    StringBuilder source = new StringBuilder("abc");
+    String snapshot = source.toString();
+    source.setCharAt(0, 'z');
+    System.out.println(snapshot);
+    System.out.println(source);
+
+    try {
+      source.charAt(3);
+    } catch (StringIndexOutOfBoundsException e) {
+      System.out.println(e.getClass().getName());
+    }
+    try {
+      source.insert(5, "x");
+    } catch (StringIndexOutOfBoundsException e) {
+      System.out.println(e.getClass().getName());
+    }
+    try {
+      source.delete(2, 1);
+    } catch (StringIndexOutOfBoundsException e) {
+      System.out.println(e.getClass().getName());
+    }
+  }
+}
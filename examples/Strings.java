@@ -0,0 +1,133 @@
+import java.util.regex.PatternSyntaxException;
+
+public class Strings {
+  static String kind(String command) {
+    switch (command) {
+      case "start":
+        return "starting";
+      case "stop":
+        return "stopping";
+      // "Aa" and "BB" have the same hash code
+      case "Aa":
+        return "Aa";
+      case "BB":
+        return "BB";
+      default:
+        return "unknown " + command;
+    }
+  }
+
+  static void print(String[] strings) {
+    String joined = "[";
+    for (int i = 0; i < strings.length; i++) {
+      joined = joined + (i == 0 ? "" : ", ") + strings[i];
+    }
+    System.out.println(joined + "] " + strings.length);
+  }
+
+  // Returns the name of the exception thrown by splitting with a malformed or unsupported regex
+  static String splitError(String regex) {
+    try {
+      "ab".split(regex);
+      return "none";
+    } catch (PatternSyntaxException e) {
+      return e.getClass().getName();
+    }
+  }
+
+  public static void main(String[] args) {
+    String s = "Hello, World";
+    System.out.println(s.length());
+    System.out.println(String.valueOf(s.charAt(7)));
+    System.out.println(s.substring(7));
+    System.out.println(s.substring(0, 5));
+    System.out.println(s.indexOf('o'));
+    System.out.println(s.indexOf('o', 5));
+    System.out.println(s.lastIndexOf('o'));
+    System.out.println(s.indexOf("World"));
+    System.out.println(s.indexOf("world"));
+    System.out.println(s.lastIndexOf("l"));
+    System.out.println(s.contains("lo, W"));
+    System.out.println(s.startsWith("Hell"));
+    System.out.println(s.endsWith("World"));
+    System.out.println(s.isEmpty());
+    System.out.println("".isEmpty());
+    System.out.println(s.toUpperCase());
+    System.out.println(s.toLowerCase());
+    System.out.println("  padded\t".trim());
+    System.out.println(s.replace('l', 'L'));
+    System.out.println(s.concat("!"));
+    System.out.println(new String(s.toCharArray(), 7, 5));
+
+    // Lengths count UTF-16 code units, not bytes
+    String accented = "h\u00e9llo w\u00f6rld";
+    System.out.println(accented.length());
+    System.out.println(accented.toUpperCase());
+    System.out.println("stra\u00dfe".toUpperCase());
+    String smile = "a\ud83d\ude00b";
+    System.out.println(smile.length());
+    System.out.println(smile.indexOf(0x1F600));
+    System.out.println(smile.indexOf('b'));
+    System.out.println((int) smile.charAt(1));
+    System.out.println(smile.substring(1, 3));
+
+    // equals, hashCode and compareTo
+    String built = new StringBuilder().append("Hello, ").append("World").toString();
+    System.out.println(built == s);
+    System.out.println(built.equals(s));
+    System.out.println(s.equals(null));
+    System.out.println(s.equals("Hello"));
+    System.out.println(built.hashCode() == s.hashCode());
+    System.out.println(s.hashCode());
+    System.out.println("".hashCode());
+    System.out.println(accented.hashCode());
+    System.out.println("apple".compareTo("banana"));
+    System.out.println("apple".compareTo("app"));
+    System.out.println("apple".compareTo("apple"));
+    System.out.println("HELLO".equalsIgnoreCase("hello"));
+
+    // Literals and interned strings are the same objects
+    System.out.println(built.intern() == s);
+    System.out.println(new String("x") == "x");
+    System.out.println(new String("x").intern() == "x");
+    System.out.println(Other.GREETING == "Hello, World");
+
+    print("a,b,,c,,".split(","));
+    print("a,b,,c,,".split(",", -1));
+    print("a,b,,c,,".split(",", 2));
+    print("one  two three".split(" +"));
+    print("abc".split(""));
+    print("".split(","));
+    print(",".split(","));
+    print("key=value; other=thing".split("[=;] ?"));
+    // Java regex syntax is translated: \d and \s are ASCII only, \Q...\E quotes
+    System.out.println("a1b\u0663c22d".split("\\d+").length);
+    System.out.println("a\u00a0b c".split("\\s").length);
+    print("1+1=2".split("\\Q+\\E|="));
+    print("a[b]c".split("[\\Q[]\\E]"));
+    print("x\\py".split("\\\\"));
+    System.out.println(splitError("(a"));
+    System.out.println(splitError("[a"));
+
+    System.out.println(kind("start"));
+    System.out.println(kind("stop"));
+    System.out.println(kind("Aa"));
+    System.out.println(kind("BB"));
+    System.out.println(kind("restart"));
+
+    try {
+      s.charAt(12);
+    } catch (StringIndexOutOfBoundsException e) {
+      System.out.println(e.getClass().getName());
+    }
+    try {
+      s.substring(5, 3);
+    } catch (StringIndexOutOfBoundsException e) {
+      System.out.println(e.getClass().getName());
+    }
+  }
+}
+
+class Other {
+  static final String GREETING = new StringBuilder().append("Hello, World").toString().intern();
+}
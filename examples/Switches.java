@@ -0,0 +1,88 @@
+class Switches {
+  // Compiled to tableswitch
+  static int daysInMonth(int month, int year) {
+    switch (month) {
+      case 2:
+        return year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) ? 29 : 28;
+      case 4:
+      case 6:
+      case 9:
+      case 11:
+        return 30;
+      case 1:
+      case 3:
+      case 5:
+      case 7:
+      case 8:
+      case 10:
+      case 12:
+        return 31;
+      default:
+        return -1;
+    }
+  }
+
+  // Compiled to lookupswitch
+  static int score(int c) {
+    switch (c) {
+      case -1000:
+        return -1;
+      case 'a':
+      case 'e':
+      case 'i':
+      case 'o':
+      case 'u':
+        return 1;
+      case 'q':
+      case 'z':
+        return 10;
+      case 100000:
+        return 100;
+      default:
+        return 2;
+    }
+  }
+
+  // Collatz steps with the parity decided by a switch in a loop
+  static int collatz(int n) {
+    int steps = 0;
+    while (n != 1) {
+      switch (n % 2) {
+        case 0:
+          n /= 2;
+          break;
+        default:
+          n = 3 * n + 1;
+      }
+      steps++;
+    }
+    return steps;
+  }
+
+  public static void main(String[] args) {
+    int days = 0;
+    for (int month = 0; month <= 13; month++) {
+      days += daysInMonth(month, 2000);
+    }
+    System.out.println(days);
+    System.out.println(daysInMonth(2, 1900) + daysInMonth(2, 2024));
+
+    byte[] word = {'q', 'u', 'i', 'z', 'z', 'i', 'n', 'g'};
+    int total = 0;
+    for (int i = 0; i < word.length; i++) {
+      total += score(word[i]);
+    }
+    System.out.println(total);
+    System.out.println(score(-1000) + score(100000) + score(-1) + score(100001));
+
+    System.out.println(collatz(27));
+    int longest = 0;
+    for (int i = 1; i < 1000; i++) {
+      int steps = collatz(i);
+      if (steps > longest) {
+        longest = steps;
+      }
+    }
+    System.out.println(longest);
+  }
+}
@@ -0,0 +1,9 @@
+public class SystemProperties {
+  public static void main(String[] args) {
+    System.out.println(System.getProperty("greeting", "Hello") + " from " + System.getProperty("java.vm.name"));
+  }
+
+  public static String get(String key) {
+    return System.getProperty(key);
+  }
+}
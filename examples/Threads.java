@@ -0,0 +1,100 @@
+class SyncCounter {
+  int count;
+
+  SyncCounter() {
+    count = 0;
+  }
+
+  public synchronized void increment() {
+    count++;
+  }
+}
+
+class CountingWorker extends Thread {
+  SyncCounter counter;
+
+  CountingWorker(SyncCounter counter) {
+    this.counter = counter;
+  }
+
+  public void run() {
+    for (int i = 0; i < 10000; i++) {
+      counter.increment();
+    }
+  }
+}
+
+class Mailbox {
+  String message;
+
+  Mailbox() {
+    message = null;
+  }
+
+  public synchronized void put(String new_message) {
+    message = new_message;
+    notifyAll();
+  }
+
+  public synchronized String take() throws InterruptedException {
+    while (message == null) {
+      wait();
+    }
+    return message;
+  }
+}
+
+class Sender implements Runnable {
+  Mailbox mailbox;
+
+  Sender(Mailbox mailbox) {
+    this.mailbox = mailbox;
+  }
+
+  public void run() {
+    try {
+      Thread.sleep(10);
+    } catch (InterruptedException e) {
+    }
+    mailbox.put("hello from " + Thread.currentThread().getName());
+  }
+}
+
+class Threads {
+  public static void main(String[] args) throws InterruptedException {
+    SyncCounter counter = new SyncCounter();
+    CountingWorker workers[] = new CountingWorker[4];
+    for (int i = 0; i < 4; i++) {
+      workers[i] = new CountingWorker(counter);
+      workers[i].start();
+    }
+    for (int i = 0; i < 4; i++) {
+      workers[i].join();
+    }
+    System.out.println("count = " + counter.count);
+
+    Object lock = new Object();
+    int n = 0;
+    for (int i = 0; i < 100; i++) {
+      synchronized (lock) {
+        n += i;
+      }
+    }
+    System.out.println("n = " + n);
+
+    Mailbox mailbox = new Mailbox();
+    Thread sender = new Thread(new Sender(mailbox), "sender");
+    sender.start();
+    System.out.println(mailbox.take());
+    sender.join();
+    System.out.println(sender.isAlive());
+
+    try {
+      lock.notify();
+    } catch (IllegalMonitorStateException e) {
+      System.out.println("caught: " + e.getMessage());
+    }
+
+    System.out.println(Thread.currentThread().getName());
+  }
+}
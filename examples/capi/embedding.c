@@ -0,0 +1,31 @@
+// Calls Java methods of examples/Embedding.java through the C API.
+//
+//   cargo build --release
+//   cc -Iinclude -o embedding examples/capi/embedding.c -Ltarget/release -lferrugo
+//   LD_LIBRARY_PATH=target/release ./embedding
+
+#include <stdio.h>
+#include "ferrugo.h"
+
+int main(void) {
+  FerrugoVm *vm = ferrugo_vm_new();
+  ferrugo_vm_set_classpath(vm, "./examples");
+
+  FerrugoValue args[2] = {
+      {.ty = FERRUGO_TYPE_INT, .data = {.int_value = 10}},
+      {.ty = FERRUGO_TYPE_STRING, .data = {.string_value = "ferrugo"}},
+  };
+  FerrugoValue ret;
+  if (ferrugo_vm_invoke_static(vm, "Hello", "fibo", "(I)I", args, 1, &ret) == FERRUGO_STATUS_OK)
+    printf("fibo(10) = %d\n", ret.data.int_value);
+  if (ferrugo_vm_invoke_static(vm, "Embedding", "greet", "(Ljava/lang/String;)Ljava/lang/String;",
+                               &args[1], 1, &ret) == FERRUGO_STATUS_OK) {
+    printf("%s\n", ret.data.string_value);
+    ferrugo_string_free(ret.data.string_value);
+  }
+  if (ferrugo_vm_invoke_static(vm, "NoSuchClass", "main", "()V", NULL, 0, NULL) != FERRUGO_STATUS_OK)
+    printf("error: %s\n", ferrugo_vm_last_error(vm));
+
+  ferrugo_vm_destroy(vm);
+  return 0;
+}
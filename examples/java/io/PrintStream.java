@@ -4,7 +4,10 @@ public class PrintStream {
   public PrintStream() { }
   public native void print(String msg);
   public native void println(String msg);
+  public native void println(Object obj);
+  public native void println(float f);
   public native void println(double d);
   public native void println(int i);
+  public native void println(long l);
   public native void println(boolean b);
 }
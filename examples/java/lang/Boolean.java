@@ -0,0 +1,66 @@
+package java.lang;
+
+public final class Boolean implements Comparable<Boolean> {
+  public static final Boolean TRUE = new Boolean(true);
+  public static final Boolean FALSE = new Boolean(false);
+
+  public static final Class<Boolean> TYPE = (Class<Boolean>) Class.getPrimitiveClass("boolean");
+
+  private final boolean value;
+
+  public Boolean(boolean value) {
+    this.value = value;
+  }
+
+  public Boolean(String s) {
+    this(parseBoolean(s));
+  }
+
+  // Any string other than "true" (ignoring case), including null, is false
+  public static boolean parseBoolean(String s) {
+    return s != null && s.equalsIgnoreCase("true");
+  }
+
+  public static Boolean valueOf(boolean b) {
+    return b ? TRUE : FALSE;
+  }
+
+  public static Boolean valueOf(String s) {
+    return parseBoolean(s) ? TRUE : FALSE;
+  }
+
+  public static String toString(boolean b) {
+    return b ? "true" : "false";
+  }
+
+  public static int compare(boolean x, boolean y) {
+    return (x == y) ? 0 : (x ? 1 : -1);
+  }
+
+  public static int hashCode(boolean value) {
+    return value ? 1231 : 1237;
+  }
+
+  public boolean booleanValue() {
+    return value;
+  }
+
+  public int compareTo(Boolean b) {
+    return compare(value, b.value);
+  }
+
+  public boolean equals(Object obj) {
+    if (obj instanceof Boolean) {
+      return value == ((Boolean) obj).value;
+    }
+    return false;
+  }
+
+  public int hashCode() {
+    return hashCode(value);
+  }
+
+  public String toString() {
+    return toString(value);
+  }
+}
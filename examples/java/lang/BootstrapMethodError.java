@@ -0,0 +1,11 @@
+package java.lang;
+
+public class BootstrapMethodError extends LinkageError {
+  public BootstrapMethodError() {
+    super();
+  }
+
+  public BootstrapMethodError(String message) {
+    super(message);
+  }
+}
@@ -0,0 +1,108 @@
+package java.lang;
+
+public final class Byte extends Number implements Comparable<Byte> {
+  public static final byte MIN_VALUE = -128;
+  public static final byte MAX_VALUE = 127;
+  public static final int SIZE = 8;
+  public static final int BYTES = 1;
+
+  public static final Class<Byte> TYPE = (Class<Byte>) Class.getPrimitiveClass("byte");
+
+  // Every value is cached, so valueOf never allocates
+  private static final Byte[] cache = new Byte[256];
+
+  static {
+    for (int i = 0; i < cache.length; i++) {
+      cache[i] = new Byte((byte) (i - 128));
+    }
+  }
+
+  private final byte value;
+
+  public Byte(byte value) {
+    this.value = value;
+  }
+
+  public Byte(String s) throws NumberFormatException {
+    this.value = parseByte(s, 10);
+  }
+
+  public static String toString(byte b) {
+    return Integer.toString((int) b);
+  }
+
+  public static byte parseByte(String s) throws NumberFormatException {
+    return parseByte(s, 10);
+  }
+
+  public static byte parseByte(String s, int radix) throws NumberFormatException {
+    int i = Integer.parseInt(s, radix);
+    if (i < MIN_VALUE || i > MAX_VALUE) {
+      throw new NumberFormatException("Value out of range. Value:\"" + s + "\" Radix:" + radix);
+    }
+    return (byte) i;
+  }
+
+  public static Byte valueOf(byte b) {
+    return cache[b + 128];
+  }
+
+  public static Byte valueOf(String s) throws NumberFormatException {
+    return valueOf(parseByte(s, 10));
+  }
+
+  public static Byte valueOf(String s, int radix) throws NumberFormatException {
+    return valueOf(parseByte(s, radix));
+  }
+
+  public static int compare(byte x, byte y) {
+    return x - y;
+  }
+
+  public static int hashCode(byte value) {
+    return (int) value;
+  }
+
+  public byte byteValue() {
+    return value;
+  }
+
+  public short shortValue() {
+    return (short) value;
+  }
+
+  public int intValue() {
+    return (int) value;
+  }
+
+  public long longValue() {
+    return (long) value;
+  }
+
+  public float floatValue() {
+    return (float) value;
+  }
+
+  public double doubleValue() {
+    return (double) value;
+  }
+
+  public int compareTo(Byte anotherByte) {
+    return compare(value, anotherByte.value);
+  }
+
+  public boolean equals(Object obj) {
+    if (obj instanceof Byte) {
+      return value == ((Byte) obj).value;
+    }
+    return false;
+  }
+
+  public int hashCode() {
+    return hashCode(value);
+  }
+
+  public String toString() {
+    return toString(value);
+  }
+}
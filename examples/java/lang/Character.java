@@ -0,0 +1,100 @@
+package java.lang;
+
+public final class Character implements Comparable<Character> {
+  public static final char MIN_VALUE = '\u0000';
+  public static final char MAX_VALUE = '\uFFFF';
+  public static final int MIN_RADIX = 2;
+  public static final int MAX_RADIX = 36;
+  public static final int SIZE = 16;
+  public static final int BYTES = 2;
+
+  public static final Class<Character> TYPE = (Class<Character>) Class.getPrimitiveClass("char");
+
+  // valueOf returns the same instance for '\u0000'..'\u007f'
+  private static final Character[] cache = new Character[128];
+
+  static {
+    for (int i = 0; i < cache.length; i++) {
+      cache[i] = new Character((char) i);
+    }
+  }
+
+  private final char value;
+
+  public Character(char value) {
+    this.value = value;
+  }
+
+  public static Character valueOf(char c) {
+    if (c <= 127) {
+      return cache[c];
+    }
+    return new Character(c);
+  }
+
+  public static String toString(char c) {
+    return String.valueOf(c);
+  }
+
+  // Only ASCII digits and letters are recognized
+  public static int digit(char ch, int radix) {
+    if (radix < MIN_RADIX || radix > MAX_RADIX) {
+      return -1;
+    }
+    int value = -1;
+    if (ch >= '0' && ch <= '9') {
+      value = ch - '0';
+    } else if (ch >= 'a' && ch <= 'z') {
+      value = ch - 'a' + 10;
+    } else if (ch >= 'A' && ch <= 'Z') {
+      value = ch - 'A' + 10;
+    }
+    return value < radix ? value : -1;
+  }
+
+  public static char forDigit(int digit, int radix) {
+    if (digit >= radix || digit < 0 || radix < MIN_RADIX || radix > MAX_RADIX) {
+      return '\u0000';
+    }
+    if (digit < 10) {
+      return (char) ('0' + digit);
+    }
+    return (char) ('a' - 10 + digit);
+  }
+
+  // ASCII only, like digit
+  public static boolean isDigit(char ch) {
+    return ch >= '0' && ch <= '9';
+  }
+
+  public static int compare(char x, char y) {
+    return x - y;
+  }
+
+  public static int hashCode(char value) {
+    return (int) value;
+  }
+
+  public char charValue() {
+    return value;
+  }
+
+  public int compareTo(Character anotherCharacter) {
+    return compare(value, anotherCharacter.value);
+  }
+
+  public boolean equals(Object obj) {
+    if (obj instanceof Character) {
+      return value == ((Character) obj).value;
+    }
+    return false;
+  }
+
+  public int hashCode() {
+    return hashCode(value);
+  }
+
+  public String toString() {
+    return toString(value);
+  }
+}
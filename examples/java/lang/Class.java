@@ -0,0 +1,83 @@
+package java.lang;
+
+import java.lang.annotation.Annotation;
+import java.lang.reflect.Field;
+import java.lang.reflect.Method;
+
+public final class Class<T> {
+  // The name returned by getName(). Class objects are created only by the VM.
+  private final String name;
+
+  private Class() {
+    this.name = null;
+  }
+
+  static native Class<?> getPrimitiveClass(String name);
+
+  public static native Class<?> forName(String className) throws ClassNotFoundException;
+
+  public String getName() {
+    return name;
+  }
+
+  public native String getSimpleName();
+
+  public native boolean isInterface();
+  public native boolean isArray();
+  public native boolean isPrimitive();
+  public native boolean isInstance(Object obj);
+  public native int getModifiers();
+
+  public native Class<? super T> getSuperclass();
+  public native Class<?>[] getInterfaces();
+
+  public native T newInstance() throws InstantiationException, IllegalAccessException;
+
+  public native Method[] getDeclaredMethods();
+  public native Field[] getDeclaredFields();
+
+  public native Method getDeclaredMethod(String name, Class<?>... parameterTypes)
+      throws NoSuchMethodException;
+
+  // Searches the public methods of this class and its super classes
+  public native Method getMethod(String name, Class<?>... parameterTypes)
+      throws NoSuchMethodException;
+
+  public native Field getDeclaredField(String name) throws NoSuchFieldException;
+
+  // Searches the public fields of this class and its super classes
+  public native Field getField(String name) throws NoSuchFieldException;
+
+  // Includes the @Inherited annotations of the super classes
+  public native Annotation[] getAnnotations();
+  public native Annotation[] getDeclaredAnnotations();
+
+  public <A extends Annotation> A getAnnotation(Class<A> annotationClass) {
+    for (Annotation annotation : getAnnotations()) {
+      if (annotation.annotationType() == annotationClass) {
+        return (A) annotation;
+      }
+    }
+    return null;
+  }
+
+  public boolean isAnnotationPresent(Class<? extends Annotation> annotationClass) {
+    return getAnnotation(annotationClass) != null;
+  }
+
+  public <A extends Annotation> A getDeclaredAnnotation(Class<A> annotationClass) {
+    for (Annotation annotation : getDeclaredAnnotations()) {
+      if (annotation.annotationType() == annotationClass) {
+        return (A) annotation;
+      }
+    }
+    return null;
+  }
+
+  public String toString() {
+    if (isPrimitive()) {
+      return name;
+    }
+    return (isInterface() ? "interface " : "class ") + name;
+  }
+}
@@ -0,0 +1,124 @@
+package java.lang;
+
+public final class Double extends Number implements Comparable<Double> {
+  public static final double POSITIVE_INFINITY = 1.0 / 0.0;
+  public static final double NEGATIVE_INFINITY = -1.0 / 0.0;
+  public static final double NaN = 0.0d / 0.0;
+  public static final double MAX_VALUE = 0x1.fffffffffffffP+1023;
+  public static final double MIN_NORMAL = 0x1.0p-1022;
+  public static final double MIN_VALUE = 0x0.0000000000001P-1022;
+  public static final int MAX_EXPONENT = 1023;
+  public static final int MIN_EXPONENT = -1022;
+  public static final int SIZE = 64;
+  public static final int BYTES = 8;
+
+  public static final Class<Double> TYPE = (Class<Double>) Class.getPrimitiveClass("double");
+
+  private final double value;
+
+  public Double(double value) {
+    this.value = value;
+  }
+
+  public Double(String s) throws NumberFormatException {
+    this.value = parseDouble(s);
+  }
+
+  public static native String toString(double d);
+
+  // Accepts what Double.valueOf(String) in the JDK does, except hexadecimal floating-point
+  // literals
+  public static native double parseDouble(String s) throws NumberFormatException;
+
+  public static native long doubleToRawLongBits(double value);
+
+  public static native double longBitsToDouble(long bits);
+
+  // Every NaN is mapped to the canonical one
+  public static long doubleToLongBits(double value) {
+    if (isNaN(value)) {
+      return 0x7ff8000000000000L;
+    }
+    return doubleToRawLongBits(value);
+  }
+
+  public static Double valueOf(double d) {
+    return new Double(d);
+  }
+
+  public static Double valueOf(String s) throws NumberFormatException {
+    return new Double(parseDouble(s));
+  }
+
+  public static boolean isNaN(double v) {
+    return v != v;
+  }
+
+  public static boolean isInfinite(double v) {
+    return v == POSITIVE_INFINITY || v == NEGATIVE_INFINITY;
+  }
+
+  public static boolean isFinite(double d) {
+    return Math.abs(d) <= MAX_VALUE;
+  }
+
+  // -0.0 is less than 0.0, and NaN is greater than everything else including POSITIVE_INFINITY
+  public static int compare(double d1, double d2) {
+    if (d1 < d2) {
+      return -1;
+    }
+    if (d1 > d2) {
+      return 1;
+    }
+    long thisBits = doubleToLongBits(d1);
+    long anotherBits = doubleToLongBits(d2);
+    return thisBits == anotherBits ? 0 : (thisBits < anotherBits ? -1 : 1);
+  }
+
+  public static int hashCode(double value) {
+    long bits = doubleToLongBits(value);
+    return (int) (bits ^ (bits >>> 32));
+  }
+
+  public boolean isNaN() {
+    return isNaN(value);
+  }
+
+  public boolean isInfinite() {
+    return isInfinite(value);
+  }
+
+  public double doubleValue() {
+    return value;
+  }
+
+  public int intValue() {
+    return (int) value;
+  }
+
+  public long longValue() {
+    return (long) value;
+  }
+
+  public float floatValue() {
+    return (float) value;
+  }
+
+  public int compareTo(Double anotherDouble) {
+    return compare(value, anotherDouble.value);
+  }
+
+  // Unlike ==, NaN equals itself and 0.0 doesn't equal -0.0
+  public boolean equals(Object obj) {
+    return (obj instanceof Double)
+        && doubleToLongBits(((Double) obj).value) == doubleToLongBits(value);
+  }
+
+  public int hashCode() {
+    return hashCode(value);
+  }
+
+  public String toString() {
+    return toString(value);
+  }
+}
@@ -0,0 +1,25 @@
+package java.lang;
+
+public abstract class Enum<E extends Enum<E>> {
+  private final String name;
+  private final int ordinal;
+
+  protected Enum(String name, int ordinal) {
+    this.name = name;
+    this.ordinal = ordinal;
+  }
+
+  public final String name() {
+    return name;
+  }
+
+  public final int ordinal() {
+    return ordinal;
+  }
+
+  public String toString() {
+    return name;
+  }
+
+  public static native <T extends Enum<T>> T valueOf(Class<T> enumType, String name);
+}
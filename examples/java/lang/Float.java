@@ -0,0 +1,126 @@
+package java.lang;
+
+public final class Float extends Number implements Comparable<Float> {
+  public static final float POSITIVE_INFINITY = 1.0f / 0.0f;
+  public static final float NEGATIVE_INFINITY = -1.0f / 0.0f;
+  public static final float NaN = 0.0f / 0.0f;
+  public static final float MAX_VALUE = 0x1.fffffeP+127f;
+  public static final float MIN_NORMAL = 0x1.0p-126f;
+  public static final float MIN_VALUE = 0x0.000002P-126f;
+  public static final int MAX_EXPONENT = 127;
+  public static final int MIN_EXPONENT = -126;
+  public static final int SIZE = 32;
+  public static final int BYTES = 4;
+
+  public static final Class<Float> TYPE = (Class<Float>) Class.getPrimitiveClass("float");
+
+  private final float value;
+
+  public Float(float value) {
+    this.value = value;
+  }
+
+  public Float(double value) {
+    this.value = (float) value;
+  }
+
+  public Float(String s) throws NumberFormatException {
+    this.value = parseFloat(s);
+  }
+
+  public static native String toString(float f);
+
+  // Accepts what Float.valueOf(String) in the JDK does, except hexadecimal floating-point
+  // literals
+  public static native float parseFloat(String s) throws NumberFormatException;
+
+  public static native int floatToRawIntBits(float value);
+
+  public static native float intBitsToFloat(int bits);
+
+  // Every NaN is mapped to the canonical one
+  public static int floatToIntBits(float value) {
+    if (isNaN(value)) {
+      return 0x7fc00000;
+    }
+    return floatToRawIntBits(value);
+  }
+
+  public static Float valueOf(float f) {
+    return new Float(f);
+  }
+
+  public static Float valueOf(String s) throws NumberFormatException {
+    return new Float(parseFloat(s));
+  }
+
+  public static boolean isNaN(float v) {
+    return v != v;
+  }
+
+  public static boolean isInfinite(float v) {
+    return v == POSITIVE_INFINITY || v == NEGATIVE_INFINITY;
+  }
+
+  public static boolean isFinite(float f) {
+    return f >= -MAX_VALUE && f <= MAX_VALUE;
+  }
+
+  // -0.0f is less than 0.0f, and NaN is greater than everything else including POSITIVE_INFINITY
+  public static int compare(float f1, float f2) {
+    if (f1 < f2) {
+      return -1;
+    }
+    if (f1 > f2) {
+      return 1;
+    }
+    int thisBits = floatToIntBits(f1);
+    int anotherBits = floatToIntBits(f2);
+    return thisBits == anotherBits ? 0 : (thisBits < anotherBits ? -1 : 1);
+  }
+
+  public static int hashCode(float value) {
+    return floatToIntBits(value);
+  }
+
+  public boolean isNaN() {
+    return isNaN(value);
+  }
+
+  public boolean isInfinite() {
+    return isInfinite(value);
+  }
+
+  public float floatValue() {
+    return value;
+  }
+
+  public int intValue() {
+    return (int) value;
+  }
+
+  public long longValue() {
+    return (long) value;
+  }
+
+  public double doubleValue() {
+    return (double) value;
+  }
+
+  public int compareTo(Float anotherFloat) {
+    return compare(value, anotherFloat.value);
+  }
+
+  // Unlike ==, NaN equals itself and 0.0f doesn't equal -0.0f
+  public boolean equals(Object obj) {
+    return (obj instanceof Float) && floatToIntBits(((Float) obj).value) == floatToIntBits(value);
+  }
+
+  public int hashCode() {
+    return hashCode(value);
+  }
+
+  public String toString() {
+    return toString(value);
+  }
+}
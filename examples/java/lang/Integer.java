@@ -0,0 +1,168 @@
+package java.lang;
+
+public final class Integer extends Number implements Comparable<Integer> {
+  public static final int MIN_VALUE = 0x80000000;
+  public static final int MAX_VALUE = 0x7fffffff;
+  public static final int SIZE = 32;
+  public static final int BYTES = 4;
+
+  public static final Class<Integer> TYPE = (Class<Integer>) Class.getPrimitiveClass("int");
+
+  // valueOf returns the same instance for -128..127
+  private static final Integer[] cache = new Integer[256];
+
+  static {
+    for (int i = 0; i < cache.length; i++) {
+      cache[i] = new Integer(i - 128);
+    }
+  }
+
+  private final int value;
+
+  public Integer(int value) {
+    this.value = value;
+  }
+
+  public Integer(String s) throws NumberFormatException {
+    this.value = parseInt(s, 10);
+  }
+
+  public static native String toHexString(int i);
+
+  public static String toString(int i) {
+    return String.valueOf(i);
+  }
+
+  public static String toString(int i, int radix) {
+    if (radix < Character.MIN_RADIX || radix > Character.MAX_RADIX) {
+      radix = 10;
+    }
+    if (radix == 10) {
+      return toString(i);
+    }
+    // Works on the negated value so that MIN_VALUE doesn't overflow
+    char[] buf = new char[33];
+    boolean negative = i < 0;
+    int pos = 32;
+    if (!negative) {
+      i = -i;
+    }
+    while (i <= -radix) {
+      buf[pos--] = Character.forDigit(-(i % radix), radix);
+      i = i / radix;
+    }
+    buf[pos] = Character.forDigit(-i, radix);
+    if (negative) {
+      buf[--pos] = '-';
+    }
+    return new String(buf, pos, 33 - pos);
+  }
+
+  public static int parseInt(String s) throws NumberFormatException {
+    return parseInt(s, 10);
+  }
+
+  public static int parseInt(String s, int radix) throws NumberFormatException {
+    if (s == null) {
+      throw new NumberFormatException("Cannot parse null string");
+    }
+    if (radix < Character.MIN_RADIX) {
+      throw new NumberFormatException("radix " + radix + " less than Character.MIN_RADIX");
+    }
+    if (radix > Character.MAX_RADIX) {
+      throw new NumberFormatException("radix " + radix + " greater than Character.MAX_RADIX");
+    }
+
+    // Accumulates negatively so that MIN_VALUE doesn't overflow
+    int len = s.length();
+    if (len == 0) {
+      throw NumberFormatException.forInputString(s, radix);
+    }
+    boolean negative = false;
+    int limit = -MAX_VALUE;
+    int i = 0;
+    char first = s.charAt(0);
+    if (first == '-' || first == '+') {
+      if (len == 1) {
+        throw NumberFormatException.forInputString(s, radix);
+      }
+      if (first == '-') {
+        negative = true;
+        limit = MIN_VALUE;
+      }
+      i++;
+    }
+    int multmin = limit / radix;
+    int result = 0;
+    while (i < len) {
+      int digit = Character.digit(s.charAt(i++), radix);
+      if (digit < 0 || result < multmin) {
+        throw NumberFormatException.forInputString(s, radix);
+      }
+      result *= radix;
+      if (result < limit + digit) {
+        throw NumberFormatException.forInputString(s, radix);
+      }
+      result -= digit;
+    }
+    return negative ? result : -result;
+  }
+
+  public static Integer valueOf(int i) {
+    if (i >= -128 && i <= 127) {
+      return cache[i + 128];
+    }
+    return new Integer(i);
+  }
+
+  public static Integer valueOf(String s) throws NumberFormatException {
+    return valueOf(parseInt(s, 10));
+  }
+
+  public static Integer valueOf(String s, int radix) throws NumberFormatException {
+    return valueOf(parseInt(s, radix));
+  }
+
+  public static int compare(int x, int y) {
+    return (x < y) ? -1 : ((x == y) ? 0 : 1);
+  }
+
+  public static int hashCode(int value) {
+    return value;
+  }
+
+  public int intValue() {
+    return value;
+  }
+
+  public long longValue() {
+    return (long) value;
+  }
+
+  public float floatValue() {
+    return (float) value;
+  }
+
+  public double doubleValue() {
+    return (double) value;
+  }
+
+  public int compareTo(Integer anotherInteger) {
+    return compare(value, anotherInteger.value);
+  }
+
+  public boolean equals(Object obj) {
+    if (obj instanceof Integer) {
+      return value == ((Integer) obj).value;
+    }
+    return false;
+  }
+
+  public int hashCode() {
+    return hashCode(value);
+  }
+
+  public String toString() {
+    return toString(value);
+  }
+}
@@ -0,0 +1,166 @@
+package java.lang;
+
+public final class Long extends Number implements Comparable<Long> {
+  public static final long MIN_VALUE = 0x8000000000000000L;
+  public static final long MAX_VALUE = 0x7fffffffffffffffL;
+  public static final int SIZE = 64;
+  public static final int BYTES = 8;
+
+  public static final Class<Long> TYPE = (Class<Long>) Class.getPrimitiveClass("long");
+
+  // valueOf returns the same instance for -128..127
+  private static final Long[] cache = new Long[256];
+
+  static {
+    for (int i = 0; i < cache.length; i++) {
+      cache[i] = new Long(i - 128);
+    }
+  }
+
+  private final long value;
+
+  public Long(long value) {
+    this.value = value;
+  }
+
+  public Long(String s) throws NumberFormatException {
+    this.value = parseLong(s, 10);
+  }
+
+  public static String toString(long i) {
+    return String.valueOf(i);
+  }
+
+  public static String toString(long i, int radix) {
+    if (radix < Character.MIN_RADIX || radix > Character.MAX_RADIX) {
+      radix = 10;
+    }
+    if (radix == 10) {
+      return toString(i);
+    }
+    // Works on the negated value so that MIN_VALUE doesn't overflow
+    char[] buf = new char[65];
+    boolean negative = i < 0;
+    int pos = 64;
+    if (!negative) {
+      i = -i;
+    }
+    while (i <= -radix) {
+      buf[pos--] = Character.forDigit((int) (-(i % radix)), radix);
+      i = i / radix;
+    }
+    buf[pos] = Character.forDigit((int) (-i), radix);
+    if (negative) {
+      buf[--pos] = '-';
+    }
+    return new String(buf, pos, 65 - pos);
+  }
+
+  public static long parseLong(String s) throws NumberFormatException {
+    return parseLong(s, 10);
+  }
+
+  public static long parseLong(String s, int radix) throws NumberFormatException {
+    if (s == null) {
+      throw new NumberFormatException("Cannot parse null string");
+    }
+    if (radix < Character.MIN_RADIX) {
+      throw new NumberFormatException("radix " + radix + " less than Character.MIN_RADIX");
+    }
+    if (radix > Character.MAX_RADIX) {
+      throw new NumberFormatException("radix " + radix + " greater than Character.MAX_RADIX");
+    }
+
+    // Accumulates negatively so that MIN_VALUE doesn't overflow
+    int len = s.length();
+    if (len == 0) {
+      throw NumberFormatException.forInputString(s, radix);
+    }
+    boolean negative = false;
+    long limit = -MAX_VALUE;
+    int i = 0;
+    char first = s.charAt(0);
+    if (first == '-' || first == '+') {
+      if (len == 1) {
+        throw NumberFormatException.forInputString(s, radix);
+      }
+      if (first == '-') {
+        negative = true;
+        limit = MIN_VALUE;
+      }
+      i++;
+    }
+    long multmin = limit / radix;
+    long result = 0;
+    while (i < len) {
+      int digit = Character.digit(s.charAt(i++), radix);
+      if (digit < 0 || result < multmin) {
+        throw NumberFormatException.forInputString(s, radix);
+      }
+      result *= radix;
+      if (result < limit + digit) {
+        throw NumberFormatException.forInputString(s, radix);
+      }
+      result -= digit;
+    }
+    return negative ? result : -result;
+  }
+
+  public static Long valueOf(long l) {
+    if (l >= -128 && l <= 127) {
+      return cache[(int) l + 128];
+    }
+    return new Long(l);
+  }
+
+  public static Long valueOf(String s) throws NumberFormatException {
+    return valueOf(parseLong(s, 10));
+  }
+
+  public static Long valueOf(String s, int radix) throws NumberFormatException {
+    return valueOf(parseLong(s, radix));
+  }
+
+  public static int compare(long x, long y) {
+    return (x < y) ? -1 : ((x == y) ? 0 : 1);
+  }
+
+  public static int hashCode(long value) {
+    return (int) (value ^ (value >>> 32));
+  }
+
+  public long longValue() {
+    return value;
+  }
+
+  public int intValue() {
+    return (int) value;
+  }
+
+  public float floatValue() {
+    return (float) value;
+  }
+
+  public double doubleValue() {
+    return (double) value;
+  }
+
+  public int compareTo(Long anotherLong) {
+    return compare(value, anotherLong.value);
+  }
+
+  public boolean equals(Object obj) {
+    if (obj instanceof Long) {
+      return value == ((Long) obj).value;
+    }
+    return false;
+  }
+
+  public int hashCode() {
+    return hashCode(value);
+  }
+
+  public String toString() {
+    return toString(value);
+  }
+}
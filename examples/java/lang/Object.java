@@ -1,4 +1,24 @@
 package java.lang;
 
 public class Object {
+  public final native Class<?> getClass();
+
+  // The identity hash code, which doesn't change during the lifetime of the object
+  public native int hashCode();
+
+  public boolean equals(Object obj) {
+    return this == obj;
+  }
+
+  // A shallow copy. Throws CloneNotSupportedException unless the class implements Cloneable.
+  protected native Object clone() throws CloneNotSupportedException;
+
+  public String toString() {
+    return getClass().getName() + "@" + Integer.toHexString(hashCode());
+  }
+
+  public final native void wait() throws InterruptedException;
+  public final native void wait(long timeout) throws InterruptedException;
+  public final native void notify();
+  public final native void notifyAll();
 }
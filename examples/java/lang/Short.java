@@ -0,0 +1,111 @@
+package java.lang;
+
+public final class Short extends Number implements Comparable<Short> {
+  public static final short MIN_VALUE = -32768;
+  public static final short MAX_VALUE = 32767;
+  public static final int SIZE = 16;
+  public static final int BYTES = 2;
+
+  public static final Class<Short> TYPE = (Class<Short>) Class.getPrimitiveClass("short");
+
+  // valueOf returns the same instance for -128..127
+  private static final Short[] cache = new Short[256];
+
+  static {
+    for (int i = 0; i < cache.length; i++) {
+      cache[i] = new Short((short) (i - 128));
+    }
+  }
+
+  private final short value;
+
+  public Short(short value) {
+    this.value = value;
+  }
+
+  public Short(String s) throws NumberFormatException {
+    this.value = parseShort(s, 10);
+  }
+
+  public static String toString(short s) {
+    return Integer.toString((int) s);
+  }
+
+  public static short parseShort(String s) throws NumberFormatException {
+    return parseShort(s, 10);
+  }
+
+  public static short parseShort(String s, int radix) throws NumberFormatException {
+    int i = Integer.parseInt(s, radix);
+    if (i < MIN_VALUE || i > MAX_VALUE) {
+      throw new NumberFormatException("Value out of range. Value:\"" + s + "\" Radix:" + radix);
+    }
+    return (short) i;
+  }
+
+  public static Short valueOf(short s) {
+    if (s >= -128 && s <= 127) {
+      return cache[s + 128];
+    }
+    return new Short(s);
+  }
+
+  public static Short valueOf(String s) throws NumberFormatException {
+    return valueOf(parseShort(s, 10));
+  }
+
+  public static Short valueOf(String s, int radix) throws NumberFormatException {
+    return valueOf(parseShort(s, radix));
+  }
+
+  public static int compare(short x, short y) {
+    return x - y;
+  }
+
+  public static int hashCode(short value) {
+    return (int) value;
+  }
+
+  public short shortValue() {
+    return value;
+  }
+
+  public byte byteValue() {
+    return (byte) value;
+  }
+
+  public int intValue() {
+    return (int) value;
+  }
+
+  public long longValue() {
+    return (long) value;
+  }
+
+  public float floatValue() {
+    return (float) value;
+  }
+
+  public double doubleValue() {
+    return (double) value;
+  }
+
+  public int compareTo(Short anotherShort) {
+    return compare(value, anotherShort.value);
+  }
+
+  public boolean equals(Object obj) {
+    if (obj instanceof Short) {
+      return value == ((Short) obj).value;
+    }
+    return false;
+  }
+
+  public int hashCode() {
+    return hashCode(value);
+  }
+
+  public String toString() {
+    return toString(value);
+  }
+}
@@ -1,12 +1,283 @@
 package java.lang;
 
-public class String {
+public final class String implements CharSequence, Comparable<String> {
+  // UTF-16 code units. This must be the first field.
   private final char value[];
+  // The cached hash code, or 0 until it's computed
+  private int hash;
+
   public static native String valueOf(int n);
+  public static native String valueOf(long n);
+  public static native String valueOf(float f);
+  public static native String valueOf(double d);
+  public static native String valueOf(Object obj);
+
+  public static String valueOf(char c) {
+    return new String(new char[] {c});
+  }
+
+  public static String valueOf(boolean b) {
+    return b ? "true" : "false";
+  }
+
+  public static String valueOf(char[] data) {
+    return new String(data);
+  }
+
   public String() {
     this.value = new char[0];
   }
+
+  public String(char[] value) {
+    this(value, 0, value.length);
+  }
+
+  public String(char[] value, int offset, int count) {
+    if (offset < 0) {
+      throw new StringIndexOutOfBoundsException(offset);
+    }
+    if (count < 0) {
+      throw new StringIndexOutOfBoundsException(count);
+    }
+    if (offset > value.length - count) {
+      throw new StringIndexOutOfBoundsException(offset + count);
+    }
+    this.value = new char[count];
+    for (int i = 0; i < count; i++) {
+      this.value[i] = value[offset + i];
+    }
+  }
+
+  public String(String original) {
+    this.value = original.value;
+    this.hash = original.hash;
+  }
+
   public int length() {
     return value.length;
   }
+
+  public boolean isEmpty() {
+    return value.length == 0;
+  }
+
+  public char charAt(int index) {
+    if (index < 0 || index >= value.length) {
+      throw new StringIndexOutOfBoundsException(index);
+    }
+    return value[index];
+  }
+
+  public String substring(int beginIndex) {
+    return substring(beginIndex, value.length);
+  }
+
+  public String substring(int beginIndex, int endIndex) {
+    if (beginIndex < 0) {
+      throw new StringIndexOutOfBoundsException(beginIndex);
+    }
+    if (endIndex > value.length) {
+      throw new StringIndexOutOfBoundsException(endIndex);
+    }
+    if (beginIndex > endIndex) {
+      throw new StringIndexOutOfBoundsException(endIndex - beginIndex);
+    }
+    if (beginIndex == 0 && endIndex == value.length) {
+      return this;
+    }
+    return new String(value, beginIndex, endIndex - beginIndex);
+  }
+
+  public CharSequence subSequence(int beginIndex, int endIndex) {
+    return substring(beginIndex, endIndex);
+  }
+
+  public int indexOf(int ch) {
+    return indexOf(ch, 0);
+  }
+
+  public int indexOf(int ch, int fromIndex) {
+    if (ch >= 0x10000) {
+      return indexOf(fromCodePoint(ch), fromIndex);
+    }
+    for (int i = fromIndex < 0 ? 0 : fromIndex; i < value.length; i++) {
+      if (value[i] == ch) {
+        return i;
+      }
+    }
+    return -1;
+  }
+
+  public int indexOf(String str) {
+    return indexOf(str, 0);
+  }
+
+  public int indexOf(String str, int fromIndex) {
+    for (int i = fromIndex < 0 ? 0 : fromIndex; i <= value.length - str.value.length; i++) {
+      if (regionMatches(i, str)) {
+        return i;
+      }
+    }
+    return str.value.length == 0 ? value.length : -1;
+  }
+
+  public int lastIndexOf(int ch) {
+    if (ch >= 0x10000) {
+      return lastIndexOf(fromCodePoint(ch));
+    }
+    for (int i = value.length - 1; i >= 0; i--) {
+      if (value[i] == ch) {
+        return i;
+      }
+    }
+    return -1;
+  }
+
+  public int lastIndexOf(String str) {
+    for (int i = value.length - str.value.length; i >= 0; i--) {
+      if (regionMatches(i, str)) {
+        return i;
+      }
+    }
+    return -1;
+  }
+
+  public boolean contains(CharSequence s) {
+    return indexOf(s.toString()) >= 0;
+  }
+
+  public boolean startsWith(String prefix) {
+    return startsWith(prefix, 0);
+  }
+
+  public boolean startsWith(String prefix, int toffset) {
+    return toffset >= 0
+        && toffset <= value.length - prefix.value.length
+        && regionMatches(toffset, prefix);
+  }
+
+  public boolean endsWith(String suffix) {
+    return startsWith(suffix, value.length - suffix.value.length);
+  }
+
+  public boolean equals(Object anObject) {
+    if (this == anObject) {
+      return true;
+    }
+    if (!(anObject instanceof String)) {
+      return false;
+    }
+    String other = (String) anObject;
+    return value.length == other.value.length && regionMatches(0, other);
+  }
+
+  public boolean equalsIgnoreCase(String anotherString) {
+    return anotherString != null && toLowerCase().equals(anotherString.toLowerCase());
+  }
+
+  // s[0]*31^(n-1) + s[1]*31^(n-2) + ... + s[n-1]
+  public int hashCode() {
+    int h = hash;
+    if (h == 0) {
+      for (int i = 0; i < value.length; i++) {
+        h = 31 * h + value[i];
+      }
+      hash = h;
+    }
+    return h;
+  }
+
+  // Compares lexicographically by UTF-16 code units
+  public int compareTo(String anotherString) {
+    int len1 = value.length;
+    int len2 = anotherString.value.length;
+    for (int i = 0; i < len1 && i < len2; i++) {
+      char c1 = value[i];
+      char c2 = anotherString.value[i];
+      if (c1 != c2) {
+        return c1 - c2;
+      }
+    }
+    return len1 - len2;
+  }
+
+  public String concat(String str) {
+    if (str.value.length == 0) {
+      return this;
+    }
+    char[] chars = new char[value.length + str.value.length];
+    for (int i = 0; i < value.length; i++) {
+      chars[i] = value[i];
+    }
+    for (int i = 0; i < str.value.length; i++) {
+      chars[value.length + i] = str.value[i];
+    }
+    return new String(chars);
+  }
+
+  public String replace(char oldChar, char newChar) {
+    if (oldChar == newChar || indexOf(oldChar) < 0) {
+      return this;
+    }
+    char[] chars = toCharArray();
+    for (int i = 0; i < chars.length; i++) {
+      if (chars[i] == oldChar) {
+        chars[i] = newChar;
+      }
+    }
+    return new String(chars);
+  }
+
+  public String trim() {
+    int begin = 0;
+    int end = value.length;
+    while (begin < end && value[begin] <= ' ') {
+      begin++;
+    }
+    while (begin < end && value[end - 1] <= ' ') {
+      end--;
+    }
+    return substring(begin, end);
+  }
+
+  public char[] toCharArray() {
+    char[] chars = new char[value.length];
+    for (int i = 0; i < value.length; i++) {
+      chars[i] = value[i];
+    }
+    return chars;
+  }
+
+  public String[] split(String regex) {
+    return split(regex, 0);
+  }
+
+  public native String[] split(String regex, int limit);
+
+  public native String toUpperCase();
+
+  public native String toLowerCase();
+
+  public native String intern();
+
+  public String toString() {
+    return this;
+  }
+
+  // Whether ``other`` occurs at ``offset``, which must leave room for it
+  private boolean regionMatches(int offset, String other) {
+    for (int i = 0; i < other.value.length; i++) {
+      if (value[offset + i] != other.value[i]) {
+        return false;
+      }
+    }
+    return true;
+  }
+
+  private static String fromCodePoint(int codePoint) {
+    int offset = codePoint - 0x10000;
+    char high = (char) (0xd800 + (offset >> 10));
+    char low = (char) (0xdc00 + (offset & 0x3ff));
+    return new String(new char[] {high, low});
+  }
 }
@@ -1,19 +1,52 @@
 package java.lang;
 
-public class StringBuilder {
-	private final String str;
+public final class StringBuilder implements CharSequence {
+  // The characters are the first ``count`` elements of ``value``. These must be the first fields.
+  private char[] value;
+  private int count;
 
   public StringBuilder() {
-    this.str = "";
+    this.value = new char[16];
   }
-	
-	public native StringBuilder append(String strAppend);
-	public native StringBuilder append(int append);
-	// public native StringBuilder append(char append);
-	// public native StringBuilder append(boolean append);
-	// public native StringBuilder append(float append);
-	// public native StringBuilder append(double append);
-	// public native StringBuilder append(long append);	
-	// public native StringBuilder append(Object append);
+
+  public StringBuilder(int capacity) {
+    this.value = new char[capacity];
+  }
+
+  public StringBuilder(String str) {
+    this.value = new char[str.length() + 16];
+    append(str);
+  }
+
+  public native StringBuilder append(String str);
+  public native StringBuilder append(Object obj);
+  public native StringBuilder append(char[] str);
+  public native StringBuilder append(char c);
+  public native StringBuilder append(boolean b);
+  public native StringBuilder append(int i);
+  public native StringBuilder append(long l);
+  public native StringBuilder append(float f);
+  public native StringBuilder append(double d);
+
+  public native StringBuilder insert(int offset, String str);
+  public native StringBuilder insert(int offset, Object obj);
+  public native StringBuilder insert(int offset, char c);
+  public native StringBuilder insert(int offset, boolean b);
+  public native StringBuilder insert(int offset, int i);
+  public native StringBuilder insert(int offset, long l);
+
+  public native StringBuilder delete(int start, int end);
+  public native StringBuilder deleteCharAt(int index);
+  public native StringBuilder reverse();
+
+  public native void setLength(int newLength);
+  public native char charAt(int index);
+  public native void setCharAt(int index, char ch);
+  public native int length();
+
+  public CharSequence subSequence(int start, int end) {
+    return toString().substring(start, end);
+  }
+
   public native String toString();
 }
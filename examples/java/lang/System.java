@@ -6,4 +6,22 @@ public class System {
   static {
     out = new PrintStream();
   }
+
+  public static native String getProperty(String key);
+
+  public static String getProperty(String key, String def) {
+    String value = getProperty(key);
+    if (value == null) {
+      return def;
+    }
+    return value;
+  }
+
+  public static native int identityHashCode(Object x);
+
+  public static native long currentTimeMillis();
+  public static native long nanoTime();
+
+  public static native void load(String filename);
+  public static native void loadLibrary(String libname);
 }
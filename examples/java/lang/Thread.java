@@ -0,0 +1,48 @@
+package java.lang;
+
+public class Thread implements Runnable {
+  private Runnable target;
+  private String name;
+
+  private static int threadInitNumber = 0;
+
+  private static synchronized int nextThreadNum() {
+    return threadInitNumber++;
+  }
+
+  public Thread() {
+    this.name = "Thread-" + nextThreadNum();
+  }
+
+  public Thread(Runnable target) {
+    this.target = target;
+    this.name = "Thread-" + nextThreadNum();
+  }
+
+  public Thread(String name) {
+    this.name = name;
+  }
+
+  public Thread(Runnable target, String name) {
+    this.target = target;
+    this.name = name;
+  }
+
+  public void run() {
+    if (target != null) {
+      target.run();
+    }
+  }
+
+  public String getName() {
+    return name;
+  }
+
+  public native void start();
+  public final native void join() throws InterruptedException;
+  public final native void join(long millis) throws InterruptedException;
+  public final native boolean isAlive();
+  public static native void sleep(long millis) throws InterruptedException;
+  public static native void yield();
+  public static native Thread currentThread();
+}
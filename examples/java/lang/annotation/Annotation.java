@@ -0,0 +1,12 @@
+package java.lang.annotation;
+
+// Implemented by the classes the VM spins for annotations read by reflection
+public interface Annotation {
+  boolean equals(Object obj);
+
+  int hashCode();
+
+  String toString();
+
+  Class<? extends Annotation> annotationType();
+}
@@ -0,0 +1,29 @@
+package java.lang.invoke;
+
+public final class MethodHandle {
+  // The member this handle refers to. kind is the reference kind of a CONSTANT_MethodHandle.
+  private final int kind;
+  private final Class<?> refc;
+  private final String name;
+  private final MethodType type;
+
+  // Method handles are created only by the VM
+  private MethodHandle() {
+    this.kind = 0;
+    this.refc = null;
+    this.name = null;
+    this.type = null;
+  }
+
+  public MethodType type() {
+    return type;
+  }
+
+  // Signature polymorphic: the VM runs them with the descriptor of each call site
+  public final native Object invokeExact(Object... args) throws Throwable;
+  public final native Object invoke(Object... args) throws Throwable;
+
+  public String toString() {
+    return "MethodHandle" + type.toString();
+  }
+}
@@ -0,0 +1,27 @@
+package java.lang.invoke;
+
+public class MethodHandles {
+  private MethodHandles() {}
+
+  public static Lookup lookup() {
+    return new Lookup();
+  }
+
+  public static Lookup publicLookup() {
+    return new Lookup();
+  }
+
+  // Access is not checked
+  public static final class Lookup {
+    Lookup() {}
+
+    public native MethodHandle findStatic(Class<?> refc, String name, MethodType type)
+        throws NoSuchMethodException, IllegalAccessException;
+
+    public native MethodHandle findVirtual(Class<?> refc, String name, MethodType type)
+        throws NoSuchMethodException, IllegalAccessException;
+
+    public native MethodHandle findGetter(Class<?> refc, String name, Class<?> type)
+        throws NoSuchFieldException, IllegalAccessException;
+  }
+}
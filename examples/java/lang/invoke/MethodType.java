@@ -0,0 +1,44 @@
+package java.lang.invoke;
+
+public final class MethodType {
+  // A method descriptor like (ILjava/lang/String;)V. Method types are interned by the VM.
+  private final String descriptor;
+
+  private MethodType() {
+    this.descriptor = null;
+  }
+
+  public static MethodType methodType(Class<?> rtype) {
+    return methodType(rtype, new Class<?>[0]);
+  }
+
+  public static MethodType methodType(Class<?> rtype, Class<?> ptype0) {
+    return methodType(rtype, new Class<?>[] { ptype0 });
+  }
+
+  public static MethodType methodType(Class<?> rtype, Class<?> ptype0, Class<?>... ptypes) {
+    Class<?>[] all = new Class<?>[ptypes.length + 1];
+    all[0] = ptype0;
+    for (int i = 0; i < ptypes.length; i++) {
+      all[i + 1] = ptypes[i];
+    }
+    return methodType(rtype, all);
+  }
+
+  public static MethodType methodType(Class<?> rtype, MethodType ptypes) {
+    return methodType(rtype, ptypes.parameterArray());
+  }
+
+  public static native MethodType methodType(Class<?> rtype, Class<?>[] ptypes);
+
+  public native Class<?> returnType();
+  public native Class<?> parameterType(int num);
+  public native int parameterCount();
+  public native Class<?>[] parameterArray();
+
+  public String toMethodDescriptorString() {
+    return descriptor;
+  }
+
+  public native String toString();
+}
@@ -0,0 +1,78 @@
+package java.lang.reflect;
+
+import java.lang.annotation.Annotation;
+
+public final class Field {
+  private final Class<?> clazz;
+  private final String name;
+  private final Class<?> type;
+  private final int modifiers;
+
+  // Field objects are created only by the VM
+  private Field() {
+    this.clazz = null;
+    this.name = null;
+    this.type = null;
+    this.modifiers = 0;
+  }
+
+  public Class<?> getDeclaringClass() {
+    return clazz;
+  }
+
+  public String getName() {
+    return name;
+  }
+
+  public Class<?> getType() {
+    return type;
+  }
+
+  public int getModifiers() {
+    return modifiers;
+  }
+
+  public native Annotation[] getDeclaredAnnotations();
+
+  public Annotation[] getAnnotations() {
+    return getDeclaredAnnotations();
+  }
+
+  public <A extends Annotation> A getAnnotation(Class<A> annotationClass) {
+    for (Annotation annotation : getDeclaredAnnotations()) {
+      if (annotation.annotationType() == annotationClass) {
+        return (A) annotation;
+      }
+    }
+    return null;
+  }
+
+  public boolean isAnnotationPresent(Class<? extends Annotation> annotationClass) {
+    return getAnnotation(annotationClass) != null;
+  }
+
+  // Access is not checked
+  public void setAccessible(boolean flag) {}
+
+  // Primitive values are boxed and unboxed
+  public native Object get(Object obj) throws IllegalAccessException;
+  public native void set(Object obj, Object value) throws IllegalAccessException;
+
+  public int getInt(Object obj) throws IllegalAccessException {
+    return ((Number) get(obj)).intValue();
+  }
+
+  public long getLong(Object obj) throws IllegalAccessException {
+    return ((Number) get(obj)).longValue();
+  }
+
+  public double getDouble(Object obj) throws IllegalAccessException {
+    return ((Number) get(obj)).doubleValue();
+  }
+
+  public boolean getBoolean(Object obj) throws IllegalAccessException {
+    return ((Boolean) get(obj)).booleanValue();
+  }
+
+  public native String toString();
+}
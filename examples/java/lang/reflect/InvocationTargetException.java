@@ -0,0 +1,28 @@
+package java.lang.reflect;
+
+// Wraps an exception thrown by a method called by Method.invoke
+public class InvocationTargetException extends ReflectiveOperationException {
+  private Throwable target;
+
+  protected InvocationTargetException() {
+    super();
+  }
+
+  public InvocationTargetException(Throwable target) {
+    super();
+    this.target = target;
+  }
+
+  public InvocationTargetException(Throwable target, String message) {
+    super(message);
+    this.target = target;
+  }
+
+  public Throwable getTargetException() {
+    return target;
+  }
+
+  public Throwable getCause() {
+    return target;
+  }
+}
@@ -0,0 +1,78 @@
+package java.lang.reflect;
+
+import java.lang.annotation.Annotation;
+
+public final class Method {
+  private final Class<?> clazz;
+  private final String name;
+  private final Class<?> returnType;
+  private final Class<?>[] parameterTypes;
+  private final int modifiers;
+
+  // Method objects are created only by the VM
+  private Method() {
+    this.clazz = null;
+    this.name = null;
+    this.returnType = null;
+    this.parameterTypes = null;
+    this.modifiers = 0;
+  }
+
+  public Class<?> getDeclaringClass() {
+    return clazz;
+  }
+
+  public String getName() {
+    return name;
+  }
+
+  public Class<?> getReturnType() {
+    return returnType;
+  }
+
+  public Class<?>[] getParameterTypes() {
+    Class<?>[] types = new Class<?>[parameterTypes.length];
+    for (int i = 0; i < types.length; i++) {
+      types[i] = parameterTypes[i];
+    }
+    return types;
+  }
+
+  public int getParameterCount() {
+    return parameterTypes.length;
+  }
+
+  public int getModifiers() {
+    return modifiers;
+  }
+
+  public native Annotation[] getDeclaredAnnotations();
+
+  public Annotation[] getAnnotations() {
+    return getDeclaredAnnotations();
+  }
+
+  public <A extends Annotation> A getAnnotation(Class<A> annotationClass) {
+    for (Annotation annotation : getDeclaredAnnotations()) {
+      if (annotation.annotationType() == annotationClass) {
+        return (A) annotation;
+      }
+    }
+    return null;
+  }
+
+  public boolean isAnnotationPresent(Class<? extends Annotation> annotationClass) {
+    return getAnnotation(annotationClass) != null;
+  }
+
+  // One array for each parameter
+  public native Annotation[][] getParameterAnnotations();
+
+  // Access is not checked
+  public void setAccessible(boolean flag) {}
+
+  public native Object invoke(Object obj, Object... args)
+      throws IllegalAccessException, InvocationTargetException;
+
+  public native String toString();
+}
@@ -0,0 +1,19 @@
+package java.util;
+
+public final class Objects {
+  private Objects() {}
+
+  public static <T> T requireNonNull(T obj) {
+    if (obj == null) {
+      throw new NullPointerException();
+    }
+    return obj;
+  }
+
+  public static <T> T requireNonNull(T obj, String message) {
+    if (obj == null) {
+      throw new NullPointerException(message);
+    }
+    return obj;
+  }
+}
@@ -0,0 +1,39 @@
+package java.util.concurrent.atomic;
+
+public class AtomicInteger {
+  private volatile int value;
+
+  public AtomicInteger() {
+    this.value = 0;
+  }
+
+  public AtomicInteger(int initialValue) {
+    this.value = initialValue;
+  }
+
+  public final native int get();
+  public final native void set(int newValue);
+  public final native boolean compareAndSet(int expect, int update);
+  public final native int getAndAdd(int delta);
+  public final native int getAndSet(int newValue);
+
+  public final int getAndIncrement() {
+    return getAndAdd(1);
+  }
+
+  public final int getAndDecrement() {
+    return getAndAdd(-1);
+  }
+
+  public final int incrementAndGet() {
+    return getAndAdd(1) + 1;
+  }
+
+  public final int decrementAndGet() {
+    return getAndAdd(-1) - 1;
+  }
+
+  public final int addAndGet(int delta) {
+    return getAndAdd(delta) + delta;
+  }
+}
@@ -0,0 +1,39 @@
+package java.util.concurrent.atomic;
+
+public class AtomicLong {
+  private volatile long value;
+
+  public AtomicLong() {
+    this.value = 0;
+  }
+
+  public AtomicLong(long initialValue) {
+    this.value = initialValue;
+  }
+
+  public final native long get();
+  public final native void set(long newValue);
+  public final native boolean compareAndSet(long expect, long update);
+  public final native long getAndAdd(long delta);
+  public final native long getAndSet(long newValue);
+
+  public final long getAndIncrement() {
+    return getAndAdd(1);
+  }
+
+  public final long getAndDecrement() {
+    return getAndAdd(-1);
+  }
+
+  public final long incrementAndGet() {
+    return getAndAdd(1) + 1;
+  }
+
+  public final long decrementAndGet() {
+    return getAndAdd(-1) - 1;
+  }
+
+  public final long addAndGet(long delta) {
+    return getAndAdd(delta) + delta;
+  }
+}
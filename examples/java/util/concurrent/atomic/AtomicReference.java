@@ -0,0 +1,18 @@
+package java.util.concurrent.atomic;
+
+public class AtomicReference<V> {
+  private volatile V value;
+
+  public AtomicReference() {
+    this.value = null;
+  }
+
+  public AtomicReference(V initialValue) {
+    this.value = initialValue;
+  }
+
+  public final native V get();
+  public final native void set(V newValue);
+  public final native boolean compareAndSet(V expect, V update);
+  public final native V getAndSet(V newValue);
+}
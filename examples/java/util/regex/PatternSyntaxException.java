@@ -0,0 +1,7 @@
+package java.util.regex;
+
+public class PatternSyntaxException extends IllegalArgumentException {
+  public PatternSyntaxException(String message) {
+    super(message);
+  }
+}
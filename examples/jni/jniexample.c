@@ -0,0 +1,86 @@
+// Native methods of examples/JniExample.java
+//
+//   cc -shared -fPIC -I$JAVA_HOME/include -I$JAVA_HOME/include/linux \
+//      -o libjniexample.so examples/jni/jniexample.c
+
+#include <jni.h>
+#include <math.h>
+#include <stdio.h>
+
+JNIEXPORT jint JNICALL Java_JniExample_add(JNIEnv *env, jclass cls, jint x, jint y) {
+  return x + y;
+}
+
+JNIEXPORT jdouble JNICALL Java_JniExample_hypot(JNIEnv *env, jclass cls, jdouble x, jdouble y) {
+  return sqrt(x * x + y * y);
+}
+
+JNIEXPORT jstring JNICALL Java_JniExample_greet(JNIEnv *env, jclass cls, jstring name) {
+  jfieldID greeting_id = (*env)->GetStaticFieldID(env, cls, "greeting", "Ljava/lang/String;");
+  jstring greeting = (*env)->GetStaticObjectField(env, cls, greeting_id);
+  const char *greeting_chars = (*env)->GetStringUTFChars(env, greeting, NULL);
+  const char *name_chars = (*env)->GetStringUTFChars(env, name, NULL);
+  char buf[256];
+  snprintf(buf, sizeof(buf), "%s, %s (%d)", greeting_chars, name_chars,
+           (*env)->GetStringLength(env, name));
+  (*env)->ReleaseStringUTFChars(env, name, name_chars);
+  (*env)->ReleaseStringUTFChars(env, greeting, greeting_chars);
+  return (*env)->NewStringUTF(env, buf);
+}
+
+JNIEXPORT jint JNICALL Java_JniExample_sum(JNIEnv *env, jclass cls, jintArray xs) {
+  jsize len = (*env)->GetArrayLength(env, xs);
+  jint *elems = (*env)->GetIntArrayElements(env, xs, NULL);
+  jint sum = 0;
+  for (jsize i = 0; i < len; i++) sum += elems[i];
+  (*env)->ReleaseIntArrayElements(env, xs, elems, JNI_ABORT);
+  return sum;
+}
+
+JNIEXPORT jdouble JNICALL Java_JniExample_mix(JNIEnv *env, jclass cls, jint a, jlong b,
+                                              jfloat c, jdouble d, jbyte e, jshort f, jchar g,
+                                              jboolean h, jint i, jlong j, jfloat k, jdouble l,
+                                              jint m, jlong n, jfloat o, jdouble p) {
+  return a + b + c + d + e + f + g + (h ? 8 : 0) + i + j + k + l + m + n + o + p;
+}
+
+JNIEXPORT jintArray JNICALL Java_JniExample_squares(JNIEnv *env, jclass cls, jint n) {
+  jintArray squares = (*env)->NewIntArray(env, n);
+  for (jint i = 0; i < n; i++) {
+    jint square = i * i;
+    (*env)->SetIntArrayRegion(env, squares, i, 1, &square);
+  }
+  return squares;
+}
+
+JNIEXPORT jint JNICALL Java_JniExample_applyTwice(JNIEnv *env, jclass cls, jint x) {
+  jmethodID twice = (*env)->GetStaticMethodID(env, cls, "twice", "(I)I");
+  jint y = (*env)->CallStaticIntMethod(env, cls, twice, x);
+  return (*env)->CallStaticIntMethod(env, cls, twice, y);
+}
+
+JNIEXPORT void JNICALL Java_JniExample_fail(JNIEnv *env, jclass cls, jstring message) {
+  const char *chars = (*env)->GetStringUTFChars(env, message, NULL);
+  jclass exception = (*env)->FindClass(env, "java/lang/IllegalArgumentException");
+  (*env)->ThrowNew(env, exception, chars);
+  (*env)->ReleaseStringUTFChars(env, message, chars);
+}
+
+JNIEXPORT void JNICALL Java_JniExample_increment(JNIEnv *env, jobject obj) {
+  jclass cls = (*env)->GetObjectClass(env, obj);
+  jfieldID count = (*env)->GetFieldID(env, cls, "count", "I");
+  (*env)->SetIntField(env, obj, count, (*env)->GetIntField(env, obj, count) + 1);
+}
+
+static jint triple(JNIEnv *env, jclass cls, jint x) {
+  return x * 3;
+}
+
+JNIEXPORT jint JNICALL JNI_OnLoad(JavaVM *vm, void *reserved) {
+  JNIEnv *env;
+  if ((*vm)->GetEnv(vm, (void **)&env, JNI_VERSION_1_8) != JNI_OK) return JNI_ERR;
+  jclass cls = (*env)->FindClass(env, "JniExample");
+  JNINativeMethod methods[] = {{"triple", "(I)I", (void *)triple}};
+  if ((*env)->RegisterNatives(env, cls, methods, 1) != JNI_OK) return JNI_ERR;
+  return JNI_VERSION_1_8;
+}
@@ -0,0 +1,34 @@
+package sun.misc;
+
+// Offsets are in bytes. Every field of an object occupies 8 bytes.
+public final class Unsafe {
+  private static final Unsafe theUnsafe = new Unsafe();
+
+  private Unsafe() {}
+
+  public static Unsafe getUnsafe() {
+    return theUnsafe;
+  }
+
+  // Unlike the JDK, takes the name of the class since there are no Class objects.
+  public native long objectFieldOffset(String className, String fieldName);
+  public native int arrayBaseOffset(Object array);
+  public native int arrayIndexScale(Object array);
+
+  public final native boolean compareAndSwapInt(Object o, long offset, int expected, int x);
+  public final native boolean compareAndSwapLong(Object o, long offset, long expected, long x);
+  public final native boolean compareAndSwapObject(Object o, long offset, Object expected, Object x);
+
+  public native int getIntVolatile(Object o, long offset);
+  public native long getLongVolatile(Object o, long offset);
+  public native Object getObjectVolatile(Object o, long offset);
+  public native void putIntVolatile(Object o, long offset, int x);
+  public native void putLongVolatile(Object o, long offset, long x);
+  public native void putObjectVolatile(Object o, long offset, Object x);
+
+  public final native int getAndAddInt(Object o, long offset, int delta);
+  public final native long getAndAddLong(Object o, long offset, long delta);
+  public final native int getAndSetInt(Object o, long offset, int newValue);
+  public final native long getAndSetLong(Object o, long offset, long newValue);
+  public final native Object getAndSetObject(Object o, long offset, Object newValue);
+}
@@ -0,0 +1,125 @@
+/* ferrugo C API. Link with the cdylib built by `cargo build --release`. */
+
+#ifndef FERRUGO_H
+#define FERRUGO_H
+
+/* Generated by cbindgen from src/jvm/capi.rs. Do not edit. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// The result of a function
+typedef enum FerrugoStatus {
+  FERRUGO_STATUS_OK = 0,
+  FERRUGO_STATUS_CLASS_NOT_FOUND,
+  FERRUGO_STATUS_METHOD_NOT_FOUND,
+  FERRUGO_STATUS_INVALID_ARGUMENTS,
+  FERRUGO_STATUS_INVALID_RETURN_TYPE,
+  // An exception was thrown and not caught by Java code
+  FERRUGO_STATUS_UNCAUGHT_EXCEPTION,
+  // A string passed is null or not valid UTF-8
+  FERRUGO_STATUS_INVALID_STRING,
+  // The VM failed unexpectedly. It must not be used any longer.
+  FERRUGO_STATUS_INTERNAL_ERROR,
+} FerrugoStatus;
+
+// The type of a ``FerrugoValue``
+typedef enum FerrugoType {
+  FERRUGO_TYPE_VOID = 0,
+  FERRUGO_TYPE_INT,
+  FERRUGO_TYPE_LONG,
+  FERRUGO_TYPE_FLOAT,
+  FERRUGO_TYPE_DOUBLE,
+  FERRUGO_TYPE_BOOLEAN,
+  // ``java.lang.String``. A null pointer is ``null``.
+  FERRUGO_TYPE_STRING,
+} FerrugoType;
+
+// A Java virtual machine
+typedef struct FerrugoVm FerrugoVm;
+
+typedef union FerrugoValueData {
+  int32_t int_value;
+  int64_t long_value;
+  float float_value;
+  double double_value;
+  bool boolean_value;
+  char *string_value;
+} FerrugoValueData;
+
+// An argument passed to or a value returned from Java. A returned string is owned by the caller
+// and must be freed with ``ferrugo_string_free``.
+typedef struct FerrugoValue {
+  enum FerrugoType ty;
+  union FerrugoValueData data;
+} FerrugoValue;
+
+// Creates a VM. The class library is searched for in ``./examples``.
+struct FerrugoVm *ferrugo_vm_new(void);
+
+// Destroys a VM after waiting for the Java threads to terminate.
+//
+// # Safety
+//
+// ``vm`` must be null or a VM returned by ``ferrugo_vm_new`` and not destroyed yet.
+void ferrugo_vm_destroy(struct FerrugoVm *vm);
+
+// Sets the directories to search for class files, separated by ``:``.
+//
+// # Safety
+//
+// ``vm`` must be a VM returned by ``ferrugo_vm_new`` and not destroyed yet, and ``classpath`` must
+// be null or point to a NUL-terminated string.
+enum FerrugoStatus ferrugo_vm_set_classpath(struct FerrugoVm *vm, const char *classpath);
+
+// Loads a class in the classpath (e.g. ``pkg/Main``).
+//
+// # Safety
+//
+// ``vm`` must be a VM returned by ``ferrugo_vm_new`` and not destroyed yet, and ``class_name``
+// must be null or point to a NUL-terminated string.
+enum FerrugoStatus ferrugo_vm_load_class(struct FerrugoVm *vm, const char *class_name);
+
+// Loads a class file not in the classpath. The name of the class is stored in ``class_name``
+// unless it's null.
+//
+// # Safety
+//
+// ``vm`` must be a VM returned by ``ferrugo_vm_new`` and not destroyed yet, and ``file_name`` must
+// be null or point to a NUL-terminated string. ``class_name`` must be null or valid for writes.
+enum FerrugoStatus ferrugo_vm_load_class_file(struct FerrugoVm *vm,
+                                              const char *file_name,
+                                              char **class_name);
+
+// Calls a static method. The returned value is stored in ``ret`` unless it's null.
+//
+// # Safety
+//
+// ``vm`` must be a VM returned by ``ferrugo_vm_new`` and not destroyed yet. The names and the
+// descriptor must be null or point to NUL-terminated strings, ``args`` must point to ``args_len``
+// values unless ``args_len`` is 0, and ``ret`` must be null or valid for writes.
+enum FerrugoStatus ferrugo_vm_invoke_static(struct FerrugoVm *vm,
+                                            const char *class_name,
+                                            const char *method_name,
+                                            const char *descriptor,
+                                            const struct FerrugoValue *args,
+                                            size_t args_len,
+                                            struct FerrugoValue *ret);
+
+// Returns the description of the last error, such as ``java.lang.IllegalStateException:
+// message`` for an uncaught exception, or null. It's valid until the next call with ``vm``.
+//
+// # Safety
+//
+// ``vm`` must be a VM returned by ``ferrugo_vm_new`` and not destroyed yet.
+const char *ferrugo_vm_last_error(const struct FerrugoVm *vm);
+
+// Frees a string returned by ferrugo.
+//
+// # Safety
+//
+// ``string`` must be null or a string returned by ferrugo and not freed yet.
+void ferrugo_string_free(char *string);
+
+#endif /* FERRUGO_H */
@@ -1,13 +1,20 @@
+use super::super::exec::call_site::CallSite;
 use super::super::exec::frame::{ObjectBody, VariableType};
-use super::super::exec::jit::{FuncJITExecInfo, LoopJITExecInfo};
+use super::super::exec::jit::{FuncJITExecInfo, JitMode, LoopJITExecInfo};
 use super::super::exec::objectheap::ObjectHeap;
+use super::super::exec::vm::{d2u, f2u};
 use super::super::gc::gc::GcType;
 use super::classfile::read::ClassFileReader;
 use super::classfile::{
-    classfile::ClassFile, constant::Constant, field::FieldInfo, method::MethodInfo,
+    attribute::{Attribute, BootstrapMethod},
+    classfile::ClassFile,
+    constant::Constant,
+    field::FieldInfo,
+    method::{access_flags, MethodInfo},
 };
 use super::classheap::ClassHeap;
 use rustc_hash::FxHashMap;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct JITInfoManager {
@@ -21,7 +28,20 @@ pub struct Class {
     pub classheap: Option<GcType<ClassHeap>>,
     pub static_variables: FxHashMap<String, u64>,
     pub fields: FxHashMap<String, (usize, VariableType)>,
-    pub jit_info_mgr: FxHashMap<(/*(name_index, descriptor_index)=*/ usize, usize), JITInfoManager>,
+    // Boxed since the interpreter keeps a reference to the manager of a running method while
+    // other methods are added
+    pub jit_info_mgr:
+        FxHashMap<(/*(name_index, descriptor_index)=*/ usize, usize), Box<JITInfoManager>>,
+    // Linked ``invokedynamic`` call sites keyed by the index of their ``InvokeDynamicInfo``
+    pub call_sites: FxHashMap<usize, Arc<CallSite>>,
+    // ``MethodHandle`` objects loaded by ``ldc`` keyed by the index of their ``MethodHandleInfo``
+    pub method_handles: FxHashMap<usize, u64>,
+}
+
+impl Default for Class {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Class {
@@ -32,13 +52,13 @@ impl Class {
             static_variables: FxHashMap::default(),
             fields: FxHashMap::default(),
             jit_info_mgr: FxHashMap::default(),
+            call_sites: FxHashMap::default(),
+            method_handles: FxHashMap::default(),
         }
     }
 
     pub fn get_static_variable(&self, name: &str) -> Option<u64> {
-        self.static_variables
-            .get(name)
-            .and_then(|var| Some(var.clone()))
+        self.static_variables.get(name).copied()
     }
 
     pub fn put_static_variable(&mut self, name: &str, val: u64) {
@@ -49,7 +69,6 @@ impl Class {
         let mut cf_reader = ClassFileReader::new(filename)?;
         let cf = cf_reader.read()?;
         self.classfile = cf;
-        self.number_fields();
         Some(())
     }
 
@@ -69,7 +88,11 @@ impl Class {
         self.classfile.constant_pool[index].get_utf8()
     }
 
-    pub fn get_java_string_utf8_from_const_pool(
+    /// # Safety
+    ///
+    /// ``objectheap`` must point to a valid object heap, and the class must belong to a class heap
+    /// in which ``java/lang/String`` is loaded.
+    pub unsafe fn get_java_string_utf8_from_const_pool(
         &mut self,
         objectheap: GcType<ObjectHeap>,
         index: usize,
@@ -86,8 +109,10 @@ impl Class {
             return Some(*java_string as u64);
         }
 
-        let jstring =
-            unsafe { &mut *objectheap }.create_string_object(s.clone(), self.classheap.unwrap());
+        // String literals are interned so that equal literals are the same object in any class
+        let classheap = self.classheap.unwrap();
+        let jstring = unsafe { &mut *classheap }
+            .intern(unsafe { (*objectheap).create_string_object(s.clone(), classheap) });
         *java_string = Some(jstring as GcType<ObjectBody>);
 
         Some(jstring)
@@ -185,8 +210,11 @@ impl Class {
         }
     }
 
-    fn number_fields(&mut self) {
-        let init_count = self.count_super_class_fields();
+    /// The super class must be loaded before calling this
+    pub fn number_fields(&mut self) {
+        let init_count = self.get_super_class().map_or(0, |super_class| {
+            unsafe { &*super_class }.get_object_field_count()
+        });
         for i in 0..self.classfile.fields_count as usize {
             let name = self.classfile.constant_pool[(self.classfile.fields[i].name_index) as usize]
                 .get_utf8()
@@ -195,6 +223,12 @@ impl Class {
                 [(self.classfile.fields[i].descriptor_index) as usize]
                 .get_utf8()
                 .unwrap();
+            // Static fields hold their ``ConstantValue`` if any, or zero (or null) until they are
+            // assigned
+            if self.classfile.fields[i].access_flags & access_flags::ACC_PACC_STATIC != 0 {
+                let value = self.get_constant_value(&self.classfile.fields[i]);
+                self.static_variables.insert(name.clone(), value);
+            }
             self.fields.insert(
                 name.clone(),
                 (
@@ -205,12 +239,25 @@ impl Class {
         }
     }
 
-    fn count_super_class_fields(&self) -> usize {
-        if let Some(super_class_ptr) = self.get_super_class() {
-            let super_class = unsafe { &*super_class_ptr };
-            return self.fields.len() + super_class.count_super_class_fields();
-        }
-        0
+    // Only numeric constants are set here. ``String`` ones stay null, which is fine as long as
+    // javac inlines them.
+    fn get_constant_value(&self, field: &FieldInfo) -> u64 {
+        field
+            .attributes
+            .iter()
+            .find_map(|attr| match attr.info {
+                Attribute::ConstantValue {
+                    constantvalue_index,
+                } => match self.classfile.constant_pool[constantvalue_index as usize] {
+                    Constant::IntegerInfo { i } => Some(i as u64),
+                    Constant::FloatInfo { f } => Some(f2u(f)),
+                    Constant::LongInfo { i } => Some(i as u64),
+                    Constant::DoubleInfo { f } => Some(d2u(f)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .unwrap_or(0)
     }
 
     pub fn get_super_class(&self) -> Option<GcType<Class>> {
@@ -218,6 +265,50 @@ impl Class {
         unsafe { &(*self.classheap.unwrap()) }.get_class(name)
     }
 
+    pub fn get_interface_names(&self) -> Vec<&String> {
+        // Each entry of ``interfaces`` holds an index of a ``ClassInfo`` in the constant pool
+        self.classfile
+            .interfaces
+            .iter()
+            .filter_map(|interface| {
+                let const_class =
+                    &self.classfile.constant_pool[interface.get_class_name_index()?];
+                self.classfile.constant_pool[const_class.get_class_name_index()?].get_utf8()
+            })
+            .collect()
+    }
+
+    /// Returns true if this class is ``class_name``, extends it or implements it
+    pub fn is_subclass_of(&self, class_name: &str) -> bool {
+        if self.get_name().unwrap() == class_name {
+            return true;
+        }
+        let classheap = unsafe { &*self.classheap.unwrap() };
+        let implements = self.get_interface_names().iter().any(|name| {
+            *name == class_name
+                || classheap
+                    .get_class(name)
+                    .is_some_and(|interface| unsafe { &*interface }.is_subclass_of(class_name))
+        });
+        implements
+            || self
+                .get_super_class()
+                .is_some_and(|class| unsafe { &*class }.is_subclass_of(class_name))
+    }
+
+    pub fn get_bootstrap_method(&self, index: usize) -> Option<&BootstrapMethod> {
+        self.classfile
+            .attributes
+            .iter()
+            .find_map(|attr| match attr.info {
+                Attribute::BootstrapMethods {
+                    ref bootstrap_methods,
+                    ..
+                } => bootstrap_methods.get(index),
+                _ => None,
+            })
+    }
+
     pub fn get_object_field_count(&self) -> usize {
         let mut count = self.classfile.fields_count as usize;
         if let Some(super_class) = self.get_super_class() {
@@ -233,7 +324,13 @@ impl Class {
     ) -> &mut JITInfoManager {
         self.jit_info_mgr
             .entry((name_index, descriptor_index))
-            .or_insert(JITInfoManager::new())
+            .or_insert_with(|| Box::new(JITInfoManager::new()))
+    }
+}
+
+impl Default for JITInfoManager {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -255,20 +352,26 @@ impl JITInfoManager {
         *count += 1;
     }
 
-    pub fn loop_executed_enough_times(&self, start: usize) -> bool {
+    pub fn loop_executed_enough_times(&self, start: usize, mode: JitMode) -> bool {
         let (_, count, _) = self.loop_count.get(&start).unwrap();
-        *count > 7
-        // false
+        match mode {
+            JitMode::Mixed => *count > 7,
+            JitMode::Interpreted => false,
+            JitMode::Compiled => true,
+        }
     }
 
-    pub fn func_executed_enough_times(&self) -> bool {
+    pub fn func_executed_enough_times(&self, mode: JitMode) -> bool {
         let (count, _) = &self.whole_method;
-        *count > 4
-        // false
+        match mode {
+            JitMode::Mixed => *count > 4,
+            JitMode::Interpreted => false,
+            JitMode::Compiled => true,
+        }
     }
 
     pub fn get_jit_loop(&mut self, start: usize) -> &mut Option<LoopJITExecInfo> {
-        &mut (*self.loop_count.get_mut(&start).unwrap()).2
+        &mut self.loop_count.get_mut(&start).unwrap().2
     }
 
     pub fn get_jit_func(&mut self) -> &mut Option<FuncJITExecInfo> {
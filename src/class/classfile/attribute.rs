@@ -45,6 +45,21 @@ pub enum Attribute {
         num_annotations: u16,
         annotations: Vec<Annotation>,
     },
+    RuntimeInvisibleAnnotations {
+        num_annotations: u16,
+        annotations: Vec<Annotation>,
+    },
+    RuntimeVisibleParameterAnnotations {
+        num_parameters: u8,
+        parameter_annotations: Vec<ParameterAnnotations>,
+    },
+    RuntimeInvisibleParameterAnnotations {
+        num_parameters: u8,
+        parameter_annotations: Vec<ParameterAnnotations>,
+    },
+    AnnotationDefault {
+        default_value: ElementValue,
+    },
     InnerClasses {
         number_of_classes: u16,
         classes: Vec<InnerClassesBody>,
@@ -52,6 +67,21 @@ pub enum Attribute {
     ConstantValue {
         constantvalue_index: u16,
     },
+    BootstrapMethods {
+        num_bootstrap_methods: u16,
+        bootstrap_methods: Vec<BootstrapMethod>,
+    },
+    // Attributes the VM doesn't use, like ``NestHost``, are kept as they are (JVMS 4.7)
+    Unknown {
+        info: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct BootstrapMethod {
+    pub bootstrap_method_ref: u16,
+    pub num_bootstrap_arguments: u16,
+    pub bootstrap_arguments: Vec<u16>,
 }
 
 #[derive(Debug, Clone)]
@@ -88,6 +118,10 @@ pub enum StackMapFrameBody {
     SameLocals1StackItemFrame {
         stack: VerificationTypeInfo,
     },
+    SameLocals1StackItemFrameExtended {
+        offset_delta: u16,
+        stack: VerificationTypeInfo,
+    },
     AppendFrame {
         offset_delta: u16,
         locals: Vec<VerificationTypeInfo>,
@@ -117,7 +151,7 @@ pub enum VerificationTypeInfo {
     Null,
     UninitializedThis,
     Object { cpool_index: u16 },
-    Uninitialized,
+    Uninitialized { offset: u16 },
 }
 
 #[derive(Clone, Debug)]
@@ -135,7 +169,29 @@ pub struct ElementValuePair {
 
 #[derive(Clone, Debug)]
 pub struct ElementValue {
-    // TODO: Implement
+    pub tag: u8,
+    pub value: ElementValueBody,
+}
+
+#[derive(Clone, Debug)]
+pub enum ElementValueBody {
+    ConstValueIndex(u16),
+    EnumConstValue {
+        type_name_index: u16,
+        const_name_index: u16,
+    },
+    ClassInfoIndex(u16),
+    AnnotationValue(Annotation),
+    ArrayValue {
+        num_values: u16,
+        values: Vec<ElementValue>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct ParameterAnnotations {
+    pub num_annotations: u16,
+    pub annotations: Vec<Annotation>,
 }
 
 impl CodeAttribute {
@@ -166,6 +222,11 @@ impl CodeAttribute {
                 Inst::iconst_3 => println!("iconst_3"),
                 Inst::iconst_4 => println!("iconst_4"),
                 Inst::iconst_5 => println!("iconst_5"),
+                Inst::lconst_0 => println!("lconst_0"),
+                Inst::lconst_1 => println!("lconst_1"),
+                Inst::fconst_0 => println!("fconst_0"),
+                Inst::fconst_1 => println!("fconst_1"),
+                Inst::fconst_2 => println!("fconst_2"),
                 Inst::dconst_0 => println!("dconst_0"),
                 Inst::dconst_1 => println!("dconst_1"),
                 Inst::bipush => println!("bipush"),
@@ -173,28 +234,51 @@ impl CodeAttribute {
                 Inst::ldc => println!("ldc"),
                 Inst::ldc2_w => println!("ldc2_w"),
                 Inst::iload => println!("iload"),
+                Inst::lload => println!("lload"),
+                Inst::fload => println!("fload"),
                 Inst::dload => println!("dload"),
                 Inst::aload_0 => println!("aload_0"),
                 Inst::aload_1 => println!("aload_1"),
                 Inst::aload_2 => println!("aload_2"),
                 Inst::aload_3 => println!("aload_3"),
                 Inst::istore => println!("istore"),
+                Inst::lstore => println!("lstore"),
+                Inst::fstore => println!("fstore"),
                 Inst::istore_0 => println!("istore_0"),
                 Inst::istore_1 => println!("istore_1"),
                 Inst::istore_2 => println!("istore_2"),
                 Inst::istore_3 => println!("istore_3"),
+                Inst::lstore_0 => println!("lstore_0"),
+                Inst::lstore_1 => println!("lstore_1"),
+                Inst::lstore_2 => println!("lstore_2"),
+                Inst::lstore_3 => println!("lstore_3"),
+                Inst::fstore_0 => println!("fstore_0"),
+                Inst::fstore_1 => println!("fstore_1"),
+                Inst::fstore_2 => println!("fstore_2"),
+                Inst::fstore_3 => println!("fstore_3"),
                 Inst::aload => println!("aload"),
                 Inst::iload_0 => println!("iload_0"),
                 Inst::iload_1 => println!("iload_1"),
                 Inst::iload_2 => println!("iload_2"),
                 Inst::iload_3 => println!("iload_3"),
+                Inst::lload_0 => println!("lload_0"),
+                Inst::lload_1 => println!("lload_1"),
+                Inst::lload_2 => println!("lload_2"),
+                Inst::lload_3 => println!("lload_3"),
+                Inst::fload_0 => println!("fload_0"),
+                Inst::fload_1 => println!("fload_1"),
+                Inst::fload_2 => println!("fload_2"),
+                Inst::fload_3 => println!("fload_3"),
                 Inst::dload_0 => println!("dload_0"),
                 Inst::dload_1 => println!("dload_1"),
                 Inst::dload_2 => println!("dload_2"),
                 Inst::dload_3 => println!("dload_3"),
                 Inst::iaload => println!("iaload"),
+                Inst::laload => println!("laload"),
+                Inst::faload => println!("faload"),
                 Inst::daload => println!("daload"),
                 Inst::aaload => println!("aaload"),
+                Inst::baload => println!("baload"),
                 Inst::dstore => println!("dstore"),
                 Inst::astore => println!("astore"),
                 Inst::dstore_0 => println!("dstore_0"),
@@ -206,8 +290,11 @@ impl CodeAttribute {
                 Inst::astore_2 => println!("astore_2"),
                 Inst::astore_3 => println!("astore_3"),
                 Inst::iastore => println!("iastore"),
+                Inst::lastore => println!("lastore"),
+                Inst::fastore => println!("fastore"),
                 Inst::dastore => println!("dastore"),
                 Inst::aastore => println!("aastore"),
+                Inst::bastore => println!("bastore"),
                 Inst::pop => println!("pop"),
                 Inst::pop2 => println!("pop2"),
                 Inst::dup => println!("dup"),
@@ -215,38 +302,77 @@ impl CodeAttribute {
                 Inst::dup2 => println!("dup2"),
                 Inst::dup2_x1 => println!("dup2_x1"),
                 Inst::iadd => println!("iadd"),
+                Inst::ladd => println!("ladd"),
+                Inst::fadd => println!("fadd"),
                 Inst::dadd => println!("dadd"),
                 Inst::isub => println!("isub"),
+                Inst::lsub => println!("lsub"),
+                Inst::fsub => println!("fsub"),
                 Inst::dsub => println!("dsub"),
                 Inst::imul => println!("imul"),
+                Inst::lmul => println!("lmul"),
+                Inst::fmul => println!("fmul"),
                 Inst::dmul => println!("dmul"),
                 Inst::idiv => println!("idiv"),
+                Inst::ldiv => println!("ldiv"),
+                Inst::fdiv => println!("fdiv"),
                 Inst::ddiv => println!("ddiv"),
                 Inst::irem => println!("irem"),
+                Inst::lrem => println!("lrem"),
+                Inst::frem => println!("frem"),
+                Inst::lneg => println!("lneg"),
+                Inst::fneg => println!("fneg"),
                 Inst::dneg => println!("dneg"),
                 Inst::ishl => println!("ishl"),
+                Inst::lshl => println!("lshl"),
                 Inst::ishr => println!("ishr"),
+                Inst::lshr => println!("lshr"),
+                Inst::lushr => println!("lushr"),
                 Inst::iand => println!("iand"),
+                Inst::land => println!("land"),
+                Inst::lor => println!("lor"),
                 Inst::ixor => println!("ixor"),
+                Inst::lxor => println!("lxor"),
                 Inst::iinc => println!("iinc"),
+                Inst::i2l => println!("i2l"),
+                Inst::i2f => println!("i2f"),
                 Inst::i2d => println!("i2d"),
+                Inst::l2i => println!("l2i"),
+                Inst::l2f => println!("l2f"),
+                Inst::l2d => println!("l2d"),
+                Inst::f2i => println!("f2i"),
+                Inst::f2l => println!("f2l"),
+                Inst::f2d => println!("f2d"),
                 Inst::d2i => println!("d2i"),
+                Inst::d2l => println!("d2l"),
+                Inst::d2f => println!("d2f"),
                 Inst::i2s => println!("i2s"),
+                Inst::lcmp => println!("lcmp"),
+                Inst::fcmpl => println!("fcmpl"),
+                Inst::fcmpg => println!("fcmpg"),
                 Inst::dcmpl => println!("dcmpl"),
                 Inst::dcmpg => println!("dcmpg"),
                 Inst::ifeq => println!("ifeq"),
                 Inst::ifne => println!("ifne"),
                 Inst::iflt => println!("iflt"),
                 Inst::ifge => println!("ifge"),
+                Inst::ifgt => println!("ifgt"),
                 Inst::ifle => println!("ifle"),
                 Inst::if_icmpeq => println!("if_icmpeq"),
                 Inst::if_icmpne => println!("if_icmpne"),
                 Inst::if_icmpge => println!("if_icmpge"),
                 Inst::if_icmpgt => println!("if_icmpgt"),
                 Inst::if_icmplt => println!("if_icmplt"),
+                Inst::if_icmple => println!("if_icmple"),
                 Inst::if_acmpne => println!("if_acmpne"),
                 Inst::goto => println!("goto"),
+                Inst::jsr => println!("jsr"),
+                Inst::ret => println!("ret"),
+                Inst::tableswitch => println!("tableswitch"),
+                Inst::lookupswitch => println!("lookupswitch"),
                 Inst::ireturn => println!("ireturn"),
+                Inst::lreturn => println!("lreturn"),
+                Inst::freturn => println!("freturn"),
                 Inst::dreturn => println!("dreturn"),
                 Inst::areturn => println!("areturn"),
                 Inst::return_ => println!("return_"),
@@ -257,13 +383,38 @@ impl CodeAttribute {
                 Inst::invokevirtual => println!("invokevirtual"),
                 Inst::invokespecial => println!("invokespecial"),
                 Inst::invokestatic => println!("invokestatic"),
+                Inst::invokeinterface => println!("invokeinterface"),
+                Inst::invokedynamic => println!("invokedynamic"),
                 Inst::new => println!("new"),
                 Inst::newarray => println!("newarray"),
                 Inst::anewarray => println!("anewarray"),
+                Inst::multianearray => println!("multianewarray"),
                 Inst::arraylength => println!("arraylength"),
+                Inst::athrow => println!("athrow"),
+                Inst::checkcast => println!("checkcast"),
+                Inst::instanceof => println!("instanceof"),
                 Inst::monitorenter => println!("monitorenter"),
+                Inst::monitorexit => println!("monitorexit"),
                 Inst::ifnull => println!("ifnull"),
                 Inst::ifnonnull => println!("ifnonnull"),
+                Inst::jsr_w => println!("jsr_w"),
+                Inst::nop => println!("nop"),
+                Inst::ldc_w => println!("ldc_w"),
+                Inst::caload => println!("caload"),
+                Inst::saload => println!("saload"),
+                Inst::castore => println!("castore"),
+                Inst::sastore => println!("sastore"),
+                Inst::dup_x2 => println!("dup_x2"),
+                Inst::dup2_x2 => println!("dup2_x2"),
+                Inst::swap => println!("swap"),
+                Inst::drem => println!("drem"),
+                Inst::ineg => println!("ineg"),
+                Inst::iushr => println!("iushr"),
+                Inst::ior => println!("ior"),
+                Inst::i2b => println!("i2b"),
+                Inst::i2c => println!("i2c"),
+                Inst::if_acmpeq => println!("if_acmpeq"),
+                Inst::wide => println!("wide"),
                 Inst::getfield_quick => println!("getfield_quick"),
                 Inst::putfield_quick => println!("putfield_quick"),
                 Inst::getfield2_quick => println!("getfield2_quick"),
@@ -271,7 +422,7 @@ impl CodeAttribute {
                 _ => unreachable!(),
             }
 
-            pc += Inst::get_inst_size(cur_code);
+            pc += Inst::get_inst_size_at(code, pc, 0);
         }
     }
 }
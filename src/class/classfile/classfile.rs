@@ -23,6 +23,12 @@ pub struct ClassFile {
     pub attributes: Vec<AttributeInfo>,
 }
 
+impl Default for ClassFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ClassFile {
     pub fn new() -> Self {
         ClassFile {
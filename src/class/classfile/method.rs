@@ -10,6 +10,12 @@ pub struct MethodInfo {
     pub code: Option<CodeAttribute>,
 }
 
+impl Default for MethodInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MethodInfo {
     pub fn new() -> Self {
         MethodInfo {
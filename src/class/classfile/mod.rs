@@ -1,4 +1,5 @@
 pub mod attribute;
+#[allow(clippy::module_inception)]
 pub mod classfile;
 pub mod constant;
 pub mod field;
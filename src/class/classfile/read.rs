@@ -1,6 +1,7 @@
 use super::attribute::{
-    Annotation, Attribute, AttributeInfo, CodeAttribute, ElementValue, ElementValuePair, Exception,
-    InnerClassesBody, LineNumber, StackMapFrame, StackMapFrameBody, VerificationTypeInfo,
+    Annotation, Attribute, AttributeInfo, BootstrapMethod, CodeAttribute, ElementValue,
+    ElementValueBody, ElementValuePair, Exception, InnerClassesBody, LineNumber,
+    ParameterAnnotations, StackMapFrame, StackMapFrameBody, VerificationTypeInfo,
 };
 use super::classfile::ClassFile;
 use super::constant;
@@ -9,7 +10,6 @@ use super::field::FieldInfo;
 use super::method::MethodInfo;
 use std::fs::File;
 use std::io::{BufReader, Read};
-use std::mem::transmute;
 
 #[derive(Debug)]
 pub struct ClassFileReader {
@@ -218,7 +218,7 @@ impl ClassFileReader {
             bytes.push(self.read_u8()?);
         }
         Some(Constant::Utf8 {
-            s: String::from_utf8(bytes).ok()?,
+            s: String::from_utf16_lossy(&decode_modified_utf8(&bytes)),
             java_string: None,
         })
     }
@@ -231,7 +231,7 @@ impl ClassFileReader {
     fn read_constant_float_info(&mut self) -> Option<Constant> {
         let bytes = self.read_u32()?;
         Some(Constant::FloatInfo {
-            f: unsafe { transmute::<u32, f32>(bytes) },
+            f: f32::from_bits(bytes),
         })
     }
 
@@ -247,7 +247,7 @@ impl ClassFileReader {
         let high_bytes = self.read_u32()?;
         let low_bytes = self.read_u32()?;
         Some(Constant::DoubleInfo {
-            f: unsafe { transmute::<u64, f64>(((high_bytes as u64) << 32) + low_bytes as u64) },
+            f: f64::from_bits(((high_bytes as u64) << 32) + low_bytes as u64),
         })
     }
 
@@ -340,9 +340,18 @@ impl ClassFileReader {
             "Exceptions" => self.read_exceptions_attribute()?,
             "Deprecated" => self.read_deprecated_attribute()?,
             "RuntimeVisibleAnnotations" => self.read_runtime_visible_annotations_attribute()?,
+            "RuntimeInvisibleAnnotations" => self.read_runtime_invisible_annotations_attribute()?,
+            "RuntimeVisibleParameterAnnotations" => {
+                self.read_runtime_visible_parameter_annotations_attribute()?
+            }
+            "RuntimeInvisibleParameterAnnotations" => {
+                self.read_runtime_invisible_parameter_annotations_attribute()?
+            }
+            "AnnotationDefault" => self.read_annotation_default_attribute()?,
             "InnerClasses" => self.read_inner_classes_attribute()?,
             "ConstantValue" => self.read_constant_value_attribute()?,
-            e => unimplemented!("{}", e),
+            "BootstrapMethods" => self.read_bootstrap_methods_attribute()?,
+            _ => self.read_unknown_attribute(attribute_length)?,
         };
         Some(AttributeInfo {
             attribute_name_index,
@@ -351,6 +360,12 @@ impl ClassFileReader {
         })
     }
 
+    fn read_unknown_attribute(&mut self, attribute_length: u32) -> Option<Attribute> {
+        let mut info = vec![0; attribute_length as usize];
+        self.reader.read_exact(&mut info).ok()?;
+        Some(Attribute::Unknown { info })
+    }
+
     fn read_code_attribute(&mut self, constant_pool: &Vec<Constant>) -> Option<Attribute> {
         let max_stack = self.read_u16()?;
         let max_locals = self.read_u16()?;
@@ -432,17 +447,42 @@ impl ClassFileReader {
     }
 
     fn read_runtime_visible_annotations_attribute(&mut self) -> Option<Attribute> {
-        let num_annotations = self.read_u16()?;
-        let mut annotations = vec![];
-        for _ in 0..num_annotations {
-            annotations.push(self.read_annotation()?);
-        }
+        let (num_annotations, annotations) = self.read_annotations()?;
         Some(Attribute::RuntimeVisibleAnnotations {
             num_annotations,
             annotations,
         })
     }
 
+    fn read_runtime_invisible_annotations_attribute(&mut self) -> Option<Attribute> {
+        let (num_annotations, annotations) = self.read_annotations()?;
+        Some(Attribute::RuntimeInvisibleAnnotations {
+            num_annotations,
+            annotations,
+        })
+    }
+
+    fn read_runtime_visible_parameter_annotations_attribute(&mut self) -> Option<Attribute> {
+        let (num_parameters, parameter_annotations) = self.read_parameter_annotations()?;
+        Some(Attribute::RuntimeVisibleParameterAnnotations {
+            num_parameters,
+            parameter_annotations,
+        })
+    }
+
+    fn read_runtime_invisible_parameter_annotations_attribute(&mut self) -> Option<Attribute> {
+        let (num_parameters, parameter_annotations) = self.read_parameter_annotations()?;
+        Some(Attribute::RuntimeInvisibleParameterAnnotations {
+            num_parameters,
+            parameter_annotations,
+        })
+    }
+
+    fn read_annotation_default_attribute(&mut self) -> Option<Attribute> {
+        let default_value = self.read_element_value()?;
+        Some(Attribute::AnnotationDefault { default_value })
+    }
+
     fn read_inner_classes_attribute(&mut self) -> Option<Attribute> {
         let number_of_classes = self.read_u16()?;
         let mut classes = vec![];
@@ -462,6 +502,32 @@ impl ClassFileReader {
         })
     }
 
+    fn read_bootstrap_methods_attribute(&mut self) -> Option<Attribute> {
+        let num_bootstrap_methods = self.read_u16()?;
+        let mut bootstrap_methods = vec![];
+        for _ in 0..num_bootstrap_methods {
+            bootstrap_methods.push(self.read_bootstrap_method()?)
+        }
+        Some(Attribute::BootstrapMethods {
+            num_bootstrap_methods,
+            bootstrap_methods,
+        })
+    }
+
+    fn read_bootstrap_method(&mut self) -> Option<BootstrapMethod> {
+        let bootstrap_method_ref = self.read_u16()?;
+        let num_bootstrap_arguments = self.read_u16()?;
+        let mut bootstrap_arguments = vec![];
+        for _ in 0..num_bootstrap_arguments {
+            bootstrap_arguments.push(self.read_u16()?)
+        }
+        Some(BootstrapMethod {
+            bootstrap_method_ref,
+            num_bootstrap_arguments,
+            bootstrap_arguments,
+        })
+    }
+
     fn read_classes(&mut self) -> Option<InnerClassesBody> {
         let inner_class_info_index = self.read_u16()?;
         let outer_class_info_index = self.read_u16()?;
@@ -475,6 +541,28 @@ impl ClassFileReader {
         })
     }
 
+    fn read_annotations(&mut self) -> Option<(u16, Vec<Annotation>)> {
+        let num_annotations = self.read_u16()?;
+        let mut annotations = vec![];
+        for _ in 0..num_annotations {
+            annotations.push(self.read_annotation()?);
+        }
+        Some((num_annotations, annotations))
+    }
+
+    fn read_parameter_annotations(&mut self) -> Option<(u8, Vec<ParameterAnnotations>)> {
+        let num_parameters = self.read_u8()?;
+        let mut parameter_annotations = vec![];
+        for _ in 0..num_parameters {
+            let (num_annotations, annotations) = self.read_annotations()?;
+            parameter_annotations.push(ParameterAnnotations {
+                num_annotations,
+                annotations,
+            });
+        }
+        Some((num_parameters, parameter_annotations))
+    }
+
     fn read_annotation(&mut self) -> Option<Annotation> {
         let type_index = self.read_u16()?;
         let num_element_value_pairs = self.read_u16()?;
@@ -499,18 +587,51 @@ impl ClassFileReader {
     }
 
     fn read_element_value(&mut self) -> Option<ElementValue> {
-        unimplemented!()
+        let tag = self.read_u8()?;
+        let value = match tag {
+            b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b's' => {
+                ElementValueBody::ConstValueIndex(self.read_u16()?)
+            }
+            b'e' => {
+                let type_name_index = self.read_u16()?;
+                let const_name_index = self.read_u16()?;
+                ElementValueBody::EnumConstValue {
+                    type_name_index,
+                    const_name_index,
+                }
+            }
+            b'c' => ElementValueBody::ClassInfoIndex(self.read_u16()?),
+            b'@' => ElementValueBody::AnnotationValue(self.read_annotation()?),
+            b'[' => {
+                let num_values = self.read_u16()?;
+                let mut values = vec![];
+                for _ in 0..num_values {
+                    values.push(self.read_element_value()?);
+                }
+                ElementValueBody::ArrayValue { num_values, values }
+            }
+            _ => return None,
+        };
+        Some(ElementValue { tag, value })
     }
 
     fn read_stack_map_frame(&mut self) -> Option<StackMapFrame> {
         let frame_type = self.read_u8()?;
         let body = match frame_type {
-            0...63 => StackMapFrameBody::SameFrame,
-            64...127 => {
+            0..=63 => StackMapFrameBody::SameFrame,
+            64..=127 => {
                 let stack = self.read_verification_type_info()?;
                 StackMapFrameBody::SameLocals1StackItemFrame { stack }
             }
-            252...254 => {
+            247 => {
+                let offset_delta = self.read_u16()?;
+                let stack = self.read_verification_type_info()?;
+                StackMapFrameBody::SameLocals1StackItemFrameExtended {
+                    offset_delta,
+                    stack,
+                }
+            }
+            252..=254 => {
                 let offset_delta = self.read_u16()?;
                 let mut locals = vec![];
                 for _ in 0..(frame_type - 251) {
@@ -521,7 +642,7 @@ impl ClassFileReader {
                     locals,
                 }
             }
-            248...250 => {
+            248..=250 => {
                 let offset_delta = self.read_u16()?;
                 StackMapFrameBody::ChopFrame { offset_delta }
             }
@@ -549,8 +670,8 @@ impl ClassFileReader {
                     stack,
                 }
             }
-            // TODO: Implement all frame types
-            e => unimplemented!("{}", e),
+            // 128-246 are reserved
+            _ => return None,
         };
         Some(StackMapFrame { frame_type, body })
     }
@@ -563,11 +684,17 @@ impl ClassFileReader {
             2 => Some(VerificationTypeInfo::Float),
             3 => Some(VerificationTypeInfo::Double),
             4 => Some(VerificationTypeInfo::Long),
+            5 => Some(VerificationTypeInfo::Null),
+            6 => Some(VerificationTypeInfo::UninitializedThis),
             7 => {
                 let cpool_index = self.read_u16()?;
                 Some(VerificationTypeInfo::Object { cpool_index })
             }
-            e => unimplemented!("verification type info {}", e),
+            8 => {
+                let offset = self.read_u16()?;
+                Some(VerificationTypeInfo::Uninitialized { offset })
+            }
+            _ => None,
         }
     }
 
@@ -626,3 +753,41 @@ impl ClassFileReader {
         }
     }
 }
+
+/// Decodes modified UTF-8 into UTF-16 code units. NUL is encoded in two bytes, and supplementary
+/// characters as two three-byte surrogates. Four-byte sequences of standard UTF-8 are accepted as
+/// well. Malformed sequences are replaced with U+FFFD.
+pub fn decode_modified_utf8(bytes: &[u8]) -> Vec<u16> {
+    let mut chars = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i] as u32;
+        let (len, init) = match b {
+            0x00..=0x7f => (1, b),
+            0xc0..=0xdf => (2, b & 0x1f),
+            0xe0..=0xef => (3, b & 0x0f),
+            0xf0..=0xf7 => (4, b & 0x07),
+            _ => (0, 0),
+        };
+        let continuation = bytes
+            .get(i + 1..i + len)
+            .filter(|rest| len > 0 && rest.iter().all(|b| b & 0xc0 == 0x80));
+        match continuation {
+            Some(rest) => {
+                let c = rest.iter().fold(init, |c, b| (c << 6) | (*b as u32 & 0x3f));
+                if c >= 0x10000 {
+                    chars.push((0xd800 + ((c - 0x10000) >> 10)) as u16);
+                    chars.push((0xdc00 + ((c - 0x10000) & 0x3ff)) as u16);
+                } else {
+                    chars.push(c as u16);
+                }
+                i += len;
+            }
+            None => {
+                chars.push(0xfffd);
+                i += 1;
+            }
+        }
+    }
+    chars
+}
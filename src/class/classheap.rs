@@ -1,3 +1,4 @@
+use super::super::exec::frame::ObjectBody;
 use super::super::gc::gc::GcType;
 use super::class::Class;
 use rustc_hash::FxHashMap;
@@ -5,29 +6,53 @@ use rustc_hash::FxHashMap;
 #[derive(Debug, Clone)]
 pub struct ClassHeap {
     pub class_map: FxHashMap<String, GcType<Class>>,
+    // ``java.lang.Class`` objects keyed by the descriptors of their types
+    pub mirrors: FxHashMap<String, u64>,
+    // Interned ``String`` objects keyed by their UTF-16 code units. They are strong GC roots, so
+    // an interned string is never collected, even when nothing else refers to it.
+    pub strings: FxHashMap<Vec<u16>, u64>,
+}
+
+impl Default for ClassHeap {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ClassHeap {
     pub fn new() -> Self {
         ClassHeap {
             class_map: FxHashMap::default(),
+            mirrors: FxHashMap::default(),
+            strings: FxHashMap::default(),
         }
     }
 
     pub fn get_class(&self, class_name: &str) -> Option<GcType<Class>> {
-        self.class_map
-            .get(class_name)
-            .and_then(|class| Some(*class))
+        self.class_map.get(class_name).copied()
     }
 
-    pub fn load_class(&mut self, class_name: &str, class: GcType<Class>) -> Option<()> {
+    /// # Safety
+    ///
+    /// ``class`` must point to a valid class.
+    pub unsafe fn load_class(&mut self, class_name: &str, class: GcType<Class>) -> Option<()> {
         let class = unsafe { &mut *class };
         class.load_classfile(class_name)?;
         self.add_class(class);
         Some(())
     }
 
-    pub fn add_class(&mut self, class: GcType<Class>) -> Option<()> {
+    /// Returns the canonical ``String`` equal to ``string``. If there's none yet, ``string``
+    /// itself becomes canonical and lives as long as the VM.
+    pub fn intern(&mut self, string: u64) -> u64 {
+        let chars = unsafe { &*(string as GcType<ObjectBody>) }.get_string_chars();
+        *self.strings.entry(chars.to_vec()).or_insert(string)
+    }
+
+    /// # Safety
+    ///
+    /// ``class`` must point to a valid class that lives as long as the class heap.
+    pub unsafe fn add_class(&mut self, class: GcType<Class>) -> Option<()> {
         let class = unsafe { &mut *class };
         self.class_map.insert(class.get_name()?.to_owned(), class);
         Some(())
@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod class;
 pub mod classfile;
 pub mod classheap;
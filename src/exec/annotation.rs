@@ -0,0 +1,719 @@
+// Annotations read by reflection. An annotation is an instance of a class spun to implement the
+// annotation interface, whose methods return the values in ``RuntimeVisibleAnnotations`` or the
+// defaults of the interface.
+// https://docs.oracle.com/javase/8/docs/api/java/lang/annotation/Annotation.html
+
+use super::super::class::{
+    class::Class,
+    classfile::{
+        attribute::{
+            Annotation, Attribute, AttributeInfo, CodeAttribute, ElementValue, ElementValueBody,
+        },
+        classfile::ClassFile,
+        constant::Constant,
+        field::FieldInfo,
+        method::{access_flags, MethodInfo},
+    },
+};
+use super::super::gc::gc::GcType;
+use super::frame::{Array, ObjectBody};
+use super::lambda::{class_name, parse_descriptor, push_u16, return_inst, ConstantPool};
+use super::method_handle::{get_field, set_field};
+use super::mirror;
+use super::number;
+use super::reflect;
+use super::string_concat;
+use super::vm::{d2u, f2u, u2d, u2f, Inst, VM};
+
+/// ``getDeclaredAnnotations`` of a class, a method or a field with ``attributes`` in ``class``
+pub fn declared_annotations(
+    vm: &mut VM,
+    class: GcType<Class>,
+    attributes: &[AttributeInfo],
+) -> u64 {
+    let annotations = visible_annotations(vm, class, attributes)
+        .into_iter()
+        .map(|annotation| (class, annotation))
+        .collect();
+    new_annotation_array(vm, annotations)
+}
+
+/// ``Class.getAnnotations`` and ``getDeclaredAnnotations`` (``declared_only``). Annotations of
+/// the super classes are included if their types are ``@Inherited`` and not present on the
+/// subclasses.
+pub fn class_annotations(vm: &mut VM, mirror: u64, declared_only: bool) -> u64 {
+    let mut annotations: Vec<(GcType<Class>, Annotation)> = vec![];
+    let mut class = mirror::class(vm, mirror);
+    let mut inherited_only = false;
+    while let Some(cur_class) = class {
+        let attributes = &unsafe { &*cur_class }.classfile.attributes;
+        for annotation in visible_annotations(vm, cur_class, attributes) {
+            let ty = type_of(cur_class, &annotation);
+            let present = annotations
+                .iter()
+                .any(|(class, annotation)| type_of(*class, annotation) == ty);
+            if present || (inherited_only && !is_inherited(vm, ty)) {
+                continue;
+            }
+            annotations.push((cur_class, annotation));
+        }
+        class = if declared_only {
+            None
+        } else {
+            unsafe { &*cur_class }.get_super_class()
+        };
+        inherited_only = true;
+    }
+    new_annotation_array(vm, annotations)
+}
+
+/// ``Method.getParameterAnnotations``. The array has an element for each parameter, empty if the
+/// parameter has no annotations.
+pub fn parameter_annotations(vm: &mut VM, class: GcType<Class>, method: &MethodInfo) -> u64 {
+    let descriptor = unsafe { &*class }
+        .get_utf8_from_const_pool(method.descriptor_index as usize)
+        .unwrap();
+    let (params, _) = parse_descriptor(descriptor);
+    let mut parameter_annotations = vec![vec![]; params.len()];
+    for attribute in &method.attributes {
+        if let Attribute::RuntimeVisibleParameterAnnotations {
+            parameter_annotations: ref annotations,
+            ..
+        } = attribute.info
+        {
+            // Synthetic parameters like the outer instance of an inner class may not be counted
+            let skipped = params.len().saturating_sub(annotations.len());
+            for (i, annotations) in annotations.iter().enumerate().take(params.len()) {
+                parameter_annotations[skipped + i] = annotations
+                    .annotations
+                    .iter()
+                    .filter(|annotation| prepare(vm, class, annotation))
+                    .cloned()
+                    .collect();
+            }
+        }
+    }
+
+    let arrays: Vec<u64> = parameter_annotations
+        .into_iter()
+        .map(|annotations| {
+            let annotations = annotations
+                .into_iter()
+                .map(|annotation| new_annotation(vm, class, &annotation))
+                .collect::<Vec<_>>();
+            new_array(vm, &annotations)
+        })
+        .collect();
+    let atype = vm.resolve_class_name("[[Ljava/lang/annotation/Annotation;");
+    let array = unsafe { &mut *vm.objectheap }.create_array(atype, arrays.len());
+    for (i, annotations) in arrays.iter().enumerate() {
+        unsafe { &mut *(array as GcType<Array>) }.store(i as isize, *annotations);
+    }
+    array
+}
+
+/// ``toString`` of an annotation, like ``@Range(min=1, message="too big")``. The values are
+/// written as in source code. Returns None if ``toString`` of a value throws an exception.
+pub fn to_string(vm: &mut VM, annotation: u64) -> Option<String> {
+    let interface = annotation_interface(annotation);
+    let mut elements = vec![];
+    for (name, ty) in elements_of(interface) {
+        let value = get_field(annotation, name.as_str());
+        elements.push(format!(
+            "{}={}",
+            name,
+            value_to_string(vm, value, ty.as_str())?
+        ));
+    }
+    Some(format!(
+        "@{}({})",
+        unsafe { &*interface }.get_name().unwrap().replace("/", "."),
+        elements.join(", ")
+    ))
+}
+
+/// ``equals`` of an annotation. True if ``other`` is an instance of the same annotation
+/// interface and the values of all the elements are equal. Floating-point values are equal if
+/// ``Float.equals`` or ``Double.equals`` says so, and arrays if ``Arrays.equals`` does.
+pub fn equals(vm: &mut VM, annotation: u64, other: u64) -> bool {
+    if annotation == other {
+        return true;
+    }
+    let interface = annotation_interface(annotation);
+    let interface_name = unsafe { &*interface }.get_name().unwrap().clone();
+    if other == 0 || !vm.is_instance_of(other, interface_name.as_str()) {
+        return false;
+    }
+    elements_of(interface).iter().all(|(name, ty)| {
+        let value = get_field(annotation, name.as_str());
+        element_value(vm, other, name.as_str(), ty.as_str())
+            .and_then(|other_value| value_equals(vm, value, other_value, ty.as_str()))
+            .unwrap_or(false)
+    })
+}
+
+/// ``hashCode`` of an annotation: the sum of ``(127 * name.hashCode()) ^ value.hashCode()`` of
+/// the elements, where arrays are hashed by ``Arrays.hashCode``
+pub fn hash_code(vm: &mut VM, annotation: u64) -> i32 {
+    let interface = annotation_interface(annotation);
+    let mut hash_code = 0i32;
+    for (name, ty) in elements_of(interface) {
+        let value = get_field(annotation, name.as_str());
+        let value_hash_code = match value_hash_code(vm, value, ty.as_str()) {
+            Some(value_hash_code) => value_hash_code,
+            None => return 0,
+        };
+        let name_hash_code = string_hash_code(name.encode_utf16());
+        hash_code = hash_code.wrapping_add(127i32.wrapping_mul(name_hash_code) ^ value_hash_code);
+    }
+    hash_code
+}
+
+// Writes a value as in source code, as the JDK does
+fn value_to_string(vm: &mut VM, value: u64, ty: &str) -> Option<String> {
+    Some(match ty {
+        "Z" => format!("{}", value != 0),
+        "C" => format!("'{}'", quote(value as u16)),
+        "B" => format!("(byte)0x{:02x}", value as u8),
+        "S" => format!("{}", value as i16),
+        "I" => format!("{}", value as i32),
+        "J" => format!("{}L", value as i64),
+        "F" => match u2f(value) {
+            f if f.is_nan() => "0.0f/0.0f".to_string(),
+            f if f.is_infinite() => format!("{}1.0f/0.0f", if f < 0.0 { "-" } else { "" }),
+            f => format!("{}f", number::float_to_string(f)),
+        },
+        "D" => match u2d(value) {
+            d if d.is_nan() => "0.0/0.0".to_string(),
+            d if d.is_infinite() => format!("{}1.0/0.0", if d < 0.0 { "-" } else { "" }),
+            d => number::double_to_string(d),
+        },
+        ty if ty.starts_with('[') => {
+            let mut elements = vec![];
+            for element in array_elements(value, &ty[1..]) {
+                elements.push(value_to_string(vm, element, &ty[1..])?);
+            }
+            format!("{{{}}}", elements.join(", "))
+        }
+        "Ljava/lang/String;" if value != 0 => {
+            let chars = unsafe { &*(value as GcType<ObjectBody>) }.get_string_chars();
+            let quoted: String = chars.iter().map(|c| quote(*c)).collect();
+            format!("\"{}\"", quoted)
+        }
+        "Ljava/lang/Class;" if value != 0 => format!(
+            "{}.class",
+            mirror::type_name_of_descriptor(mirror::descriptor(value).as_str())
+        ),
+        _ => string_concat::object_to_string(vm, value)?,
+    })
+}
+
+// Escapes a character in a char or String literal
+fn quote(c: u16) -> String {
+    match c {
+        0x08 => "\\b".to_string(),
+        0x0c => "\\f".to_string(),
+        0x0a => "\\n".to_string(),
+        0x0d => "\\r".to_string(),
+        0x09 => "\\t".to_string(),
+        0x27 => "\\'".to_string(),
+        0x22 => "\\\"".to_string(),
+        0x5c => "\\\\".to_string(),
+        0x20..=0x7e => (c as u8 as char).to_string(),
+        c => format!("\\u{:04x}", c),
+    }
+}
+
+// Returns None if a method called throws an exception
+fn value_equals(vm: &mut VM, value: u64, other: u64, ty: &str) -> Option<bool> {
+    Some(match ty {
+        "Z" | "B" => value as u8 == other as u8,
+        "C" | "S" => value as u16 == other as u16,
+        "I" => value as u32 == other as u32,
+        "J" => value == other,
+        "F" => float_bits(value) == float_bits(other),
+        "D" => double_bits(value) == double_bits(other),
+        _ if value == 0 || other == 0 => value == other,
+        ty if ty.starts_with('[') => {
+            let elements = array_elements(value, &ty[1..]);
+            let other_elements = array_elements(other, &ty[1..]);
+            if elements.len() != other_elements.len() {
+                return Some(false);
+            }
+            for (element, other_element) in elements.into_iter().zip(other_elements) {
+                if !value_equals(vm, element, other_element, &ty[1..])? {
+                    return Some(false);
+                }
+            }
+            true
+        }
+        "Ljava/lang/String;" => {
+            let string = unsafe { &*(value as GcType<ObjectBody>) };
+            let other_string = unsafe { &*(other as GcType<ObjectBody>) };
+            string.get_string_chars() == other_string.get_string_chars()
+        }
+        _ => {
+            let descriptor = "(Ljava/lang/Object;)Z";
+            invoke_virtual(vm, value, "equals", descriptor, &[value, other])? as u8 != 0
+        }
+    })
+}
+
+// Returns None if a method called throws an exception
+fn value_hash_code(vm: &mut VM, value: u64, ty: &str) -> Option<i32> {
+    Some(match ty {
+        "Z" if value as u8 != 0 => 1231,
+        "Z" => 1237,
+        "B" => value as i8 as i32,
+        "C" => value as u16 as i32,
+        "S" => value as i16 as i32,
+        "I" => value as i32,
+        "J" => (value ^ (value >> 32)) as i32,
+        "F" => float_bits(value) as i32,
+        "D" => {
+            let bits = double_bits(value);
+            (bits ^ (bits >> 32)) as i32
+        }
+        _ if value == 0 => 0,
+        ty if ty.starts_with('[') => {
+            let mut hash_code = 1i32;
+            for element in array_elements(value, &ty[1..]) {
+                let element_hash_code = value_hash_code(vm, element, &ty[1..])?;
+                hash_code = hash_code.wrapping_mul(31).wrapping_add(element_hash_code);
+            }
+            hash_code
+        }
+        "Ljava/lang/String;" => {
+            let string = unsafe { &*(value as GcType<ObjectBody>) };
+            string_hash_code(string.get_string_chars().iter().cloned())
+        }
+        _ => invoke_virtual(vm, value, "hashCode", "()I", &[value])? as i32,
+    })
+}
+
+// ``String.hashCode`` of ``chars``
+fn string_hash_code(chars: impl Iterator<Item = u16>) -> i32 {
+    chars.fold(0i32, |hash_code, c| {
+        hash_code.wrapping_mul(31).wrapping_add(c as i32)
+    })
+}
+
+// ``Float.floatToIntBits``, which collapses NaNs into one
+fn float_bits(value: u64) -> u32 {
+    match u2f(value) {
+        f if f.is_nan() => f32::NAN.to_bits(),
+        f => f.to_bits(),
+    }
+}
+
+// ``Double.doubleToLongBits``, which collapses NaNs into one
+fn double_bits(value: u64) -> u64 {
+    match u2d(value) {
+        d if d.is_nan() => f64::NAN.to_bits(),
+        d => d.to_bits(),
+    }
+}
+
+// The elements of an array whose element type is ``ty``
+fn array_elements(array: u64, ty: &str) -> Vec<u64> {
+    let array = unsafe { &*(array as GcType<Array>) };
+    (0..array.get_length() as isize)
+        .map(|i| match ty {
+            "Z" | "B" => array.at::<u8>(i),
+            "C" | "S" => array.at::<u16>(i),
+            "I" | "F" => array.at::<u32>(i),
+            _ => array.at::<u64>(i),
+        })
+        .collect()
+}
+
+// The value of the element ``name`` of type ``ty`` of an annotation, which may be implemented by
+// any class. Returns None if the element method throws an exception.
+fn element_value(vm: &mut VM, annotation: u64, name: &str, ty: &str) -> Option<u64> {
+    let class = unsafe { &*(*(annotation as GcType<ObjectBody>)).class };
+    if class.get_name().unwrap().ends_with("$$Proxy") {
+        return Some(get_field(annotation, name));
+    }
+    let descriptor = format!("(){}", ty);
+    invoke_virtual(vm, annotation, name, descriptor.as_str(), &[annotation])
+}
+
+// Calls the method of ``object`` with ``args`` including ``object`` itself. Returns None if it
+// throws an exception.
+fn invoke_virtual(
+    vm: &mut VM,
+    object: u64,
+    name: &str,
+    descriptor: &str,
+    args: &[u64],
+) -> Option<u64> {
+    let class = unsafe { &*(object as GcType<ObjectBody>) }.class;
+    let (class, method) = unsafe { &*class }.get_method(name, descriptor)?;
+    let ret = vm.invoke_method(class, method, args);
+    if vm.exception.is_some() {
+        return None;
+    }
+    Some(ret)
+}
+
+// The annotations of a class, a method or a field whose types are available. Their classes are
+// loaded in advance, since it may run Java code (and GC), which must not happen while
+// annotations are only referred to from Rust.
+fn visible_annotations(
+    vm: &mut VM,
+    class: GcType<Class>,
+    attributes: &[AttributeInfo],
+) -> Vec<Annotation> {
+    let mut annotations = vec![];
+    for attribute in attributes {
+        if let Attribute::RuntimeVisibleAnnotations {
+            annotations: ref visible,
+            ..
+        } = attribute.info
+        {
+            annotations.extend(
+                visible
+                    .iter()
+                    .filter(|annotation| prepare(vm, class, annotation))
+                    .cloned(),
+            );
+        }
+    }
+    annotations
+}
+
+// Loads the classes and creates the mirrors an annotation refers to, and spins its proxy class.
+// Returns false if a class is not found, and the annotation is ignored as the JDK does.
+fn prepare(vm: &mut VM, class: GcType<Class>, annotation: &Annotation) -> bool {
+    let ty = type_of(class, annotation).to_string();
+    if !reflect::class_exists(vm, class_name(ty.as_str())) {
+        return false;
+    }
+    let interface = vm.load_class(class_name(ty.as_str()));
+    mirror::of_descriptor(vm, ty.as_str());
+    proxy_class(vm, interface);
+
+    let mut values: Vec<(GcType<Class>, ElementValue)> = annotation
+        .element_value_pairs
+        .iter()
+        .map(|pair| (class, pair.value.clone()))
+        .collect();
+    for method in &unsafe { &*interface }.classfile.methods {
+        if let Some(default_value) = default_value_of(method) {
+            values.push((interface, default_value.clone()));
+        }
+    }
+    values
+        .iter()
+        .all(|(class, value)| prepare_value(vm, *class, value))
+}
+
+fn prepare_value(vm: &mut VM, class: GcType<Class>, value: &ElementValue) -> bool {
+    match value.value {
+        ElementValueBody::EnumConstValue {
+            type_name_index, ..
+        } => {
+            let ty = utf8(class, type_name_index).to_string();
+            let exists = reflect::class_exists(vm, class_name(ty.as_str()));
+            if exists {
+                vm.load_class(class_name(ty.as_str()));
+            }
+            exists
+        }
+        ElementValueBody::ClassInfoIndex(class_info_index) => {
+            let ty = utf8(class, class_info_index).to_string();
+            let element = ty.trim_start_matches('[');
+            let exists =
+                !element.starts_with('L') || reflect::class_exists(vm, class_name(element));
+            if exists {
+                mirror::of_descriptor(vm, ty.as_str());
+            }
+            exists
+        }
+        ElementValueBody::AnnotationValue(ref annotation) => prepare(vm, class, annotation),
+        ElementValueBody::ArrayValue { ref values, .. } => {
+            values.iter().all(|value| prepare_value(vm, class, value))
+        }
+        ElementValueBody::ConstValueIndex(_) => true,
+    }
+}
+
+// Creates an instance of the proxy class of ``annotation`` in ``class``, which must have been
+// prepared
+fn new_annotation(vm: &mut VM, class: GcType<Class>, annotation: &Annotation) -> u64 {
+    let ty = type_of(class, annotation);
+    let interface = unsafe { &*vm.classheap }.get_class(class_name(ty)).unwrap();
+    let proxy = proxy_class(vm, interface);
+    let object = unsafe { (*vm.objectheap).create_object(proxy) };
+
+    for method in &unsafe { &*interface }.classfile.methods {
+        if method.check_access_flags(access_flags::ACC_PACC_STATIC) {
+            continue;
+        }
+        let name = utf8(interface, method.name_index);
+        let descriptor = utf8(interface, method.descriptor_index);
+        let value = annotation
+            .element_value_pairs
+            .iter()
+            .find(|pair| utf8(class, pair.element_name_index) == name)
+            .map(|pair| (class, &pair.value))
+            .or_else(|| default_value_of(method).map(|value| (interface, value)));
+        if let Some((class, value)) = value {
+            let (_, ret) = parse_descriptor(descriptor);
+            let value = new_value(vm, class, value, ret);
+            set_field(object, name, value);
+        }
+    }
+    object
+}
+
+// Converts an element value in the constant pool of ``class`` to a value of type ``ty``
+fn new_value(vm: &mut VM, class: GcType<Class>, value: &ElementValue, ty: &str) -> u64 {
+    match value.value {
+        ElementValueBody::ConstValueIndex(index) => {
+            match unsafe { &*class }.classfile.constant_pool[index as usize] {
+                Constant::IntegerInfo { i } => i as u64,
+                Constant::FloatInfo { f } => f2u(f),
+                Constant::LongInfo { i } => i as u64,
+                Constant::DoubleInfo { f } => d2u(f),
+                _ => unsafe {
+                    (*class).get_java_string_utf8_from_const_pool(vm.objectheap, index as usize)
+                }
+                .unwrap(),
+            }
+        }
+        ElementValueBody::EnumConstValue {
+            type_name_index,
+            const_name_index,
+        } => {
+            let enum_class = unsafe { &*vm.classheap }
+                .get_class(class_name(utf8(class, type_name_index)))
+                .unwrap();
+            unsafe { &*enum_class }
+                .get_static_variable(utf8(class, const_name_index))
+                .unwrap_or(0)
+        }
+        ElementValueBody::ClassInfoIndex(class_info_index) => {
+            mirror::of_descriptor(vm, utf8(class, class_info_index))
+        }
+        ElementValueBody::AnnotationValue(ref annotation) => new_annotation(vm, class, annotation),
+        ElementValueBody::ArrayValue { ref values, .. } => {
+            let element_type = &ty[1..];
+            let atype = vm.resolve_class_name(element_type);
+            let array = unsafe { &mut *vm.objectheap }.create_array(atype, values.len());
+            for (i, value) in values.iter().enumerate() {
+                let value = new_value(vm, class, value, element_type);
+                let array = unsafe { &mut *(array as GcType<Array>) };
+                let i = i as isize;
+                match element_type {
+                    "Z" | "B" => array.store(i, value as u8),
+                    "C" | "S" => array.store(i, value as u16),
+                    "I" | "F" => array.store(i, value as u32),
+                    _ => array.store(i, value),
+                }
+            }
+            array
+        }
+    }
+}
+
+// Returns the class spun to implement an annotation interface, whose fields hold the values of
+// the elements of an annotation
+fn proxy_class(vm: &mut VM, interface: GcType<Class>) -> GcType<Class> {
+    let interface_name = unsafe { &*interface }.get_name().unwrap().clone();
+    let class_name = format!("{}$$Proxy", interface_name);
+    if let Some(class) = unsafe { &*vm.classheap }.get_class(class_name.as_str()) {
+        return class;
+    }
+
+    let mut pool = ConstantPool::new();
+    let this_class = pool.class(class_name.as_str());
+    let super_class = pool.class("java/lang/Object");
+    let interfaces = vec![Constant::ClassInfo {
+        name_index: pool.class(interface_name.as_str()),
+    }];
+    let mut fields = vec![];
+    let mut methods = vec![];
+    for (name, ty) in elements_of(interface) {
+        fields.push(FieldInfo {
+            access_flags: 0x0012, // private final
+            name_index: pool.utf8(name.as_str()),
+            descriptor_index: pool.utf8(ty.as_str()),
+            attributes_count: 0,
+            attributes: vec![],
+        });
+        let mut code = vec![Inst::aload_0, Inst::getfield];
+        let field = pool.field_ref(class_name.as_str(), name.as_str(), ty.as_str());
+        push_u16(&mut code, field);
+        code.push(return_inst(ty.as_str()));
+        methods.push(new_method(
+            &mut pool,
+            name.as_str(),
+            format!("(){}", ty).as_str(),
+            code,
+        ));
+    }
+
+    let mut code = vec![Inst::ldc_w];
+    let annotation_type = pool.class(interface_name.as_str());
+    push_u16(&mut code, annotation_type);
+    code.push(Inst::areturn);
+    methods.push(new_method(
+        &mut pool,
+        "annotationType",
+        "()Ljava/lang/Class;",
+        code,
+    ));
+    for (name, descriptor) in &[
+        ("toString", "()Ljava/lang/String;"),
+        ("equals", "(Ljava/lang/Object;)Z"),
+        ("hashCode", "()I"),
+    ] {
+        methods.push(MethodInfo {
+            access_flags: 0x0101, // public native
+            name_index: pool.utf8(name),
+            descriptor_index: pool.utf8(descriptor),
+            ..MethodInfo::new()
+        });
+    }
+
+    let class = Class {
+        classfile: ClassFile {
+            magic: 0xCAFEBABE,
+            major_version: 52,
+            constant_pool_count: pool.constants.len() as u16,
+            constant_pool: pool.constants,
+            access_flags: 0x1030, // final super synthetic
+            this_class,
+            super_class,
+            interfaces_count: interfaces.len() as u16,
+            interfaces,
+            fields_count: fields.len() as u16,
+            fields,
+            methods_count: methods.len() as u16,
+            methods,
+            ..ClassFile::new()
+        },
+        classheap: Some(vm.classheap),
+        ..Class::new()
+    };
+    let class = unsafe { &mut *vm.objectheap }.gc.alloc(class);
+    unsafe { vm.define_class(class, "java.lang.reflect.Proxy") };
+
+    let natives = &mut unsafe { &mut *vm.runtime_env }.natives;
+    natives.register(
+        format!("{}.toString:()Ljava/lang/String;", class_name).as_str(),
+        |vm: &mut VM, this: GcType<ObjectBody>| to_string(vm, this as u64),
+    );
+    natives.register(
+        format!("{}.equals:(Ljava/lang/Object;)Z", class_name).as_str(),
+        |vm: &mut VM, this: GcType<ObjectBody>, other: GcType<ObjectBody>| {
+            equals(vm, this as u64, other as u64)
+        },
+    );
+    natives.register(
+        format!("{}.hashCode:()I", class_name).as_str(),
+        |vm: &mut VM, this: GcType<ObjectBody>| hash_code(vm, this as u64),
+    );
+    class
+}
+
+fn new_method(pool: &mut ConstantPool, name: &str, descriptor: &str, code: Vec<u8>) -> MethodInfo {
+    MethodInfo {
+        access_flags: 0x0011, // public final
+        name_index: pool.utf8(name),
+        descriptor_index: pool.utf8(descriptor),
+        code: Some(CodeAttribute {
+            max_stack: 2,
+            max_locals: 1,
+            code_length: code.len() as u32,
+            code: Box::into_raw(Box::new(code)),
+            exception_table_length: 0,
+            exception_table: vec![],
+            attributes_count: 0,
+            attributes: vec![],
+        }),
+        ..MethodInfo::new()
+    }
+}
+
+fn new_annotation_array(vm: &mut VM, annotations: Vec<(GcType<Class>, Annotation)>) -> u64 {
+    let annotations: Vec<u64> = annotations
+        .iter()
+        .map(|(class, annotation)| new_annotation(vm, *class, annotation))
+        .collect();
+    new_array(vm, &annotations)
+}
+
+fn new_array(vm: &mut VM, annotations: &[u64]) -> u64 {
+    let class = vm.load_class("java/lang/annotation/Annotation");
+    let array = unsafe { &mut *vm.objectheap }.create_obj_array(class, annotations.len());
+    for (i, annotation) in annotations.iter().enumerate() {
+        unsafe { &mut *(array as GcType<Array>) }.store(i as isize, *annotation);
+    }
+    array
+}
+
+// The names and the types of the elements of an annotation interface, in declaration order
+fn elements_of(interface: GcType<Class>) -> Vec<(String, String)> {
+    unsafe { &*interface }
+        .classfile
+        .methods
+        .iter()
+        .filter(|method| !method.check_access_flags(access_flags::ACC_PACC_STATIC))
+        .map(|method| {
+            let (_, ret) = parse_descriptor(utf8(interface, method.descriptor_index));
+            (
+                utf8(interface, method.name_index).to_string(),
+                ret.to_string(),
+            )
+        })
+        .collect()
+}
+
+// The annotation interface a proxy implements
+fn annotation_interface(annotation: u64) -> GcType<Class> {
+    let proxy = unsafe { &*(*(annotation as GcType<ObjectBody>)).class };
+    let name = proxy.get_interface_names()[0].clone();
+    unsafe { &*proxy.classheap.unwrap() }
+        .get_class(name.as_str())
+        .unwrap()
+}
+
+// Whether the annotation interface of ``ty`` is annotated with ``@Inherited``
+fn is_inherited(vm: &mut VM, ty: &str) -> bool {
+    let interface = vm.load_class(class_name(ty));
+    unsafe { &*interface }
+        .classfile
+        .attributes
+        .iter()
+        .any(|attribute| match attribute.info {
+            Attribute::RuntimeVisibleAnnotations {
+                ref annotations, ..
+            } => annotations.iter().any(|annotation| {
+                type_of(interface, annotation) == "Ljava/lang/annotation/Inherited;"
+            }),
+            _ => false,
+        })
+}
+
+fn default_value_of(method: &MethodInfo) -> Option<&ElementValue> {
+    method
+        .attributes
+        .iter()
+        .find_map(|attribute| match attribute.info {
+            Attribute::AnnotationDefault { ref default_value } => Some(default_value),
+            _ => None,
+        })
+}
+
+// The descriptor of the type of ``annotation`` in ``class``
+fn type_of<'a>(class: GcType<Class>, annotation: &Annotation) -> &'a str {
+    utf8(class, annotation.type_index)
+}
+
+fn utf8<'a>(class: GcType<Class>, index: u16) -> &'a str {
+    unsafe { &*class }
+        .get_utf8_from_const_pool(index as usize)
+        .unwrap()
+}
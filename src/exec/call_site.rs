@@ -0,0 +1,199 @@
+// Linking of ``invokedynamic`` call sites with the bootstrap methods of their classes
+// https://docs.oracle.com/javase/specs/jvms/se8/html/jvms-6.html#jvms-6.5.invokedynamic
+
+use super::super::class::{class::Class, classfile::constant::Constant};
+use super::super::gc::gc::GcType;
+use super::lambda;
+use super::string_concat::{self, ConcatPart};
+use super::vm::VM;
+use std::sync::Arc;
+
+/// What an ``invokedynamic`` instruction does once it's linked
+#[derive(Debug)]
+pub enum CallSite {
+    /// Creates an instance of a class spun by ``LambdaMetafactory``, capturing the arguments
+    Lambda { class: GcType<Class> },
+    /// Concatenates the arguments into a new string as specialized by ``StringConcatFactory``
+    Concat {
+        parts: Vec<ConcatPart>,
+        slots: usize,
+    },
+}
+
+// Call sites are shared by the threads through their classes, which are only accessed while
+// holding the VM lock
+unsafe impl Send for CallSite {}
+unsafe impl Sync for CallSite {}
+
+/// A static argument passed to a bootstrap method
+#[derive(Debug, Clone)]
+pub enum BootstrapArgument {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    Class(String),
+    MethodType(String),
+    MethodHandle(MethodHandleRef),
+}
+
+/// A ``CONSTANT_MethodHandle`` resolved to the member it refers to
+#[derive(Debug, Clone)]
+pub struct MethodHandleRef {
+    pub reference_kind: u8,
+    pub class: String,
+    pub name: String,
+    pub descriptor: String,
+    pub interface: bool,
+}
+
+#[allow(dead_code)]
+pub mod reference_kind {
+    pub const REF_GET_FIELD: u8 = 1;
+    pub const REF_GET_STATIC: u8 = 2;
+    pub const REF_PUT_FIELD: u8 = 3;
+    pub const REF_PUT_STATIC: u8 = 4;
+    pub const REF_INVOKE_VIRTUAL: u8 = 5;
+    pub const REF_INVOKE_STATIC: u8 = 6;
+    pub const REF_INVOKE_SPECIAL: u8 = 7;
+    pub const REF_NEW_INVOKE_SPECIAL: u8 = 8;
+    pub const REF_INVOKE_INTERFACE: u8 = 9;
+}
+
+impl CallSite {
+    /// Runs the call site with its arguments on top of the operand stack of the current frame
+    pub fn invoke(&self, vm: &mut VM) {
+        match self {
+            CallSite::Lambda { class } => lambda::new_instance(vm, *class),
+            CallSite::Concat { parts, slots } => string_concat::invoke(vm, parts, *slots),
+        }
+    }
+}
+
+/// Links the call site of the ``InvokeDynamicInfo`` at ``index`` in the constant pool of
+/// ``class``. Throws ``BootstrapMethodError`` and returns None if it can't be linked.
+pub fn link(vm: &mut VM, class: GcType<Class>, index: usize) -> Option<Arc<CallSite>> {
+    let caller = unsafe { &*class };
+    let constant_pool = &caller.classfile.constant_pool;
+    let (bootstrap_method_attr_index, name_and_type_index) = fld!(
+        Constant::InvokeDynamicInfo,
+        &constant_pool[index],
+        bootstrap_method_attr_index,
+        name_and_type_index
+    );
+    let (name_index, descriptor_index) = fld!(
+        Constant::NameAndTypeInfo,
+        &constant_pool[name_and_type_index],
+        name_index,
+        descriptor_index
+    );
+    let name = constant_pool[name_index].get_utf8().unwrap();
+    let descriptor = constant_pool[descriptor_index].get_utf8().unwrap();
+
+    let bootstrap_method = caller
+        .get_bootstrap_method(bootstrap_method_attr_index)
+        .unwrap();
+    let bootstrap = method_handle_ref(caller, bootstrap_method.bootstrap_method_ref as usize);
+    let args: Vec<BootstrapArgument> = bootstrap_method
+        .bootstrap_arguments
+        .iter()
+        .map(|&index| bootstrap_argument(caller, index as usize))
+        .collect();
+
+    let call_site = match (bootstrap.class.as_str(), bootstrap.name.as_str()) {
+        ("java/lang/invoke/LambdaMetafactory", "metafactory")
+        | ("java/lang/invoke/LambdaMetafactory", "altMetafactory") => {
+            lambda::metafactory(vm, class, name, descriptor, &args)
+        }
+        ("java/lang/invoke/StringConcatFactory", "makeConcatWithConstants")
+        | ("java/lang/invoke/StringConcatFactory", "makeConcat") => {
+            string_concat::make_concat(bootstrap.name.as_str(), descriptor, &args)
+        }
+        _ => Err(format!(
+            "{}.{}{} is not supported",
+            bootstrap.class.replace("/", "."),
+            bootstrap.name,
+            bootstrap.descriptor
+        )),
+    };
+
+    match call_site {
+        Ok(call_site) => Some(Arc::new(call_site)),
+        Err(message) => {
+            vm.throw_exception("java/lang/BootstrapMethodError", Some(message.as_str()));
+            None
+        }
+    }
+}
+
+pub fn method_handle_ref(class: &Class, index: usize) -> MethodHandleRef {
+    let constant_pool = &class.classfile.constant_pool;
+    let (reference_kind, reference_index) = fld!(
+        Constant::MethodHandleInfo,
+        &constant_pool[index],
+        reference_kind,
+        reference_index
+    );
+    let (class_index, name_and_type_index, interface) = match &constant_pool[reference_index] {
+        Constant::MethodrefInfo {
+            class_index,
+            name_and_type_index,
+        }
+        | Constant::FieldrefInfo {
+            class_index,
+            name_and_type_index,
+        } => (*class_index as usize, *name_and_type_index as usize, false),
+        Constant::InterfaceMethodrefInfo {
+            class_index,
+            name_and_type_index,
+        } => (*class_index as usize, *name_and_type_index as usize, true),
+        _ => panic!(),
+    };
+    let class_name_index = fld!(Constant::ClassInfo, &constant_pool[class_index], name_index);
+    let (name_index, descriptor_index) = fld!(
+        Constant::NameAndTypeInfo,
+        &constant_pool[name_and_type_index],
+        name_index,
+        descriptor_index
+    );
+    MethodHandleRef {
+        reference_kind: reference_kind as u8,
+        class: constant_pool[class_name_index].get_utf8().unwrap().clone(),
+        name: constant_pool[name_index].get_utf8().unwrap().clone(),
+        descriptor: constant_pool[descriptor_index].get_utf8().unwrap().clone(),
+        interface,
+    }
+}
+
+fn bootstrap_argument(class: &Class, index: usize) -> BootstrapArgument {
+    let constant_pool = &class.classfile.constant_pool;
+    match &constant_pool[index] {
+        Constant::IntegerInfo { i } => BootstrapArgument::Int(*i),
+        Constant::LongInfo { i } => BootstrapArgument::Long(*i),
+        Constant::FloatInfo { f } => BootstrapArgument::Float(*f),
+        Constant::DoubleInfo { f } => BootstrapArgument::Double(*f),
+        Constant::String { string_index } => BootstrapArgument::String(
+            constant_pool[*string_index as usize]
+                .get_utf8()
+                .unwrap()
+                .clone(),
+        ),
+        Constant::ClassInfo { name_index } => BootstrapArgument::Class(
+            constant_pool[*name_index as usize]
+                .get_utf8()
+                .unwrap()
+                .clone(),
+        ),
+        Constant::MethodTypeInfo { descriptor_index } => BootstrapArgument::MethodType(
+            constant_pool[*descriptor_index as usize]
+                .get_utf8()
+                .unwrap()
+                .clone(),
+        ),
+        Constant::MethodHandleInfo { .. } => {
+            BootstrapArgument::MethodHandle(method_handle_ref(class, index))
+        }
+        e => panic!("invalid bootstrap argument: {:?}", e),
+    }
+}
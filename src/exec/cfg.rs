@@ -1,4 +1,4 @@
-use super::vm::Inst;
+use super::vm::{Inst, SwitchTable};
 use std::collections::BTreeMap;
 
 #[derive(Debug, Clone)]
@@ -43,6 +43,12 @@ impl Block {
 #[derive(Debug, Clone)]
 pub struct CFGMaker {}
 
+impl Default for CFGMaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CFGMaker {
     pub fn new() -> Self {
         CFGMaker {}
@@ -50,7 +56,9 @@ impl CFGMaker {
 }
 
 impl CFGMaker {
-    pub fn make(&mut self, code: &Vec<Inst::Code>, start: usize, end: usize) -> Vec<Block> {
+    // Returns None for code that uses subroutines (``jsr``/``ret``), which the JIT can't compile
+    // since the destination of ``ret`` is only known at runtime
+    pub fn make(&mut self, code: &[Inst::Code], start: usize, end: usize) -> Option<Vec<Block>> {
         let mut map = BTreeMap::new();
         let mut pc = start;
 
@@ -61,10 +69,14 @@ impl CFGMaker {
                 Inst::if_icmpne
                 | Inst::if_icmpge
                 | Inst::if_icmpgt
+                | Inst::if_icmplt
+                | Inst::if_icmple
                 | Inst::if_icmpeq
                 | Inst::ifne
                 | Inst::ifeq
+                | Inst::iflt
                 | Inst::ifle
+                | Inst::ifgt
                 | Inst::ifge => {
                     let branch = ((code[pc + 1] as i16) << 8) + code[pc + 2] as i16;
                     let dst = (pc as isize + branch as isize) as usize;
@@ -83,64 +95,76 @@ impl CFGMaker {
                     map.insert(pc + 3 - 1, BrKind::UnconditionalJmp { destination: dst });
                     map.insert(dst, BrKind::BlockStart);
                 }
+                Inst::tableswitch | Inst::lookupswitch => {
+                    let table = SwitchTable::read(code, pc, 0);
+                    let destinations = table.destinations(pc);
+                    for &dst in &destinations {
+                        map.insert(dst, BrKind::BlockStart);
+                    }
+                    map.insert(pc + table.size - 1, BrKind::ConditionalJmp { destinations });
+                }
+                Inst::jsr | Inst::jsr_w | Inst::ret => return None,
+                Inst::wide if code[pc + 1] == Inst::ret => return None,
                 _ => {}
             }
-            pc += Inst::get_inst_size(cur_code);
+            pc += Inst::get_inst_size_at(code, pc, 0);
         }
 
         let mut start = Some(start);
         let mut blocks = vec![];
 
         for (key, kind) in map {
+            // The start of the block ending at ``key``, if it isn't empty
+            let block_start = start.filter(|&start| start < end && start < key);
             match kind {
                 BrKind::BlockStart => {
-                    if start.is_some() && start.unwrap() < end && start.unwrap() < key {
-                        dprintln!("cfg: range: [{}, {}]", start.unwrap(), key - 1);
+                    if let Some(block_start) = block_start {
+                        dprintln!("cfg: range: [{}, {}]", block_start, key - 1);
                         blocks.push(Block {
-                            code: code[start.unwrap()..key].to_vec(),
-                            start: start.unwrap(),
+                            code: code[block_start..key].to_vec(),
+                            start: block_start,
                             kind: BrKind::JmpRequired { destination: key },
                             generated: false,
                         });
                     }
                     start = Some(key);
                 }
-                BrKind::ConditionalJmp { .. } | BrKind::UnconditionalJmp { .. }
-                    if start.is_some() && start.unwrap() < end && start.unwrap() < key =>
-                {
-                    dprintln!(
-                        "cfg: range: {}[{}, {}]",
-                        match kind {
-                            BrKind::ConditionalJmp { ref destinations } => {
-                                format!("IF({:?}) ", destinations)
-                            }
-                            _ => "".to_string(),
-                        },
-                        start.unwrap(),
-                        key
-                    );
-                    blocks.push(Block {
-                        code: code[start.unwrap()..key + 1].to_vec(),
-                        start: start.unwrap(),
-                        kind,
-                        generated: false,
-                    });
-                    start = None;
+                BrKind::ConditionalJmp { .. } | BrKind::UnconditionalJmp { .. } => {
+                    if let Some(block_start) = block_start {
+                        dprintln!(
+                            "cfg: range: {}[{}, {}]",
+                            match kind {
+                                BrKind::ConditionalJmp { ref destinations } => {
+                                    format!("IF({:?}) ", destinations)
+                                }
+                                _ => "".to_string(),
+                            },
+                            block_start,
+                            key
+                        );
+                        blocks.push(Block {
+                            code: code[block_start..key + 1].to_vec(),
+                            start: block_start,
+                            kind,
+                            generated: false,
+                        });
+                        start = None;
+                    }
                 }
                 _ => {}
             }
         }
 
-        if start.is_some() && start.unwrap() < end {
-            dprintln!("cfg: range: [{}, {}]", start.unwrap(), end - 1);
+        if let Some(start) = start.filter(|&start| start < end) {
+            dprintln!("cfg: range: [{}, {}]", start, end - 1);
             blocks.push(Block {
-                code: code[start.unwrap()..end].to_vec(),
-                start: start.unwrap(),
+                code: code[start..end].to_vec(),
+                start,
                 kind: BrKind::BlockStart,
                 generated: false,
             });
         }
 
-        blocks
+        Some(blocks)
     }
 }
@@ -1,7 +1,8 @@
 use super::super::class::class::Class;
 use super::super::class::classfile::method::MethodInfo;
 use super::super::gc::gc::GcType;
-use std::ptr;
+use std::ops::{Deref, DerefMut};
+use std::{ptr, slice};
 
 #[derive(Debug, Clone)]
 pub struct Frame {
@@ -11,6 +12,12 @@ pub struct Frame {
     pub sp: usize,
 }
 
+impl Default for Frame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Frame {
     pub fn new() -> Self {
         Frame {
@@ -37,14 +44,21 @@ pub enum VariableType {
     Pointer,
     Double,
     Long,
+    Float,
 }
 
+// The JIT reads ``class`` at offset 0 to check types quickly. The first word of an ``Array`` is
+// the discriminant of its ``atype``, which never looks like a pointer to a class.
+#[repr(C)]
 #[derive(Debug, Clone)]
 pub struct ObjectBody {
     pub class: GcType<Class>,
     pub variables: Vec<u64>,
+    // The identity hash code, or 0 until ``hashCode`` is first called
+    pub hash_code: i32,
 }
 
+#[repr(u64)]
 #[derive(Debug, Clone)]
 pub enum AType {
     Boolean,
@@ -62,33 +76,36 @@ pub enum AType {
     },
 }
 
+#[repr(C)]
 #[derive(Debug, Clone)]
 pub struct Array {
     pub atype: AType,
-    pub elements: Vec<u8>,
-    // TODO: Treat as special. Need a better way.
-    pub string: Option<String>,
+    pub elements: Elements,
+    // The identity hash code, or 0 until ``hashCode`` is first called
+    pub hash_code: i32,
 }
 
 impl Array {
-    pub fn new(atype: AType, len: usize, string: Option<String>) -> Array {
+    pub fn new(atype: AType, len: usize) -> Array {
         Array {
-            elements: unsafe {
-                let actual_len = len * atype.size_in_byte();
-                let mut vec: Vec<u8> = Vec::with_capacity(actual_len);
-                vec.set_len(actual_len);
-                vec
-            },
+            // Elements are initialized to the default value (0, null, false...)
+            elements: Elements::new(len * atype.size_in_byte()),
             atype,
-            string,
+            hash_code: 0,
         }
     }
 
     pub fn get_length(&self) -> usize {
-        if let Some(ref string) = self.string {
-            string.len()
-        } else {
-            self.elements.len() / self.atype.size_in_byte()
+        self.elements.len() / self.atype.size_in_byte()
+    }
+
+    /// Returns the elements of a ``char[]`` as UTF-16 code units
+    pub fn as_chars(&self) -> &[u16] {
+        match self.atype {
+            AType::Char => unsafe {
+                slice::from_raw_parts(self.elements.as_ptr() as *const u16, self.get_length())
+            },
+            _ => panic!(),
         }
     }
 
@@ -101,6 +118,38 @@ impl Array {
     }
 }
 
+/// The bytes of the elements of an ``Array``. They are stored in words so that every element is
+/// aligned whatever its type, and the pointer is never null even when there are no elements.
+#[derive(Debug, Clone)]
+pub struct Elements {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Elements {
+    /// Returns ``len`` zeroed bytes
+    pub fn new(len: usize) -> Elements {
+        Elements {
+            words: vec![0; len.div_ceil(8)],
+            len,
+        }
+    }
+}
+
+impl Deref for Elements {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.words.as_ptr() as *const u8, self.len) }
+    }
+}
+
+impl DerefMut for Elements {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.words.as_mut_ptr() as *mut u8, self.len) }
+    }
+}
+
 impl AType {
     pub fn reduce_dimension(self) -> AType {
         match self {
@@ -108,7 +157,7 @@ impl AType {
                 element_type,
                 dimensions,
             } => AType::Multi {
-                element_type: element_type,
+                element_type,
                 dimensions: dimensions - 1,
             },
             _ => panic!(),
@@ -176,11 +225,14 @@ impl AType {
 }
 
 impl ObjectBody {
-    pub fn get_string_mut(&mut self) -> &mut String {
-        unsafe { &mut *(self.variables[0] as GcType<Array>) }
-            .string
-            .as_mut()
-            .unwrap()
+    /// Returns the UTF-16 code units of a ``String``, which are in its ``value`` field
+    pub fn get_string_chars(&self) -> &[u16] {
+        unsafe { &*(self.variables[0] as GcType<Array>) }.as_chars()
+    }
+
+    /// Returns the text of a ``String``. Unpaired surrogates are replaced with U+FFFD.
+    pub fn get_string(&self) -> String {
+        String::from_utf16_lossy(self.get_string_chars())
     }
 }
 
@@ -207,9 +259,9 @@ impl VariableType {
             }
             '[' => VariableType::Pointer, // TODO
             'V' => VariableType::Void,
-            'I' => VariableType::Int,
-            'Z' => VariableType::Int,
+            'I' | 'Z' | 'B' | 'C' | 'S' => VariableType::Int,
             'J' => VariableType::Long,
+            'F' => VariableType::Float,
             'D' => VariableType::Double,
             _ => return None,
         };
@@ -223,9 +275,9 @@ impl VariableType {
             'L' => VariableType::Pointer,
             '[' => VariableType::Pointer,
             'V' => VariableType::Void,
-            'I' => VariableType::Int,
-            'Z' => VariableType::Int,
+            'I' | 'Z' | 'B' | 'C' | 'S' => VariableType::Int,
             'J' => VariableType::Long,
+            'F' => VariableType::Float,
             'D' => VariableType::Double,
             e => panic!("{:?}", e),
         };
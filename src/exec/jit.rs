@@ -1,12 +1,19 @@
 use super::{
     super::{
-        class::{class::Class, classfile::constant::Constant},
+        class::{
+            class::Class,
+            classfile::{constant::Constant, method::access_flags},
+        },
         gc::gc::GcType,
+        jvm::marshal,
     },
+    call_site::CallSite,
     cfg::{Block, BrKind},
     frame::VariableType,
     native_functions,
-    vm::{d2u, u2d, Inst, RuntimeEnvironment},
+    native_registry::{NativeLink, NativeMethod},
+    string_concat::ConcatPart,
+    vm::{d2u, f2u, u2d, u2f, Inst, RuntimeEnvironment, SwitchTable},
 };
 use libc;
 use llvm;
@@ -23,18 +30,33 @@ pub enum Error {
     General,
 }
 
+/// When methods and loops are compiled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JitMode {
+    /// Once they are run several times
+    Mixed,
+    /// Never (``-Xint``)
+    Interpreted,
+    /// The first time they are run (``-Xcomp``)
+    Compiled,
+}
+
 pub trait CastIntoLLVMType {
+    /// # Safety
+    ///
+    /// ``ctx`` must be a valid LLVM context.
     unsafe fn to_llvmty(&self, ctx: LLVMContextRef) -> LLVMTypeRef;
 }
 
 impl CastIntoLLVMType for VariableType {
     unsafe fn to_llvmty(&self, ctx: LLVMContextRef) -> LLVMTypeRef {
-        match self {
-            &VariableType::Int => LLVMInt32TypeInContext(ctx),
-            &VariableType::Double => LLVMDoubleTypeInContext(ctx),
-            &VariableType::Void => LLVMVoidTypeInContext(ctx),
-            &VariableType::Pointer => LLVMPointerType(LLVMInt8TypeInContext(ctx), 0),
-            &VariableType::Long => unimplemented!(),
+        match *self {
+            VariableType::Int => LLVMInt32TypeInContext(ctx),
+            VariableType::Float => LLVMFloatTypeInContext(ctx),
+            VariableType::Double => LLVMDoubleTypeInContext(ctx),
+            VariableType::Void => LLVMVoidTypeInContext(ctx),
+            VariableType::Pointer => LLVMPointerType(LLVMInt8TypeInContext(ctx), 0),
+            VariableType::Long => LLVMInt64TypeInContext(ctx),
         }
     }
 }
@@ -103,9 +125,19 @@ pub struct JIT {
     phi_stack: FxHashMap<usize, Vec<PhiStack>>, // destination,
     native_functions: FxHashMap<String, LLVMValueRef>,
     runtime_env: GcType<RuntimeEnvironment>,
+    // The class of the object that last passed ``checkcast`` or ``instanceof`` at each site.
+    // Compiled code holds pointers to the elements, so they're boxed not to move.
+    #[allow(clippy::vec_box)]
+    type_check_caches: Vec<Box<u64>>,
+    // The native methods called from compiled code, boxed for the same reason
+    #[allow(clippy::vec_box)]
+    native_links: Vec<Box<NativeLink>>,
 }
 
 impl JIT {
+    /// # Safety
+    ///
+    /// ``runtime_env`` must point to a valid runtime environment that outlives the JIT.
     pub unsafe fn new(runtime_env: GcType<RuntimeEnvironment>) -> Self {
         llvm::target::LLVM_InitializeNativeTarget();
         llvm::target::LLVM_InitializeNativeAsmPrinter();
@@ -139,14 +171,20 @@ impl JIT {
             phi_stack: FxHashMap::default(),
             runtime_env,
             native_functions: native_functions::native_functions(module, context),
+            type_check_caches: vec![],
+            native_links: vec![],
         }
     }
 }
 
 impl JIT {
+    /// # Safety
+    ///
+    /// ``exec_info`` must have been compiled by this JIT, and the arguments of the method must be
+    /// on top of the operand stack of the frame at ``bp``.
     pub unsafe fn run_func(
         &self,
-        stack: &mut Vec<u64>,
+        stack: &mut [u64],
         bp: usize,
         mut sp: usize,
         exec_info: &FuncJITExecInfo,
@@ -159,6 +197,11 @@ impl JIT {
             let val = stack[i];
             local_vars.push(match params_ty_iter.next().unwrap() {
                 VariableType::Int => llvm_const_int32(self.context, val),
+                VariableType::Long => {
+                    i += 1;
+                    llvm_const_int64(self.context, val)
+                }
+                VariableType::Float => llvm_const_float(self.context, u2f(val)),
                 VariableType::Double => {
                     i += 1;
                     llvm_const_double(self.context, u2d(val))
@@ -171,7 +214,7 @@ impl JIT {
 
         sp -= exec_info.params_len;
 
-        let ret_ty = exec_info.ret_ty.clone().unwrap();
+        let ret_ty = exec_info.ret_ty.unwrap();
         let func_ret_ty = ret_ty.to_llvmty(self.context);
         let func_ty = LLVMFunctionType(func_ret_ty, vec![].as_mut_ptr(), 0, 0);
         let func_name = format!("ferrugo-jit-func-executer-{}", random::<u32>());
@@ -197,7 +240,7 @@ impl JIT {
         // when_debug!(LLVMDumpValue(func));
         // TODO: Is this REALLY right way???
         let mut ee = 0 as llvm::execution_engine::LLVMExecutionEngineRef;
-        let mut error = 0 as *mut i8;
+        let mut error = ptr::null_mut::<i8>();
         if llvm::execution_engine::LLVMCreateExecutionEngineForModule(
             &mut ee,
             self.module,
@@ -217,7 +260,14 @@ impl JIT {
                 stack[bp + sp] = ret_int as i32 as u64;
                 sp += 1
             }
-            VariableType::Double => {
+            VariableType::Float => {
+                let float_ty = VariableType::Float.to_llvmty(self.context);
+                let ret_float =
+                    llvm::execution_engine::LLVMGenericValueToFloat(float_ty, ret_val) as f32;
+                stack[bp + sp] = f2u(ret_float);
+                sp += 1
+            }
+            VariableType::Long | VariableType::Double => {
                 stack[bp + sp] = ret_int as u64;
                 sp += 2
             }
@@ -233,9 +283,12 @@ impl JIT {
         Some(sp)
     }
 
+    /// # Safety
+    ///
+    /// ``exec_info`` must have been compiled by this JIT for the method of the frame at ``bp``.
     pub unsafe fn run_loop(
         &self,
-        stack: &mut Vec<u64>,
+        stack: &mut [u64],
         bp: usize,
         exec_info: &LoopJITExecInfo,
     ) -> Option<usize> {
@@ -245,8 +298,10 @@ impl JIT {
             let val = stack[bp + offset];
             raw_local_vars.push(match ty {
                 VariableType::Int => Box::into_raw(Box::new(val as i32)) as *mut libc::c_void,
+                VariableType::Long => Box::into_raw(Box::new(val as i64)) as *mut libc::c_void,
+                VariableType::Float => Box::into_raw(Box::new(u2f(val))) as *mut libc::c_void,
                 VariableType::Double => Box::into_raw(Box::new(u2d(val))) as *mut libc::c_void,
-                VariableType::Pointer => Box::into_raw(Box::new(val as u64)) as *mut libc::c_void,
+                VariableType::Pointer => Box::into_raw(Box::new(val)) as *mut libc::c_void,
                 _ => return None,
             });
         }
@@ -255,21 +310,38 @@ impl JIT {
             raw_local_vars.as_mut_slice().as_mut_ptr(),
         );
 
+        // Each variable is freed as the type it was allocated as
         for (i, (offset, ty)) in exec_info.local_variables.iter().enumerate() {
             stack[bp + offset] = match ty {
-                VariableType::Int => *(raw_local_vars[i] as *mut i32) as u64,
-                VariableType::Double => d2u(*(raw_local_vars[i] as *mut f64)),
-                VariableType::Pointer => *(raw_local_vars[i] as *mut u64),
+                VariableType::Int => *Box::from_raw(raw_local_vars[i] as *mut i32) as u64,
+                VariableType::Long => *Box::from_raw(raw_local_vars[i] as *mut i64) as u64,
+                VariableType::Float => f2u(*Box::from_raw(raw_local_vars[i] as *mut f32)),
+                VariableType::Double => d2u(*Box::from_raw(raw_local_vars[i] as *mut f64)),
+                VariableType::Pointer => *Box::from_raw(raw_local_vars[i] as *mut u64),
                 _ => return None,
             };
-            Box::from_raw(raw_local_vars[i]);
         }
 
         Some(pc as usize)
     }
 }
 
+impl Drop for JIT {
+    fn drop(&mut self) {
+        // Disposing the context frees the module as well. The execution engines, which hold the
+        // compiled code, are never freed.
+        unsafe {
+            LLVMDisposePassManager(self.pass_mgr);
+            LLVMDisposeBuilder(self.builder);
+            LLVMContextDispose(self.context);
+        }
+    }
+}
+
 impl JIT {
+    /// # Safety
+    ///
+    /// ``class`` must point to a valid class declaring the method.
     pub unsafe fn compile_func(
         &mut self,
         (name_index, descriptor_index): (usize, usize),
@@ -334,16 +406,15 @@ impl JIT {
             LLVMBuildStore(
                 self.builder,
                 LLVMGetParam(func, i as u32),
-                self.declare_local_var(var_id, &ty),
+                self.declare_local_var(var_id, ty),
             );
             var_id += match ty {
-                // TODO: VariableType::Long
-                VariableType::Double => 2,
+                VariableType::Long | VariableType::Double => 2,
                 _ => 1,
             };
         }
 
-        assert!(blocks.len() > 0);
+        assert!(!blocks.is_empty());
 
         for block in &*blocks {
             if block.start > 0 {
@@ -377,8 +448,8 @@ impl JIT {
         }
 
         let mut iter_bb = LLVMGetFirstBasicBlock(func);
-        while iter_bb != ptr::null_mut() {
-            if LLVMIsATerminatorInst(LLVMGetLastInstruction(iter_bb)) == ptr::null_mut() {
+        while !iter_bb.is_null() {
+            if LLVMIsATerminatorInst(LLVMGetLastInstruction(iter_bb)).is_null() {
                 let terminator_builder = LLVMCreateBuilderInContext(self.context);
                 LLVMPositionBuilderAtEnd(terminator_builder, iter_bb);
                 if ret_ty == VariableType::Void {
@@ -416,7 +487,7 @@ impl JIT {
             cant_compile: false,
             params_len: arg_types.iter().fold(0, |acc, ty| {
                 acc + match ty {
-                    VariableType::Double => 2,
+                    VariableType::Long | VariableType::Double => 2,
                     _ => 1,
                 }
             }),
@@ -425,6 +496,9 @@ impl JIT {
         })
     }
 
+    /// # Safety
+    ///
+    /// ``class`` must point to a valid class declaring the method.
     pub unsafe fn compile_loop(
         &mut self,
         class: GcType<Class>,
@@ -501,7 +575,7 @@ impl JIT {
 
         self.env = env;
 
-        assert!(blocks.len() > 0);
+        assert!(!blocks.is_empty());
 
         for block in &*blocks {
             if block.start > 0 {
@@ -574,7 +648,7 @@ impl JIT {
 
         // TODO: Is this REALLY right way???
         let mut ee = 0 as llvm::execution_engine::LLVMExecutionEngineRef;
-        let mut error = 0 as *mut i8;
+        let mut error = ptr::null_mut::<i8>();
         if llvm::execution_engine::LLVMCreateExecutionEngineForModule(
             &mut ee,
             self.module,
@@ -620,7 +694,7 @@ impl JIT {
 
             for phi_stack in &phi_stacks[1..] {
                 let src_bb = phi_stack.src_bb;
-                for (i, val) in (&phi_stack.stack).iter().enumerate() {
+                for (i, val) in phi_stack.stack.iter().enumerate() {
                     let phi = stack[init_size + i];
                     LLVMAddIncoming(phi, vec![*val].as_mut_ptr(), vec![src_bb].as_mut_ptr(), 1);
                 }
@@ -638,7 +712,7 @@ impl JIT {
         loop_compile: bool,
     ) -> CResult<usize> {
         #[rustfmt::skip]
-        macro_rules! block { () => {{ &mut blocks[idx] }}; };
+        macro_rules! block { () => {{ &mut blocks[idx] }}; }
 
         if block!().generated {
             return Ok(0);
@@ -652,7 +726,7 @@ impl JIT {
         let phi_stack = self.build_phi_stack(block!().start, init_stack);
         let stack = self.compile_bytecode(block!(), phi_stack, loop_compile)?;
 
-        fn find(pc: usize, blocks: &Vec<Block>) -> Option<usize> {
+        fn find(pc: usize, blocks: &[Block]) -> Option<usize> {
             for (i, block) in blocks.iter().enumerate() {
                 if block.start == pc {
                     return Some(i);
@@ -675,7 +749,8 @@ impl JIT {
                 Ok(d)
             }
             BrKind::UnconditionalJmp { destination } => {
-                let src_bb = self.get_basic_block(block!().start).retrieve();
+                // Code such as type checks may have split the block into several basic blocks
+                let src_bb = LLVMGetInsertBlock(self.builder);
                 self.phi_stack
                     .entry(destination)
                     .or_insert(vec![])
@@ -683,7 +758,7 @@ impl JIT {
                 Ok(destination)
             }
             BrKind::JmpRequired { destination } => {
-                let src_bb = self.get_basic_block(block!().start).retrieve();
+                let src_bb = LLVMGetInsertBlock(self.builder);
                 if cur_bb_has_no_terminator(self.builder) {
                     let bb = self
                         .get_basic_block(destination)
@@ -724,6 +799,14 @@ impl JIT {
                     let num = (cur_code as i64 - Inst::iconst_0 as i64) as u64;
                     stack.push(llvm_const_int32(self.context, num));
                 }
+                Inst::lconst_0 | Inst::lconst_1 => {
+                    let num = (cur_code - Inst::lconst_0) as u64;
+                    stack.push(llvm_const_int64(self.context, num));
+                }
+                Inst::fconst_0 | Inst::fconst_1 | Inst::fconst_2 => {
+                    let num = (cur_code - Inst::fconst_0) as f32;
+                    stack.push(llvm_const_float(self.context, num));
+                }
                 Inst::dconst_0 | Inst::dconst_1 => {
                     let num = cur_code as f64 - Inst::dconst_0 as f64;
                     stack.push(llvm_const_double(self.context, num));
@@ -738,7 +821,7 @@ impl JIT {
                     );
                 }
                 Inst::istore => {
-                    let index = code[pc as usize + 1] as usize;
+                    let index = code[pc + 1] as usize;
                     let val = stack.pop().unwrap();
                     LLVMBuildStore(
                         self.builder,
@@ -746,8 +829,44 @@ impl JIT {
                         self.declare_local_var(index, &VariableType::Int),
                     );
                 }
+                Inst::lstore_0 | Inst::lstore_1 | Inst::lstore_2 | Inst::lstore_3 => {
+                    let name = (cur_code - Inst::lstore_0) as usize;
+                    let val = stack.pop().unwrap();
+                    LLVMBuildStore(
+                        self.builder,
+                        val,
+                        self.declare_local_var(name, &VariableType::Long),
+                    );
+                }
+                Inst::lstore => {
+                    let index = code[pc + 1] as usize;
+                    let val = stack.pop().unwrap();
+                    LLVMBuildStore(
+                        self.builder,
+                        val,
+                        self.declare_local_var(index, &VariableType::Long),
+                    );
+                }
+                Inst::fstore_0 | Inst::fstore_1 | Inst::fstore_2 | Inst::fstore_3 => {
+                    let name = (cur_code - Inst::fstore_0) as usize;
+                    let val = stack.pop().unwrap();
+                    LLVMBuildStore(
+                        self.builder,
+                        val,
+                        self.declare_local_var(name, &VariableType::Float),
+                    );
+                }
+                Inst::fstore => {
+                    let index = code[pc + 1] as usize;
+                    let val = stack.pop().unwrap();
+                    LLVMBuildStore(
+                        self.builder,
+                        val,
+                        self.declare_local_var(index, &VariableType::Float),
+                    );
+                }
                 Inst::dstore => {
-                    let index = code[pc as usize + 1] as usize;
+                    let index = code[pc + 1] as usize;
                     let val = stack.pop().unwrap();
                     LLVMBuildStore(
                         self.builder,
@@ -773,6 +892,42 @@ impl JIT {
                         CString::new("").unwrap().as_ptr(),
                     ))
                 }
+                Inst::lload_0 | Inst::lload_1 | Inst::lload_2 | Inst::lload_3 => {
+                    let name = (cur_code - Inst::lload_0) as usize;
+                    let var = self.declare_local_var(name, &VariableType::Long);
+                    stack.push(LLVMBuildLoad(
+                        self.builder,
+                        var,
+                        CString::new("").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::lload => {
+                    let index = code[pc + 1] as usize;
+                    let var = self.declare_local_var(index, &VariableType::Long);
+                    stack.push(LLVMBuildLoad(
+                        self.builder,
+                        var,
+                        CString::new("").unwrap().as_ptr(),
+                    ))
+                }
+                Inst::fload_0 | Inst::fload_1 | Inst::fload_2 | Inst::fload_3 => {
+                    let name = (cur_code - Inst::fload_0) as usize;
+                    let var = self.declare_local_var(name, &VariableType::Float);
+                    stack.push(LLVMBuildLoad(
+                        self.builder,
+                        var,
+                        CString::new("").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::fload => {
+                    let index = code[pc + 1] as usize;
+                    let var = self.declare_local_var(index, &VariableType::Float);
+                    stack.push(LLVMBuildLoad(
+                        self.builder,
+                        var,
+                        CString::new("").unwrap().as_ptr(),
+                    ))
+                }
                 Inst::dload_0 | Inst::dload_1 | Inst::dload_2 | Inst::dload_3 => {
                     let name = (cur_code - Inst::dload_0) as usize;
                     let var = self.declare_local_var(name, &VariableType::Double);
@@ -809,7 +964,12 @@ impl JIT {
                         CString::new("").unwrap().as_ptr(),
                     ))
                 }
-                Inst::if_icmpne | Inst::if_icmpge | Inst::if_icmpgt | Inst::if_icmpeq => {
+                Inst::if_icmpne
+                | Inst::if_icmpge
+                | Inst::if_icmpgt
+                | Inst::if_icmplt
+                | Inst::if_icmple
+                | Inst::if_icmpeq => {
                     let val2 = stack.pop().unwrap();
                     let val1 = stack.pop().unwrap();
                     let cond_val = LLVMBuildICmp(
@@ -819,6 +979,8 @@ impl JIT {
                             Inst::if_icmpne => llvm::LLVMIntPredicate::LLVMIntNE,
                             Inst::if_icmpge => llvm::LLVMIntPredicate::LLVMIntSGE,
                             Inst::if_icmpgt => llvm::LLVMIntPredicate::LLVMIntSGT,
+                            Inst::if_icmplt => llvm::LLVMIntPredicate::LLVMIntSLT,
+                            Inst::if_icmple => llvm::LLVMIntPredicate::LLVMIntSLE,
                             _ => unreachable!(),
                         },
                         val1,
@@ -826,20 +988,23 @@ impl JIT {
                         CString::new("icmp").unwrap().as_ptr(),
                     );
                     let destinations = block.kind.get_conditional_jump_destinations();
+                    self.gen_safepoint_poll(block.start + pc, destinations);
                     let bb_then = self.get_basic_block(destinations[0]).retrieve();
                     let bb_else = self.get_basic_block(destinations[1]).retrieve();
 
                     LLVMBuildCondBr(self.builder, cond_val, bb_then, bb_else);
                 }
-                Inst::ifne | Inst::ifeq | Inst::ifle | Inst::ifge => {
+                Inst::ifne | Inst::ifeq | Inst::iflt | Inst::ifle | Inst::ifgt | Inst::ifge => {
                     let val = stack.pop().unwrap();
                     let cond_val = LLVMBuildICmp(
                         self.builder,
                         match cur_code {
                             Inst::ifeq => llvm::LLVMIntPredicate::LLVMIntEQ,
                             Inst::ifne => llvm::LLVMIntPredicate::LLVMIntNE,
-                            Inst::ifge => llvm::LLVMIntPredicate::LLVMIntSGE,
+                            Inst::iflt => llvm::LLVMIntPredicate::LLVMIntSLT,
                             Inst::ifle => llvm::LLVMIntPredicate::LLVMIntSLE,
+                            Inst::ifgt => llvm::LLVMIntPredicate::LLVMIntSGT,
+                            Inst::ifge => llvm::LLVMIntPredicate::LLVMIntSGE,
                             _ => unreachable!(),
                         },
                         val,
@@ -847,14 +1012,32 @@ impl JIT {
                         CString::new("icmp").unwrap().as_ptr(),
                     );
                     let destinations = block.kind.get_conditional_jump_destinations();
+                    self.gen_safepoint_poll(block.start + pc, destinations);
                     let bb_then = self.get_basic_block(destinations[0]).retrieve();
                     let bb_else = self.get_basic_block(destinations[1]).retrieve();
                     LLVMBuildCondBr(self.builder, cond_val, bb_then, bb_else);
                 }
+                Inst::tableswitch | Inst::lookupswitch => {
+                    let key = stack.pop().unwrap();
+                    let switch_pc = (block.start + pc) as isize;
+                    let table = SwitchTable::read(code, pc, block.start);
+                    let bb_default = self
+                        .get_basic_block((switch_pc + table.default) as usize)
+                        .retrieve();
+                    let switch =
+                        LLVMBuildSwitch(self.builder, key, bb_default, table.cases.len() as u32);
+                    for (case, offset) in table.cases {
+                        let bb = self
+                            .get_basic_block((switch_pc + offset) as usize)
+                            .retrieve();
+                        LLVMAddCase(switch, llvm_const_int32(self.context, case as u64), bb);
+                    }
+                }
                 Inst::goto => {
                     let destination = block.kind.get_unconditional_jump_destination();
                     let bb_goto = self.get_basic_block(destination).retrieve();
                     if cur_bb_has_no_terminator(self.builder) {
+                        self.gen_safepoint_poll(block.start + pc, &[destination]);
                         LLVMBuildBr(self.builder, bb_goto);
                     }
                 }
@@ -867,7 +1050,7 @@ impl JIT {
                     let inc = LLVMBuildAdd(
                         self.builder,
                         var_val,
-                        llvm_const_uint32(self.context, const_ as u64),
+                        llvm_const_int32(self.context, const_ as i8 as u64),
                         CString::new("iinc").unwrap().as_ptr(),
                     );
                     LLVMBuildStore(self.builder, inc, var_ref);
@@ -942,6 +1125,64 @@ impl JIT {
                         CString::new("dmul").unwrap().as_ptr(),
                     ));
                 }
+                Inst::fadd => {
+                    let val2 = stack.pop().unwrap();
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildFAdd(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("fadd").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::fsub => {
+                    let val2 = stack.pop().unwrap();
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildFSub(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("fsub").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::fmul => {
+                    let val2 = stack.pop().unwrap();
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildFMul(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("fmul").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::fdiv => {
+                    let val2 = stack.pop().unwrap();
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildFDiv(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("fdiv").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::frem => {
+                    let val2 = stack.pop().unwrap();
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildFRem(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("frem").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::fneg => {
+                    let val = stack.pop().unwrap();
+                    stack.push(LLVMBuildFNeg(
+                        self.builder,
+                        val,
+                        CString::new("fneg").unwrap().as_ptr(),
+                    ));
+                }
                 Inst::iand => {
                     let val2 = stack.pop().unwrap();
                     let val1 = stack.pop().unwrap();
@@ -982,7 +1223,108 @@ impl JIT {
                         CString::new("ishr").unwrap().as_ptr(),
                     ));
                 }
-                Inst::dcmpl | Inst::dcmpg => self.gen_dcmp(&mut stack)?,
+                Inst::ladd => {
+                    let val2 = stack.pop().unwrap();
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildAdd(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("ladd").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::lsub => {
+                    let val2 = stack.pop().unwrap();
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildSub(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("lsub").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::lmul => {
+                    let val2 = stack.pop().unwrap();
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildMul(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("lmul").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::lneg => {
+                    let val = stack.pop().unwrap();
+                    stack.push(LLVMBuildNeg(
+                        self.builder,
+                        val,
+                        CString::new("lneg").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::land => {
+                    let val2 = stack.pop().unwrap();
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildAnd(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("land").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::lor => {
+                    let val2 = stack.pop().unwrap();
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildOr(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("lor").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::lxor => {
+                    let val2 = stack.pop().unwrap();
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildXor(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("lxor").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::lshl => {
+                    let val2 = self.gen_long_shift_count(stack.pop().unwrap());
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildShl(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("lshl").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::lshr => {
+                    let val2 = self.gen_long_shift_count(stack.pop().unwrap());
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildAShr(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("lshr").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::lushr => {
+                    let val2 = self.gen_long_shift_count(stack.pop().unwrap());
+                    let val1 = stack.pop().unwrap();
+                    stack.push(LLVMBuildLShr(
+                        self.builder,
+                        val1,
+                        val2,
+                        CString::new("lushr").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::lcmp => self.gen_lcmp(&mut stack),
+                Inst::fcmpl | Inst::fcmpg | Inst::dcmpl | Inst::dcmpg => {
+                    self.gen_dcmp(&mut stack)?
+                }
                 Inst::bipush => {
                     stack.push(llvm_const_int32(self.context, code[pc + 1] as i8 as u64));
                 }
@@ -997,10 +1339,7 @@ impl JIT {
                         Constant::IntegerInfo { i } => {
                             stack.push(llvm_const_int32(self.context, i as u64))
                         }
-                        Constant::FloatInfo { f } => stack.push(LLVMConstReal(
-                            LLVMFloatTypeInContext(self.context),
-                            f as f64,
-                        )),
+                        Constant::FloatInfo { f } => stack.push(llvm_const_float(self.context, f)),
                         Constant::String { string_index } => stack.push({
                             let string_object = (&mut *self.cur_class.unwrap())
                                 .get_java_string_utf8_from_const_pool(
@@ -1010,6 +1349,23 @@ impl JIT {
                                 .unwrap();
                             llvm_const_ptr(self.context, string_object as GcType<u64>)
                         }),
+                        // Only mirrors already created by the interpreter are compiled
+                        Constant::ClassInfo { name_index } => {
+                            let name = cur_class.classfile.constant_pool[name_index as usize]
+                                .get_utf8()
+                                .unwrap();
+                            let descriptor = if name.starts_with('[') {
+                                name.clone()
+                            } else {
+                                format!("L{};", name)
+                            };
+                            match (&*cur_class.classheap.unwrap()).mirrors.get(&descriptor) {
+                                Some(mirror) => {
+                                    stack.push(llvm_const_ptr(self.context, *mirror as GcType<u64>))
+                                }
+                                None => return Err(Error::CouldntCompile),
+                            }
+                        }
                         _ => return Err(Error::CouldntCompile),
                     };
                 }
@@ -1020,6 +1376,9 @@ impl JIT {
                         Constant::DoubleInfo { f } => {
                             stack.push(llvm_const_double(self.context, f))
                         }
+                        Constant::LongInfo { i } => {
+                            stack.push(llvm_const_int64(self.context, i as u64))
+                        }
                         _ => return Err(Error::CouldntCompile),
                     };
                 }
@@ -1041,6 +1400,7 @@ impl JIT {
                             index,
                         ],
                     );
+                    self.gen_exit_if_exception_pending(block.start + pc, loop_compile);
                     stack.push(val);
                 }
                 Inst::bastore => {
@@ -1059,8 +1419,11 @@ impl JIT {
                             val,
                         ],
                     );
+                    self.gen_exit_if_exception_pending(block.start + pc, loop_compile);
                 }
-                Inst::ireturn | Inst::dreturn | Inst::areturn if !loop_compile => {
+                Inst::ireturn | Inst::lreturn | Inst::freturn | Inst::dreturn | Inst::areturn
+                    if !loop_compile =>
+                {
                     let val = stack.pop().unwrap();
                     LLVMBuildRet(self.builder, val);
                 }
@@ -1088,11 +1451,19 @@ impl JIT {
                     let class = (&*cur_class.classheap.unwrap())
                         .get_class(class_name)
                         .unwrap();
-                    let name_index = fld!(
+                    let (name_index, descriptor_index) = fld!(
                         Constant::NameAndTypeInfo,
                         &cur_class.classfile.constant_pool[name_and_type_index],
-                        name_index
+                        name_index,
+                        descriptor_index
                     );
+                    // Only references are supported
+                    let descriptor = cur_class.classfile.constant_pool[descriptor_index]
+                        .get_utf8()
+                        .unwrap();
+                    if !descriptor.starts_with('L') && !descriptor.starts_with('[') {
+                        return Err(Error::CouldntCompile);
+                    }
                     let name = cur_class.classfile.constant_pool[name_index]
                         .get_utf8()
                         .unwrap();
@@ -1121,11 +1492,29 @@ impl JIT {
                     );
                     stack.push(ret);
                 }
+                Inst::checkcast => {
+                    let objectref = *stack.last().unwrap();
+                    let class_index = ((code[pc + 1] as usize) << 8) + code[pc + 2] as usize;
+                    let passed = self.gen_type_check(cur_code, objectref, class_index);
+                    let failed = LLVMBuildICmp(
+                        self.builder,
+                        llvm::LLVMIntPredicate::LLVMIntEQ,
+                        passed,
+                        llvm_const_int32(self.context, 0),
+                        CString::new("").unwrap().as_ptr(),
+                    );
+                    self.gen_exit_on_exception(failed, block.start + pc, loop_compile);
+                }
+                Inst::instanceof => {
+                    let objectref = stack.pop().unwrap();
+                    let class_index = ((code[pc + 1] as usize) << 8) + code[pc + 2] as usize;
+                    stack.push(self.gen_type_check(cur_code, objectref, class_index));
+                }
                 Inst::pop => {
                     stack.pop().unwrap();
                 }
                 Inst::dup => {
-                    let val = stack.last().clone().unwrap();
+                    let val = stack.last().unwrap();
                     stack.push(*val);
                 }
                 Inst::i2d => {
@@ -1146,126 +1535,877 @@ impl JIT {
                         CString::new("d2i").unwrap().as_ptr(),
                     ));
                 }
-                Inst::invokespecial => {}
-                Inst::invokestatic | Inst::invokevirtual => {
-                    // TODO: The following code should be a method.
-                    let cur_class = &mut *self.cur_class.unwrap();
-                    let mref_index = ((code[pc + 1] as usize) << 8) + code[pc + 2] as usize;
-                    let (class_index, name_and_type_index) = fld!(
-                        Constant::MethodrefInfo,
-                        &cur_class.classfile.constant_pool[mref_index],
-                        class_index,
-                        name_and_type_index
-                    );
-                    let name_index = fld!(
-                        Constant::ClassInfo,
-                        &cur_class.classfile.constant_pool[class_index],
-                        name_index
-                    );
-                    let class_name = cur_class.classfile.constant_pool[name_index as usize]
-                        .get_utf8()
-                        .unwrap();
-                    let class = (&*cur_class.classheap.unwrap())
-                        .get_class(class_name)
-                        .unwrap();
-                    let (name_index, descriptor_index) = fld!(
-                        Constant::NameAndTypeInfo,
-                        &cur_class.classfile.constant_pool[name_and_type_index],
-                        name_index,
-                        descriptor_index
-                    );
-                    let jit_info_mgr = (&mut *class).get_jit_info_mgr(name_index, descriptor_index);
-                    let jit_func = jit_info_mgr.get_jit_func();
-                    let mut renv_need = false;
-
-                    let llvm_func = if Some((
-                        class_name.clone(),
-                        name_index as usize,
-                        descriptor_index as usize,
-                    )) == self.cur_func_indices
-                    {
-                        self.cur_func.unwrap()
-                    } else if let Some(native_func) = {
-                        let name = cur_class.classfile.constant_pool[name_index]
-                            .get_utf8()
-                            .unwrap();
-                        let descriptor = cur_class.classfile.constant_pool[descriptor_index]
-                            .get_utf8()
-                            .unwrap();
-                        let signature = format!("{}.{}:{}", class_name, name, descriptor);
-                        self.native_functions.get(signature.as_str())
-                    } {
-                        renv_need = true;
-                        *native_func
-                    } else {
-                        if jit_func.is_none() {
-                            return Err(Error::CouldntCompile);
-                        }
-
-                        let exec_info = jit_func.clone().unwrap();
-                        if exec_info.cant_compile {
-                            return Err(Error::CouldntCompile);
-                        }
-
-                        exec_info.func
-                    };
-
-                    let mut args = vec![];
-                    let args_count = LLVMCountParams(llvm_func) - if renv_need { 1 } else { 0 };
-                    for _ in 0..args_count {
-                        args.push(stack.pop().unwrap());
+                Inst::i2l => {
+                    let val = stack.pop().unwrap();
+                    stack.push(LLVMBuildSExt(
+                        self.builder,
+                        val,
+                        VariableType::Long.to_llvmty(self.context),
+                        CString::new("i2l").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::l2i => {
+                    let val = stack.pop().unwrap();
+                    stack.push(LLVMBuildTrunc(
+                        self.builder,
+                        val,
+                        VariableType::Int.to_llvmty(self.context),
+                        CString::new("l2i").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::l2d => {
+                    let val = stack.pop().unwrap();
+                    stack.push(LLVMBuildSIToFP(
+                        self.builder,
+                        val,
+                        VariableType::Double.to_llvmty(self.context),
+                        CString::new("l2d").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::d2l => {
+                    let val = stack.pop().unwrap();
+                    stack.push(LLVMBuildFPToSI(
+                        self.builder,
+                        val,
+                        VariableType::Long.to_llvmty(self.context),
+                        CString::new("d2l").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::i2f => {
+                    let val = stack.pop().unwrap();
+                    stack.push(LLVMBuildSIToFP(
+                        self.builder,
+                        val,
+                        VariableType::Float.to_llvmty(self.context),
+                        CString::new("i2f").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::l2f => {
+                    let val = stack.pop().unwrap();
+                    stack.push(LLVMBuildSIToFP(
+                        self.builder,
+                        val,
+                        VariableType::Float.to_llvmty(self.context),
+                        CString::new("l2f").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::f2i => {
+                    let val = stack.pop().unwrap();
+                    stack.push(LLVMBuildFPToSI(
+                        self.builder,
+                        val,
+                        VariableType::Int.to_llvmty(self.context),
+                        CString::new("f2i").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::f2l => {
+                    let val = stack.pop().unwrap();
+                    stack.push(LLVMBuildFPToSI(
+                        self.builder,
+                        val,
+                        VariableType::Long.to_llvmty(self.context),
+                        CString::new("f2l").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::f2d => {
+                    let val = stack.pop().unwrap();
+                    stack.push(LLVMBuildFPExt(
+                        self.builder,
+                        val,
+                        VariableType::Double.to_llvmty(self.context),
+                        CString::new("f2d").unwrap().as_ptr(),
+                    ));
+                }
+                Inst::d2f => {
+                    let val = stack.pop().unwrap();
+                    stack.push(LLVMBuildFPTrunc(
+                        self.builder,
+                        val,
+                        VariableType::Float.to_llvmty(self.context),
+                        CString::new("d2f").unwrap().as_ptr(),
+                    ));
+                }
+                // Constructors and private or super methods aren't called from compiled code yet.
+                // Skipping the call would leave objects created by ``new`` uninitialized.
+                Inst::invokespecial => return Err(Error::CouldntCompile),
+                Inst::invokestatic | Inst::invokevirtual => {
+                    // TODO: The following code should be a method.
+                    let cur_class = &mut *self.cur_class.unwrap();
+                    let mref_index = ((code[pc + 1] as usize) << 8) + code[pc + 2] as usize;
+                    let (class_index, name_and_type_index) = fld!(
+                        Constant::MethodrefInfo,
+                        &cur_class.classfile.constant_pool[mref_index],
+                        class_index,
+                        name_and_type_index
+                    );
+                    let name_index = fld!(
+                        Constant::ClassInfo,
+                        &cur_class.classfile.constant_pool[class_index],
+                        name_index
+                    );
+                    let class_name = cur_class.classfile.constant_pool[name_index as usize]
+                        .get_utf8()
+                        .unwrap();
+                    // Methods of array types like ``[I.clone`` are left to the interpreter
+                    let class = match (&*cur_class.classheap.unwrap()).get_class(class_name) {
+                        Some(class) => class,
+                        None => return Err(Error::CouldntCompile),
+                    };
+                    let (name_index, descriptor_index) = fld!(
+                        Constant::NameAndTypeInfo,
+                        &cur_class.classfile.constant_pool[name_and_type_index],
+                        name_index,
+                        descriptor_index
+                    );
+
+                    let signature = format!(
+                        "{}.{}:{}",
+                        class_name,
+                        cur_class.classfile.constant_pool[name_index]
+                            .get_utf8()
+                            .unwrap(),
+                        cur_class.classfile.constant_pool[descriptor_index]
+                            .get_utf8()
+                            .unwrap()
+                    );
+
+                    if self.gen_atomic_intrinsic(
+                        signature.as_str(),
+                        class,
+                        &mut stack,
+                        block.start + pc,
+                        loop_compile,
+                    ) {
+                        pc += Inst::get_inst_size_at(code, pc, block.start);
+                        continue;
+                    }
+
+                    if let Some(native) = (&*self.runtime_env).natives.get(signature.as_str()) {
+                        let native = native.clone();
+                        self.gen_native_call(&native, cur_code == Inst::invokevirtual, &mut stack);
+
+                        // Including when the arguments can't be passed to the native method
+                        self.gen_exit_if_exception_pending(block.start + pc, loop_compile);
+
+                        pc += Inst::get_inst_size_at(code, pc, block.start);
+                        continue;
                     }
-                    if renv_need {
-                        args.push(llvm_const_ptr(self.context, self.runtime_env as *mut u64));
+
+                    let jit_info_mgr = (&mut *class).get_jit_info_mgr(name_index, descriptor_index);
+                    let jit_func = jit_info_mgr.get_jit_func();
+                    let llvm_func = if Some((class_name.clone(), name_index, descriptor_index))
+                        == self.cur_func_indices
+                    {
+                        self.cur_func.unwrap()
+                    } else {
+                        if jit_func.is_none() {
+                            return Err(Error::CouldntCompile);
+                        }
+
+                        let exec_info = jit_func.clone().unwrap();
+                        if exec_info.cant_compile {
+                            return Err(Error::CouldntCompile);
+                        }
+
+                        exec_info.func
+                    };
+
+                    let mut args = vec![];
+                    for _ in 0..LLVMCountParams(llvm_func) {
+                        args.push(stack.pop().unwrap());
                     }
                     args.reverse();
 
                     let ret = self.call_function(llvm_func, args);
 
+                    self.gen_exit_if_exception_pending(block.start + pc, loop_compile);
+
                     if LLVMGetTypeKind(LLVMGetElementType(LLVMGetReturnType(LLVMTypeOf(llvm_func))))
                         != llvm::LLVMTypeKind::LLVMVoidTypeKind
                     {
                         stack.push(ret);
                     }
                 }
+                Inst::invokedynamic => {
+                    let index = ((code[pc + 1] as usize) << 8) + code[pc + 2] as usize;
+                    // Only call sites already linked by the interpreter are compiled
+                    let call_site = match (&*self.cur_class.unwrap()).call_sites.get(&index) {
+                        Some(call_site) => call_site.clone(),
+                        None => return Err(Error::CouldntCompile),
+                    };
+                    let (args_count, slots_count) = match &*call_site {
+                        CallSite::Concat { parts, slots } => (
+                            parts
+                                .iter()
+                                .filter(|part| match part {
+                                    ConcatPart::Argument(..) => true,
+                                    ConcatPart::Literal(_) => false,
+                                })
+                                .count(),
+                            *slots,
+                        ),
+                        _ => return Err(Error::CouldntCompile),
+                    };
+
+                    let mut args = vec![];
+                    for _ in 0..args_count {
+                        args.push(stack.pop().unwrap());
+                    }
+                    args.reverse();
+
+                    let slots = self.gen_slots_array(args, slots_count);
+                    let string = self.call_function(
+                        *self
+                            .native_functions
+                            .get("ferrugo_internal_concat")
+                            .unwrap(),
+                        vec![
+                            llvm_const_ptr(self.context, self.runtime_env as *mut u64),
+                            llvm_const_ptr(
+                                self.context,
+                                &*call_site as *const CallSite as *mut u64,
+                            ),
+                            slots,
+                        ],
+                    );
+
+                    self.gen_exit_if_exception_pending(block.start + pc, loop_compile);
+
+                    stack.push(string);
+                }
                 e => {
                     dprintln!("***JIT: unimplemented instruction: {}***", e);
                     return Err(Error::CouldntCompile);
                 }
             }
 
-            pc += Inst::get_inst_size(cur_code);
+            pc += Inst::get_inst_size_at(code, pc, block.start);
         }
 
         Ok(stack)
     }
 
+    /// Emits a call to a registered native method. The arguments are passed in an array laid out
+    /// as slots of the operand stack.
+    unsafe fn gen_native_call(
+        &mut self,
+        native: &NativeMethod,
+        has_this: bool,
+        stack: &mut Vec<LLVMValueRef>,
+    ) {
+        let mut args = vec![];
+        for _ in 0..marshal::param_types(native.descriptor.as_str()).len() + has_this as usize {
+            args.push(stack.pop().unwrap());
+        }
+        args.reverse();
+
+        let slots = self.gen_slots_array(args, native.slots_count(has_this));
+        let link = NativeLink::new(&(&*self.runtime_env).natives, native.signature.as_str());
+        self.native_links.push(Box::new(link));
+        let link = &mut **self.native_links.last_mut().unwrap() as *mut NativeLink;
+        let ret = self.call_function(
+            *self
+                .native_functions
+                .get("ferrugo_internal_call_native")
+                .unwrap(),
+            vec![
+                llvm_const_ptr(self.context, self.runtime_env as *mut u64),
+                llvm_const_ptr(self.context, link as *mut u64),
+                llvm_const_int32(self.context, has_this as u64),
+                slots,
+            ],
+        );
+
+        let ret_ty = native
+            .descriptor
+            .chars()
+            .skip_while(|c| *c != ')')
+            .nth(1)
+            .unwrap();
+        let ret = match ret_ty {
+            'V' => return,
+            'J' => ret,
+            'F' => LLVMBuildBitCast(
+                self.builder,
+                LLVMBuildTrunc(
+                    self.builder,
+                    ret,
+                    LLVMInt32TypeInContext(self.context),
+                    CString::new("").unwrap().as_ptr(),
+                ),
+                VariableType::Float.to_llvmty(self.context),
+                CString::new("").unwrap().as_ptr(),
+            ),
+            'D' => LLVMBuildBitCast(
+                self.builder,
+                ret,
+                VariableType::Double.to_llvmty(self.context),
+                CString::new("").unwrap().as_ptr(),
+            ),
+            'L' | '[' => LLVMBuildIntToPtr(
+                self.builder,
+                ret,
+                VariableType::Pointer.to_llvmty(self.context),
+                CString::new("").unwrap().as_ptr(),
+            ),
+            // A boolean is an int
+            _ => LLVMBuildTrunc(
+                self.builder,
+                ret,
+                VariableType::Int.to_llvmty(self.context),
+                CString::new("").unwrap().as_ptr(),
+            ),
+        };
+        stack.push(ret);
+    }
+
+    /// Stores ``args`` into an array laid out as slots of the operand stack and returns a pointer
+    /// to it
+    unsafe fn gen_slots_array(
+        &mut self,
+        args: Vec<LLVMValueRef>,
+        slots_count: usize,
+    ) -> LLVMValueRef {
+        let slot_ty = LLVMInt64TypeInContext(self.context);
+
+        // Allocate the array in the entry block not to grow the stack in loops
+        let cur_bb = LLVMGetInsertBlock(self.builder);
+        let entry_bb = LLVMGetEntryBasicBlock(LLVMGetBasicBlockParent(cur_bb));
+        let builder = LLVMCreateBuilderInContext(self.context);
+        let first_inst = LLVMGetFirstInstruction(entry_bb);
+        if first_inst.is_null() {
+            LLVMPositionBuilderAtEnd(builder, entry_bb);
+        } else {
+            LLVMPositionBuilderBefore(builder, first_inst);
+        }
+        let slots = LLVMBuildAlloca(
+            builder,
+            LLVMArrayType(slot_ty, slots_count.max(1) as u32),
+            CString::new("").unwrap().as_ptr(),
+        );
+        LLVMDisposeBuilder(builder);
+
+        let mut i = 0;
+        for arg in args {
+            let ty = LLVMTypeOf(arg);
+            let (slot, size) = match LLVMGetTypeKind(ty) {
+                llvm::LLVMTypeKind::LLVMDoubleTypeKind => (
+                    LLVMBuildBitCast(
+                        self.builder,
+                        arg,
+                        slot_ty,
+                        CString::new("").unwrap().as_ptr(),
+                    ),
+                    2,
+                ),
+                llvm::LLVMTypeKind::LLVMPointerTypeKind => (
+                    LLVMBuildPtrToInt(
+                        self.builder,
+                        arg,
+                        slot_ty,
+                        CString::new("").unwrap().as_ptr(),
+                    ),
+                    1,
+                ),
+                // A float occupies the low 32 bits of a slot
+                llvm::LLVMTypeKind::LLVMFloatTypeKind => (
+                    LLVMBuildZExt(
+                        self.builder,
+                        LLVMBuildBitCast(
+                            self.builder,
+                            arg,
+                            LLVMInt32TypeInContext(self.context),
+                            CString::new("").unwrap().as_ptr(),
+                        ),
+                        slot_ty,
+                        CString::new("").unwrap().as_ptr(),
+                    ),
+                    1,
+                ),
+                _ if LLVMGetIntTypeWidth(ty) == 64 => (arg, 2),
+                _ => (
+                    LLVMBuildSExt(
+                        self.builder,
+                        arg,
+                        slot_ty,
+                        CString::new("").unwrap().as_ptr(),
+                    ),
+                    1,
+                ),
+            };
+            let mut indices = vec![
+                llvm_const_int32(self.context, 0),
+                llvm_const_int32(self.context, i),
+            ];
+            let ptr = LLVMBuildGEP(
+                self.builder,
+                slots,
+                indices.as_mut_ptr(),
+                2,
+                CString::new("").unwrap().as_ptr(),
+            );
+            LLVMBuildStore(self.builder, slot, ptr);
+            i += size;
+        }
+
+        LLVMBuildBitCast(
+            self.builder,
+            slots,
+            VariableType::Pointer.to_llvmty(self.context),
+            CString::new("").unwrap().as_ptr(),
+        )
+    }
+
+    /// Emits atomic instructions in place of calling the methods of ``AtomicInteger``,
+    /// ``AtomicLong``, ``AtomicReference`` and the CAS methods of ``sun.misc.Unsafe``. Returns
+    /// false if ``signature`` is not such a method or the method may be overridden.
+    unsafe fn gen_atomic_intrinsic(
+        &mut self,
+        signature: &str,
+        class: GcType<Class>,
+        stack: &mut Vec<LLVMValueRef>,
+        pc: usize,
+        loop_compile: bool,
+    ) -> bool {
+        let (class_name, method) = match signature.find('.') {
+            Some(pos) => (&signature[..pos], &signature[pos + 1..]),
+            None => return false,
+        };
+        let is_unsafe = class_name == "sun/misc/Unsafe";
+        let (ty, op) = match (class_name, method) {
+            ("java/util/concurrent/atomic/AtomicInteger", m) => match m {
+                "get:()I" => (VariableType::Int, "get"),
+                "set:(I)V" => (VariableType::Int, "set"),
+                "compareAndSet:(II)Z" => (VariableType::Int, "compareAndSet"),
+                "getAndAdd:(I)I" => (VariableType::Int, "getAndAdd"),
+                "getAndSet:(I)I" => (VariableType::Int, "getAndSet"),
+                _ => return false,
+            },
+            ("java/util/concurrent/atomic/AtomicLong", m) => match m {
+                "get:()J" => (VariableType::Long, "get"),
+                "set:(J)V" => (VariableType::Long, "set"),
+                "compareAndSet:(JJ)Z" => (VariableType::Long, "compareAndSet"),
+                "getAndAdd:(J)J" => (VariableType::Long, "getAndAdd"),
+                "getAndSet:(J)J" => (VariableType::Long, "getAndSet"),
+                _ => return false,
+            },
+            ("java/util/concurrent/atomic/AtomicReference", m) => match m {
+                "get:()Ljava/lang/Object;" => (VariableType::Pointer, "get"),
+                "set:(Ljava/lang/Object;)V" => (VariableType::Pointer, "set"),
+                "compareAndSet:(Ljava/lang/Object;Ljava/lang/Object;)Z" => {
+                    (VariableType::Pointer, "compareAndSet")
+                }
+                "getAndSet:(Ljava/lang/Object;)Ljava/lang/Object;" => {
+                    (VariableType::Pointer, "getAndSet")
+                }
+                _ => return false,
+            },
+            ("sun/misc/Unsafe", m) => match m {
+                "compareAndSwapInt:(Ljava/lang/Object;JII)Z" => (VariableType::Int, "compareAndSet"),
+                "compareAndSwapLong:(Ljava/lang/Object;JJJ)Z" => {
+                    (VariableType::Long, "compareAndSet")
+                }
+                "compareAndSwapObject:(Ljava/lang/Object;JLjava/lang/Object;Ljava/lang/Object;)Z" => {
+                    (VariableType::Pointer, "compareAndSet")
+                }
+                "getAndAddInt:(Ljava/lang/Object;JI)I" => (VariableType::Int, "getAndAdd"),
+                "getAndAddLong:(Ljava/lang/Object;JJ)J" => (VariableType::Long, "getAndAdd"),
+                "getAndSetInt:(Ljava/lang/Object;JI)I" => (VariableType::Int, "getAndSet"),
+                "getAndSetLong:(Ljava/lang/Object;JJ)J" => (VariableType::Long, "getAndSet"),
+                "getAndSetObject:(Ljava/lang/Object;JLjava/lang/Object;)Ljava/lang/Object;" => {
+                    (VariableType::Pointer, "getAndSet")
+                }
+                _ => return false,
+            },
+            _ => return false,
+        };
+
+        // A subclass may override the method unless it or its class is final
+        let (name, descriptor) = method.split_at(method.find(':').unwrap());
+        let overridable = match (&*class).get_method(name, &descriptor[1..]) {
+            Some((_, method)) => {
+                !method.check_access_flags(access_flags::ACC_PACC_FINAL)
+                    && (&*class).classfile.access_flags & access_flags::ACC_PACC_FINAL == 0
+            }
+            None => true,
+        };
+        if overridable {
+            return false;
+        }
+
+        let mut args = vec![];
+        for _ in 0..match op {
+            "get" => 0,
+            "compareAndSet" => 2,
+            _ => 1,
+        } {
+            args.push(stack.pop().unwrap());
+        }
+        args.reverse();
+
+        // An int is held in the lower half of its 8-byte slot. A reference is handled as an i64
+        // since atomic instructions don't take pointer operands.
+        let (val_ty, align) = match ty {
+            VariableType::Int => (VariableType::Int.to_llvmty(self.context), 4),
+            _ => (LLVMInt64TypeInContext(self.context), 8),
+        };
+        let slot = if is_unsafe {
+            // The target is checked at runtime as the offset is an arbitrary long
+            let offset = stack.pop().unwrap();
+            let object = stack.pop().unwrap();
+            stack.pop().unwrap(); // The Unsafe instance
+            let slot = self.call_function(
+                *self
+                    .native_functions
+                    .get("ferrugo_internal_unsafe_target")
+                    .unwrap(),
+                vec![
+                    llvm_const_ptr(self.context, self.runtime_env as *mut u64),
+                    object,
+                    offset,
+                    llvm_const_int32(self.context, align as u64),
+                ],
+            );
+            let failed = LLVMBuildIsNull(self.builder, slot, CString::new("").unwrap().as_ptr());
+            self.gen_exit_on_exception(failed, pc, loop_compile);
+            slot
+        } else {
+            let object = stack.pop().unwrap();
+            self.gen_null_check(object, pc, loop_compile);
+            let (id, _) = *(&*class).get_numbered_field_info("value").unwrap();
+            self.call_function(
+                *self
+                    .native_functions
+                    .get("ferrugo_internal_field_ptr")
+                    .unwrap(),
+                vec![
+                    llvm_const_ptr(self.context, self.runtime_env as *mut u64),
+                    object,
+                    llvm_const_int32(self.context, id as u64),
+                ],
+            )
+        };
+        let slot = LLVMBuildBitCast(
+            self.builder,
+            slot,
+            LLVMPointerType(val_ty, 0),
+            CString::new("").unwrap().as_ptr(),
+        );
+        let args: Vec<LLVMValueRef> = args
+            .into_iter()
+            .map(|arg| match ty {
+                VariableType::Pointer => LLVMBuildPtrToInt(
+                    self.builder,
+                    arg,
+                    val_ty,
+                    CString::new("").unwrap().as_ptr(),
+                ),
+                _ => arg,
+            })
+            .collect();
+        let seq_cst = llvm::LLVMAtomicOrdering::LLVMAtomicOrderingSequentiallyConsistent;
+
+        let ret = match op {
+            "get" => {
+                let val = LLVMBuildLoad(self.builder, slot, CString::new("").unwrap().as_ptr());
+                LLVMSetOrdering(val, seq_cst);
+                LLVMSetAlignment(val, align);
+                Some(val)
+            }
+            "set" => {
+                let store = LLVMBuildStore(self.builder, args[0], slot);
+                LLVMSetOrdering(store, seq_cst);
+                LLVMSetAlignment(store, align);
+                None
+            }
+            "compareAndSet" => {
+                let pair = LLVMBuildAtomicCmpXchg(
+                    self.builder,
+                    slot,
+                    args[0],
+                    args[1],
+                    seq_cst,
+                    seq_cst,
+                    0,
+                );
+                let success = LLVMBuildExtractValue(
+                    self.builder,
+                    pair,
+                    1,
+                    CString::new("").unwrap().as_ptr(),
+                );
+                // A boolean is an int
+                Some(LLVMBuildZExt(
+                    self.builder,
+                    success,
+                    VariableType::Int.to_llvmty(self.context),
+                    CString::new("").unwrap().as_ptr(),
+                ))
+            }
+            "getAndAdd" => Some(LLVMBuildAtomicRMW(
+                self.builder,
+                llvm::LLVMAtomicRMWBinOp::LLVMAtomicRMWBinOpAdd,
+                slot,
+                args[0],
+                seq_cst,
+                0,
+            )),
+            "getAndSet" => Some(LLVMBuildAtomicRMW(
+                self.builder,
+                llvm::LLVMAtomicRMWBinOp::LLVMAtomicRMWBinOpXchg,
+                slot,
+                args[0],
+                seq_cst,
+                0,
+            )),
+            _ => unreachable!(),
+        };
+
+        if let Some(ret) = ret {
+            stack.push(match ty {
+                VariableType::Pointer if op != "compareAndSet" => LLVMBuildIntToPtr(
+                    self.builder,
+                    ret,
+                    VariableType::Pointer.to_llvmty(self.context),
+                    CString::new("").unwrap().as_ptr(),
+                ),
+                _ => ret,
+            });
+        }
+
+        true
+    }
+
+    /// Throws ``NullPointerException`` and leaves the compiled code if ``objectref`` is null.
+    unsafe fn gen_null_check(&mut self, objectref: LLVMValueRef, pc: usize, loop_compile: bool) {
+        let func = self.cur_func.unwrap();
+        let bb_null = LLVMAppendBasicBlock(func, CString::new("").unwrap().as_ptr());
+        let bb_cont = LLVMAppendBasicBlock(func, CString::new("").unwrap().as_ptr());
+        let is_null = LLVMBuildIsNull(self.builder, objectref, CString::new("").unwrap().as_ptr());
+        LLVMBuildCondBr(self.builder, is_null, bb_null, bb_cont);
+
+        LLVMPositionBuilderAtEnd(self.builder, bb_null);
+        self.call_function(
+            *self
+                .native_functions
+                .get("ferrugo_internal_throw_null_pointer_exception")
+                .unwrap(),
+            vec![llvm_const_ptr(self.context, self.runtime_env as *mut u64)],
+        );
+        self.gen_exit(pc, loop_compile);
+
+        LLVMPositionBuilderAtEnd(self.builder, bb_cont);
+    }
+
     #[rustfmt::skip]
+    /// Emits ``checkcast`` or ``instanceof`` of ``objectref`` and returns whether it passed. Only
+    /// when the class of ``objectref`` differs from the one cached at the site is the runtime
+    /// called, which throws ``ClassCastException`` for a failed ``checkcast``.
+    unsafe fn gen_type_check(
+        &mut self,
+        inst: Inst::Code,
+        objectref: LLVMValueRef,
+        class_index: usize,
+    ) -> LLVMValueRef {
+        let func = self.cur_func.unwrap();
+        let int64_ptr_ty = LLVMPointerType(LLVMInt64TypeInContext(self.context), 0);
+
+        // Neither a class nor the first word of an array (a discriminant of ``AType``) is ``!0``
+        self.type_check_caches.push(Box::new(!0));
+        let cache = &mut **self.type_check_caches.last_mut().unwrap() as *mut u64;
+
+        let bb_entry = LLVMGetInsertBlock(self.builder);
+        let bb_load = LLVMAppendBasicBlock(func, CString::new("").unwrap().as_ptr());
+        let bb_slow = LLVMAppendBasicBlock(func, CString::new("").unwrap().as_ptr());
+        let bb_done = LLVMAppendBasicBlock(func, CString::new("").unwrap().as_ptr());
+
+        let is_null = LLVMBuildIsNull(
+            self.builder,
+            objectref,
+            CString::new("").unwrap().as_ptr(),
+        );
+        LLVMBuildCondBr(self.builder, is_null, bb_done, bb_load);
+
+        // The class of an object is at offset 0
+        LLVMPositionBuilderAtEnd(self.builder, bb_load);
+        let class = LLVMBuildLoad(
+            self.builder,
+            LLVMBuildPointerCast(
+                self.builder,
+                objectref,
+                int64_ptr_ty,
+                CString::new("").unwrap().as_ptr(),
+            ),
+            CString::new("class").unwrap().as_ptr(),
+        );
+        let cached_class = LLVMBuildLoad(
+            self.builder,
+            LLVMConstIntToPtr(
+                LLVMConstInt(LLVMInt64TypeInContext(self.context), cache as u64, 0),
+                int64_ptr_ty,
+            ),
+            CString::new("cached_class").unwrap().as_ptr(),
+        );
+        let hit = LLVMBuildICmp(
+            self.builder,
+            llvm::LLVMIntPredicate::LLVMIntEQ,
+            class,
+            cached_class,
+            CString::new("").unwrap().as_ptr(),
+        );
+        LLVMBuildCondBr(self.builder, hit, bb_done, bb_slow);
+
+        LLVMPositionBuilderAtEnd(self.builder, bb_slow);
+        let checked = self.call_function(
+            *self
+                .native_functions
+                .get(match inst {
+                    Inst::checkcast => "ferrugo_internal_checkcast",
+                    Inst::instanceof => "ferrugo_internal_instanceof",
+                    _ => unreachable!(),
+                })
+                .unwrap(),
+            vec![
+                llvm_const_ptr(self.context, self.runtime_env as *mut u64),
+                objectref,
+                llvm_const_ptr(self.context, self.cur_class.unwrap() as *mut u64),
+                llvm_const_int32(self.context, class_index as u64),
+                llvm_const_ptr(self.context, cache),
+            ],
+        );
+        LLVMBuildBr(self.builder, bb_done);
+
+        LLVMPositionBuilderAtEnd(self.builder, bb_done);
+        let passed = LLVMBuildPhi(
+            self.builder,
+            VariableType::Int.to_llvmty(self.context),
+            CString::new("").unwrap().as_ptr(),
+        );
+        // null passes ``checkcast`` but is an instance of nothing
+        let null_passed = llvm_const_int32(self.context, (inst == Inst::checkcast) as u64);
+        LLVMAddIncoming(
+            passed,
+            vec![null_passed, llvm_const_int32(self.context, 1), checked].as_mut_ptr(),
+            vec![bb_entry, bb_load, bb_slow].as_mut_ptr(),
+            3,
+        );
+        passed
+    }
+
+    /// Leaves the compiled code if ``cond`` holds because an exception was thrown at ``pc``. A
+    /// function returns a dummy value and a loop returns ``pc``, and then the interpreter handles
+    /// the exception.
+    unsafe fn gen_exit_on_exception(&mut self, cond: LLVMValueRef, pc: usize, loop_compile: bool) {
+        let func = self.cur_func.unwrap();
+        let bb_exit = LLVMAppendBasicBlock(func, CString::new("").unwrap().as_ptr());
+        let bb_cont = LLVMAppendBasicBlock(func, CString::new("").unwrap().as_ptr());
+        LLVMBuildCondBr(self.builder, cond, bb_exit, bb_cont);
+
+        LLVMPositionBuilderAtEnd(self.builder, bb_exit);
+        self.gen_exit(pc, loop_compile);
+
+        LLVMPositionBuilderAtEnd(self.builder, bb_cont);
+    }
+
+    /// Leaves the compiled code if the runtime function just called threw an exception
+    unsafe fn gen_exit_if_exception_pending(&mut self, pc: usize, loop_compile: bool) {
+        let thrown = self.call_function(
+            *self
+                .native_functions
+                .get("ferrugo_internal_exception_pending")
+                .unwrap(),
+            vec![llvm_const_ptr(self.context, self.runtime_env as *mut u64)],
+        );
+        let thrown = LLVMBuildICmp(
+            self.builder,
+            llvm::LLVMIntPredicate::LLVMIntNE,
+            thrown,
+            llvm_const_int32(self.context, 0),
+            CString::new("").unwrap().as_ptr(),
+        );
+        self.gen_exit_on_exception(thrown, pc, loop_compile);
+    }
+
+    /// Leaves the compiled code for the interpreter to handle the exception thrown at ``pc``.
+    unsafe fn gen_exit(&mut self, pc: usize, loop_compile: bool) {
+        if loop_compile {
+            LLVMBuildRet(self.builder, llvm_const_int32(self.context, pc as u64));
+        } else {
+            let func = self.cur_func.unwrap();
+            let ret_ty = LLVMGetReturnType(LLVMGetElementType(LLVMTypeOf(func)));
+            if LLVMGetTypeKind(ret_ty) == llvm::LLVMTypeKind::LLVMVoidTypeKind {
+                LLVMBuildRetVoid(self.builder);
+            } else {
+                LLVMBuildRet(self.builder, LLVMConstNull(ret_ty));
+            }
+        }
+    }
+
+    /// Polls a safepoint if a branch at ``pc`` may jump backward to one of ``destinations``, so
+    /// that a compiled loop hands the VM lock over to other threads like the interpreter does.
+    unsafe fn gen_safepoint_poll(&mut self, pc: usize, destinations: &[usize]) {
+        if destinations.iter().all(|&dst| dst > pc) {
+            return;
+        }
+        self.call_function(
+            *self
+                .native_functions
+                .get("ferrugo_internal_safepoint")
+                .unwrap(),
+            vec![llvm_const_ptr(self.context, self.runtime_env as *mut u64)],
+        );
+    }
+
     unsafe fn gen_dcmp(&mut self, stack: &mut Vec<LLVMValueRef>) -> CResult<()> {
         let func = self.cur_func.unwrap();
         let v2 = stack.pop().unwrap();
         let v1 = stack.pop().unwrap();
-        let bb_merge = LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
+        let bb_merge =
+            LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
 
-        let cond1 = LLVMBuildFCmp(self.builder, llvm::LLVMRealPredicate::LLVMRealOGT, v1, v2, CString::new("").unwrap().as_ptr());
-        let bb_then1 = LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
-        let bb_else = LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
+        let cond1 = LLVMBuildFCmp(
+            self.builder,
+            llvm::LLVMRealPredicate::LLVMRealOGT,
+            v1,
+            v2,
+            CString::new("").unwrap().as_ptr(),
+        );
+        let bb_then1 =
+            LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
+        let bb_else =
+            LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
         LLVMBuildCondBr(self.builder, cond1, bb_then1, bb_else);
         LLVMPositionBuilderAtEnd(self.builder, bb_then1);
         LLVMBuildBr(self.builder, bb_merge);
 
         LLVMPositionBuilderAtEnd(self.builder, bb_else);
-        let cond2 = LLVMBuildFCmp(self.builder, llvm::LLVMRealPredicate::LLVMRealOEQ, v1, v2, CString::new("").unwrap().as_ptr());
-        let bb_then2 = LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
-        let bb_else = LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
+        let cond2 = LLVMBuildFCmp(
+            self.builder,
+            llvm::LLVMRealPredicate::LLVMRealOEQ,
+            v1,
+            v2,
+            CString::new("").unwrap().as_ptr(),
+        );
+        let bb_then2 =
+            LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
+        let bb_else =
+            LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
         LLVMBuildCondBr(self.builder, cond2, bb_then2, bb_else);
         LLVMPositionBuilderAtEnd(self.builder, bb_then2);
         LLVMBuildBr(self.builder, bb_merge);
 
         LLVMPositionBuilderAtEnd(self.builder, bb_else);
-        let cond3 = LLVMBuildFCmp(self.builder, llvm::LLVMRealPredicate::LLVMRealOLT, v1, v2, CString::new("").unwrap().as_ptr());
-        let bb_then3 = LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
-        let bb_else = LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
+        let cond3 = LLVMBuildFCmp(
+            self.builder,
+            llvm::LLVMRealPredicate::LLVMRealOLT,
+            v1,
+            v2,
+            CString::new("").unwrap().as_ptr(),
+        );
+        let bb_then3 =
+            LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
+        let bb_else =
+            LLVMAppendBasicBlockInContext(self.context, func, CString::new("").unwrap().as_ptr());
         LLVMBuildCondBr(self.builder, cond3, bb_then3, bb_else);
         LLVMPositionBuilderAtEnd(self.builder, bb_then3);
         LLVMBuildBr(self.builder, bb_merge);
@@ -1274,17 +2414,84 @@ impl JIT {
         LLVMBuildBr(self.builder, bb_merge);
 
         LLVMPositionBuilderAtEnd(self.builder, bb_merge);
-        let phi = LLVMBuildPhi(self.builder, VariableType::Int.to_llvmty(self.context), CString::new("").unwrap().as_ptr());
-        LLVMAddIncoming(phi, vec![llvm_const_int32(self.context, 1)].as_mut_ptr(), vec![bb_then1].as_mut_ptr(), 1);
-        LLVMAddIncoming(phi, vec![llvm_const_int32(self.context, 0)].as_mut_ptr(), vec![bb_then2].as_mut_ptr(), 1);
-        LLVMAddIncoming(phi, vec![llvm_const_int32(self.context, (0-1) as u64)].as_mut_ptr(), vec![bb_then3].as_mut_ptr(), 1);
-        LLVMAddIncoming(phi, vec![llvm_const_int32(self.context, 0)].as_mut_ptr(), vec![bb_else].as_mut_ptr(), 1); // TODO
+        let phi = LLVMBuildPhi(
+            self.builder,
+            VariableType::Int.to_llvmty(self.context),
+            CString::new("").unwrap().as_ptr(),
+        );
+        LLVMAddIncoming(
+            phi,
+            vec![llvm_const_int32(self.context, 1)].as_mut_ptr(),
+            vec![bb_then1].as_mut_ptr(),
+            1,
+        );
+        LLVMAddIncoming(
+            phi,
+            vec![llvm_const_int32(self.context, 0)].as_mut_ptr(),
+            vec![bb_then2].as_mut_ptr(),
+            1,
+        );
+        LLVMAddIncoming(
+            phi,
+            vec![llvm_const_int32(self.context, (0 - 1) as u64)].as_mut_ptr(),
+            vec![bb_then3].as_mut_ptr(),
+            1,
+        );
+        LLVMAddIncoming(
+            phi,
+            vec![llvm_const_int32(self.context, 0)].as_mut_ptr(),
+            vec![bb_else].as_mut_ptr(),
+            1,
+        ); // TODO
 
         stack.push(phi);
 
         Ok(())
     }
 
+    // lcmp yields (val1 > val2) - (val1 < val2)
+    unsafe fn gen_lcmp(&mut self, stack: &mut Vec<LLVMValueRef>) {
+        let val2 = stack.pop().unwrap();
+        let val1 = stack.pop().unwrap();
+        let int_ty = VariableType::Int.to_llvmty(self.context);
+        let gt = LLVMBuildICmp(
+            self.builder,
+            llvm::LLVMIntPredicate::LLVMIntSGT,
+            val1,
+            val2,
+            CString::new("lcmp").unwrap().as_ptr(),
+        );
+        let lt = LLVMBuildICmp(
+            self.builder,
+            llvm::LLVMIntPredicate::LLVMIntSLT,
+            val1,
+            val2,
+            CString::new("lcmp").unwrap().as_ptr(),
+        );
+        stack.push(LLVMBuildSub(
+            self.builder,
+            LLVMBuildZExt(self.builder, gt, int_ty, CString::new("").unwrap().as_ptr()),
+            LLVMBuildZExt(self.builder, lt, int_ty, CString::new("").unwrap().as_ptr()),
+            CString::new("lcmp").unwrap().as_ptr(),
+        ));
+    }
+
+    // The shift count of a long shift is an int of which only the low 6 bits are used
+    unsafe fn gen_long_shift_count(&mut self, count: LLVMValueRef) -> LLVMValueRef {
+        let count = LLVMBuildAnd(
+            self.builder,
+            count,
+            llvm_const_int32(self.context, 0x3f),
+            CString::new("").unwrap().as_ptr(),
+        );
+        LLVMBuildZExt(
+            self.builder,
+            count,
+            VariableType::Long.to_llvmty(self.context),
+            CString::new("").unwrap().as_ptr(),
+        )
+    }
+
     unsafe fn call_function(
         &self,
         callee: LLVMValueRef,
@@ -1309,7 +2516,7 @@ impl JIT {
         let entry_bb = LLVMGetEntryBasicBlock(func);
         let first_inst = LLVMGetFirstInstruction(entry_bb);
         // A variable is always declared at the first point of entry block
-        if first_inst == ptr::null_mut() {
+        if first_inst.is_null() {
             LLVMPositionBuilderAtEnd(builder, entry_bb);
         } else {
             LLVMPositionBuilderBefore(builder, first_inst);
@@ -1359,10 +2566,30 @@ impl JIT {
                         let index = block.code[pc + 1] as usize;
                         vars.insert(index, VariableType::Double);
                     }
+                    Inst::lload_0 | Inst::lload_1 | Inst::lload_2 | Inst::lload_3 => {
+                        vars.insert((cur_code - Inst::lload_0) as usize, VariableType::Long);
+                    }
+                    Inst::lstore_0 | Inst::lstore_1 | Inst::lstore_2 | Inst::lstore_3 => {
+                        vars.insert((cur_code - Inst::lstore_0) as usize, VariableType::Long);
+                    }
+                    Inst::lstore | Inst::lload => {
+                        let index = block.code[pc + 1] as usize;
+                        vars.insert(index, VariableType::Long);
+                    }
+                    Inst::fload_0 | Inst::fload_1 | Inst::fload_2 | Inst::fload_3 => {
+                        vars.insert((cur_code - Inst::fload_0) as usize, VariableType::Float);
+                    }
+                    Inst::fstore_0 | Inst::fstore_1 | Inst::fstore_2 | Inst::fstore_3 => {
+                        vars.insert((cur_code - Inst::fstore_0) as usize, VariableType::Float);
+                    }
+                    Inst::fstore | Inst::fload => {
+                        let index = block.code[pc + 1] as usize;
+                        vars.insert(index, VariableType::Float);
+                    }
                     // TODO: Add
                     _ => {}
                 }
-                pc += Inst::get_inst_size(cur_code);
+                pc += Inst::get_inst_size_at(&block.code, pc, block.start);
             }
         }
 
@@ -1386,6 +2613,8 @@ impl JIT {
                 }
                 'I' => VariableType::Int,
                 'Z' => VariableType::Int,
+                'J' => VariableType::Long,
+                'F' => VariableType::Float,
                 'D' => VariableType::Double,
                 'V' => VariableType::Void,
                 ')' => {
@@ -1417,7 +2646,7 @@ impl JIT {
 }
 
 unsafe fn cur_bb_has_no_terminator(builder: LLVMBuilderRef) -> bool {
-    LLVMIsATerminatorInst(LLVMGetLastInstruction(LLVMGetInsertBlock(builder))) == ptr::null_mut()
+    LLVMIsATerminatorInst(LLVMGetLastInstruction(LLVMGetInsertBlock(builder))).is_null()
 }
 
 impl BasicBlockInfo {
@@ -1428,18 +2657,14 @@ impl BasicBlockInfo {
     }
 
     pub fn set_positioned(&mut self) -> &Self {
-        match self {
-            BasicBlockInfo::Unpositioned(bb) => *self = BasicBlockInfo::Positioned(*bb),
-            _ => {}
+        if let BasicBlockInfo::Unpositioned(bb) = self {
+            *self = BasicBlockInfo::Positioned(*bb)
         };
         self
     }
 
     pub fn is_positioned(&self) -> bool {
-        match self {
-            BasicBlockInfo::Positioned(_) => true,
-            _ => false,
-        }
+        matches!(self, BasicBlockInfo::Positioned(_))
     }
 }
 
@@ -1447,20 +2672,24 @@ unsafe fn llvm_const_int32(ctx: LLVMContextRef, n: u64) -> LLVMValueRef {
     LLVMConstInt(LLVMInt32TypeInContext(ctx), n, 1)
 }
 
-unsafe fn llvm_const_uint32(ctx: LLVMContextRef, n: u64) -> LLVMValueRef {
-    LLVMConstInt(LLVMInt32TypeInContext(ctx), n, 0)
+unsafe fn llvm_const_int64(ctx: LLVMContextRef, n: u64) -> LLVMValueRef {
+    LLVMConstInt(LLVMInt64TypeInContext(ctx), n, 1)
 }
 
 unsafe fn llvm_const_uint64(ctx: LLVMContextRef, n: u64) -> LLVMValueRef {
     LLVMConstInt(LLVMInt64TypeInContext(ctx), n, 0)
 }
 
+unsafe fn llvm_const_float(ctx: LLVMContextRef, f: f32) -> LLVMValueRef {
+    LLVMConstReal(LLVMFloatTypeInContext(ctx), f as f64)
+}
+
 unsafe fn llvm_const_double(ctx: LLVMContextRef, f: f64) -> LLVMValueRef {
     LLVMConstReal(LLVMDoubleTypeInContext(ctx), f)
 }
 
 unsafe fn llvm_const_ptr(ctx: LLVMContextRef, p: *mut u64) -> LLVMValueRef {
     let ptr_as_int = LLVMConstInt(LLVMInt64TypeInContext(ctx), p as u64, 0);
-    let const_ptr = LLVMConstIntToPtr(ptr_as_int, VariableType::Pointer.to_llvmty(ctx));
-    const_ptr
+
+    LLVMConstIntToPtr(ptr_as_int, VariableType::Pointer.to_llvmty(ctx))
 }
@@ -0,0 +1,1485 @@
+// Java Native Interface: loading native libraries and the function table passed to them
+// https://docs.oracle.com/javase/8/docs/technotes/guides/jni/spec/functions.html
+
+#![allow(non_camel_case_types)]
+
+use super::super::class::{
+    class::Class,
+    classfile::{method, method::MethodInfo, read::decode_modified_utf8},
+};
+use super::super::gc::gc::GcType;
+use super::super::jvm::marshal::{self, JavaValue};
+use super::frame::{AType, Array, ObjectBody};
+use super::native_registry::{self, NativeFunction, NativeMethod};
+use super::vm::{d2u, describe_exception, u2d, VM};
+use libc;
+use libffi::middle::{Arg, Cif, CodePtr, Type};
+use rustc_hash::FxHashMap;
+use std::ffi::{CStr, CString, VaList};
+use std::mem::transmute;
+use std::os::raw::{c_char, c_void};
+use std::path::Path;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Once,
+};
+use std::{ptr, slice};
+
+pub type jint = i32;
+pub type jlong = i64;
+pub type jboolean = u8;
+pub type jbyte = i8;
+pub type jchar = u16;
+pub type jshort = i16;
+pub type jfloat = f32;
+pub type jdouble = f64;
+pub type jsize = jint;
+// References are raw pointers to objects since the GC never moves objects. A class is
+// referred to by the pointer to ``Class``.
+pub type jobject = u64;
+pub type jclass = GcType<Class>;
+pub type jmethodID = *const MethodId;
+pub type jfieldID = *const FieldId;
+// A pointer to the function table
+pub type JNIEnv = *const *const c_void;
+pub type JavaVM = *const *const c_void;
+
+pub const JNI_OK: jint = 0;
+pub const JNI_ERR: jint = -1;
+pub const JNI_VERSION_1_1: jint = 0x0001_0001;
+pub const JNI_VERSION_1_8: jint = 0x0001_0008;
+
+#[repr(C)]
+pub struct JNINativeMethod {
+    name: *const c_char,
+    signature: *const c_char,
+    fn_ptr: *const c_void,
+}
+
+#[derive(Debug, Clone)]
+pub struct MethodId {
+    class: GcType<Class>,
+    name: String,
+    descriptor: String,
+    method: MethodInfo,
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldId {
+    // The class given to ``GetStaticFieldID``, which holds the value of a static field
+    class: GcType<Class>,
+    name: String,
+    // The index in ``ObjectBody::variables``. Unused for a static field.
+    id: usize,
+}
+
+/// JNI state shared by all threads
+#[derive(Debug, Clone, Default)]
+pub struct JniState {
+    // Paths and handles of loaded libraries
+    libraries: Vec<(String, usize)>,
+    // Objects referred to by global references and the number of the references
+    pub global_refs: FxHashMap<u64, usize>,
+    method_ids: FxHashMap<(usize, String, String), usize>,
+    field_ids: FxHashMap<(usize, String, String), usize>,
+    // Buffers returned by GetStringChars
+    string_chars: FxHashMap<usize, Vec<jchar>>,
+}
+
+// Loading Libraries
+
+/// Loads a native library and calls its ``JNI_OnLoad``. Returns an error message on failure.
+pub fn load_library(vm: &mut VM, path: &str) -> Result<(), String> {
+    let jni = &mut unsafe { &mut *vm.runtime_env }.jni;
+    if jni.libraries.iter().any(|(loaded, _)| loaded == path) {
+        return Ok(());
+    }
+
+    let handle = unsafe {
+        libc::dlopen(
+            CString::new(path).unwrap().as_ptr(),
+            libc::RTLD_NOW | libc::RTLD_LOCAL,
+        )
+    };
+    if handle.is_null() {
+        return Err(unsafe { CStr::from_ptr(libc::dlerror()) }
+            .to_string_lossy()
+            .into_owned());
+    }
+    jni.libraries.push((path.to_string(), handle as usize));
+
+    let on_load = unsafe { libc::dlsym(handle, CString::new("JNI_OnLoad").unwrap().as_ptr()) };
+    if on_load.is_null() {
+        return Ok(());
+    }
+    let on_load: unsafe extern "C" fn(*mut JavaVM, *mut c_void) -> jint =
+        unsafe { transmute(on_load) };
+    vm.jni_local_frames.push(vec![]);
+    let version = unsafe { on_load(java_vm(), ptr::null_mut()) };
+    vm.jni_local_frames.pop();
+    if !(JNI_VERSION_1_1..=JNI_VERSION_1_8).contains(&version) {
+        return Err(format!(
+            "unsupported JNI version 0x{:x} required by {}",
+            version, path
+        ));
+    }
+    Ok(())
+}
+
+/// Loads ``lib<name>.so`` (or the platform's equivalent) from the library path, then from the
+/// directories the dynamic linker searches.
+pub fn load_library_by_name(vm: &mut VM, name: &str) -> Result<(), String> {
+    let file_name = map_library_name(name);
+    let library_path = unsafe { &*vm.runtime_env }.library_path.clone();
+    for dir in library_path {
+        let path = Path::new(dir.as_str()).join(file_name.as_str());
+        if path.is_file() {
+            return load_library(vm, path.to_str().unwrap());
+        }
+    }
+    load_library(vm, file_name.as_str()).map_err(|_| format!("no {} in java.library.path", name))
+}
+
+fn map_library_name(name: &str) -> String {
+    if cfg!(target_os = "macos") {
+        format!("lib{}.dylib", name)
+    } else if cfg!(target_os = "windows") {
+        format!("{}.dll", name)
+    } else {
+        format!("lib{}.so", name)
+    }
+}
+
+/// Looks up ``Java_<class>_<method>`` (or the long name with the argument signature) in the
+/// loaded libraries and registers it as the native method of the signature.
+pub fn resolve_native_method(
+    vm: &mut VM,
+    class: GcType<Class>,
+    method_name: &str,
+    descriptor: &str,
+    is_static: bool,
+) -> Option<Arc<NativeMethod>> {
+    let class_name = unsafe { &*class }.get_name().unwrap().clone();
+    let short_name = format!(
+        "Java_{}_{}",
+        mangle(class_name.as_str()),
+        mangle(method_name)
+    );
+    let params = &descriptor[1..descriptor.find(')').unwrap()];
+    let long_name = format!("{}__{}", short_name, mangle(params));
+
+    let renv = unsafe { &mut *vm.runtime_env };
+    let func = renv.jni.libraries.iter().find_map(|(_, handle)| {
+        [&short_name, &long_name].iter().find_map(|name| {
+            let func = unsafe {
+                libc::dlsym(
+                    *handle as *mut c_void,
+                    CString::new(name.as_str()).unwrap().as_ptr(),
+                )
+            };
+            if func.is_null() {
+                None
+            } else {
+                Some(func)
+            }
+        })
+    })?;
+
+    let signature = format!("{}.{}:{}", class_name, method_name, descriptor);
+    renv.natives.register_function(
+        signature.as_str(),
+        jni_native_function(func, class, descriptor, is_static),
+    );
+    renv.natives.get(signature.as_str()).cloned()
+}
+
+// https://docs.oracle.com/javase/8/docs/technotes/guides/jni/spec/design.html#resolving_native_method_names
+fn mangle(name: &str) -> String {
+    let mut mangled = String::new();
+    for c in name.chars() {
+        match c {
+            '/' => mangled.push('_'),
+            '_' => mangled.push_str("_1"),
+            ';' => mangled.push_str("_2"),
+            '[' => mangled.push_str("_3"),
+            c if c.is_ascii_alphanumeric() => mangled.push(c),
+            c => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    mangled.push_str(format!("_0{:04x}", unit).as_str())
+                }
+            }
+        }
+    }
+    mangled
+}
+
+/// Makes a native method calling the C function ``func`` with ``JNIEnv`` and ``jclass`` (for a
+/// static method) or ``this`` followed by the arguments.
+fn jni_native_function(
+    func: *const c_void,
+    class: GcType<Class>,
+    descriptor: &str,
+    is_static: bool,
+) -> NativeFunction {
+    let (func, class) = (func as usize, class as u64);
+    let ret_ty = descriptor.chars().skip_while(|c| *c != ')').nth(1).unwrap();
+    Arc::new(move |vm: &mut VM, args: &[JavaValue]| {
+        let mut c_args = vec![CArg::Pointer(jni_env() as u64)];
+        if is_static {
+            c_args.push(CArg::Pointer(class));
+        }
+        for arg in args {
+            c_args.push(match *arg {
+                JavaValue::Int(i) => CArg::Int(i),
+                JavaValue::Long(l) => CArg::Long(l),
+                JavaValue::Boolean(b) => CArg::Boolean(b as jboolean),
+                JavaValue::Object(o) => CArg::Pointer(o),
+                JavaValue::Float(f) => CArg::Float(f),
+                JavaValue::Double(d) => CArg::Double(d),
+                JavaValue::Void => return None,
+            });
+        }
+
+        vm.jni_local_frames.push(vec![]);
+        let ret = unsafe { call_c_function(func, &c_args, ret_ty) };
+        vm.jni_local_frames.pop();
+
+        Some(match ret_ty {
+            'V' => JavaValue::Void,
+            'Z' => JavaValue::Boolean(ret as jboolean != 0),
+            'B' => JavaValue::Int(ret as jbyte as i32),
+            'C' => JavaValue::Int(ret as jchar as i32),
+            'S' => JavaValue::Int(ret as jshort as i32),
+            'J' => JavaValue::Long(ret as jlong),
+            'F' => JavaValue::Float(f32::from_bits(ret as u32)),
+            'D' => JavaValue::Double(f64::from_bits(ret)),
+            'L' | '[' => JavaValue::Object(ret),
+            _ => JavaValue::Int(ret as jint),
+        })
+    })
+}
+
+// Calling C Functions
+
+// An argument of a C function. Ints narrower than ``jint`` are passed as ``jint``s.
+enum CArg {
+    Int(jint),
+    Long(jlong),
+    Boolean(jboolean),
+    Float(jfloat),
+    Double(jdouble),
+    Pointer(u64),
+}
+
+/// Calls a C function with libffi, which handles any number of arguments. ``ret_ty`` is the
+/// return type in a descriptor. An int is returned in the lower bits and a float as its bits.
+unsafe fn call_c_function(func: usize, args: &[CArg], ret_ty: char) -> u64 {
+    let arg_types = args.iter().map(|arg| match arg {
+        CArg::Int(_) => Type::i32(),
+        CArg::Long(_) => Type::i64(),
+        CArg::Boolean(_) => Type::u8(),
+        CArg::Float(_) => Type::f32(),
+        CArg::Double(_) => Type::f64(),
+        CArg::Pointer(_) => Type::pointer(),
+    });
+    let ret_type = match ret_ty {
+        'V' => Type::void(),
+        'Z' => Type::u8(),
+        'B' => Type::i8(),
+        'C' => Type::u16(),
+        'S' => Type::i16(),
+        'I' => Type::i32(),
+        'J' => Type::i64(),
+        'F' => Type::f32(),
+        'D' => Type::f64(),
+        _ => Type::pointer(),
+    };
+    let cif = Cif::new(arg_types, ret_type);
+    let args: Vec<Arg> = args
+        .iter()
+        .map(|arg| match arg {
+            CArg::Int(i) => Arg::new(i),
+            CArg::Long(l) => Arg::new(l),
+            CArg::Boolean(b) => Arg::new(b),
+            CArg::Float(f) => Arg::new(f),
+            CArg::Double(d) => Arg::new(d),
+            CArg::Pointer(p) => Arg::new(p),
+        })
+        .collect();
+    let func = CodePtr(func as *mut c_void);
+    // libffi widens an int return value to a register, so it's read as a u64
+    match ret_ty {
+        'V' => {
+            cif.call::<()>(func, &args);
+            0
+        }
+        'F' => cif.call::<f32>(func, &args).to_bits() as u64,
+        'D' => cif.call::<f64>(func, &args).to_bits(),
+        _ => cif.call::<u64>(func, &args),
+    }
+}
+
+// Function Tables
+
+// Both point to a function table. Only one ``JNIEnv`` exists for all threads since every JNI
+// function finds the VM of the calling thread by itself.
+static JNI_ENV: AtomicUsize = AtomicUsize::new(0);
+static JAVA_VM: AtomicUsize = AtomicUsize::new(0);
+static INIT_TABLES: Once = Once::new();
+
+pub fn jni_env() -> *mut JNIEnv {
+    init_tables();
+    JNI_ENV.load(Ordering::SeqCst) as *mut JNIEnv
+}
+
+pub fn java_vm() -> *mut JavaVM {
+    init_tables();
+    JAVA_VM.load(Ordering::SeqCst) as *mut JavaVM
+}
+
+fn init_tables() {
+    INIT_TABLES.call_once(|| {
+        let env_table = Box::leak(native_interface().into_boxed_slice()).as_ptr();
+        let vm_table = Box::leak(invoke_interface().into_boxed_slice()).as_ptr();
+        JNI_ENV.store(
+            Box::into_raw(Box::new(env_table)) as usize,
+            Ordering::SeqCst,
+        );
+        JAVA_VM.store(Box::into_raw(Box::new(vm_table)) as usize, Ordering::SeqCst);
+    })
+}
+
+#[rustfmt::skip]
+fn invoke_interface() -> Vec<*const c_void> {
+    vec![
+        ptr::null(), ptr::null(), ptr::null(),
+        destroy_java_vm as *const c_void,
+        attach_current_thread as *const c_void,
+        detach_current_thread as *const c_void,
+        get_env as *const c_void,
+        attach_current_thread as *const c_void,
+    ]
+}
+
+#[rustfmt::skip]
+fn native_interface() -> Vec<*const c_void> {
+    let mut table = vec![unsupported as *const c_void; 235];
+    for reserved in &mut table[0..4] {
+        *reserved = ptr::null();
+    }
+
+    macro_rules! set {
+        ($($index:expr => $func:expr,)*) => { $(table[$index] = $func as *const c_void;)* };
+    }
+    // Typed functions are laid out in the order of Object, Boolean, Byte, Char, Short, Int,
+    // Long, Float, Double (and Void).
+    macro_rules! set_typed {
+        ($start:expr, $step:expr, [$($ty:ident),*], $func:ident) => {{
+            let mut index = $start;
+            $(table[index] = typed::$ty::$func as *const c_void; index += $step;)*
+            let _ = index;
+        }};
+    }
+    macro_rules! set_calls {
+        ($start:expr, $method:ident, $method_v:ident, $method_a:ident) => {
+            set_typed!($start,     3, [object, boolean, byte, char, short, int, long, float, double, void], $method);
+            set_typed!($start + 1, 3, [object, boolean, byte, char, short, int, long, float, double, void], $method_v);
+            set_typed!($start + 2, 3, [object, boolean, byte, char, short, int, long, float, double, void], $method_a);
+        };
+    }
+
+    set! {
+        4 => get_version,
+        6 => find_class,
+        10 => get_superclass,
+        11 => is_assignable_from,
+        13 => throw,
+        14 => throw_new,
+        15 => exception_occurred,
+        16 => exception_describe,
+        17 => exception_clear,
+        18 => fatal_error,
+        19 => push_local_frame,
+        20 => pop_local_frame,
+        21 => new_global_ref,
+        22 => delete_global_ref,
+        23 => delete_local_ref,
+        24 => is_same_object,
+        25 => new_local_ref,
+        26 => ensure_local_capacity,
+        27 => alloc_object,
+        28 => new_object,
+        29 => new_object_v,
+        30 => new_object_a,
+        31 => get_object_class,
+        32 => is_instance_of,
+        33 => get_method_id,
+        94 => get_field_id,
+        113 => get_static_method_id,
+        144 => get_static_field_id,
+        163 => new_string,
+        164 => get_string_length,
+        165 => get_string_chars,
+        166 => release_string_chars,
+        167 => new_string_utf,
+        168 => get_string_utf_length,
+        169 => get_string_utf_chars,
+        170 => release_string_utf_chars,
+        171 => get_array_length,
+        172 => new_object_array,
+        173 => get_object_array_element,
+        174 => set_object_array_element,
+        215 => register_natives,
+        216 => unregister_natives,
+        217 => monitor_enter,
+        218 => monitor_exit,
+        219 => get_java_vm,
+        220 => get_string_region,
+        221 => get_string_utf_region,
+        222 => get_primitive_array_critical,
+        223 => release_primitive_array_critical,
+        224 => get_string_chars,
+        225 => release_string_chars,
+        226 => new_weak_global_ref,
+        227 => delete_weak_global_ref,
+        228 => exception_check,
+        232 => get_object_ref_type,
+    }
+    set_calls!(34, call_method, call_method_v, call_method_a);
+    set_calls!(64, call_nonvirtual_method, call_nonvirtual_method_v, call_nonvirtual_method_a);
+    set_calls!(114, call_static_method, call_static_method_v, call_static_method_a);
+    set_typed!(95,  1, [object, boolean, byte, char, short, int, long, float, double], get_field);
+    set_typed!(104, 1, [object, boolean, byte, char, short, int, long, float, double], set_field);
+    set_typed!(145, 1, [object, boolean, byte, char, short, int, long, float, double], get_static_field);
+    set_typed!(154, 1, [object, boolean, byte, char, short, int, long, float, double], set_static_field);
+    set_typed!(175, 1, [boolean, byte, char, short, int, long, float, double], new_array);
+    set_typed!(183, 1, [boolean, byte, char, short, int, long, float, double], get_array_elements);
+    set_typed!(191, 1, [boolean, byte, char, short, int, long, float, double], release_array_elements);
+    set_typed!(199, 1, [boolean, byte, char, short, int, long, float, double], get_array_region);
+    set_typed!(207, 1, [boolean, byte, char, short, int, long, float, double], set_array_region);
+
+    table
+}
+
+unsafe extern "C" fn unsupported(_env: *mut JNIEnv) {
+    eprintln!("ferrugo: unsupported JNI function called");
+    libc::abort();
+}
+
+// Invocation Interface
+
+unsafe extern "C" fn destroy_java_vm(_vm: *mut JavaVM) -> jint {
+    JNI_ERR
+}
+
+unsafe extern "C" fn attach_current_thread(
+    vm: *mut JavaVM,
+    env: *mut *mut JNIEnv,
+    _args: *mut c_void,
+) -> jint {
+    // Only threads started by Java can call JNI functions
+    get_env(vm, env, JNI_VERSION_1_8)
+}
+
+unsafe extern "C" fn detach_current_thread(_vm: *mut JavaVM) -> jint {
+    JNI_OK
+}
+
+unsafe extern "C" fn get_env(_vm: *mut JavaVM, env: *mut *mut JNIEnv, version: jint) -> jint {
+    const JNI_EDETACHED: jint = -2;
+    const JNI_EVERSION: jint = -3;
+    if native_registry::current_vm().is_null() {
+        *env = ptr::null_mut();
+        return JNI_EDETACHED;
+    }
+    if !(JNI_VERSION_1_1..=JNI_VERSION_1_8).contains(&version) {
+        *env = ptr::null_mut();
+        return JNI_EVERSION;
+    }
+    *env = jni_env();
+    JNI_OK
+}
+
+// Helpers
+
+fn current_vm<'a>() -> &'a mut VM {
+    unsafe { &mut *native_registry::current_vm() }
+}
+
+fn cstr<'a>(s: *const c_char) -> &'a str {
+    unsafe { CStr::from_ptr(s) }.to_str().unwrap()
+}
+
+/// Makes a local reference to ``object``, which lives until the native method returns.
+fn local_ref(vm: &mut VM, object: u64) -> jobject {
+    if object != 0 {
+        if let Some(frame) = vm.jni_local_frames.last_mut() {
+            frame.push(object)
+        }
+    }
+    object
+}
+
+fn java_string(string: jobject) -> String {
+    unsafe { &*(string as GcType<ObjectBody>) }.get_string()
+}
+
+fn java_chars<'a>(string: jobject) -> &'a [u16] {
+    unsafe { &*(string as GcType<ObjectBody>) }.get_string_chars()
+}
+
+fn array<'a>(array: jobject) -> &'a mut Array {
+    unsafe { &mut *(array as GcType<Array>) }
+}
+
+fn class_name<'a>(class: jclass) -> &'a String {
+    unsafe { &*class }.get_name().unwrap()
+}
+
+fn is_instance(vm: &mut VM, object: jobject, class: jclass) -> bool {
+    object == 0 || vm.is_instance_of(object, class_name(class))
+}
+
+/// Throws ``ArrayIndexOutOfBoundsException`` (or the given exception) if ``[start, start+len)``
+/// is not within ``[0, length)``
+fn check_bounds(vm: &mut VM, exception: &str, length: usize, start: jsize, len: jsize) -> bool {
+    if start < 0 || len < 0 || start as usize + len as usize > length {
+        let message = format!("{}", if start < 0 { start } else { start + len });
+        vm.throw_exception(exception, Some(message.as_str()));
+        return false;
+    }
+    true
+}
+
+// Version Information
+
+unsafe extern "C" fn get_version(_env: *mut JNIEnv) -> jint {
+    JNI_VERSION_1_8
+}
+
+// Class Operations
+
+unsafe extern "C" fn find_class(_env: *mut JNIEnv, name: *const c_char) -> jclass {
+    let vm = current_vm();
+    let name = cstr(name);
+    if (&*vm.classheap).get_class(name).is_none() && vm.find_class_file(name).is_none() {
+        vm.throw_exception("java/lang/NoClassDefFoundError", Some(name));
+        return ptr::null_mut();
+    }
+    vm.load_class(name)
+}
+
+unsafe extern "C" fn get_superclass(_env: *mut JNIEnv, class: jclass) -> jclass {
+    (&*class).get_super_class().unwrap_or(ptr::null_mut())
+}
+
+unsafe extern "C" fn is_assignable_from(_env: *mut JNIEnv, sub: jclass, sup: jclass) -> jboolean {
+    (&*sub).is_subclass_of(class_name(sup)) as jboolean
+}
+
+// Exceptions
+
+unsafe extern "C" fn throw(_env: *mut JNIEnv, exception: jobject) -> jint {
+    current_vm().exception = Some(exception);
+    JNI_OK
+}
+
+unsafe extern "C" fn throw_new(_env: *mut JNIEnv, class: jclass, message: *const c_char) -> jint {
+    let message = if message.is_null() {
+        None
+    } else {
+        Some(cstr(message))
+    };
+    current_vm().throw_exception(class_name(class), message);
+    JNI_OK
+}
+
+unsafe extern "C" fn exception_occurred(_env: *mut JNIEnv) -> jobject {
+    let vm = current_vm();
+    let exception = vm.exception.unwrap_or(0);
+    local_ref(vm, exception)
+}
+
+unsafe extern "C" fn exception_describe(_env: *mut JNIEnv) {
+    if let Some(exception) = current_vm().exception.take() {
+        eprintln!("{}", describe_exception(exception));
+    }
+}
+
+unsafe extern "C" fn exception_clear(_env: *mut JNIEnv) {
+    current_vm().exception = None;
+}
+
+unsafe extern "C" fn exception_check(_env: *mut JNIEnv) -> jboolean {
+    current_vm().exception.is_some() as jboolean
+}
+
+unsafe extern "C" fn fatal_error(_env: *mut JNIEnv, message: *const c_char) {
+    eprintln!("FATAL ERROR in native method: {}", cstr(message));
+    libc::abort();
+}
+
+// References
+
+unsafe extern "C" fn push_local_frame(_env: *mut JNIEnv, _capacity: jint) -> jint {
+    current_vm().jni_local_frames.push(vec![]);
+    JNI_OK
+}
+
+unsafe extern "C" fn pop_local_frame(_env: *mut JNIEnv, result: jobject) -> jobject {
+    let vm = current_vm();
+    vm.jni_local_frames.pop();
+    local_ref(vm, result)
+}
+
+unsafe extern "C" fn new_local_ref(_env: *mut JNIEnv, object: jobject) -> jobject {
+    local_ref(current_vm(), object)
+}
+
+unsafe extern "C" fn delete_local_ref(_env: *mut JNIEnv, object: jobject) {
+    if let Some(frame) = current_vm().jni_local_frames.last_mut() {
+        if let Some(pos) = frame.iter().rposition(|o| *o == object) {
+            frame.swap_remove(pos);
+        }
+    }
+}
+
+unsafe extern "C" fn ensure_local_capacity(_env: *mut JNIEnv, _capacity: jint) -> jint {
+    JNI_OK
+}
+
+unsafe extern "C" fn new_global_ref(_env: *mut JNIEnv, object: jobject) -> jobject {
+    if object != 0 {
+        *(&mut *current_vm().runtime_env)
+            .jni
+            .global_refs
+            .entry(object)
+            .or_insert(0) += 1;
+    }
+    object
+}
+
+unsafe extern "C" fn delete_global_ref(_env: *mut JNIEnv, object: jobject) {
+    let global_refs = &mut (&mut *current_vm().runtime_env).jni.global_refs;
+    if let Some(count) = global_refs.get_mut(&object) {
+        *count -= 1;
+        if *count == 0 {
+            global_refs.remove(&object);
+        }
+    }
+}
+
+// A weak global reference doesn't keep the object alive
+unsafe extern "C" fn new_weak_global_ref(_env: *mut JNIEnv, object: jobject) -> jobject {
+    object
+}
+
+unsafe extern "C" fn delete_weak_global_ref(_env: *mut JNIEnv, _object: jobject) {}
+
+unsafe extern "C" fn is_same_object(_env: *mut JNIEnv, a: jobject, b: jobject) -> jboolean {
+    (a == b) as jboolean
+}
+
+unsafe extern "C" fn get_object_ref_type(_env: *mut JNIEnv, object: jobject) -> jint {
+    const JNI_INVALID_REF_TYPE: jint = 0;
+    const JNI_LOCAL_REF_TYPE: jint = 1;
+    const JNI_GLOBAL_REF_TYPE: jint = 2;
+    let vm = current_vm();
+    if (&*vm.runtime_env).jni.global_refs.contains_key(&object) {
+        JNI_GLOBAL_REF_TYPE
+    } else if vm
+        .jni_local_frames
+        .iter()
+        .any(|frame| frame.contains(&object))
+    {
+        JNI_LOCAL_REF_TYPE
+    } else {
+        JNI_INVALID_REF_TYPE
+    }
+}
+
+// Object Operations
+
+unsafe extern "C" fn alloc_object(_env: *mut JNIEnv, class: jclass) -> jobject {
+    let vm = current_vm();
+    let object = (&mut *vm.objectheap).create_object(class);
+    local_ref(vm, object)
+}
+
+unsafe extern "C" fn new_object(
+    env: *mut JNIEnv,
+    class: jclass,
+    method_id: jmethodID,
+    args: ...
+) -> jobject {
+    new_object_v(env, class, method_id, args)
+}
+
+unsafe extern "C" fn new_object_v(
+    env: *mut JNIEnv,
+    class: jclass,
+    method_id: jmethodID,
+    mut args: VaList,
+) -> jobject {
+    let object = alloc_object(env, class);
+    call_method(object, method_id, false, |ty| read_va_arg(ty, &mut args));
+    object
+}
+
+unsafe extern "C" fn new_object_a(
+    env: *mut JNIEnv,
+    class: jclass,
+    method_id: jmethodID,
+    args: *const u64,
+) -> jobject {
+    let object = alloc_object(env, class);
+    let mut args = args;
+    call_method(object, method_id, false, |ty| {
+        read_jvalue_array(ty, &mut args)
+    });
+    object
+}
+
+unsafe extern "C" fn get_object_class(_env: *mut JNIEnv, object: jobject) -> jclass {
+    let vm = current_vm();
+    if (&*vm.objectheap).gc.is_array(object) {
+        return vm.load_class("java/lang/Object");
+    }
+    (&*(object as GcType<ObjectBody>)).class
+}
+
+unsafe extern "C" fn is_instance_of(_env: *mut JNIEnv, object: jobject, class: jclass) -> jboolean {
+    is_instance(current_vm(), object, class) as jboolean
+}
+
+// Calling Methods
+
+unsafe extern "C" fn get_method_id(
+    _env: *mut JNIEnv,
+    class: jclass,
+    name: *const c_char,
+    sig: *const c_char,
+) -> jmethodID {
+    find_method_id(class, cstr(name), cstr(sig), false)
+}
+
+unsafe extern "C" fn get_static_method_id(
+    _env: *mut JNIEnv,
+    class: jclass,
+    name: *const c_char,
+    sig: *const c_char,
+) -> jmethodID {
+    find_method_id(class, cstr(name), cstr(sig), true)
+}
+
+fn find_method_id(class: jclass, name: &str, descriptor: &str, is_static: bool) -> jmethodID {
+    let vm = current_vm();
+    let (class, method) =
+        match unsafe { &*class }
+            .get_method(name, descriptor)
+            .filter(|(_, method)| {
+                method.check_access_flags(method::access_flags::ACC_PACC_STATIC) == is_static
+            }) {
+            Some(found) => found,
+            None => {
+                vm.throw_exception("java/lang/NoSuchMethodError", Some(name));
+                return ptr::null();
+            }
+        };
+
+    let method_ids = &mut unsafe { &mut *vm.runtime_env }.jni.method_ids;
+    *method_ids
+        .entry((class as usize, name.to_string(), descriptor.to_string()))
+        .or_insert_with(|| {
+            Box::into_raw(Box::new(MethodId {
+                class,
+                name: name.to_string(),
+                descriptor: descriptor.to_string(),
+                method,
+            })) as usize
+        }) as jmethodID
+}
+
+/// Calls a method. ``next_arg`` reads the argument of the given type as a slot.
+unsafe fn call_method<F: FnMut(char) -> u64>(
+    object: jobject,
+    method_id: jmethodID,
+    is_virtual: bool,
+    mut next_arg: F,
+) -> u64 {
+    let vm = current_vm();
+    let method_id = &*method_id;
+
+    let mut slots = vec![];
+    let (class, method) = if object == 0 {
+        (method_id.class, method_id.method.clone())
+    } else {
+        slots.push(object);
+        let object_class = (&*(object as GcType<ObjectBody>)).class;
+        match (&*object_class).get_method(method_id.name.as_str(), method_id.descriptor.as_str()) {
+            Some(found) if is_virtual => found,
+            _ => (method_id.class, method_id.method.clone()),
+        }
+    };
+    for ty in marshal::param_types(method_id.descriptor.as_str()) {
+        slots.push(next_arg(ty));
+        if ty == 'J' || ty == 'D' {
+            slots.push(0);
+        }
+    }
+
+    vm.invoke_method(class, method, &slots)
+}
+
+// Arguments passed to variadic functions are promoted to int or double
+unsafe fn read_va_arg(ty: char, args: &mut VaList) -> u64 {
+    match ty {
+        'J' => args.arg::<jlong>() as u64,
+        'F' => (args.arg::<f64>() as f32).to_bits() as u64,
+        'D' => d2u(args.arg::<f64>()),
+        'L' => args.arg::<u64>(),
+        ty => read_jvalue(ty, args.arg::<jint>() as u64),
+    }
+}
+
+// A jvalue is a union of 8 bytes
+unsafe fn read_jvalue_array(ty: char, args: &mut *const u64) -> u64 {
+    let value = **args;
+    *args = args.offset(1);
+    read_jvalue(ty, value)
+}
+
+fn read_jvalue(ty: char, value: u64) -> u64 {
+    match ty {
+        'Z' => (value as jboolean != 0) as u64,
+        'B' => value as jbyte as u64,
+        'C' => value as jchar as u64,
+        'S' => value as jshort as u64,
+        'I' => value as jint as u64,
+        'F' => value as u32 as u64,
+        _ => value,
+    }
+}
+
+// Fields
+
+unsafe extern "C" fn get_field_id(
+    _env: *mut JNIEnv,
+    class: jclass,
+    name: *const c_char,
+    sig: *const c_char,
+) -> jfieldID {
+    find_field_id(class, cstr(name), cstr(sig), false)
+}
+
+unsafe extern "C" fn get_static_field_id(
+    _env: *mut JNIEnv,
+    class: jclass,
+    name: *const c_char,
+    sig: *const c_char,
+) -> jfieldID {
+    find_field_id(class, cstr(name), cstr(sig), true)
+}
+
+fn find_field_id(class: jclass, name: &str, descriptor: &str, is_static: bool) -> jfieldID {
+    let vm = current_vm();
+    let found = unsafe { &*class }
+        .get_field(name, descriptor)
+        .filter(|(_, field)| {
+            (field.access_flags & method::access_flags::ACC_PACC_STATIC != 0) == is_static
+        });
+    if found.is_none() {
+        vm.throw_exception("java/lang/NoSuchFieldError", Some(name));
+        return ptr::null();
+    }
+    let id = if is_static {
+        0
+    } else {
+        unsafe { &*class }.get_numbered_field_info(name).unwrap().0
+    };
+
+    let field_ids = &mut unsafe { &mut *vm.runtime_env }.jni.field_ids;
+    *field_ids
+        .entry((class as usize, name.to_string(), descriptor.to_string()))
+        .or_insert_with(|| {
+            Box::into_raw(Box::new(FieldId {
+                class,
+                name: name.to_string(),
+                id,
+            })) as usize
+        }) as jfieldID
+}
+
+// String Operations
+
+fn new_java_string(vm: &mut VM, chars: &[u16]) -> jobject {
+    vm.load_class("java/lang/String");
+    let object = unsafe { (*vm.objectheap).create_string_object_from_chars(chars, vm.classheap) };
+    local_ref(vm, object)
+}
+
+// Modified UTF-8 encodes NUL in two bytes so that it doesn't terminate a string.
+// Supplementary characters are encoded in six bytes (as surrogate pairs) in modified UTF-8, but
+// they are left in four bytes here.
+fn to_modified_utf8(string: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(string.len());
+    for &b in string.as_bytes() {
+        if b == 0 {
+            bytes.extend_from_slice(&[0xc0, 0x80]);
+        } else {
+            bytes.push(b);
+        }
+    }
+    bytes
+}
+
+unsafe extern "C" fn new_string(_env: *mut JNIEnv, chars: *const jchar, len: jsize) -> jobject {
+    new_java_string(current_vm(), slice::from_raw_parts(chars, len as usize))
+}
+
+unsafe extern "C" fn get_string_length(_env: *mut JNIEnv, string: jobject) -> jsize {
+    java_chars(string).len() as jsize
+}
+
+// Also used for GetStringCritical
+unsafe extern "C" fn get_string_chars(
+    _env: *mut JNIEnv,
+    string: jobject,
+    is_copy: *mut jboolean,
+) -> *const jchar {
+    if !is_copy.is_null() {
+        *is_copy = 1;
+    }
+    let mut chars = java_chars(string).to_vec();
+    chars.push(0);
+    let ptr = chars.as_ptr();
+    (&mut *current_vm().runtime_env)
+        .jni
+        .string_chars
+        .insert(ptr as usize, chars);
+    ptr
+}
+
+unsafe extern "C" fn release_string_chars(
+    _env: *mut JNIEnv,
+    _string: jobject,
+    chars: *const jchar,
+) {
+    (&mut *current_vm().runtime_env)
+        .jni
+        .string_chars
+        .remove(&(chars as usize));
+}
+
+unsafe extern "C" fn new_string_utf(_env: *mut JNIEnv, bytes: *const c_char) -> jobject {
+    if bytes.is_null() {
+        return 0;
+    }
+    let chars = decode_modified_utf8(CStr::from_ptr(bytes).to_bytes());
+    new_java_string(current_vm(), &chars)
+}
+
+unsafe extern "C" fn get_string_utf_length(_env: *mut JNIEnv, string: jobject) -> jsize {
+    to_modified_utf8(java_string(string).as_str()).len() as jsize
+}
+
+unsafe extern "C" fn get_string_utf_chars(
+    _env: *mut JNIEnv,
+    string: jobject,
+    is_copy: *mut jboolean,
+) -> *const c_char {
+    if !is_copy.is_null() {
+        *is_copy = 1;
+    }
+    CString::new(to_modified_utf8(java_string(string).as_str()))
+        .unwrap()
+        .into_raw()
+}
+
+unsafe extern "C" fn release_string_utf_chars(
+    _env: *mut JNIEnv,
+    _string: jobject,
+    chars: *const c_char,
+) {
+    drop(CString::from_raw(chars as *mut c_char));
+}
+
+unsafe extern "C" fn get_string_region(
+    _env: *mut JNIEnv,
+    string: jobject,
+    start: jsize,
+    len: jsize,
+    buf: *mut jchar,
+) {
+    let chars = java_chars(string);
+    let exception = "java/lang/StringIndexOutOfBoundsException";
+    if check_bounds(current_vm(), exception, chars.len(), start, len) {
+        let region = &chars[start as usize..(start + len) as usize];
+        ptr::copy_nonoverlapping(region.as_ptr(), buf, len as usize);
+    }
+}
+
+unsafe extern "C" fn get_string_utf_region(
+    _env: *mut JNIEnv,
+    string: jobject,
+    start: jsize,
+    len: jsize,
+    buf: *mut c_char,
+) {
+    let chars = java_chars(string);
+    let exception = "java/lang/StringIndexOutOfBoundsException";
+    if check_bounds(current_vm(), exception, chars.len(), start, len) {
+        let region = String::from_utf16_lossy(&chars[start as usize..(start + len) as usize]);
+        let bytes = to_modified_utf8(region.as_str());
+        ptr::copy_nonoverlapping(bytes.as_ptr() as *const c_char, buf, bytes.len());
+        *buf.add(bytes.len()) = 0;
+    }
+}
+
+// Array Operations
+
+unsafe extern "C" fn get_array_length(_env: *mut JNIEnv, array: jobject) -> jsize {
+    self::array(array).get_length() as jsize
+}
+
+unsafe extern "C" fn new_object_array(
+    _env: *mut JNIEnv,
+    len: jsize,
+    class: jclass,
+    init: jobject,
+) -> jobject {
+    let vm = current_vm();
+    if len < 0 {
+        vm.throw_exception("java/lang/NegativeArraySizeException", None);
+        return 0;
+    }
+    let array = (&mut *vm.objectheap).create_obj_array(class, len as usize);
+    for i in 0..len as isize {
+        self::array(array).store(i, init);
+    }
+    local_ref(vm, array)
+}
+
+unsafe extern "C" fn get_object_array_element(
+    _env: *mut JNIEnv,
+    array: jobject,
+    index: jsize,
+) -> jobject {
+    let vm = current_vm();
+    let array = self::array(array);
+    let exception = "java/lang/ArrayIndexOutOfBoundsException";
+    if !check_bounds(vm, exception, array.get_length(), index, 1) {
+        return 0;
+    }
+    let element = array.at::<u64>(index as isize);
+    local_ref(vm, element)
+}
+
+unsafe extern "C" fn set_object_array_element(
+    _env: *mut JNIEnv,
+    array: jobject,
+    index: jsize,
+    value: jobject,
+) {
+    let array = self::array(array);
+    let exception = "java/lang/ArrayIndexOutOfBoundsException";
+    if check_bounds(current_vm(), exception, array.get_length(), index, 1) {
+        array.store(index as isize, value);
+    }
+}
+
+// Arrays are never copied since the GC doesn't move them
+unsafe extern "C" fn get_primitive_array_critical(
+    _env: *mut JNIEnv,
+    array: jobject,
+    is_copy: *mut jboolean,
+) -> *mut c_void {
+    if !is_copy.is_null() {
+        *is_copy = 0;
+    }
+    self::array(array).elements.as_mut_ptr() as *mut c_void
+}
+
+unsafe extern "C" fn release_primitive_array_critical(
+    _env: *mut JNIEnv,
+    _array: jobject,
+    _elems: *mut c_void,
+    _mode: jint,
+) {
+}
+
+// Registering Native Methods
+
+unsafe extern "C" fn register_natives(
+    _env: *mut JNIEnv,
+    class: jclass,
+    methods: *const JNINativeMethod,
+    count: jint,
+) -> jint {
+    let vm = current_vm();
+    let class_name = class_name(class);
+    for method in slice::from_raw_parts(methods, count as usize) {
+        let (name, descriptor) = (cstr(method.name), cstr(method.signature));
+        let is_static = match (&*class).get_method(name, descriptor) {
+            Some((_, info)) if info.check_access_flags(method::access_flags::ACC_PACC_NATIVE) => {
+                info.check_access_flags(method::access_flags::ACC_PACC_STATIC)
+            }
+            _ => {
+                vm.throw_exception("java/lang/NoSuchMethodError", Some(name));
+                return JNI_ERR;
+            }
+        };
+        (&mut *vm.runtime_env).natives.register_function(
+            format!("{}.{}:{}", class_name, name, descriptor).as_str(),
+            jni_native_function(method.fn_ptr, class, descriptor, is_static),
+        );
+    }
+    JNI_OK
+}
+
+unsafe extern "C" fn unregister_natives(_env: *mut JNIEnv, class: jclass) -> jint {
+    let vm = current_vm();
+    let class = &*class;
+    for method in &class.classfile.methods {
+        if !method.check_access_flags(method::access_flags::ACC_PACC_NATIVE) {
+            continue;
+        }
+        let name = class
+            .get_utf8_from_const_pool(method.name_index as usize)
+            .unwrap();
+        let descriptor = class
+            .get_utf8_from_const_pool(method.descriptor_index as usize)
+            .unwrap();
+        (&mut *vm.runtime_env)
+            .natives
+            .unregister(format!("{}.{}:{}", class.get_name().unwrap(), name, descriptor).as_str());
+    }
+    JNI_OK
+}
+
+// Monitor Operations
+
+unsafe extern "C" fn monitor_enter(_env: *mut JNIEnv, object: jobject) -> jint {
+    current_vm().monitor_enter(object);
+    JNI_OK
+}
+
+unsafe extern "C" fn monitor_exit(_env: *mut JNIEnv, object: jobject) -> jint {
+    let vm = current_vm();
+    vm.monitor_exit(object);
+    if vm.exception.is_some() {
+        JNI_ERR
+    } else {
+        JNI_OK
+    }
+}
+
+// JavaVM Interface
+
+unsafe extern "C" fn get_java_vm(_env: *mut JNIEnv, vm: *mut *mut JavaVM) -> jint {
+    *vm = java_vm();
+    JNI_OK
+}
+
+// Typed Functions
+
+/// JNI types converted from and into slots of the operand stack
+trait Slot: Sized {
+    fn from_slot(slot: u64) -> Self;
+    fn into_slot(self) -> u64;
+    // Makes a local reference to a returned object
+    fn returned(self, _vm: &mut VM) -> Self {
+        self
+    }
+}
+
+macro_rules! impl_slot {
+    ($ty:ty, |$slot:ident| $from:expr, |$value:ident| $into:expr) => {
+        impl Slot for $ty {
+            fn from_slot($slot: u64) -> Self {
+                $from
+            }
+            fn into_slot(self) -> u64 {
+                let $value = self;
+                $into
+            }
+        }
+    };
+}
+
+impl_slot!(jboolean, |slot| (slot != 0) as jboolean, |v| (v != 0)
+    as u64);
+impl_slot!(jbyte, |slot| slot as jbyte, |v| v as u64);
+impl_slot!(jchar, |slot| slot as jchar, |v| v as u64);
+impl_slot!(jshort, |slot| slot as jshort, |v| v as u64);
+impl_slot!(jint, |slot| slot as jint, |v| v as u64);
+impl_slot!(jlong, |slot| slot as jlong, |v| v as u64);
+impl_slot!(jfloat, |slot| f32::from_bits(slot as u32), |v| v.to_bits()
+    as u64);
+impl_slot!(jdouble, |slot| u2d(slot), |v| d2u(v));
+impl_slot!((), |_slot| (), |_v| 0);
+
+impl Slot for jobject {
+    fn from_slot(slot: u64) -> Self {
+        slot
+    }
+    fn into_slot(self) -> u64 {
+        self
+    }
+    fn returned(self, vm: &mut VM) -> Self {
+        local_ref(vm, self)
+    }
+}
+
+macro_rules! call_functions {
+    ($ty:ty) => {
+        pub unsafe extern "C" fn call_method(
+            env: *mut JNIEnv,
+            object: jobject,
+            method_id: jmethodID,
+            args: ...
+        ) -> $ty {
+            call_method_v(env, object, method_id, args)
+        }
+
+        pub unsafe extern "C" fn call_method_v(
+            _env: *mut JNIEnv,
+            object: jobject,
+            method_id: jmethodID,
+            mut args: VaList,
+        ) -> $ty {
+            let ret =
+                super::super::call_method(object, method_id, true, |ty| read_va_arg(ty, &mut args));
+            <$ty>::from_slot(ret).returned(current_vm())
+        }
+
+        pub unsafe extern "C" fn call_method_a(
+            _env: *mut JNIEnv,
+            object: jobject,
+            method_id: jmethodID,
+            args: *const u64,
+        ) -> $ty {
+            let mut args = args;
+            let ret = super::super::call_method(object, method_id, true, |ty| {
+                read_jvalue_array(ty, &mut args)
+            });
+            <$ty>::from_slot(ret).returned(current_vm())
+        }
+
+        pub unsafe extern "C" fn call_nonvirtual_method(
+            env: *mut JNIEnv,
+            object: jobject,
+            class: jclass,
+            method_id: jmethodID,
+            args: ...
+        ) -> $ty {
+            call_nonvirtual_method_v(env, object, class, method_id, args)
+        }
+
+        pub unsafe extern "C" fn call_nonvirtual_method_v(
+            _env: *mut JNIEnv,
+            object: jobject,
+            _class: jclass,
+            method_id: jmethodID,
+            mut args: VaList,
+        ) -> $ty {
+            let ret = super::super::call_method(object, method_id, false, |ty| {
+                read_va_arg(ty, &mut args)
+            });
+            <$ty>::from_slot(ret).returned(current_vm())
+        }
+
+        pub unsafe extern "C" fn call_nonvirtual_method_a(
+            _env: *mut JNIEnv,
+            object: jobject,
+            _class: jclass,
+            method_id: jmethodID,
+            args: *const u64,
+        ) -> $ty {
+            let mut args = args;
+            let ret = super::super::call_method(object, method_id, false, |ty| {
+                read_jvalue_array(ty, &mut args)
+            });
+            <$ty>::from_slot(ret).returned(current_vm())
+        }
+
+        pub unsafe extern "C" fn call_static_method(
+            env: *mut JNIEnv,
+            class: jclass,
+            method_id: jmethodID,
+            args: ...
+        ) -> $ty {
+            call_static_method_v(env, class, method_id, args)
+        }
+
+        pub unsafe extern "C" fn call_static_method_v(
+            _env: *mut JNIEnv,
+            _class: jclass,
+            method_id: jmethodID,
+            mut args: VaList,
+        ) -> $ty {
+            let ret =
+                super::super::call_method(0, method_id, false, |ty| read_va_arg(ty, &mut args));
+            <$ty>::from_slot(ret).returned(current_vm())
+        }
+
+        pub unsafe extern "C" fn call_static_method_a(
+            _env: *mut JNIEnv,
+            _class: jclass,
+            method_id: jmethodID,
+            args: *const u64,
+        ) -> $ty {
+            let mut args = args;
+            let ret = super::super::call_method(0, method_id, false, |ty| {
+                read_jvalue_array(ty, &mut args)
+            });
+            <$ty>::from_slot(ret).returned(current_vm())
+        }
+    };
+}
+
+macro_rules! field_functions {
+    ($ty:ty) => {
+        pub unsafe extern "C" fn get_field(
+            _env: *mut JNIEnv,
+            object: jobject,
+            field_id: jfieldID,
+        ) -> $ty {
+            let object = &*(object as GcType<ObjectBody>);
+            <$ty>::from_slot(object.variables[(&*field_id).id]).returned(current_vm())
+        }
+
+        pub unsafe extern "C" fn set_field(
+            _env: *mut JNIEnv,
+            object: jobject,
+            field_id: jfieldID,
+            value: $ty,
+        ) {
+            let object = &mut *(object as GcType<ObjectBody>);
+            object.variables[(&*field_id).id] = value.into_slot();
+        }
+
+        pub unsafe extern "C" fn get_static_field(
+            _env: *mut JNIEnv,
+            _class: jclass,
+            field_id: jfieldID,
+        ) -> $ty {
+            let field_id = &*field_id;
+            let value = (&*field_id.class)
+                .get_static_variable(field_id.name.as_str())
+                .unwrap_or(0);
+            <$ty>::from_slot(value).returned(current_vm())
+        }
+
+        pub unsafe extern "C" fn set_static_field(
+            _env: *mut JNIEnv,
+            _class: jclass,
+            field_id: jfieldID,
+            value: $ty,
+        ) {
+            let field_id = &*field_id;
+            (&mut *field_id.class).put_static_variable(field_id.name.as_str(), value.into_slot());
+        }
+    };
+}
+
+macro_rules! array_functions {
+    ($ty:ty, $atype:expr) => {
+        pub unsafe extern "C" fn new_array(_env: *mut JNIEnv, len: jsize) -> jobject {
+            let vm = current_vm();
+            if len < 0 {
+                vm.throw_exception("java/lang/NegativeArraySizeException", None);
+                return 0;
+            }
+            let array = (&mut *vm.objectheap).create_array($atype, len as usize);
+            local_ref(vm, array)
+        }
+
+        // Arrays are never copied since the GC doesn't move them
+        pub unsafe extern "C" fn get_array_elements(
+            _env: *mut JNIEnv,
+            array: jobject,
+            is_copy: *mut jboolean,
+        ) -> *mut $ty {
+            if !is_copy.is_null() {
+                *is_copy = 0;
+            }
+            super::super::array(array).elements.as_mut_ptr() as *mut $ty
+        }
+
+        pub unsafe extern "C" fn release_array_elements(
+            _env: *mut JNIEnv,
+            _array: jobject,
+            _elems: *mut $ty,
+            _mode: jint,
+        ) {
+        }
+
+        pub unsafe extern "C" fn get_array_region(
+            _env: *mut JNIEnv,
+            array: jobject,
+            start: jsize,
+            len: jsize,
+            buf: *mut $ty,
+        ) {
+            let array = super::super::array(array);
+            let exception = "java/lang/ArrayIndexOutOfBoundsException";
+            if check_bounds(current_vm(), exception, array.get_length(), start, len) {
+                let elements = array.elements.as_ptr() as *const $ty;
+                ptr::copy_nonoverlapping(elements.offset(start as isize), buf, len as usize);
+            }
+        }
+
+        pub unsafe extern "C" fn set_array_region(
+            _env: *mut JNIEnv,
+            array: jobject,
+            start: jsize,
+            len: jsize,
+            buf: *const $ty,
+        ) {
+            let array = super::super::array(array);
+            let exception = "java/lang/ArrayIndexOutOfBoundsException";
+            if check_bounds(current_vm(), exception, array.get_length(), start, len) {
+                let elements = array.elements.as_mut_ptr() as *mut $ty;
+                ptr::copy_nonoverlapping(buf, elements.offset(start as isize), len as usize);
+            }
+        }
+    };
+}
+
+// Functions for each type, laid out in the function table by ``native_interface``. The modules
+// are named after the types, which would shadow the primitive types outside ``typed``.
+macro_rules! typed_functions {
+    ($name:ident, $ty:ty) => {
+        pub mod $name {
+            use super::super::*;
+            call_functions!($ty);
+            field_functions!($ty);
+        }
+    };
+    ($name:ident, $ty:ty, $atype:expr) => {
+        pub mod $name {
+            use super::super::*;
+            call_functions!($ty);
+            field_functions!($ty);
+            array_functions!($ty, $atype);
+        }
+    };
+}
+
+mod typed {
+    typed_functions!(object, jobject);
+    typed_functions!(boolean, jboolean, AType::Boolean);
+    typed_functions!(byte, jbyte, AType::Byte);
+    typed_functions!(char, jchar, AType::Char);
+    typed_functions!(short, jshort, AType::Short);
+    typed_functions!(int, jint, AType::Int);
+    typed_functions!(long, jlong, AType::Long);
+    typed_functions!(float, jfloat, AType::Float);
+    typed_functions!(double, jdouble, AType::Double);
+
+    pub mod void {
+        use super::super::*;
+        call_functions!(());
+    }
+}
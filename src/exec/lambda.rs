@@ -0,0 +1,493 @@
+// ``LambdaMetafactory``: spins a class implementing a functional interface whose method calls
+// the implementation method of a lambda or a method reference
+// https://docs.oracle.com/javase/8/docs/api/java/lang/invoke/LambdaMetafactory.html
+
+use super::super::class::{
+    class::Class,
+    classfile::{
+        attribute::CodeAttribute, classfile::ClassFile, constant::Constant, field::FieldInfo,
+        method::MethodInfo,
+    },
+};
+use super::super::gc::gc::GcType;
+use super::call_site::{reference_kind::*, BootstrapArgument, CallSite, MethodHandleRef};
+use super::frame::ObjectBody;
+use super::vm::{Inst, VM};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Flags of ``altMetafactory``
+const FLAG_MARKERS: i32 = 1 << 1;
+const FLAG_BRIDGES: i32 = 1 << 2;
+
+// Numbers the names of spun classes like ``Main$$Lambda$1``
+static LAMBDA_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// ``LambdaMetafactory.metafactory`` and ``altMetafactory``. The call site descriptor has the
+/// captured arguments as parameters and returns the functional interface.
+pub fn metafactory(
+    vm: &mut VM,
+    caller: GcType<Class>,
+    name: &str,
+    descriptor: &str,
+    args: &[BootstrapArgument],
+) -> Result<CallSite, String> {
+    let (sam_type, implementation, instantiated_type) =
+        match (args.first(), args.get(1), args.get(2)) {
+            (
+                Some(BootstrapArgument::MethodType(sam_type)),
+                Some(BootstrapArgument::MethodHandle(implementation)),
+                Some(BootstrapArgument::MethodType(instantiated_type)),
+            ) => (sam_type, implementation, instantiated_type),
+            _ => return Err("invalid arguments for LambdaMetafactory".to_string()),
+        };
+    let (captured, interface) = parse_descriptor(descriptor);
+    let mut interfaces = vec![class_name(interface).to_string()];
+    let mut method_types = vec![sam_type.clone()];
+
+    // altMetafactory: flags, marker interfaces and bridges follow
+    if let Some(BootstrapArgument::Int(flags)) = args.get(3) {
+        let mut rest = args[4..].iter();
+        // Each list is preceded by its length, and present only if its flag is set
+        let mut counted = |flag: i32| -> Vec<BootstrapArgument> {
+            if flags & flag == 0 {
+                return vec![];
+            }
+            match rest.next() {
+                Some(BootstrapArgument::Int(count)) => {
+                    rest.by_ref().take(*count as usize).cloned().collect()
+                }
+                _ => vec![],
+            }
+        };
+        for marker in counted(FLAG_MARKERS) {
+            if let BootstrapArgument::Class(marker) = marker {
+                interfaces.push(marker);
+            }
+        }
+        for bridge in counted(FLAG_BRIDGES) {
+            if let BootstrapArgument::MethodType(bridge) = bridge {
+                method_types.push(bridge);
+            }
+        }
+    }
+
+    let caller_name = unsafe { &*caller }.get_name().unwrap();
+    let class_name = format!(
+        "{}$$Lambda${}",
+        caller_name,
+        LAMBDA_COUNT.fetch_add(1, Ordering::SeqCst) + 1
+    );
+
+    let mut pool = ConstantPool::new();
+    let this_class = pool.class(class_name.as_str());
+    let super_class = pool.class("java/lang/Object");
+    let interfaces = interfaces
+        .iter()
+        .map(|name| Constant::ClassInfo {
+            name_index: pool.class(name),
+        })
+        .collect::<Vec<_>>();
+    let fields = captured
+        .iter()
+        .enumerate()
+        .map(|(i, ty)| FieldInfo {
+            access_flags: 0x0012, // private final
+            name_index: pool.utf8(format!("arg${}", i + 1).as_str()),
+            descriptor_index: pool.utf8(ty),
+            attributes_count: 0,
+            attributes: vec![],
+        })
+        .collect::<Vec<_>>();
+    let mut methods = vec![];
+    for method_type in &method_types {
+        methods.push(gen_method(
+            &mut pool,
+            class_name.as_str(),
+            name,
+            &captured,
+            method_type.as_str(),
+            instantiated_type.as_str(),
+            implementation,
+        )?);
+    }
+
+    let class = Class {
+        classfile: ClassFile {
+            magic: 0xCAFEBABE,
+            major_version: 52,
+            constant_pool_count: pool.constants.len() as u16,
+            constant_pool: pool.constants,
+            access_flags: 0x1030, // final super synthetic
+            this_class,
+            super_class,
+            interfaces_count: interfaces.len() as u16,
+            interfaces,
+            fields_count: fields.len() as u16,
+            fields,
+            methods_count: methods.len() as u16,
+            methods,
+            ..ClassFile::new()
+        },
+        classheap: Some(vm.classheap),
+        ..Class::new()
+    };
+    let class = unsafe { &mut *vm.objectheap }.gc.alloc(class);
+    unsafe { vm.define_class(class, "java.lang.invoke.LambdaMetafactory") };
+
+    Ok(CallSite::Lambda { class })
+}
+
+/// Creates an instance of a spun class with the captured arguments on top of the operand stack
+pub fn new_instance(vm: &mut VM, class: GcType<Class>) {
+    let object = unsafe { (*vm.objectheap).create_object(class) };
+    let class = unsafe { &*class };
+    let frame = vm.frame_stack.last_mut().unwrap();
+
+    // Spun classes extend ``Object``, so the captured arguments are the only fields
+    let sizes = class.classfile.fields.iter().map(|field| {
+        slots(
+            class
+                .get_utf8_from_const_pool(field.descriptor_index as usize)
+                .unwrap(),
+        )
+    });
+    frame.sp -= sizes.clone().sum::<usize>();
+    let mut sp = frame.sp;
+    for (i, size) in sizes.enumerate() {
+        unsafe { &mut *(object as GcType<ObjectBody>) }.variables[i] = vm.stack[vm.bp + sp];
+        sp += size;
+    }
+
+    vm.stack[vm.bp + frame.sp] = object;
+    frame.sp += 1;
+
+    unsafe { &mut *vm.objectheap }.gc.mark_and_sweep(vm);
+}
+
+// Generates the method implementing the functional interface with ``method_type``. It loads the
+// captured arguments and the parameters, adapting them to the implementation method, calls it
+// and adapts the returned value back.
+fn gen_method(
+    pool: &mut ConstantPool,
+    this_class: &str,
+    name: &str,
+    captured: &[&str],
+    method_type: &str,
+    instantiated_type: &str,
+    implementation: &MethodHandleRef,
+) -> Result<MethodInfo, String> {
+    let (params, ret) = parse_descriptor(method_type);
+    let (instantiated_params, instantiated_ret) = parse_descriptor(instantiated_type);
+    let (impl_params, impl_ret) = parse_descriptor(implementation.descriptor.as_str());
+    let receiver = format!("L{};", implementation.class);
+
+    let kind = implementation.reference_kind;
+    let mut targets = vec![];
+    if kind == REF_INVOKE_VIRTUAL || kind == REF_INVOKE_SPECIAL || kind == REF_INVOKE_INTERFACE {
+        targets.push(receiver.as_str());
+    }
+    targets.extend(impl_params.iter());
+    let impl_ret = if kind == REF_NEW_INVOKE_SPECIAL {
+        receiver.as_str()
+    } else {
+        impl_ret
+    };
+    if targets.len() != captured.len() + params.len() || params.len() != instantiated_params.len() {
+        return Err(format!(
+            "type mismatch for lambda argument: {}.{}{}",
+            implementation.class, implementation.name, implementation.descriptor
+        ));
+    }
+
+    let mut code = vec![];
+    if kind == REF_NEW_INVOKE_SPECIAL {
+        code.push(Inst::new);
+        push_u16(&mut code, pool.class(implementation.class.as_str()));
+        code.push(Inst::dup);
+    }
+    for (i, ty) in captured.iter().enumerate() {
+        code.push(Inst::aload_0);
+        code.push(Inst::getfield);
+        let field = pool.field_ref(this_class, format!("arg${}", i + 1).as_str(), ty);
+        push_u16(&mut code, field);
+        adapt(&mut code, pool, ty, targets[i]);
+    }
+    let mut local = 1;
+    for (i, (ty, instantiated)) in params.iter().zip(instantiated_params.iter()).enumerate() {
+        code.push(load_inst(ty));
+        code.push(local as u8);
+        local += slots(ty);
+        adapt(&mut code, pool, ty, instantiated);
+        adapt(&mut code, pool, instantiated, targets[captured.len() + i]);
+    }
+
+    let method = pool.method_ref(
+        implementation.class.as_str(),
+        implementation.name.as_str(),
+        implementation.descriptor.as_str(),
+        implementation.interface,
+    );
+    match kind {
+        REF_INVOKE_STATIC => code.push(Inst::invokestatic),
+        REF_INVOKE_VIRTUAL => code.push(Inst::invokevirtual),
+        REF_INVOKE_INTERFACE => code.push(Inst::invokeinterface),
+        REF_INVOKE_SPECIAL | REF_NEW_INVOKE_SPECIAL => code.push(Inst::invokespecial),
+        _ => return Err(format!("invalid reference kind for lambda: {}", kind)),
+    }
+    push_u16(&mut code, method);
+    if kind == REF_INVOKE_INTERFACE {
+        code.push((1 + impl_params.iter().map(|ty| slots(ty)).sum::<usize>()) as u8);
+        code.push(0);
+    }
+
+    if ret == "V" {
+        match slots(impl_ret) {
+            1 => code.push(Inst::pop),
+            2 => code.push(Inst::pop2),
+            _ => {}
+        }
+    } else {
+        adapt(&mut code, pool, impl_ret, instantiated_ret);
+        adapt(&mut code, pool, instantiated_ret, ret);
+    }
+    code.push(return_inst(ret));
+
+    let max_stack = 2 + targets.iter().map(|ty| slots(ty)).sum::<usize>() + 2;
+    Ok(MethodInfo {
+        access_flags: 0x0001, // public
+        name_index: pool.utf8(name),
+        descriptor_index: pool.utf8(method_type),
+        code: Some(CodeAttribute {
+            max_stack: max_stack as u16,
+            max_locals: local as u16,
+            code_length: code.len() as u32,
+            code: Box::into_raw(Box::new(code)),
+            exception_table_length: 0,
+            exception_table: vec![],
+            attributes_count: 0,
+            attributes: vec![],
+        }),
+        ..MethodInfo::new()
+    })
+}
+
+// Converts the value of type ``from`` on top of the operand stack to ``to`` by casting, boxing,
+// unboxing or widening it
+fn adapt(code: &mut Vec<u8>, pool: &mut ConstantPool, from: &str, to: &str) {
+    if from == to {
+        return;
+    }
+    match (is_reference(from), is_reference(to)) {
+        (true, true) => {
+            if to != "Ljava/lang/Object;" {
+                code.push(Inst::checkcast);
+                push_u16(code, pool.class(class_name(to)));
+            }
+        }
+        (false, true) => {
+            let (wrapper, _) = wrapper(from);
+            let descriptor = format!("({})L{};", from, wrapper);
+            code.push(Inst::invokestatic);
+            push_u16(
+                code,
+                pool.method_ref(wrapper, "valueOf", descriptor.as_str(), false),
+            );
+        }
+        (true, false) => {
+            // Unboxes with the class of ``from`` if it's a wrapper, and then widens
+            let prim = WRAPPERS
+                .iter()
+                .find(|(_, wrapper, _)| class_name(from) == *wrapper)
+                .map_or(to, |(prim, _, _)| *prim);
+            let (wrapper, method) = wrapper(prim);
+            if class_name(from) != wrapper {
+                code.push(Inst::checkcast);
+                push_u16(code, pool.class(wrapper));
+            }
+            let descriptor = format!("(){}", prim);
+            code.push(Inst::invokevirtual);
+            push_u16(
+                code,
+                pool.method_ref(wrapper, method, descriptor.as_str(), false),
+            );
+            widen(code, prim, to);
+        }
+        (false, false) => widen(code, from, to),
+    }
+}
+
+fn widen(code: &mut Vec<u8>, from: &str, to: &str) {
+    let int_or = |ty| match ty {
+        "Z" | "B" | "S" | "C" => "I",
+        ty => ty,
+    };
+    match (int_or(from), int_or(to)) {
+        ("I", "J") => code.push(Inst::i2l),
+        ("I", "F") => code.push(Inst::i2f),
+        ("I", "D") => code.push(Inst::i2d),
+        ("J", "F") => code.push(Inst::l2f),
+        ("J", "D") => code.push(Inst::l2d),
+        ("F", "D") => code.push(Inst::f2d),
+        _ => {}
+    }
+}
+
+pub static WRAPPERS: [(&str, &str, &str); 8] = [
+    ("Z", "java/lang/Boolean", "booleanValue"),
+    ("B", "java/lang/Byte", "byteValue"),
+    ("S", "java/lang/Short", "shortValue"),
+    ("C", "java/lang/Character", "charValue"),
+    ("I", "java/lang/Integer", "intValue"),
+    ("J", "java/lang/Long", "longValue"),
+    ("F", "java/lang/Float", "floatValue"),
+    ("D", "java/lang/Double", "doubleValue"),
+];
+
+// Returns the wrapper class of a primitive type and the method unboxing it
+fn wrapper(prim: &str) -> (&'static str, &'static str) {
+    let (_, wrapper, method) = WRAPPERS.iter().find(|(ty, _, _)| *ty == prim).unwrap();
+    (*wrapper, *method)
+}
+
+fn load_inst(ty: &str) -> u8 {
+    match &ty[..1] {
+        "J" => Inst::lload,
+        "F" => Inst::fload,
+        "D" => Inst::dload,
+        "L" | "[" => Inst::aload,
+        _ => Inst::iload,
+    }
+}
+
+pub fn return_inst(ty: &str) -> u8 {
+    match &ty[..1] {
+        "V" => Inst::return_,
+        "J" => Inst::lreturn,
+        "F" => Inst::freturn,
+        "D" => Inst::dreturn,
+        "L" | "[" => Inst::areturn,
+        _ => Inst::ireturn,
+    }
+}
+
+pub fn push_u16(code: &mut Vec<u8>, val: u16) {
+    code.push((val >> 8) as u8);
+    code.push(val as u8);
+}
+
+pub fn is_reference(ty: &str) -> bool {
+    ty.starts_with('L') || ty.starts_with('[')
+}
+
+pub fn slots(ty: &str) -> usize {
+    match ty {
+        "V" => 0,
+        "J" | "D" => 2,
+        _ => 1,
+    }
+}
+
+// ``Ljava/lang/String;`` to ``java/lang/String``. Array types are kept as they are.
+pub fn class_name(ty: &str) -> &str {
+    if ty.starts_with('L') {
+        &ty[1..ty.len() - 1]
+    } else {
+        ty
+    }
+}
+
+// Splits a method descriptor into the types of the parameters and the return type
+pub fn parse_descriptor(descriptor: &str) -> (Vec<&str>, &str) {
+    let end = descriptor.find(')').unwrap();
+    let mut params = vec![];
+    let mut i = 1;
+    while i < end {
+        let start = i;
+        while &descriptor[i..i + 1] == "[" {
+            i += 1;
+        }
+        if &descriptor[i..i + 1] == "L" {
+            i += descriptor[i..].find(';').unwrap();
+        }
+        i += 1;
+        params.push(&descriptor[start..i]);
+    }
+    (params, &descriptor[end + 1..])
+}
+
+// The constant pool of a spun class
+pub struct ConstantPool {
+    pub constants: Vec<Constant>,
+}
+
+impl ConstantPool {
+    pub fn new() -> Self {
+        ConstantPool {
+            constants: vec![Constant::None],
+        }
+    }
+
+    fn add(&mut self, constant: Constant) -> u16 {
+        self.constants.push(constant);
+        (self.constants.len() - 1) as u16
+    }
+
+    pub fn utf8(&mut self, s: &str) -> u16 {
+        match self
+            .constants
+            .iter()
+            .position(|constant| constant.get_utf8().is_some_and(|utf8| utf8 == s))
+        {
+            Some(index) => index as u16,
+            None => self.add(Constant::Utf8 {
+                s: s.to_string(),
+                java_string: None,
+            }),
+        }
+    }
+
+    pub fn class(&mut self, name: &str) -> u16 {
+        let name_index = self.utf8(name);
+        self.add(Constant::ClassInfo { name_index })
+    }
+
+    fn name_and_type(&mut self, name: &str, descriptor: &str) -> u16 {
+        let name_index = self.utf8(name);
+        let descriptor_index = self.utf8(descriptor);
+        self.add(Constant::NameAndTypeInfo {
+            name_index,
+            descriptor_index,
+        })
+    }
+
+    pub fn field_ref(&mut self, class: &str, name: &str, descriptor: &str) -> u16 {
+        let class_index = self.class(class);
+        let name_and_type_index = self.name_and_type(name, descriptor);
+        self.add(Constant::FieldrefInfo {
+            class_index,
+            name_and_type_index,
+        })
+    }
+
+    pub fn method_ref(
+        &mut self,
+        class: &str,
+        name: &str,
+        descriptor: &str,
+        interface: bool,
+    ) -> u16 {
+        let class_index = self.class(class);
+        let name_and_type_index = self.name_and_type(name, descriptor);
+        if interface {
+            self.add(Constant::InterfaceMethodrefInfo {
+                class_index,
+                name_and_type_index,
+            })
+        } else {
+            self.add(Constant::MethodrefInfo {
+                class_index,
+                name_and_type_index,
+            })
+        }
+    }
+}
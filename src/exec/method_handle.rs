@@ -0,0 +1,505 @@
+// ``java.lang.invoke``: method handles and method types
+// https://docs.oracle.com/javase/8/docs/api/java/lang/invoke/MethodHandle.html
+
+use super::super::class::class::Class;
+use super::super::class::classfile::method::access_flags;
+use super::super::gc::gc::GcType;
+use super::call_site::{self, reference_kind::*};
+use super::frame::ObjectBody;
+use super::lambda::{class_name, is_reference, parse_descriptor, slots, WRAPPERS};
+use super::mirror;
+use super::vm::{d2u, f2u, u2f, VM};
+
+/// Returns the interned ``MethodType`` of a method descriptor
+pub fn method_type(vm: &mut VM, descriptor: &str) -> u64 {
+    if let Some(method_type) = unsafe { &*vm.runtime_env }.method_types.get(descriptor) {
+        return *method_type;
+    }
+
+    let class = vm.load_class("java/lang/invoke/MethodType");
+    let method_type = unsafe { (*vm.objectheap).create_object(class) };
+    let string =
+        unsafe { (*vm.objectheap).create_string_object(descriptor.to_string(), vm.classheap) };
+    set_field(method_type, "descriptor", string);
+
+    unsafe { &mut *vm.runtime_env }
+        .method_types
+        .insert(descriptor.to_string(), method_type);
+    method_type
+}
+
+/// Returns the descriptor of a ``MethodType``
+pub fn descriptor(method_type: u64) -> String {
+    unsafe { &*(get_field(method_type, "descriptor") as GcType<ObjectBody>) }
+        .get_string()
+}
+
+/// Formats a method descriptor as ``MethodType.toString()`` does, like ``(int,String)void``
+pub fn type_to_string(descriptor: &str) -> String {
+    let (params, ret) = parse_descriptor(descriptor);
+    let params: Vec<String> = params
+        .iter()
+        .map(|param| mirror::simple_name_of_descriptor(param))
+        .collect();
+    format!(
+        "({}){}",
+        params.join(","),
+        mirror::simple_name_of_descriptor(ret)
+    )
+}
+
+/// Creates a ``MethodHandle`` of the member ``name`` of ``class``. ``descriptor`` is the type of
+/// the handle, whose first parameter is the receiver for an instance member.
+pub fn new_handle(vm: &mut VM, kind: u8, class: &str, name: &str, descriptor: &str) -> u64 {
+    let handle_class = vm.load_class("java/lang/invoke/MethodHandle");
+    let refc = mirror::of_class_name(vm, class);
+    let method_type = method_type(vm, descriptor);
+    let name = unsafe { (*vm.objectheap).create_string_object(name.to_string(), vm.classheap) };
+
+    let handle = unsafe { (*vm.objectheap).create_object(handle_class) };
+    set_field(handle, "kind", kind as u64);
+    set_field(handle, "refc", refc);
+    set_field(handle, "name", name);
+    set_field(handle, "type", method_type);
+    handle
+}
+
+/// Returns the type of a handle of a member referred to by a ``CONSTANT_MethodHandle``.
+/// ``descriptor`` is the descriptor of the member.
+pub fn handle_type(kind: u8, class: &str, descriptor: &str) -> String {
+    let receiver = if class.starts_with('[') {
+        class.to_string()
+    } else {
+        format!("L{};", class)
+    };
+    match kind {
+        REF_GET_FIELD => format!("({}){}", receiver, descriptor),
+        REF_GET_STATIC => format!("(){}", descriptor),
+        REF_PUT_FIELD => format!("({}{})V", receiver, descriptor),
+        REF_PUT_STATIC => format!("({})V", descriptor),
+        REF_INVOKE_STATIC => descriptor.to_string(),
+        REF_NEW_INVOKE_SPECIAL => {
+            let (params, _) = parse_descriptor(descriptor);
+            format!("({}){}", params.concat(), receiver)
+        }
+        _ => format!("({}{}", receiver, &descriptor[1..]),
+    }
+}
+
+/// Loads the ``MethodHandleInfo`` at ``index`` in the constant pool of ``class`` by ``ldc``
+pub fn load_constant(vm: &mut VM, class: GcType<Class>, index: usize) -> u64 {
+    if let Some(handle) = unsafe { &*class }.method_handles.get(&index) {
+        return *handle;
+    }
+
+    let member = call_site::method_handle_ref(unsafe { &*class }, index);
+    let descriptor = handle_type(
+        member.reference_kind,
+        member.class.as_str(),
+        member.descriptor.as_str(),
+    );
+    let handle = new_handle(
+        vm,
+        member.reference_kind,
+        member.class.as_str(),
+        member.name.as_str(),
+        descriptor.as_str(),
+    );
+    unsafe { &mut *class }.method_handles.insert(index, handle);
+    handle
+}
+
+/// ``Lookup.findStatic`` and ``findVirtual``. Throws an exception and returns null if the method
+/// is not found.
+pub fn find_method(vm: &mut VM, is_static: bool, refc: u64, name: &str, method_type: u64) -> u64 {
+    let refc = mirror::descriptor(refc);
+    let descriptor = descriptor(method_type);
+    let kind = if is_static {
+        REF_INVOKE_STATIC
+    } else if mirror::is_interface(vm, refc.as_str()) {
+        REF_INVOKE_INTERFACE
+    } else {
+        REF_INVOKE_VIRTUAL
+    };
+    let member = format!(
+        "{}.{}{}/{}",
+        mirror::name_of_descriptor(refc.as_str()),
+        name,
+        type_to_string(descriptor.as_str()),
+        kind_name(kind)
+    );
+
+    let class = class_name(refc.as_str());
+    let method = if refc.starts_with('L') {
+        unsafe { &*vm.load_class(class) }.get_method(name, descriptor.as_str())
+    } else {
+        None
+    };
+    match method {
+        Some((_, method))
+            if method.check_access_flags(access_flags::ACC_PACC_STATIC) == is_static =>
+        {
+            let descriptor = handle_type(kind, class, descriptor.as_str());
+            new_handle(vm, kind, class, name, descriptor.as_str())
+        }
+        Some(_) => {
+            let message = format!("no such method: {}", member);
+            vm.throw_exception("java/lang/IllegalAccessException", Some(message.as_str()));
+            0
+        }
+        None => {
+            let message = format!("no such method: {}", member);
+            vm.throw_exception("java/lang/NoSuchMethodException", Some(message.as_str()));
+            0
+        }
+    }
+}
+
+/// ``Lookup.findGetter``. Throws an exception and returns null if the field is not found.
+pub fn find_getter(vm: &mut VM, refc: u64, name: &str, ty: u64) -> u64 {
+    let refc = mirror::descriptor(refc);
+    let ty = mirror::descriptor(ty);
+    let member = |kind| {
+        format!(
+            "{}.{}/{}/{}",
+            mirror::name_of_descriptor(refc.as_str()),
+            name,
+            mirror::simple_name_of_descriptor(ty.as_str()),
+            kind_name(kind)
+        )
+    };
+
+    let class = class_name(refc.as_str());
+    let field = if refc.starts_with('L') {
+        unsafe { &*vm.load_class(class) }.get_field(name, ty.as_str())
+    } else {
+        None
+    };
+    match field {
+        Some((_, field)) if field.access_flags & access_flags::ACC_PACC_STATIC == 0 => {
+            let descriptor = handle_type(REF_GET_FIELD, class, ty.as_str());
+            new_handle(vm, REF_GET_FIELD, class, name, descriptor.as_str())
+        }
+        Some(_) => {
+            let message = format!("expected a non-static field: {}", member(REF_GET_STATIC));
+            vm.throw_exception("java/lang/IllegalAccessException", Some(message.as_str()));
+            0
+        }
+        None => {
+            let message = format!("no such field: {}", member(REF_GET_FIELD));
+            vm.throw_exception("java/lang/NoSuchFieldException", Some(message.as_str()));
+            0
+        }
+    }
+}
+
+/// Runs ``MethodHandle.invokeExact`` (or ``invoke`` if not ``exact``), which takes the arguments
+/// as typed by ``descriptor`` of the call site. The handle and the arguments are on top of the
+/// operand stack.
+pub fn invoke(vm: &mut VM, descriptor: &str, exact: bool) {
+    let (params, ret) = parse_descriptor(descriptor);
+    let params_slots: usize = params.iter().map(|param| slots(param)).sum();
+    let sp = vm.frame_stack.last().unwrap().sp - params_slots - 1;
+    let handle = vm.stack[vm.bp + sp];
+    // The arguments are kept on the operand stack until the call returns so that they are
+    // traced by GC while the conversions allocate objects
+    let args = vm.stack[vm.bp + sp + 1..vm.bp + sp + 1 + params_slots].to_vec();
+
+    if handle == 0 {
+        vm.throw_exception("java/lang/NullPointerException", None);
+        return;
+    }
+
+    let handle_descriptor = self::descriptor(get_field(handle, "type"));
+    let (handle_params, handle_ret) = parse_descriptor(handle_descriptor.as_str());
+    let matches = if exact {
+        handle_descriptor == descriptor
+    } else {
+        params.len() == handle_params.len()
+            && params
+                .iter()
+                .zip(handle_params.iter())
+                .all(|(from, to)| is_convertible(from, to))
+            && (ret == "V" || handle_ret == "V" || is_convertible(handle_ret, ret))
+    };
+    if !matches {
+        let message = if exact {
+            format!(
+                "expected {} but found {}",
+                type_to_string(handle_descriptor.as_str()),
+                type_to_string(descriptor)
+            )
+        } else {
+            format!(
+                "cannot convert MethodHandle{} to {}",
+                type_to_string(handle_descriptor.as_str()),
+                type_to_string(descriptor)
+            )
+        };
+        vm.throw_exception(
+            "java/lang/invoke/WrongMethodTypeException",
+            Some(message.as_str()),
+        );
+        return;
+    }
+
+    // Lay out the arguments as the handle takes them
+    let mut handle_args = vec![];
+    let mut slot = 0;
+    for (from, to) in params.iter().zip(handle_params.iter()) {
+        let arg = match convert(vm, args[slot], from, to) {
+            Some(arg) => arg,
+            None => return,
+        };
+        handle_args.push(arg);
+        if slots(to) == 2 {
+            handle_args.push(0);
+        }
+        slot += slots(from);
+    }
+
+    let result = match call(vm, handle, handle_descriptor.as_str(), handle_args) {
+        Some(result) => result,
+        None => return,
+    };
+    let result = match (handle_ret, ret) {
+        (_, "V") => {
+            vm.frame_stack.last_mut().unwrap().sp = sp;
+            return;
+        }
+        // A method returning void returns null or zero
+        ("V", _) => 0,
+        (from, to) => match convert(vm, result, from, to) {
+            Some(result) => result,
+            None => return,
+        },
+    };
+
+    vm.stack[vm.bp + sp] = result;
+    vm.frame_stack.last_mut().unwrap().sp = sp + slots(ret);
+}
+
+// Runs the member a handle refers to. Returns None if an exception is thrown.
+fn call(vm: &mut VM, handle: u64, descriptor: &str, args: Vec<u64>) -> Option<u64> {
+    let kind = get_field(handle, "kind") as u8;
+    let refc = mirror::descriptor(get_field(handle, "refc"));
+    let class = vm.load_class(class_name(refc.as_str()));
+    let name = unsafe { &*(get_field(handle, "name") as GcType<ObjectBody>) }
+        .get_string();
+    let (params, ret) = parse_descriptor(descriptor);
+
+    let receiver = args.first().cloned().unwrap_or(0) as GcType<ObjectBody>;
+    if receiver.is_null()
+        && [
+            REF_GET_FIELD,
+            REF_PUT_FIELD,
+            REF_INVOKE_VIRTUAL,
+            REF_INVOKE_INTERFACE,
+        ]
+        .contains(&kind)
+    {
+        vm.throw_exception("java/lang/NullPointerException", None);
+        return None;
+    }
+
+    let result = match kind {
+        REF_GET_FIELD | REF_PUT_FIELD => {
+            let object = unsafe { &mut *receiver };
+            let (id, _) = *unsafe { &*object.class }
+                .get_numbered_field_info(name.as_str())
+                .unwrap();
+            if kind == REF_PUT_FIELD {
+                object.variables[id] = args[1];
+            }
+            object.variables[id]
+        }
+        REF_GET_STATIC => unsafe { &*class }
+            .get_static_variable(name.as_str())
+            .unwrap(),
+        REF_PUT_STATIC => {
+            unsafe { &mut *class }.put_static_variable(name.as_str(), args[0]);
+            0
+        }
+        REF_NEW_INVOKE_SPECIAL => {
+            let object = unsafe { (*vm.objectheap).create_object(class) };
+            let init = format!("({})V", params.concat());
+            let (init_class, init) = unsafe { &*class }
+                .get_method("<init>", init.as_str())
+                .unwrap();
+            let mut init_args = vec![object];
+            init_args.extend(args);
+            vm.invoke_method(init_class, init, &init_args);
+            object
+        }
+        _ => {
+            let method_descriptor = if kind == REF_INVOKE_STATIC {
+                descriptor.to_string()
+            } else {
+                format!("({}){}", params[1..].concat(), ret)
+            };
+            // Dispatch virtual methods on the class of the receiver
+            let method = if kind == REF_INVOKE_VIRTUAL || kind == REF_INVOKE_INTERFACE {
+                unsafe { &*(*receiver).class }.get_method(name.as_str(), method_descriptor.as_str())
+            } else {
+                None
+            };
+            let (method_class, method) = method
+                .or_else(|| {
+                    unsafe { &*class }.get_method(name.as_str(), method_descriptor.as_str())
+                })
+                .unwrap();
+            vm.invoke_method(method_class, method, &args)
+        }
+    };
+
+    if vm.exception.is_some() {
+        return None;
+    }
+    Some(result)
+}
+
+// Returns true if a value of type ``from`` can be converted to ``to`` by ``MethodHandle.invoke``
+fn is_convertible(from: &str, to: &str) -> bool {
+    match (is_reference(from), is_reference(to)) {
+        (true, true) => true,
+        (false, true) => {
+            let wrapper = format!("L{};", wrapper_of(from));
+            let is_number = from != "Z" && from != "C";
+            to == wrapper || to == "Ljava/lang/Object;" || (is_number && to == "Ljava/lang/Number;")
+        }
+        (true, false) => match primitive_of_wrapper(from) {
+            Some(prim) => widens(prim, to),
+            None => from == "Ljava/lang/Object;" || from == "Ljava/lang/Number;",
+        },
+        (false, false) => widens(from, to),
+    }
+}
+
+/// Returns true if ``from`` is ``to`` or can be widened to it
+pub fn widens(from: &str, to: &str) -> bool {
+    let wider = match from {
+        "B" => "BSIJFD",
+        "S" => "SIJFD",
+        "C" => "CIJFD",
+        "I" => "IJFD",
+        "J" => "JFD",
+        "F" => "FD",
+        ty => ty,
+    };
+    wider.contains(to)
+}
+
+/// Converts a value of type ``from`` to ``to`` by casting, boxing, unboxing or widening it.
+/// Returns None if an exception is thrown.
+pub fn convert(vm: &mut VM, value: u64, from: &str, to: &str) -> Option<u64> {
+    if from == to {
+        return Some(value);
+    }
+    match (is_reference(from), is_reference(to)) {
+        (true, true) => {
+            if to == "Ljava/lang/Object;" || vm.check_cast(value, class_name(to)) {
+                Some(value)
+            } else {
+                None
+            }
+        }
+        (false, true) => {
+            let wrapper = vm.load_class(wrapper_of(from));
+            let descriptor = format!("({})L{};", from, wrapper_of(from));
+            let (class, method) = unsafe { &*wrapper }
+                .get_method("valueOf", descriptor.as_str())
+                .unwrap();
+            let args = if slots(from) == 2 {
+                vec![value, 0]
+            } else {
+                vec![value]
+            };
+            let boxed = vm.invoke_method(class, method, &args);
+            if vm.exception.is_some() {
+                return None;
+            }
+            Some(boxed)
+        }
+        (true, false) => {
+            if value == 0 {
+                vm.throw_exception("java/lang/NullPointerException", None);
+                return None;
+            }
+            // Unboxes with the class of ``from`` if it's a wrapper, and then widens
+            let prim = primitive_of_wrapper(from).unwrap_or(to);
+            let (_, wrapper, method) = WRAPPERS.iter().find(|(ty, _, _)| *ty == prim).unwrap();
+            if !vm.check_cast(value, wrapper) {
+                return None;
+            }
+            let descriptor = format!("(){}", prim);
+            let (class, method) = unsafe { &*vm.load_class(wrapper) }
+                .get_method(method, descriptor.as_str())
+                .unwrap();
+            let unboxed = vm.invoke_method(class, method, &[value]);
+            if vm.exception.is_some() {
+                return None;
+            }
+            Some(widen(unboxed, prim, to))
+        }
+        (false, false) => Some(widen(value, from, to)),
+    }
+}
+
+// Widens a primitive value laid out in a slot
+fn widen(value: u64, from: &str, to: &str) -> u64 {
+    match (from, to) {
+        ("J", "F") => f2u(value as i64 as f32),
+        ("J", "D") => d2u(value as i64 as f64),
+        ("F", "D") => d2u(u2f(value) as f64),
+        (_, "J") => value as i32 as i64 as u64,
+        (_, "F") => f2u(value as i32 as f32),
+        (_, "D") => d2u(value as i32 as f64),
+        _ => value,
+    }
+}
+
+/// ``I`` to ``java/lang/Integer``
+pub fn wrapper_of(prim: &str) -> &'static str {
+    let (_, wrapper, _) = WRAPPERS.iter().find(|(ty, _, _)| *ty == prim).unwrap();
+    wrapper
+}
+
+/// ``Ljava/lang/Integer;`` to ``I``. Returns None if ``ty`` is not a wrapper class.
+pub fn primitive_of_wrapper(ty: &str) -> Option<&'static str> {
+    WRAPPERS
+        .iter()
+        .find(|(_, wrapper, _)| class_name(ty) == *wrapper)
+        .map(|(prim, _, _)| *prim)
+}
+
+fn kind_name(kind: u8) -> &'static str {
+    match kind {
+        REF_GET_FIELD => "getField",
+        REF_GET_STATIC => "getStatic",
+        REF_PUT_FIELD => "putField",
+        REF_PUT_STATIC => "putStatic",
+        REF_INVOKE_VIRTUAL => "invokeVirtual",
+        REF_INVOKE_STATIC => "invokeStatic",
+        REF_INVOKE_SPECIAL => "invokeSpecial",
+        REF_NEW_INVOKE_SPECIAL => "newInvokeSpecial",
+        _ => "invokeInterface",
+    }
+}
+
+/// Reads the field ``name`` of a Java object
+pub fn get_field(object: u64, name: &str) -> u64 {
+    let object = unsafe { &*(object as GcType<ObjectBody>) };
+    let (id, _) = *unsafe { &*object.class }
+        .get_numbered_field_info(name)
+        .unwrap();
+    object.variables[id]
+}
+
+/// Writes the field ``name`` of a Java object
+pub fn set_field(object: u64, name: &str, val: u64) {
+    let object = unsafe { &mut *(object as GcType<ObjectBody>) };
+    let (id, _) = *unsafe { &*object.class }
+        .get_numbered_field_info(name)
+        .unwrap();
+    object.variables[id] = val;
+}
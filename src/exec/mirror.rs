@@ -0,0 +1,140 @@
+// ``java.lang.Class`` objects standing for the types the VM knows. A type has one mirror, which
+// is created the first time it's asked for.
+
+use super::super::class::class::Class;
+use super::super::gc::gc::GcType;
+use super::frame::ObjectBody;
+use super::vm::VM;
+
+// ``ACC_INTERFACE`` in the access flags of a class
+pub const ACC_INTERFACE: u16 = 0x0200;
+
+static PRIMITIVES: [(&str, &str); 9] = [
+    ("Z", "boolean"),
+    ("B", "byte"),
+    ("S", "short"),
+    ("C", "char"),
+    ("I", "int"),
+    ("J", "long"),
+    ("F", "float"),
+    ("D", "double"),
+    ("V", "void"),
+];
+
+/// Returns the mirror of the type of a field descriptor (or ``V``). The class of a reference type
+/// is loaded if it's not yet.
+pub fn of_descriptor(vm: &mut VM, descriptor: &str) -> u64 {
+    if let Some(mirror) = unsafe { &*vm.classheap }.mirrors.get(descriptor) {
+        return *mirror;
+    }
+
+    if descriptor.starts_with('L') {
+        vm.load_class(&descriptor[1..descriptor.len() - 1]);
+    }
+
+    let class = vm.load_class("java/lang/Class");
+    let mirror = unsafe { (*vm.objectheap).create_object(class) };
+    let name = unsafe {
+        (*vm.objectheap).create_string_object(name_of_descriptor(descriptor), vm.classheap)
+    };
+    let (id, _) = *unsafe { &*class }.get_numbered_field_info("name").unwrap();
+    unsafe { &mut *(mirror as GcType<ObjectBody>) }.variables[id] = name;
+
+    unsafe { &mut *vm.classheap }
+        .mirrors
+        .insert(descriptor.to_string(), mirror);
+    mirror
+}
+
+/// Returns the mirror of a class name or an array descriptor as in ``CONSTANT_Class``
+pub fn of_class_name(vm: &mut VM, name: &str) -> u64 {
+    if name.starts_with('[') {
+        of_descriptor(vm, name)
+    } else {
+        of_descriptor(vm, format!("L{};", name).as_str())
+    }
+}
+
+/// Returns the mirror of the class of ``object``, which must not be null
+pub fn of_object(vm: &mut VM, object: u64) -> u64 {
+    let name = vm.class_name_of(object).replace(".", "/");
+    of_class_name(vm, name.as_str())
+}
+
+/// Returns the class ``mirror`` stands for, which is loaded if it's not yet. Returns None for
+/// primitive and array types.
+pub fn class(vm: &mut VM, mirror: u64) -> Option<GcType<Class>> {
+    let descriptor = descriptor(mirror);
+    if descriptor.starts_with('L') {
+        Some(vm.load_class(&descriptor[1..descriptor.len() - 1]))
+    } else {
+        None
+    }
+}
+
+/// Returns the descriptor of the type ``mirror`` stands for
+pub fn descriptor(mirror: u64) -> String {
+    let object = unsafe { &*(mirror as GcType<ObjectBody>) };
+    let (id, _) = *unsafe { &*object.class }
+        .get_numbered_field_info("name")
+        .unwrap();
+    let name = unsafe { &*(object.variables[id] as GcType<ObjectBody>) }
+        .get_string()
+        .replace(".", "/");
+    match primitive_of_name(name.as_str()) {
+        Some(descriptor) => descriptor.to_string(),
+        None if name.starts_with('[') => name,
+        None => format!("L{};", name),
+    }
+}
+
+/// Returns the name of a type as ``Class.getName()`` does (``int``, ``java.lang.String``, ``[I``)
+pub fn name_of_descriptor(descriptor: &str) -> String {
+    if let Some((_, name)) = PRIMITIVES.iter().find(|(ty, _)| *ty == descriptor) {
+        return name.to_string();
+    }
+    if descriptor.starts_with('L') {
+        descriptor[1..descriptor.len() - 1].replace("/", ".")
+    } else {
+        descriptor.replace("/", ".")
+    }
+}
+
+/// Returns the name of a type as ``Class.getTypeName()`` does (``int``, ``java.lang.String[]``)
+pub fn type_name_of_descriptor(descriptor: &str) -> String {
+    if let Some(component) = descriptor.strip_prefix('[') {
+        return format!("{}[]", type_name_of_descriptor(component));
+    }
+    name_of_descriptor(descriptor)
+}
+
+/// Returns the name of a type as ``Class.getSimpleName()`` does (``int``, ``String``, ``int[]``)
+pub fn simple_name_of_descriptor(descriptor: &str) -> String {
+    if let Some(component) = descriptor.strip_prefix('[') {
+        return format!("{}[]", simple_name_of_descriptor(component));
+    }
+    let name = name_of_descriptor(descriptor);
+    match name.rfind(['.', '$']) {
+        Some(i) => name[i + 1..].to_string(),
+        None => name,
+    }
+}
+
+pub fn is_primitive(descriptor: &str) -> bool {
+    PRIMITIVES.iter().any(|(ty, _)| *ty == descriptor)
+}
+
+/// Returns true if ``descriptor`` is an interface type
+pub fn is_interface(vm: &VM, descriptor: &str) -> bool {
+    descriptor.starts_with('L')
+        && unsafe { &*vm.classheap }
+            .get_class(&descriptor[1..descriptor.len() - 1])
+            .is_some_and(|class| unsafe { &*class }.classfile.access_flags & ACC_INTERFACE != 0)
+}
+
+pub fn primitive_of_name(name: &str) -> Option<&'static str> {
+    PRIMITIVES
+        .iter()
+        .find(|(_, n)| *n == name)
+        .map(|(descriptor, _)| *descriptor)
+}
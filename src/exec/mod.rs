@@ -4,4 +4,17 @@ pub mod cfg;
 pub mod frame;
 pub mod jit;
 pub mod objectheap;
-pub mod native_functions;
+pub(crate) mod native_functions;
+pub mod native_registry;
+pub(crate) mod jni;
+pub mod thread;
+pub(crate) mod call_site;
+pub(crate) mod lambda;
+pub mod string_concat;
+pub mod number;
+pub mod string;
+pub(crate) mod string_builder;
+pub mod mirror;
+pub(crate) mod method_handle;
+pub mod reflect;
+pub(crate) mod annotation;
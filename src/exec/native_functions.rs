@@ -1,14 +1,54 @@
 use super::super::class::class::Class;
 use super::super::gc::gc::GcType;
+use super::super::jvm::marshal::{self, JavaValue};
+use super::annotation;
+use super::call_site::CallSite;
 use super::jit::*;
+use super::jni;
+use super::lambda;
+use super::method_handle;
+use super::mirror;
+use super::native_registry::{self, NativeLink, NativeRegistry};
+use super::number;
+use super::reflect;
+use super::string;
+use super::string_builder;
+use super::string_concat;
 use super::{
     frame::{Array, ObjectBody, VariableType},
-    vm::RuntimeEnvironment,
+    vm::{RuntimeEnvironment, VM},
 };
 use llvm;
 use llvm::{core::*, prelude::*};
 use rustc_hash::FxHashMap;
 use std::ffi::CString;
+use std::mem;
+use std::path::Path;
+use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicI32, AtomicI64, AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// The functions JIT-compiled code calls, with their return and parameter types. Applies the
+// macro ``$m`` to each of them.
+#[rustfmt::skip]
+macro_rules! for_each_internal_function {
+    ($m:ident) => {
+        $m!(ptr,  [ptr, ptr],                ferrugo_internal_new);
+        $m!(int,  [ptr, ptr, int],           ferrugo_internal_baload);
+        $m!(ptr,  [ptr, ptr, int],           ferrugo_internal_aaload);
+        $m!(void, [ptr, ptr, int, int],      ferrugo_internal_bastore);
+        $m!(ptr,  [ptr, ptr, int],           ferrugo_internal_field_ptr);
+        $m!(i64,  [ptr, ptr, int, ptr],      ferrugo_internal_call_native);
+        $m!(int,  [ptr, ptr, ptr, int, ptr], ferrugo_internal_checkcast);
+        $m!(int,  [ptr, ptr, ptr, int, ptr], ferrugo_internal_instanceof);
+        $m!(int,  [ptr],                     ferrugo_internal_exception_pending);
+        $m!(ptr,  [ptr, ptr, ptr],           ferrugo_internal_concat);
+        $m!(void, [ptr],                     ferrugo_internal_safepoint);
+        $m!(void, [ptr],                     ferrugo_internal_throw_null_pointer_exception);
+        $m!(ptr,  [ptr, ptr, i64, int],      ferrugo_internal_unsafe_target);
+    };
+}
 
 #[rustfmt::skip]
 pub unsafe fn native_functions(
@@ -22,9 +62,10 @@ pub unsafe fn native_functions(
         (int    ) => { VariableType::Int.    to_llvmty(context) };
         (dbl)     => { VariableType::Double. to_llvmty(context) };
         (ptr) => { VariableType::Pointer.to_llvmty(context) };
+        (i64) => { LLVMInt64TypeInContext(context) };
     }
     macro_rules! define_native_function {
-        ($ret_ty:ident, [ $($param_ty:ident),* ], $name:expr) => {
+        ($ret_ty:ident, [ $($param_ty:ident),* ], $name:ident) => {
             let mut params_ty = vec![$(parse_ty!($param_ty)),*];
             let func_ty = LLVMFunctionType(
                             parse_ty!($ret_ty),
@@ -32,29 +73,13 @@ pub unsafe fn native_functions(
                             params_ty.len() as u32, 0);
             let func = LLVMAddFunction(
                         module,
-                        CString::new($name).unwrap().as_ptr(), 
+                        CString::new(stringify!($name)).unwrap().as_ptr(),
                         func_ty);
-            map.insert($name.to_string(), func);
+            map.insert(stringify!($name).to_string(), func);
         }
     }
 
-    define_native_function!(void, [ptr, ptr, int], "java/io/PrintStream.println:(I)V");
-    define_native_function!(void, [ptr, ptr, ptr], "java/io/PrintStream.println:(Ljava/lang/String;)V");
-    define_native_function!(void, [ptr, ptr, ptr], "java/io/PrintStream.print:(Ljava/lang/String;)V");
-    define_native_function!(ptr,  [ptr, ptr, int ],"java/lang/StringBuilder.append:(I)Ljava/lang/StringBuilder;");
-    define_native_function!(ptr,  [ptr, ptr, ptr], "java/lang/StringBuilder.append:(Ljava/lang/String;)Ljava/lang/StringBuilder;");
-    define_native_function!(ptr,  [ptr, ptr],      "java/lang/StringBuilder.toString:()Ljava/lang/String;");
-    define_native_function!(dbl,  [ptr],           "java/lang/Math.random:()D");
-    define_native_function!(dbl,  [ptr, dbl],      "java/lang/Math.sqrt:(D)D");
-    define_native_function!(dbl,  [ptr, dbl],      "java/lang/Math.sin:(D)D");
-    define_native_function!(dbl,  [ptr, dbl],      "java/lang/Math.cos:(D)D");
-    define_native_function!(dbl,  [ptr, dbl],      "java/lang/Math.tan:(D)D");
-    define_native_function!(dbl,  [ptr, dbl, dbl], "java/lang/Math.pow:(DD)D");
-    define_native_function!(dbl,  [ptr, dbl],      "java/lang/Math.abs:(D)D");
-    define_native_function!(ptr,  [ptr, ptr],      "ferrugo_internal_new");
-    define_native_function!(int,  [ptr, ptr, int], "ferrugo_internal_baload");
-    define_native_function!(ptr,  [ptr, ptr, int], "ferrugo_internal_aaload");
-    define_native_function!(void,  [ptr, ptr, int, int], "ferrugo_internal_bastore");
+    for_each_internal_function!(define_native_function);
 
     map
 }
@@ -63,196 +88,1040 @@ pub unsafe fn add_native_functions(
     native_functions: &FxHashMap<String, LLVMValueRef>,
     ee: llvm::execution_engine::LLVMExecutionEngineRef,
 ) {
-    for (name, func) in &[
-        (
-            "java/io/PrintStream.println:(I)V",
-            java_io_printstream_println_i_v as *mut libc::c_void,
-        ),
-        (
-            "java/io/PrintStream.println:(Ljava/lang/String;)V",
-            java_io_printstream_println_string_v as *mut libc::c_void,
-        ),
-        (
-            "java/io/PrintStream.print:(Ljava/lang/String;)V",
-            java_io_printstream_print_string_v as *mut libc::c_void,
-        ),
-        (
-            "java/lang/StringBuilder.append:(Ljava/lang/String;)Ljava/lang/StringBuilder;",
-            java_lang_stringbuilder_append_string_stringbuilder as *mut libc::c_void,
-        ),
-        (
-            "java/lang/StringBuilder.append:(I)Ljava/lang/StringBuilder;",
-            java_lang_stringbuilder_append_i_stringbuilder as *mut libc::c_void,
-        ),
-        (
-            "java/lang/StringBuilder.toString:()Ljava/lang/String;",
-            java_lang_stringbuilder_tostring_string as *mut libc::c_void,
-        ),
-        (
-            "java/lang/Math.random:()D",
-            java_lang_math_random_d as *mut libc::c_void,
-        ),
-        (
-            "java/lang/Math.sin:(D)D",
-            java_lang_math_sin_d_d as *mut libc::c_void,
-        ),
-        (
-            "java/lang/Math.pow:(DD)D",
-            java_lang_math_pow_dd_d as *mut libc::c_void,
-        ),
-        (
-            "ferrugo_internal_new",
-            ferrugo_internal_new as *mut libc::c_void,
-        ),
-        (
-            "ferrugo_internal_baload",
-            ferrugo_internal_baload as *mut libc::c_void,
-        ),
-        (
-            "ferrugo_internal_aaload",
-            ferrugo_internal_aaload as *mut libc::c_void,
-        ),
-        (
-            "ferrugo_internal_bastore",
-            ferrugo_internal_bastore as *mut libc::c_void,
-        ),
-    ] {
-        llvm::execution_engine::LLVMAddGlobalMapping(
-            ee,
-            *native_functions.get(*name).unwrap(),
-            *func,
-        );
+    macro_rules! add_global_mapping {
+        ($ret_ty:ident, [ $($param_ty:ident),* ], $name:ident) => {
+            llvm::execution_engine::LLVMAddGlobalMapping(
+                ee,
+                *native_functions.get(stringify!($name)).unwrap(),
+                $name as *mut libc::c_void,
+            );
+        };
     }
+
+    for_each_internal_function!(add_global_mapping);
 }
 
-// Builtin Native Functions
+// Builtin Native Methods
 
-#[no_mangle]
-pub extern "C" fn java_io_printstream_println_i_v(
-    _renv: *mut RuntimeEnvironment,
-    _obj: *mut ObjectBody,
-    i: i32,
-) {
-    println!("{}", i);
+pub fn register_builtin_natives(natives: &mut NativeRegistry) {
+    natives.register(
+        "java/io/PrintStream.println:(Ljava/lang/Object;)V",
+        |vm: &mut VM, _: JavaValue, obj: GcType<ObjectBody>| {
+            if let Some(s) = string_concat::object_to_string(vm, obj as u64) {
+                println!("{}", s)
+            }
+        },
+    );
+    natives.register(
+        "java/io/PrintStream.println:(I)V",
+        |_: &mut VM, _: JavaValue, i: i32| println!("{}", i),
+    );
+    natives.register(
+        "java/io/PrintStream.println:(J)V",
+        |_: &mut VM, _: JavaValue, l: i64| println!("{}", l),
+    );
+    natives.register(
+        "java/io/PrintStream.println:(F)V",
+        |_: &mut VM, _: JavaValue, f: f32| println!("{}", number::float_to_string(f)),
+    );
+    natives.register(
+        "java/io/PrintStream.println:(D)V",
+        |_: &mut VM, _: JavaValue, d: f64| println!("{}", number::double_to_string(d)),
+    );
+    natives.register(
+        "java/io/PrintStream.println:(Z)V",
+        |_: &mut VM, _: JavaValue, b: bool| println!("{}", b),
+    );
+    natives.register(
+        "java/io/PrintStream.println:(Ljava/lang/String;)V",
+        |_: &mut VM, _: JavaValue, s: String| println!("{}", s),
+    );
+    natives.register(
+        "java/io/PrintStream.print:(Ljava/lang/String;)V",
+        |_: &mut VM, _: JavaValue, s: String| print!("{}", s),
+    );
+
+    natives.register(
+        "java/lang/String.valueOf:(I)Ljava/lang/String;",
+        |_: &mut VM, i: i32| format!("{}", i),
+    );
+    natives.register(
+        "java/lang/String.valueOf:(J)Ljava/lang/String;",
+        |_: &mut VM, l: i64| format!("{}", l),
+    );
+    natives.register(
+        "java/lang/String.valueOf:(F)Ljava/lang/String;",
+        |_: &mut VM, f: f32| number::float_to_string(f),
+    );
+    natives.register(
+        "java/lang/String.valueOf:(D)Ljava/lang/String;",
+        |_: &mut VM, d: f64| number::double_to_string(d),
+    );
+    natives.register(
+        "java/lang/Float.toString:(F)Ljava/lang/String;",
+        |_: &mut VM, f: f32| number::float_to_string(f),
+    );
+    natives.register(
+        "java/lang/Double.toString:(D)Ljava/lang/String;",
+        |_: &mut VM, d: f64| number::double_to_string(d),
+    );
+    natives.register(
+        "java/lang/Double.parseDouble:(Ljava/lang/String;)D",
+        |vm: &mut VM, s: Option<String>| {
+            let s = match s {
+                Some(s) => s,
+                None => {
+                    vm.throw_exception("java/lang/NullPointerException", None);
+                    return 0.0;
+                }
+            };
+            match number::parse_double(s.as_str()) {
+                Ok(value) => value,
+                Err(message) => {
+                    vm.throw_exception("java/lang/NumberFormatException", Some(message.as_str()));
+                    0.0
+                }
+            }
+        },
+    );
+    natives.register(
+        "java/lang/Float.parseFloat:(Ljava/lang/String;)F",
+        |vm: &mut VM, s: Option<String>| {
+            let s = match s {
+                Some(s) => s,
+                None => {
+                    vm.throw_exception("java/lang/NullPointerException", None);
+                    return 0.0;
+                }
+            };
+            match number::parse_float(s.as_str()) {
+                Ok(value) => value,
+                Err(message) => {
+                    vm.throw_exception("java/lang/NumberFormatException", Some(message.as_str()));
+                    0.0
+                }
+            }
+        },
+    );
+    natives.register(
+        "java/lang/Double.doubleToRawLongBits:(D)J",
+        |_: &mut VM, d: f64| d.to_bits() as i64,
+    );
+    natives.register(
+        "java/lang/Double.longBitsToDouble:(J)D",
+        |_: &mut VM, bits: i64| f64::from_bits(bits as u64),
+    );
+    natives.register(
+        "java/lang/Float.floatToRawIntBits:(F)I",
+        |_: &mut VM, f: f32| f.to_bits() as i32,
+    );
+    natives.register(
+        "java/lang/Float.intBitsToFloat:(I)F",
+        |_: &mut VM, bits: i32| f32::from_bits(bits as u32),
+    );
+    natives.register(
+        "java/lang/String.valueOf:(Ljava/lang/Object;)Ljava/lang/String;",
+        |vm: &mut VM, obj: GcType<ObjectBody>| string_concat::object_to_string(vm, obj as u64),
+    );
+    natives.register(
+        "java/lang/String.intern:()Ljava/lang/String;",
+        |vm: &mut VM, this: GcType<ObjectBody>| {
+            unsafe { &mut *vm.classheap }.intern(this as u64) as GcType<ObjectBody>
+        },
+    );
+    natives.register(
+        "java/lang/String.toUpperCase:()Ljava/lang/String;",
+        |_: &mut VM, this: String| this.to_uppercase(),
+    );
+    natives.register(
+        "java/lang/String.toLowerCase:()Ljava/lang/String;",
+        |_: &mut VM, this: String| this.to_lowercase(),
+    );
+    natives.register(
+        "java/lang/String.split:(Ljava/lang/String;I)[Ljava/lang/String;",
+        |vm: &mut VM, this: String, regex: String, limit: i32| {
+            string::split(vm, this.as_str(), regex.as_str(), limit) as GcType<Array>
+        },
+    );
+
+    natives.register("java/lang/Math.random:()D", |_: &mut VM| {
+        use rand::random;
+        random::<f64>()
+    });
+    natives.register("java/lang/Math.sin:(D)D", |_: &mut VM, x: f64| x.sin());
+    natives.register("java/lang/Math.cos:(D)D", |_: &mut VM, x: f64| x.cos());
+    natives.register("java/lang/Math.tan:(D)D", |_: &mut VM, x: f64| x.tan());
+    natives.register("java/lang/Math.sqrt:(D)D", |_: &mut VM, x: f64| x.sqrt());
+    natives.register("java/lang/Math.pow:(DD)D", |_: &mut VM, x: f64, y: f64| {
+        x.powf(y)
+    });
+    natives.register("java/lang/Math.abs:(D)D", |_: &mut VM, x: f64| x.abs());
+
+    register_string_builder_natives(natives);
+    register_thread_natives(natives);
+    register_atomic_natives(natives);
+    register_unsafe_natives(natives);
+    register_library_natives(natives);
+    register_class_natives(natives);
+    register_reflect_natives(natives);
+    register_invoke_natives(natives);
 }
 
-#[no_mangle]
-pub extern "C" fn java_io_printstream_println_string_v(
-    _renv: *mut RuntimeEnvironment,
-    _obj: *mut ObjectBody,
-    s: *mut ObjectBody,
-) {
-    let string = unsafe { &mut *s };
-    println!("{}", string.get_string_mut());
+fn register_string_builder_natives(natives: &mut NativeRegistry) {
+    type Object = GcType<ObjectBody>;
+    let sig = |method: &str| format!("java/lang/StringBuilder.{}", method);
+
+    natives.register(
+        sig("append:(Ljava/lang/String;)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, s: Object| {
+            string_builder::append(vm, this, string_builder::chars_of_string(s))
+        },
+    );
+    natives.register(
+        sig("append:(Ljava/lang/Object;)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, obj: Object| {
+            let s = string_concat::object_to_chars(vm, obj as u64)?;
+            Some(string_builder::append(vm, this, &s))
+        },
+    );
+    natives.register(
+        sig("append:([C)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, s: GcType<Array>| {
+            string_builder::append(vm, this, unsafe { &*s }.as_chars())
+        },
+    );
+    natives.register(
+        sig("append:(C)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, c: u16| string_builder::append(vm, this, &[c]),
+    );
+    natives.register(
+        sig("append:(Z)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, b: bool| {
+            string_builder::append_str(vm, this, if b { "true" } else { "false" })
+        },
+    );
+    natives.register(
+        sig("append:(I)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, i: i32| {
+            string_builder::append_str(vm, this, format!("{}", i).as_str())
+        },
+    );
+    natives.register(
+        sig("append:(J)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, l: i64| {
+            string_builder::append_str(vm, this, format!("{}", l).as_str())
+        },
+    );
+    natives.register(
+        sig("append:(F)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, f: f32| {
+            string_builder::append_str(vm, this, number::float_to_string(f).as_str())
+        },
+    );
+    natives.register(
+        sig("append:(D)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, d: f64| {
+            string_builder::append_str(vm, this, number::double_to_string(d).as_str())
+        },
+    );
+    natives.register(
+        sig("insert:(ILjava/lang/String;)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, offset: i32, s: Object| {
+            string_builder::insert(vm, this, offset, string_builder::chars_of_string(s))
+        },
+    );
+    natives.register(
+        sig("insert:(ILjava/lang/Object;)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, offset: i32, obj: Object| {
+            let s = string_concat::object_to_chars(vm, obj as u64)?;
+            Some(string_builder::insert(vm, this, offset, &s))
+        },
+    );
+    natives.register(
+        sig("insert:(IC)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, offset: i32, c: u16| {
+            string_builder::insert(vm, this, offset, &[c])
+        },
+    );
+    natives.register(
+        sig("insert:(IZ)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, offset: i32, b: bool| {
+            string_builder::insert_str(vm, this, offset, if b { "true" } else { "false" })
+        },
+    );
+    natives.register(
+        sig("insert:(II)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, offset: i32, i: i32| {
+            string_builder::insert_str(vm, this, offset, format!("{}", i).as_str())
+        },
+    );
+    natives.register(
+        sig("insert:(IJ)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, offset: i32, l: i64| {
+            string_builder::insert_str(vm, this, offset, format!("{}", l).as_str())
+        },
+    );
+    natives.register(
+        sig("delete:(II)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, start: i32, end: i32| {
+            string_builder::delete(vm, this, start, end)
+        },
+    );
+    natives.register(
+        sig("deleteCharAt:(I)Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object, index: i32| string_builder::delete_char_at(vm, this, index),
+    );
+    natives.register(
+        sig("reverse:()Ljava/lang/StringBuilder;").as_str(),
+        |vm: &mut VM, this: Object| string_builder::reverse(vm, this),
+    );
+    natives.register(
+        sig("setLength:(I)V").as_str(),
+        |vm: &mut VM, this: Object, new_length: i32| {
+            string_builder::set_length(vm, this, new_length)
+        },
+    );
+    natives.register(
+        sig("charAt:(I)C").as_str(),
+        |vm: &mut VM, this: Object, index: i32| string_builder::char_at(vm, this, index),
+    );
+    natives.register(
+        sig("setCharAt:(IC)V").as_str(),
+        |vm: &mut VM, this: Object, index: i32, c: u16| {
+            string_builder::set_char_at(vm, this, index, c)
+        },
+    );
+    natives.register(sig("length:()I").as_str(), |_: &mut VM, this: Object| {
+        string_builder::chars(this).len() as i32
+    });
+    natives.register(
+        sig("toString:()Ljava/lang/String;").as_str(),
+        |vm: &mut VM, this: Object| string_builder::to_string(vm, this),
+    );
 }
 
-#[no_mangle]
-pub extern "C" fn java_io_printstream_print_string_v(
-    _renv: *mut RuntimeEnvironment,
-    _obj: *mut ObjectBody,
-    s: *mut ObjectBody,
-) {
-    print!("{}", unsafe { &mut *s }.get_string_mut());
+fn register_library_natives(natives: &mut NativeRegistry) {
+    natives.register(
+        "java/lang/System.getProperty:(Ljava/lang/String;)Ljava/lang/String;",
+        |vm: &mut VM, key: String| {
+            unsafe { &*vm.runtime_env }
+                .system_properties
+                .get(&key)
+                .cloned()
+        },
+    );
+    natives.register(
+        "java/lang/System.identityHashCode:(Ljava/lang/Object;)I",
+        |vm: &mut VM, obj: GcType<ObjectBody>| {
+            if obj.is_null() {
+                0
+            } else {
+                unsafe { &mut *vm.objectheap }.identity_hash_code(obj as u64)
+            }
+        },
+    );
+    natives.register("java/lang/System.currentTimeMillis:()J", |_: &mut VM| {
+        since_epoch().as_millis() as i64
+    });
+    natives.register("java/lang/System.nanoTime:()J", |_: &mut VM| {
+        since_epoch().as_nanos() as i64
+    });
+    natives.register(
+        "java/lang/System.load:(Ljava/lang/String;)V",
+        |vm: &mut VM, filename: String| {
+            let result = if Path::new(filename.as_str()).is_absolute() {
+                jni::load_library(vm, filename.as_str())
+            } else {
+                Err(format!(
+                    "Expecting an absolute path of the library: {}",
+                    filename
+                ))
+            };
+            if let Err(message) = result {
+                vm.throw_exception("java/lang/UnsatisfiedLinkError", Some(message.as_str()));
+            }
+        },
+    );
+    natives.register(
+        "java/lang/System.loadLibrary:(Ljava/lang/String;)V",
+        |vm: &mut VM, libname: String| {
+            if let Err(message) = jni::load_library_by_name(vm, libname.as_str()) {
+                vm.throw_exception("java/lang/UnsatisfiedLinkError", Some(message.as_str()));
+            }
+        },
+    );
 }
 
-#[no_mangle]
-pub extern "C" fn java_lang_stringbuilder_append_i_stringbuilder(
-    renv: *mut RuntimeEnvironment,
-    obj: *mut ObjectBody,
-    i: i32,
-) -> *mut ObjectBody {
-    unsafe {
-        let renv = &mut *renv;
-        let string_builder = &mut *obj;
-
-        let string_obj = &mut string_builder.variables[0];
-        // TODO: Currently, JIT doesn't support 'invokespecial'. Therefore StringBuilder newly
-        // created in JIT-compiled code is not initialized. That's why the code below is needed.
-        if *string_obj == 0 {
-            *string_obj =
-                (&mut *renv.objectheap).create_string_object("".to_string(), renv.classheap)
-        }
+fn register_class_natives(natives: &mut NativeRegistry) {
+    type Object = GcType<ObjectBody>;
 
-        let mut string = (&mut *(*string_obj as GcType<ObjectBody>))
-            .get_string_mut()
-            .clone();
-        string.push_str(format!("{}", i).as_str());
+    natives.register(
+        "java/lang/Class.getPrimitiveClass:(Ljava/lang/String;)Ljava/lang/Class;",
+        |vm: &mut VM, name: String| {
+            let descriptor = mirror::primitive_of_name(name.as_str()).unwrap();
+            mirror::of_descriptor(vm, descriptor) as Object
+        },
+    );
+    natives.register(
+        "java/lang/Class.isInterface:()Z",
+        |vm: &mut VM, this: Object| {
+            mirror::is_interface(vm, mirror::descriptor(this as u64).as_str())
+        },
+    );
+    natives.register("java/lang/Class.isArray:()Z", |_: &mut VM, this: Object| {
+        mirror::descriptor(this as u64).starts_with('[')
+    });
+    natives.register(
+        "java/lang/Class.isPrimitive:()Z",
+        |_: &mut VM, this: Object| mirror::is_primitive(mirror::descriptor(this as u64).as_str()),
+    );
 
-        *string_obj =
-            (&mut *renv.objectheap).create_string_object(string.to_string(), renv.classheap);
-    }
-    obj
+    natives.register(
+        "java/lang/Object.getClass:()Ljava/lang/Class;",
+        |vm: &mut VM, this: Object| mirror::of_object(vm, this as u64) as Object,
+    );
+    natives.register(
+        "java/lang/Object.hashCode:()I",
+        |vm: &mut VM, this: Object| unsafe { &mut *vm.objectheap }.identity_hash_code(this as u64),
+    );
+    natives.register(
+        "java/lang/Object.clone:()Ljava/lang/Object;",
+        |vm: &mut VM, this: Object| {
+            // Arrays are always ``Cloneable``
+            if !vm.is_instance_of(this as u64, "java/lang/Cloneable") {
+                let name = vm.class_name_of(this as u64);
+                vm.throw_exception("java/lang/CloneNotSupportedException", Some(name.as_str()));
+                return ptr::null_mut();
+            }
+            unsafe { &mut *vm.objectheap }.clone_object(this as u64) as Object
+        },
+    );
+    natives.register(
+        "java/lang/Integer.toHexString:(I)Ljava/lang/String;",
+        |_: &mut VM, i: i32| format!("{:x}", i),
+    );
+    natives.register(
+        "java/lang/Class.forName:(Ljava/lang/String;)Ljava/lang/Class;",
+        |vm: &mut VM, name: String| reflect::for_name(vm, name.as_str()) as Object,
+    );
+    natives.register(
+        "java/lang/Class.getSimpleName:()Ljava/lang/String;",
+        |_: &mut VM, this: Object| {
+            mirror::simple_name_of_descriptor(mirror::descriptor(this as u64).as_str())
+        },
+    );
+    natives.register(
+        "java/lang/Class.isInstance:(Ljava/lang/Object;)Z",
+        |vm: &mut VM, this: Object, obj: Object| reflect::is_instance(vm, this as u64, obj as u64),
+    );
+    natives.register(
+        "java/lang/Class.getModifiers:()I",
+        |vm: &mut VM, this: Object| reflect::class_modifiers(vm, this as u64),
+    );
+    natives.register(
+        "java/lang/Class.getSuperclass:()Ljava/lang/Class;",
+        |vm: &mut VM, this: Object| reflect::superclass(vm, this as u64) as Object,
+    );
+    natives.register(
+        "java/lang/Class.getInterfaces:()[Ljava/lang/Class;",
+        |vm: &mut VM, this: Object| reflect::interfaces(vm, this as u64) as GcType<Array>,
+    );
+    natives.register(
+        "java/lang/Class.newInstance:()Ljava/lang/Object;",
+        |vm: &mut VM, this: Object| reflect::new_instance(vm, this as u64) as Object,
+    );
+    natives.register(
+        "java/lang/Class.getDeclaredMethods:()[Ljava/lang/reflect/Method;",
+        |vm: &mut VM, this: Object| reflect::declared_methods(vm, this as u64) as GcType<Array>,
+    );
+    natives.register(
+        "java/lang/Class.getDeclaredFields:()[Ljava/lang/reflect/Field;",
+        |vm: &mut VM, this: Object| reflect::declared_fields(vm, this as u64) as GcType<Array>,
+    );
+    natives.register(
+        "java/lang/Class.getDeclaredMethod:(Ljava/lang/String;[Ljava/lang/Class;)Ljava/lang/reflect/Method;",
+        |vm: &mut VM, this: Object, name: String, parameter_types: GcType<Array>| {
+            reflect::find_method(vm, this as u64, name.as_str(), parameter_types, false) as Object
+        },
+    );
+    natives.register(
+        "java/lang/Class.getMethod:(Ljava/lang/String;[Ljava/lang/Class;)Ljava/lang/reflect/Method;",
+        |vm: &mut VM, this: Object, name: String, parameter_types: GcType<Array>| {
+            reflect::find_method(vm, this as u64, name.as_str(), parameter_types, true) as Object
+        },
+    );
+    natives.register(
+        "java/lang/Class.getDeclaredField:(Ljava/lang/String;)Ljava/lang/reflect/Field;",
+        |vm: &mut VM, this: Object, name: String| {
+            reflect::find_field(vm, this as u64, name.as_str(), false) as Object
+        },
+    );
+    natives.register(
+        "java/lang/Class.getField:(Ljava/lang/String;)Ljava/lang/reflect/Field;",
+        |vm: &mut VM, this: Object, name: String| {
+            reflect::find_field(vm, this as u64, name.as_str(), true) as Object
+        },
+    );
+    natives.register(
+        "java/lang/Enum.valueOf:(Ljava/lang/Class;Ljava/lang/String;)Ljava/lang/Enum;",
+        |vm: &mut VM, enum_type: Object, name: String| {
+            reflect::enum_constant(vm, enum_type as u64, name.as_str()) as Object
+        },
+    );
+    natives.register(
+        "java/lang/Class.getAnnotations:()[Ljava/lang/annotation/Annotation;",
+        |vm: &mut VM, this: Object| {
+            annotation::class_annotations(vm, this as u64, false) as GcType<Array>
+        },
+    );
+    natives.register(
+        "java/lang/Class.getDeclaredAnnotations:()[Ljava/lang/annotation/Annotation;",
+        |vm: &mut VM, this: Object| {
+            annotation::class_annotations(vm, this as u64, true) as GcType<Array>
+        },
+    );
 }
 
-#[no_mangle]
-pub extern "C" fn java_lang_stringbuilder_append_string_stringbuilder(
-    renv: *mut RuntimeEnvironment,
-    obj: *mut ObjectBody,
-    s: *mut ObjectBody,
-) -> *mut ObjectBody {
-    unsafe {
-        let renv = &mut *renv;
-        let string_builder = &mut *obj;
-
-        let string_obj = &mut string_builder.variables[0];
-        // TODO: Currently, JIT doesn't support 'invokespecial'. Therefore StringBuilder newly
-        // created in JIT-compiled code is not initialized. That's why the code below is needed.
-        if *string_obj == 0 {
-            *string_obj =
-                (&mut *renv.objectheap).create_string_object("".to_string(), renv.classheap)
-        }
+fn register_reflect_natives(natives: &mut NativeRegistry) {
+    type Object = GcType<ObjectBody>;
+    let sig = |method: &str| format!("java/lang/reflect/{}", method);
+
+    natives.register(
+        sig("Method.invoke:(Ljava/lang/Object;[Ljava/lang/Object;)Ljava/lang/Object;").as_str(),
+        |vm: &mut VM, this: Object, obj: Object, args: GcType<Array>| {
+            reflect::invoke(vm, this as u64, obj as u64, args) as Object
+        },
+    );
+    natives.register(
+        sig("Method.toString:()Ljava/lang/String;").as_str(),
+        |_: &mut VM, this: Object| reflect::method_to_string(this as u64),
+    );
+    natives.register(
+        sig("Field.get:(Ljava/lang/Object;)Ljava/lang/Object;").as_str(),
+        |vm: &mut VM, this: Object, obj: Object| {
+            reflect::get(vm, this as u64, obj as u64) as Object
+        },
+    );
+    natives.register(
+        sig("Field.set:(Ljava/lang/Object;Ljava/lang/Object;)V").as_str(),
+        |vm: &mut VM, this: Object, obj: Object, value: Object| {
+            reflect::set(vm, this as u64, obj as u64, value as u64)
+        },
+    );
+    natives.register(
+        sig("Method.getDeclaredAnnotations:()[Ljava/lang/annotation/Annotation;").as_str(),
+        |vm: &mut VM, this: Object| {
+            let (class, method) = reflect::method_info_of(vm, this as u64);
+            annotation::declared_annotations(vm, class, &method.attributes) as GcType<Array>
+        },
+    );
+    natives.register(
+        sig("Method.getParameterAnnotations:()[[Ljava/lang/annotation/Annotation;").as_str(),
+        |vm: &mut VM, this: Object| {
+            let (class, method) = reflect::method_info_of(vm, this as u64);
+            annotation::parameter_annotations(vm, class, &method) as GcType<Array>
+        },
+    );
+    natives.register(
+        sig("Field.toString:()Ljava/lang/String;").as_str(),
+        |_: &mut VM, this: Object| reflect::field_to_string(this as u64),
+    );
+    natives.register(
+        sig("Field.getDeclaredAnnotations:()[Ljava/lang/annotation/Annotation;").as_str(),
+        |vm: &mut VM, this: Object| {
+            let (class, field) = reflect::field_info_of(vm, this as u64);
+            annotation::declared_annotations(vm, class, &field.attributes) as GcType<Array>
+        },
+    );
+}
 
-        let mut str1 = (&mut *(*string_obj as GcType<ObjectBody>))
-            .get_string_mut()
-            .clone();
-        let str2 = (&mut *s).get_string_mut();
+fn register_invoke_natives(natives: &mut NativeRegistry) {
+    type Object = GcType<ObjectBody>;
+    let sig = |method: &str| format!("java/lang/invoke/{}", method);
 
-        str1.push_str(str2);
+    natives.register(
+        sig("MethodType.methodType:(Ljava/lang/Class;[Ljava/lang/Class;)Ljava/lang/invoke/MethodType;").as_str(),
+        |vm: &mut VM, rtype: Object, ptypes: GcType<Array>| {
+            let ptypes = match unsafe { ptypes.as_ref() } {
+                Some(ptypes) => (0..ptypes.get_length())
+                    .map(|i| ptypes.at::<u64>(i as isize))
+                    .collect::<Vec<u64>>(),
+                None => vec![0],
+            };
+            if rtype.is_null() || ptypes.contains(&0) {
+                vm.throw_exception("java/lang/NullPointerException", None);
+                return 0 as Object;
+            }
+            let ptypes: Vec<String> = ptypes.into_iter().map(mirror::descriptor).collect();
+            let descriptor = format!("({}){}", ptypes.concat(), mirror::descriptor(rtype as u64));
+            method_handle::method_type(vm, descriptor.as_str()) as Object
+        },
+    );
+    natives.register(
+        sig("MethodType.returnType:()Ljava/lang/Class;").as_str(),
+        |vm: &mut VM, this: Object| {
+            let descriptor = method_handle::descriptor(this as u64);
+            let (_, ret) = lambda::parse_descriptor(descriptor.as_str());
+            mirror::of_descriptor(vm, ret) as Object
+        },
+    );
+    natives.register(
+        sig("MethodType.parameterType:(I)Ljava/lang/Class;").as_str(),
+        |vm: &mut VM, this: Object, num: i32| {
+            let descriptor = method_handle::descriptor(this as u64);
+            let (params, _) = lambda::parse_descriptor(descriptor.as_str());
+            match params.get(num as usize).filter(|_| num >= 0) {
+                Some(param) => mirror::of_descriptor(vm, param) as Object,
+                None => {
+                    let message = format!("{}", num);
+                    vm.throw_exception(
+                        "java/lang/IndexOutOfBoundsException",
+                        Some(message.as_str()),
+                    );
+                    0 as Object
+                }
+            }
+        },
+    );
+    natives.register(
+        sig("MethodType.parameterCount:()I").as_str(),
+        |_: &mut VM, this: Object| {
+            let descriptor = method_handle::descriptor(this as u64);
+            lambda::parse_descriptor(descriptor.as_str()).0.len() as i32
+        },
+    );
+    natives.register(
+        sig("MethodType.parameterArray:()[Ljava/lang/Class;").as_str(),
+        |vm: &mut VM, this: Object| {
+            let descriptor = method_handle::descriptor(this as u64);
+            let (params, _) = lambda::parse_descriptor(descriptor.as_str());
+            let class = vm.load_class("java/lang/Class");
+            let array = unsafe { &mut *vm.objectheap }.create_obj_array(class, params.len());
+            for (i, param) in params.iter().enumerate() {
+                let mirror = mirror::of_descriptor(vm, param);
+                unsafe { &mut *(array as GcType<Array>) }.store(i as isize, mirror);
+            }
+            array as GcType<Array>
+        },
+    );
+    natives.register(
+        sig("MethodType.toString:()Ljava/lang/String;").as_str(),
+        |_: &mut VM, this: Object| {
+            method_handle::type_to_string(method_handle::descriptor(this as u64).as_str())
+        },
+    );
 
-        *string_obj =
-            (&mut *renv.objectheap).create_string_object(str1.to_string(), renv.classheap);
-    }
-    obj
+    natives.register(
+        sig("MethodHandles$Lookup.findStatic:(Ljava/lang/Class;Ljava/lang/String;Ljava/lang/invoke/MethodType;)Ljava/lang/invoke/MethodHandle;").as_str(),
+        |vm: &mut VM, _: Object, refc: Object, name: String, method_type: Object| {
+            method_handle::find_method(vm, true, refc as u64, name.as_str(), method_type as u64)
+                as Object
+        },
+    );
+    natives.register(
+        sig("MethodHandles$Lookup.findVirtual:(Ljava/lang/Class;Ljava/lang/String;Ljava/lang/invoke/MethodType;)Ljava/lang/invoke/MethodHandle;").as_str(),
+        |vm: &mut VM, _: Object, refc: Object, name: String, method_type: Object| {
+            method_handle::find_method(vm, false, refc as u64, name.as_str(), method_type as u64)
+                as Object
+        },
+    );
+    natives.register(
+        sig("MethodHandles$Lookup.findGetter:(Ljava/lang/Class;Ljava/lang/String;Ljava/lang/Class;)Ljava/lang/invoke/MethodHandle;").as_str(),
+        |vm: &mut VM, _: Object, refc: Object, name: String, ty: Object| {
+            method_handle::find_getter(vm, refc as u64, name.as_str(), ty as u64) as Object
+        },
+    );
 }
 
-#[no_mangle]
-pub extern "C" fn java_lang_stringbuilder_tostring_string(
-    _renv: *mut RuntimeEnvironment,
-    obj: *mut ObjectBody,
-) -> *mut ObjectBody {
-    let string_builder = unsafe { &mut *obj };
-    let s = string_builder.variables[0];
-    s as GcType<ObjectBody>
+fn since_epoch() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
 }
 
-#[no_mangle]
-pub extern "C" fn java_lang_math_random_d(_renv: *mut RuntimeEnvironment) -> f64 {
-    use rand::random;
-    random::<f64>()
-}
-#[no_mangle]
-pub extern "C" fn java_lang_math_sqrt_d_d(_renv: *mut RuntimeEnvironment, x: f64) -> f64 {
-    x.sqrt()
+// A negative timeout throws IllegalArgumentException. Zero means forever.
+fn to_timeout(vm: &mut VM, millis: i64) -> Option<Option<Duration>> {
+    if millis < 0 {
+        vm.throw_exception(
+            "java/lang/IllegalArgumentException",
+            Some("timeout value is negative"),
+        );
+        return None;
+    }
+    Some(
+        Some(Duration::from_millis(millis as u64))
+            .filter(|timeout| *timeout != Duration::from_millis(0)),
+    )
 }
-#[no_mangle]
-pub extern "C" fn java_lang_math_sin_d_d(_renv: *mut RuntimeEnvironment, x: f64) -> f64 {
-    x.sin()
+
+fn register_thread_natives(natives: &mut NativeRegistry) {
+    natives.register(
+        "java/lang/Object.wait:()V",
+        |vm: &mut VM, this: GcType<ObjectBody>| vm.monitor_wait(this as u64, None),
+    );
+    natives.register(
+        "java/lang/Object.wait:(J)V",
+        |vm: &mut VM, this: GcType<ObjectBody>, timeout: i64| {
+            if let Some(timeout) = to_timeout(vm, timeout) {
+                vm.monitor_wait(this as u64, timeout)
+            }
+        },
+    );
+    natives.register(
+        "java/lang/Object.notify:()V",
+        |vm: &mut VM, this: GcType<ObjectBody>| vm.monitor_notify(this as u64, false),
+    );
+    natives.register(
+        "java/lang/Object.notifyAll:()V",
+        |vm: &mut VM, this: GcType<ObjectBody>| vm.monitor_notify(this as u64, true),
+    );
+
+    natives.register(
+        "java/lang/Thread.start:()V",
+        |vm: &mut VM, this: GcType<ObjectBody>| vm.start_thread(this as u64),
+    );
+    natives.register(
+        "java/lang/Thread.join:()V",
+        |vm: &mut VM, this: GcType<ObjectBody>| vm.join_thread(this as u64, None),
+    );
+    natives.register(
+        "java/lang/Thread.join:(J)V",
+        |vm: &mut VM, this: GcType<ObjectBody>, millis: i64| {
+            if let Some(timeout) = to_timeout(vm, millis) {
+                vm.join_thread(this as u64, timeout)
+            }
+        },
+    );
+    natives.register("java/lang/Thread.sleep:(J)V", |vm: &mut VM, millis: i64| {
+        if millis < 0 {
+            vm.throw_exception(
+                "java/lang/IllegalArgumentException",
+                Some("timeout value is negative"),
+            );
+        } else {
+            unsafe { &*vm.thread_manager }.sleep(vm.thread_id, Duration::from_millis(millis as u64))
+        }
+    });
+    natives.register("java/lang/Thread.yield:()V", |vm: &mut VM| {
+        unsafe { &*vm.thread_manager }.yield_now(vm.thread_id)
+    });
+    natives.register(
+        "java/lang/Thread.currentThread:()Ljava/lang/Thread;",
+        |vm: &mut VM| vm.get_current_thread_object() as GcType<ObjectBody>,
+    );
+    natives.register(
+        "java/lang/Thread.isAlive:()Z",
+        |vm: &mut VM, this: GcType<ObjectBody>| {
+            let thread_manager = unsafe { &*vm.thread_manager };
+            thread_manager
+                .find_thread_by_object(this as u64)
+                .is_some_and(|tid| thread_manager.is_alive(tid))
+        },
+    );
 }
-#[no_mangle]
-pub extern "C" fn java_lang_math_cos_d_d(_renv: *mut RuntimeEnvironment, x: f64) -> f64 {
-    x.cos()
+
+// Every field of an object occupies an 8-byte slot. Atomic operations on an int work on the
+// lower half of the slot, which holds the value on little-endian platforms.
+
+/// Returns the field ``value`` of an atomic object
+fn atomic_value<T>(object: GcType<ObjectBody>) -> &'static T {
+    let object = unsafe { &mut *object };
+    let (id, _) = *unsafe { &*object.class }
+        .get_numbered_field_info("value")
+        .unwrap();
+    unsafe { &*(&mut object.variables[id] as *mut u64 as *const T) }
 }
-#[no_mangle]
-pub extern "C" fn java_lang_math_tan_d_d(_renv: *mut RuntimeEnvironment, x: f64) -> f64 {
-    x.tan()
+
+fn register_atomic_natives(natives: &mut NativeRegistry) {
+    const ATOMIC_INTEGER: &str = "java/util/concurrent/atomic/AtomicInteger";
+    const ATOMIC_LONG: &str = "java/util/concurrent/atomic/AtomicLong";
+    const ATOMIC_REFERENCE: &str = "java/util/concurrent/atomic/AtomicReference";
+    let sig = |class: &str, method: &str| format!("{}.{}", class, method);
+
+    natives.register(
+        sig(ATOMIC_INTEGER, "get:()I").as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>| {
+            atomic_value::<AtomicI32>(this).load(Ordering::SeqCst)
+        },
+    );
+    natives.register(
+        sig(ATOMIC_INTEGER, "set:(I)V").as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>, val: i32| {
+            atomic_value::<AtomicI32>(this).store(val, Ordering::SeqCst)
+        },
+    );
+    natives.register(
+        sig(ATOMIC_INTEGER, "compareAndSet:(II)Z").as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>, expect: i32, update: i32| {
+            atomic_value::<AtomicI32>(this)
+                .compare_exchange(expect, update, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+        },
+    );
+    natives.register(
+        sig(ATOMIC_INTEGER, "getAndAdd:(I)I").as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>, delta: i32| {
+            atomic_value::<AtomicI32>(this).fetch_add(delta, Ordering::SeqCst)
+        },
+    );
+    natives.register(
+        sig(ATOMIC_INTEGER, "getAndSet:(I)I").as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>, val: i32| {
+            atomic_value::<AtomicI32>(this).swap(val, Ordering::SeqCst)
+        },
+    );
+
+    natives.register(
+        sig(ATOMIC_LONG, "get:()J").as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>| {
+            atomic_value::<AtomicI64>(this).load(Ordering::SeqCst)
+        },
+    );
+    natives.register(
+        sig(ATOMIC_LONG, "set:(J)V").as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>, val: i64| {
+            atomic_value::<AtomicI64>(this).store(val, Ordering::SeqCst)
+        },
+    );
+    natives.register(
+        sig(ATOMIC_LONG, "compareAndSet:(JJ)Z").as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>, expect: i64, update: i64| {
+            atomic_value::<AtomicI64>(this)
+                .compare_exchange(expect, update, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok()
+        },
+    );
+    natives.register(
+        sig(ATOMIC_LONG, "getAndAdd:(J)J").as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>, delta: i64| {
+            atomic_value::<AtomicI64>(this).fetch_add(delta, Ordering::SeqCst)
+        },
+    );
+    natives.register(
+        sig(ATOMIC_LONG, "getAndSet:(J)J").as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>, val: i64| {
+            atomic_value::<AtomicI64>(this).swap(val, Ordering::SeqCst)
+        },
+    );
+
+    natives.register(
+        sig(ATOMIC_REFERENCE, "get:()Ljava/lang/Object;").as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>| {
+            atomic_value::<AtomicU64>(this).load(Ordering::SeqCst) as GcType<ObjectBody>
+        },
+    );
+    natives.register(
+        sig(ATOMIC_REFERENCE, "set:(Ljava/lang/Object;)V").as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>, val: GcType<ObjectBody>| {
+            atomic_value::<AtomicU64>(this).store(val as u64, Ordering::SeqCst)
+        },
+    );
+    natives.register(
+        sig(
+            ATOMIC_REFERENCE,
+            "compareAndSet:(Ljava/lang/Object;Ljava/lang/Object;)Z",
+        )
+        .as_str(),
+        |_: &mut VM,
+         this: GcType<ObjectBody>,
+         expect: GcType<ObjectBody>,
+         update: GcType<ObjectBody>| {
+            atomic_value::<AtomicU64>(this)
+                .compare_exchange(
+                    expect as u64,
+                    update as u64,
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                )
+                .is_ok()
+        },
+    );
+    natives.register(
+        sig(
+            ATOMIC_REFERENCE,
+            "getAndSet:(Ljava/lang/Object;)Ljava/lang/Object;",
+        )
+        .as_str(),
+        |_: &mut VM, this: GcType<ObjectBody>, val: GcType<ObjectBody>| {
+            atomic_value::<AtomicU64>(this).swap(val as u64, Ordering::SeqCst) as GcType<ObjectBody>
+        },
+    );
 }
-#[no_mangle]
-pub extern "C" fn java_lang_math_abs_d_d(_renv: *mut RuntimeEnvironment, x: f64) -> f64 {
-    x.abs()
+
+/// Returns the field or the array element designated by the ``(Object o, long offset)``
+/// arguments of a ``sun.misc.Unsafe`` method. Unless ``offset`` designates an aligned ``T``
+/// inside ``object``, throws ``NullPointerException`` or ``IllegalArgumentException`` and returns
+/// ``None``.
+fn unsafe_target<T>(vm: &mut VM, object: GcType<ObjectBody>, offset: i64) -> Option<&'static T> {
+    if object.is_null() {
+        vm.throw_exception("java/lang/NullPointerException", None);
+        return None;
+    }
+    let (base, len) = if unsafe { &*vm.objectheap }.gc.is_array(object as u64) {
+        let elements = &mut unsafe { &mut *(object as GcType<Array>) }.elements;
+        (elements.as_mut_ptr(), elements.len())
+    } else {
+        let variables = &mut unsafe { &mut *object }.variables;
+        (variables.as_mut_ptr() as *mut u8, variables.len() * 8)
+    };
+    let in_bounds = offset >= 0
+        && len
+            .checked_sub(mem::size_of::<T>())
+            .is_some_and(|last| offset as u64 <= last as u64);
+    let target = base.wrapping_offset(offset as isize);
+    if !in_bounds || !(target as usize).is_multiple_of(mem::align_of::<T>()) {
+        vm.throw_exception(
+            "java/lang/IllegalArgumentException",
+            Some(format!("invalid offset: {}", offset).as_str()),
+        );
+        return None;
+    }
+    Some(unsafe { &*(target as *const T) })
 }
-#[no_mangle]
-pub extern "C" fn java_lang_math_pow_dd_d(_renv: *mut RuntimeEnvironment, x: f64, y: f64) -> f64 {
-    x.powf(y)
+
+fn register_unsafe_natives(natives: &mut NativeRegistry) {
+    type Object = GcType<ObjectBody>;
+    let sig = |method: &str| format!("sun/misc/Unsafe.{}", method);
+
+    natives.register(
+        sig("objectFieldOffset:(Ljava/lang/String;Ljava/lang/String;)J").as_str(),
+        |vm: &mut VM, _: Object, class_name: String, field_name: String| {
+            let class = vm.load_class(class_name.replace(".", "/").as_str());
+            let (id, _) = *unsafe { &*class }
+                .get_numbered_field_info(field_name.as_str())
+                .unwrap();
+            (id * 8) as i64
+        },
+    );
+    natives.register(
+        sig("arrayBaseOffset:(Ljava/lang/Object;)I").as_str(),
+        |_: &mut VM, _: Object, _: GcType<Array>| 0,
+    );
+    natives.register(
+        sig("arrayIndexScale:(Ljava/lang/Object;)I").as_str(),
+        |_: &mut VM, _: Object, array: GcType<Array>| {
+            unsafe { &*array }.atype.size_in_byte() as i32
+        },
+    );
+
+    natives.register(
+        sig("compareAndSwapInt:(Ljava/lang/Object;JII)Z").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64, expect: i32, update: i32| {
+            unsafe_target::<AtomicI32>(vm, o, offset).is_some_and(|target| {
+                target
+                    .compare_exchange(expect, update, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok()
+            })
+        },
+    );
+    natives.register(
+        sig("compareAndSwapLong:(Ljava/lang/Object;JJJ)Z").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64, expect: i64, update: i64| {
+            unsafe_target::<AtomicI64>(vm, o, offset).is_some_and(|target| {
+                target
+                    .compare_exchange(expect, update, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok()
+            })
+        },
+    );
+    natives.register(
+        sig("compareAndSwapObject:(Ljava/lang/Object;JLjava/lang/Object;Ljava/lang/Object;)Z")
+            .as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64, expect: Object, update: Object| {
+            unsafe_target::<AtomicU64>(vm, o, offset).is_some_and(|target| {
+                target
+                    .compare_exchange(
+                        expect as u64,
+                        update as u64,
+                        Ordering::SeqCst,
+                        Ordering::SeqCst,
+                    )
+                    .is_ok()
+            })
+        },
+    );
+
+    natives.register(
+        sig("getIntVolatile:(Ljava/lang/Object;J)I").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64| {
+            unsafe_target::<AtomicI32>(vm, o, offset)
+                .map_or(0, |target| target.load(Ordering::SeqCst))
+        },
+    );
+    natives.register(
+        sig("getLongVolatile:(Ljava/lang/Object;J)J").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64| {
+            unsafe_target::<AtomicI64>(vm, o, offset)
+                .map_or(0, |target| target.load(Ordering::SeqCst))
+        },
+    );
+    natives.register(
+        sig("getObjectVolatile:(Ljava/lang/Object;J)Ljava/lang/Object;").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64| {
+            unsafe_target::<AtomicU64>(vm, o, offset)
+                .map_or(0, |target| target.load(Ordering::SeqCst)) as Object
+        },
+    );
+    natives.register(
+        sig("putIntVolatile:(Ljava/lang/Object;JI)V").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64, val: i32| {
+            if let Some(target) = unsafe_target::<AtomicI32>(vm, o, offset) {
+                target.store(val, Ordering::SeqCst)
+            }
+        },
+    );
+    natives.register(
+        sig("putLongVolatile:(Ljava/lang/Object;JJ)V").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64, val: i64| {
+            if let Some(target) = unsafe_target::<AtomicI64>(vm, o, offset) {
+                target.store(val, Ordering::SeqCst)
+            }
+        },
+    );
+    natives.register(
+        sig("putObjectVolatile:(Ljava/lang/Object;JLjava/lang/Object;)V").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64, val: Object| {
+            if let Some(target) = unsafe_target::<AtomicU64>(vm, o, offset) {
+                target.store(val as u64, Ordering::SeqCst)
+            }
+        },
+    );
+
+    natives.register(
+        sig("getAndAddInt:(Ljava/lang/Object;JI)I").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64, delta: i32| {
+            unsafe_target::<AtomicI32>(vm, o, offset)
+                .map_or(0, |target| target.fetch_add(delta, Ordering::SeqCst))
+        },
+    );
+    natives.register(
+        sig("getAndAddLong:(Ljava/lang/Object;JJ)J").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64, delta: i64| {
+            unsafe_target::<AtomicI64>(vm, o, offset)
+                .map_or(0, |target| target.fetch_add(delta, Ordering::SeqCst))
+        },
+    );
+    natives.register(
+        sig("getAndSetInt:(Ljava/lang/Object;JI)I").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64, val: i32| {
+            unsafe_target::<AtomicI32>(vm, o, offset)
+                .map_or(0, |target| target.swap(val, Ordering::SeqCst))
+        },
+    );
+    natives.register(
+        sig("getAndSetLong:(Ljava/lang/Object;JJ)J").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64, val: i64| {
+            unsafe_target::<AtomicI64>(vm, o, offset)
+                .map_or(0, |target| target.swap(val, Ordering::SeqCst))
+        },
+    );
+    natives.register(
+        sig("getAndSetObject:(Ljava/lang/Object;JLjava/lang/Object;)Ljava/lang/Object;").as_str(),
+        |vm: &mut VM, _: Object, o: Object, offset: i64, val: Object| {
+            unsafe_target::<AtomicU64>(vm, o, offset)
+                .map_or(0, |target| target.swap(val as u64, Ordering::SeqCst)) as Object
+        },
+    );
 }
 
 // Internal Functions
@@ -263,7 +1132,7 @@ pub extern "C" fn ferrugo_internal_new(
     class: *mut Class,
 ) -> *mut ObjectBody {
     let renv = unsafe { &mut *renv };
-    let object = unsafe { &mut *renv.objectheap }.create_object(class);
+    let object = unsafe { (*renv.objectheap).create_object(class) };
     object as GcType<ObjectBody>
 }
 
@@ -273,7 +1142,11 @@ pub extern "C" fn ferrugo_internal_baload(
     array: *mut Array,
     index: u32,
 ) -> u32 {
-    unsafe { &*array }.at::<u8>(index as isize) as u32
+    let vm = unsafe { &mut *native_registry::current_vm() };
+    match vm.checked_array(array as u64, index as u64) {
+        Some(array) => array.at::<u8>(index as i32 as isize) as i8 as u32,
+        None => 0,
+    }
 }
 
 #[no_mangle]
@@ -282,7 +1155,11 @@ pub extern "C" fn ferrugo_internal_aaload(
     array: *mut Array,
     index: u32,
 ) -> u64 {
-    unsafe { &*array }.at::<u64>(index as isize) as u64
+    let vm = unsafe { &mut *native_registry::current_vm() };
+    match vm.checked_array(array as u64, index as u64) {
+        Some(array) => array.at::<u64>(index as i32 as isize),
+        None => 0,
+    }
 }
 
 #[no_mangle]
@@ -292,5 +1169,162 @@ pub extern "C" fn ferrugo_internal_bastore(
     index: u32,
     val: u32,
 ) {
-    unsafe { &mut *array }.store(index as isize, val as u8)
+    let vm = unsafe { &mut *native_registry::current_vm() };
+    if let Some(array) = vm.checked_array(array as u64, index as u64) {
+        array.store(index as i32 as isize, val as u8)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn ferrugo_internal_field_ptr(
+    _renv: *mut RuntimeEnvironment,
+    obj: *mut ObjectBody,
+    id: u32,
+) -> *mut u64 {
+    unsafe { (&mut *obj).variables.as_mut_ptr().offset(id as isize) }
+}
+
+/// Calls a registered native method from JIT-compiled code. ``args`` holds the arguments laid
+/// out as slots of the operand stack. They are rooted while the native method runs, since it may
+/// call back into Java and collect garbage. If the method has been unregistered since the code
+/// was compiled, the method is invoked as the interpreter would.
+#[no_mangle]
+pub extern "C" fn ferrugo_internal_call_native(
+    renv: *mut RuntimeEnvironment,
+    link: *mut NativeLink,
+    has_this: u32,
+    args: *const u64,
+) -> u64 {
+    let link = unsafe { &mut *link };
+    let vm = unsafe { &mut *native_registry::current_vm() };
+    let has_this = has_this != 0;
+    let native = link.resolve(&unsafe { &*renv }.natives);
+
+    // ``pkg/Class.method:descriptor``
+    let colon = link.signature.find(':').unwrap();
+    let descriptor = &link.signature[colon + 1..];
+    let args_count = marshal::params_slots_count(descriptor) + has_this as usize;
+    let args = unsafe { slice::from_raw_parts(args, args_count) };
+    match native {
+        Some(native) => vm.with_roots(args, |vm| native.call(vm, has_this, args)),
+        None => {
+            let dot = link.signature[..colon].rfind('.').unwrap();
+            let class = vm.load_class(&link.signature[..dot]);
+            let (class, method) = unsafe { &*class }
+                .get_method(&link.signature[dot + 1..colon], descriptor)
+                .unwrap();
+            vm.invoke_method(class, method, args)
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn ferrugo_internal_checkcast(
+    _renv: *mut RuntimeEnvironment,
+    obj: *mut ObjectBody,
+    class: *mut Class,
+    class_index: u32,
+    cache: *mut u64,
+) -> u32 {
+    check_type(obj, class, class_index, cache, true) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn ferrugo_internal_instanceof(
+    _renv: *mut RuntimeEnvironment,
+    obj: *mut ObjectBody,
+    class: *mut Class,
+    class_index: u32,
+    cache: *mut u64,
+) -> u32 {
+    check_type(obj, class, class_index, cache, false) as u32
+}
+
+#[no_mangle]
+pub extern "C" fn ferrugo_internal_exception_pending(_renv: *mut RuntimeEnvironment) -> u32 {
+    unsafe { &*native_registry::current_vm() }
+        .exception
+        .is_some() as u32
+}
+
+/// Called on the back-edges of compiled loops. Lets other threads take the VM lock if they are
+/// waiting for it. They don't collect garbage meanwhile (see ``GC::enter_compiled_code``).
+#[no_mangle]
+pub extern "C" fn ferrugo_internal_safepoint(_renv: *mut RuntimeEnvironment) {
+    let vm = unsafe { &*native_registry::current_vm() };
+    unsafe { &*vm.thread_manager }.yield_now(vm.thread_id);
+}
+
+#[no_mangle]
+pub extern "C" fn ferrugo_internal_throw_null_pointer_exception(_renv: *mut RuntimeEnvironment) {
+    unsafe { &mut *native_registry::current_vm() }
+        .throw_exception("java/lang/NullPointerException", None);
+}
+
+/// Returns the target of an atomic ``sun.misc.Unsafe`` method, which is ``size`` bytes long.
+/// Returns null if an exception was thrown.
+#[no_mangle]
+pub extern "C" fn ferrugo_internal_unsafe_target(
+    _renv: *mut RuntimeEnvironment,
+    object: GcType<ObjectBody>,
+    offset: i64,
+    size: u32,
+) -> *const u8 {
+    let vm = unsafe { &mut *native_registry::current_vm() };
+    let target = match size {
+        4 => unsafe_target::<AtomicI32>(vm, object, offset).map(|t| t as *const _ as *const u8),
+        _ => unsafe_target::<AtomicI64>(vm, object, offset).map(|t| t as *const _ as *const u8),
+    };
+    target.unwrap_or(ptr::null())
+}
+
+/// Runs a string concatenation call site from JIT-compiled code. ``args`` holds the arguments
+/// laid out as slots of the operand stack, which are rooted while ``toString`` runs. Returns null
+/// if an exception was thrown.
+#[no_mangle]
+pub extern "C" fn ferrugo_internal_concat(
+    _renv: *mut RuntimeEnvironment,
+    call_site: *const CallSite,
+    args: *const u64,
+) -> u64 {
+    let vm = unsafe { &mut *native_registry::current_vm() };
+    let (parts, slots) = match unsafe { &*call_site } {
+        CallSite::Concat { parts, slots } => (parts, *slots),
+        _ => unreachable!(),
+    };
+    let args = unsafe { slice::from_raw_parts(args, slots) };
+    match vm.with_roots(args, |vm| string_concat::concat(vm, parts, args)) {
+        Some(chars) => unsafe {
+            (*vm.objectheap).create_string_object_from_chars(&chars, vm.classheap)
+        },
+        None => 0,
+    }
+}
+
+/// Checks non-null ``obj`` against the ``CONSTANT_Class`` at ``class_index`` in ``class`` when
+/// the class cached at a site in JIT-compiled code missed. The class of ``obj`` is cached if it
+/// passed.
+fn check_type(
+    obj: *mut ObjectBody,
+    class: *mut Class,
+    class_index: u32,
+    cache: *mut u64,
+    cast: bool,
+) -> bool {
+    let vm = unsafe { &mut *native_registry::current_vm() };
+    let constant_pool = &unsafe { &*class }.classfile.constant_pool;
+    let name_index = constant_pool[class_index as usize]
+        .get_class_name_index()
+        .unwrap();
+    let name = constant_pool[name_index].get_utf8().unwrap();
+
+    let passed = if cast {
+        vm.check_cast(obj as u64, name)
+    } else {
+        vm.is_instance_of(obj as u64, name)
+    };
+    if passed && !unsafe { &*vm.objectheap }.gc.is_array(obj as u64) {
+        unsafe { *cache = (*obj).class as u64 };
+    }
+    passed
 }
@@ -0,0 +1,198 @@
+// Registry of native methods shared by the interpreter and the JIT
+
+use super::super::jvm::marshal::{self, FromJava, IntoJava, JavaValue};
+use super::vm::VM;
+use rustc_hash::FxHashMap;
+use std::cell::Cell;
+use std::fmt;
+use std::ptr;
+use std::sync::Arc;
+
+pub type NativeFunction = Arc<dyn Fn(&mut VM, &[JavaValue]) -> Option<JavaValue> + Send + Sync>;
+
+pub struct NativeMethod {
+    pub signature: String,
+    pub descriptor: String,
+    func: NativeFunction,
+}
+
+/// Native methods keyed by signature (e.g. ``java/lang/Math.sin:(D)D``)
+#[derive(Clone, Default)]
+pub struct NativeRegistry {
+    methods: FxHashMap<String, Arc<NativeMethod>>,
+    // Incremented whenever methods are registered or removed
+    generation: u64,
+}
+
+/// A call of a native method from JIT-compiled code. The method is looked up again once the
+/// registry has changed, so that the method registered at the time of the call is called.
+pub struct NativeLink {
+    pub signature: String,
+    method: Option<Arc<NativeMethod>>,
+    generation: u64,
+}
+
+/// Rust functions and closures that can be registered as native methods. The first parameter is
+/// the VM running the method. The rest are the arguments of the method, preceded by ``this``
+/// for an instance method.
+pub trait IntoNativeFunction<Args> {
+    fn into_native_function(self) -> NativeFunction;
+}
+
+macro_rules! impl_into_native_function {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg),*> IntoNativeFunction<($($arg,)*)> for F
+        where
+            F: Fn(&mut VM, $($arg),*) -> R + Send + Sync + 'static,
+            R: IntoJava,
+            $($arg: FromJava),*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn into_native_function(self) -> NativeFunction {
+                Arc::new(move |vm: &mut VM, args: &[JavaValue]| {
+                    let mut args = args.iter();
+                    $(let $arg = $arg::from_java(*args.next()?, vm)?;)*
+                    Some(self(vm, $($arg),*).to_java(vm))
+                })
+            }
+        }
+    };
+}
+
+// Used by ``NativeRegistry::register_function``
+struct RawFunction(NativeFunction);
+
+impl IntoNativeFunction<RawFunction> for RawFunction {
+    fn into_native_function(self) -> NativeFunction {
+        self.0
+    }
+}
+
+impl_into_native_function!();
+impl_into_native_function!(A);
+impl_into_native_function!(A, B);
+impl_into_native_function!(A, B, C);
+impl_into_native_function!(A, B, C, D);
+impl_into_native_function!(A, B, C, D, E);
+impl_into_native_function!(A, B, C, D, E, G);
+
+impl NativeMethod {
+    /// Calls the method with the arguments laid out as slots of the operand stack and returns
+    /// the slot holding the return value. If the arguments can't be converted to the types the
+    /// function takes, throws ``NullPointerException`` when one of them is null and
+    /// ``IllegalArgumentException`` otherwise, and returns 0.
+    pub fn call(&self, vm: &mut VM, has_this: bool, slots: &[u64]) -> u64 {
+        let args = marshal::from_slots(self.descriptor.as_str(), has_this, slots);
+        match (self.func)(vm, &args) {
+            Some(ret) => marshal::to_slot(ret),
+            None if args.contains(&JavaValue::Object(0)) => {
+                vm.throw_exception("java/lang/NullPointerException", None);
+                0
+            }
+            None => {
+                let message = format!("argument type mismatch: {}", self.signature);
+                vm.throw_exception("java/lang/IllegalArgumentException", Some(message.as_str()));
+                0
+            }
+        }
+    }
+
+    /// Returns the number of slots the arguments occupy
+    pub fn slots_count(&self, has_this: bool) -> usize {
+        marshal::params_slots_count(self.descriptor.as_str()) + has_this as usize
+    }
+}
+
+impl NativeLink {
+    pub fn new(registry: &NativeRegistry, signature: &str) -> Self {
+        NativeLink {
+            signature: signature.to_string(),
+            method: registry.get(signature).cloned(),
+            generation: registry.generation,
+        }
+    }
+
+    /// Returns the method registered for the signature now, or None if it has been removed
+    pub fn resolve(&mut self, registry: &NativeRegistry) -> Option<Arc<NativeMethod>> {
+        if self.generation != registry.generation {
+            self.method = registry.get(self.signature.as_str()).cloned();
+            self.generation = registry.generation;
+        }
+        self.method.clone()
+    }
+}
+
+impl NativeRegistry {
+    pub fn new() -> Self {
+        NativeRegistry {
+            methods: FxHashMap::default(),
+            generation: 0,
+        }
+    }
+
+    /// Registers ``func`` as the native method of ``signature``
+    /// (``pkg/Class.method:descriptor``). A method registered again is replaced.
+    pub fn register<Args, F: IntoNativeFunction<Args>>(&mut self, signature: &str, func: F) {
+        let descriptor = match signature.find(':') {
+            Some(pos) if signature[..pos].contains('.') => signature[pos + 1..].to_string(),
+            _ => panic!("invalid signature of native method: {}", signature),
+        };
+        let method = Arc::new(NativeMethod {
+            signature: signature.to_string(),
+            descriptor,
+            func: func.into_native_function(),
+        });
+        self.methods.insert(signature.to_string(), method);
+        self.generation += 1;
+    }
+
+    /// Registers a function taking the arguments as ``JavaValue``s, whose number and types are
+    /// not checked
+    pub fn register_function(&mut self, signature: &str, func: NativeFunction) {
+        self.register(signature, RawFunction(func))
+    }
+
+    /// Removes the native method of ``signature``
+    pub fn unregister(&mut self, signature: &str) {
+        if self.methods.remove(signature).is_some() {
+            self.generation += 1;
+        }
+    }
+
+    pub fn get(&self, signature: &str) -> Option<&Arc<NativeMethod>> {
+        self.methods.get(signature)
+    }
+
+    /// Adds the methods of ``other``, replacing the ones with the same signatures
+    pub fn extend(&mut self, other: NativeRegistry) {
+        self.methods.extend(other.methods);
+        self.generation += 1;
+    }
+}
+
+impl fmt::Debug for NativeRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.methods.keys()).finish()
+    }
+}
+
+impl fmt::Debug for NativeLink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("NativeLink").field(&self.signature).finish()
+    }
+}
+
+thread_local! {
+    // The VM running JIT-compiled code on this thread
+    static CURRENT_VM: Cell<*mut VM> = const { Cell::new(ptr::null_mut()) };
+}
+
+/// Must be called before running JIT-compiled code, which calls native methods via
+/// ``current_vm``.
+pub fn set_current_vm(vm: *mut VM) {
+    CURRENT_VM.with(|cur| cur.set(vm))
+}
+
+pub fn current_vm() -> *mut VM {
+    CURRENT_VM.with(|cur| cur.get())
+}
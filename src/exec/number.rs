@@ -0,0 +1,717 @@
+// Conversions between numbers and text done as ``java.lang.Double`` and ``java.lang.Float`` do them
+
+use std::cmp::{self, Ordering};
+
+// ``Double.toString`` and ``Float.toString`` produce the digits with Steele & White's algorithm,
+// as ``FloatingDecimal`` in the JDK does (until JDK 19). Its output isn't always the shortest, for
+// example ``1.0E23`` is printed as ``9.999999999999999E22``. To match it exactly, the arithmetic
+// below follows it step by step, including the overflows of its ``int`` and ``long`` cases.
+
+// The position of the binary point in a normalized significand, and its hidden bit
+const EXP_SHIFT: i32 = 52;
+const FRACT_HOB: u64 = 1 << EXP_SHIFT;
+
+const DOUBLE_SIGNIFICANT_BITS: i32 = 53;
+const FLOAT_SIGNIFICANT_BITS: i32 = 24;
+
+// The range of binary exponents for which integers are converted with ``long`` arithmetic
+const MAX_SMALL_BIN_EXP: i32 = 62;
+const MIN_SMALL_BIN_EXP: i32 = -(63 / 3);
+
+// The number of bits of 5^i
+const N_5_BITS: [i32; 27] = [
+    0, 3, 5, 7, 10, 12, 14, 17, 19, 21, 24, 26, 28, 31, 33, 35, 38, 40, 42, 45, 47, 49, 52, 54, 56,
+    59, 61,
+];
+
+/// ``Double.toString(d)``
+pub fn double_to_string(d: f64) -> String {
+    if d.is_nan() {
+        return "NaN".to_string();
+    }
+    let bits = d.to_bits();
+    let is_negative = d.is_sign_negative();
+    let mut fract_bits = bits & (FRACT_HOB - 1);
+    let mut bin_exp = ((bits >> EXP_SHIFT) & 0x7ff) as i32;
+    if bin_exp == 0x7ff {
+        return if is_negative { "-Infinity" } else { "Infinity" }.to_string();
+    }
+
+    let significant_bits;
+    if bin_exp == 0 {
+        if fract_bits == 0 {
+            return if is_negative { "-0.0" } else { "0.0" }.to_string();
+        }
+        // Normalizes a subnormal number
+        let leading_zeros = fract_bits.leading_zeros() as i32;
+        let shift = leading_zeros - (63 - EXP_SHIFT);
+        fract_bits <<= shift;
+        bin_exp = 1 - shift;
+        significant_bits = 64 - leading_zeros;
+    } else {
+        fract_bits |= FRACT_HOB;
+        significant_bits = DOUBLE_SIGNIFICANT_BITS;
+    }
+    Decimal::new(bin_exp - 1023, fract_bits, significant_bits).to_string(is_negative)
+}
+
+/// ``Float.toString(f)``
+pub fn float_to_string(f: f32) -> String {
+    if f.is_nan() {
+        return "NaN".to_string();
+    }
+    let bits = f.to_bits();
+    let is_negative = f.is_sign_negative();
+    let mut fract_bits = bits & ((1 << 23) - 1);
+    let mut bin_exp = ((bits >> 23) & 0xff) as i32;
+    if bin_exp == 0xff {
+        return if is_negative { "-Infinity" } else { "Infinity" }.to_string();
+    }
+
+    let significant_bits;
+    if bin_exp == 0 {
+        if fract_bits == 0 {
+            return if is_negative { "-0.0" } else { "0.0" }.to_string();
+        }
+        let leading_zeros = fract_bits.leading_zeros() as i32;
+        let shift = leading_zeros - (31 - 23);
+        fract_bits <<= shift;
+        bin_exp = 1 - shift;
+        significant_bits = 32 - leading_zeros;
+    } else {
+        fract_bits |= 1 << 23;
+        significant_bits = FLOAT_SIGNIFICANT_BITS;
+    }
+    // The significand is aligned with that of a double
+    Decimal::new(
+        bin_exp - 127,
+        (fract_bits as u64) << (EXP_SHIFT - 23),
+        significant_bits,
+    )
+    .to_string(is_negative)
+}
+
+/// ``Double.parseDouble(s)``. Returns the message of ``NumberFormatException`` if ``s`` is
+/// malformed.
+pub fn parse_double(s: &str) -> Result<f64, String> {
+    match float_literal(s)? {
+        Literal::Decimal(literal) => literal.parse().map_err(|_| for_input_string(s)),
+        Literal::Hexadecimal(hex) => {
+            let (significand, exponent) = hex.round(DOUBLE_SIGNIFICANT_BITS, -1074);
+            let d = if exponent + DOUBLE_SIGNIFICANT_BITS as i64 > 1024 {
+                f64::INFINITY
+            } else {
+                significand as f64 * pow2(exponent)
+            };
+            Ok(if hex.negative { -d } else { d })
+        }
+    }
+}
+
+/// ``Float.parseFloat(s)``, rounded directly to the nearest ``float`` rather than through ``double``
+pub fn parse_float(s: &str) -> Result<f32, String> {
+    match float_literal(s)? {
+        Literal::Decimal(literal) => literal.parse().map_err(|_| for_input_string(s)),
+        Literal::Hexadecimal(hex) => {
+            let (significand, exponent) = hex.round(FLOAT_SIGNIFICANT_BITS, -149);
+            // Exact, since the result is a ``float``
+            let f = if exponent + FLOAT_SIGNIFICANT_BITS as i64 > 128 {
+                f32::INFINITY
+            } else {
+                (significand as f64 * pow2(exponent)) as f32
+            };
+            Ok(if hex.negative { -f } else { f })
+        }
+    }
+}
+
+enum Literal<'a> {
+    // Without surrounding whitespace and the ``f``/``d`` suffix, so that Rust can parse it
+    Decimal(&'a str),
+    Hexadecimal(HexLiteral),
+}
+
+// A hexadecimal literal like ``0x1.8p1``, whose value is ``significand * 2^exponent``. Digits that
+// don't fit in ``significand`` are dropped, and ``inexact`` tells if any of them is nonzero.
+struct HexLiteral {
+    negative: bool,
+    significand: u64,
+    exponent: i64,
+    inexact: bool,
+}
+
+impl HexLiteral {
+    // Parses ``s`` without the sign and ``0x``
+    fn parse(s: &str, negative: bool) -> Option<Self> {
+        let s = s.strip_suffix(|c| "fFdD".contains(c)).unwrap_or(s);
+        let p = s.find(['p', 'P'])?;
+        let (mantissa, exponent) = (&s[..p], &s[p + 1..]);
+        let (integer, fraction) = match mantissa.find('.') {
+            Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+            None => (mantissa, ""),
+        };
+        let is_hex_digits = |s: &str| s.bytes().all(|b| b.is_ascii_hexdigit());
+        if (integer.is_empty() && fraction.is_empty())
+            || !is_hex_digits(integer)
+            || !is_hex_digits(fraction)
+        {
+            return None;
+        }
+        let negative_exponent = exponent.starts_with('-');
+        let exponent_digits = exponent
+            .strip_prefix(|c| c == '+' || c == '-')
+            .unwrap_or(exponent);
+        if exponent_digits.is_empty() || !exponent_digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        // Far beyond the exponents of ``double`` anyway
+        let mut exponent = exponent_digits
+            .bytes()
+            .fold(0i64, |e, b| cmp::min(e * 10 + (b - b'0') as i64, 1 << 20));
+        if negative_exponent {
+            exponent = -exponent;
+        }
+
+        let mut significand = 0u64;
+        let mut inexact = false;
+        for (i, c) in integer.chars().chain(fraction.chars()).enumerate() {
+            let digit = c.to_digit(16).unwrap() as u64;
+            let in_fraction = i >= integer.len();
+            if significand >> 60 == 0 {
+                significand = significand << 4 | digit;
+                exponent -= in_fraction as i64 * 4;
+            } else {
+                inexact |= digit != 0;
+                exponent += !in_fraction as i64 * 4;
+            }
+        }
+        Some(HexLiteral {
+            negative,
+            significand,
+            exponent,
+            inexact,
+        })
+    }
+
+    // Rounds the value to ``precision`` significant bits, half to even, where the exponent of the
+    // least significant bit is at least ``min_exponent`` (that of the smallest subnormal). Returns
+    // the rounded significand and its exponent.
+    fn round(&self, precision: i32, min_exponent: i64) -> (u64, i64) {
+        if self.significand == 0 {
+            return (0, 0);
+        }
+        let bits = 64 - self.significand.leading_zeros() as i64;
+        let exponent = cmp::max(self.exponent + bits - precision as i64, min_exponent);
+        let shift = exponent - self.exponent;
+        if shift <= 0 {
+            return (self.significand << -shift, exponent);
+        }
+
+        let (kept, half, rest) = if shift > 64 {
+            (0, false, true)
+        } else {
+            let kept = if shift == 64 {
+                0
+            } else {
+                self.significand >> shift
+            };
+            let half = (self.significand >> (shift - 1)) & 1 == 1;
+            let rest = self.significand & ((1 << (shift - 1)) - 1) != 0;
+            (kept, half, rest)
+        };
+        let rounded = kept + (half && (rest || self.inexact || kept & 1 == 1)) as u64;
+        // Rounding up may carry into one more bit
+        if rounded >> precision != 0 {
+            (rounded >> 1, exponent + 1)
+        } else {
+            (rounded, exponent)
+        }
+    }
+}
+
+// 2^exponent for the exponents of ``double`` including subnormals
+fn pow2(exponent: i64) -> f64 {
+    if exponent >= -1022 {
+        f64::from_bits(((exponent + 1023) as u64) << 52)
+    } else {
+        f64::from_bits(1 << (exponent + 1074))
+    }
+}
+
+// Checks that ``s`` is a literal that Java accepts
+fn float_literal(s: &str) -> Result<Literal<'_>, String> {
+    // As ``String.trim``, every character up to ``' '`` is whitespace
+    let s = s.trim_matches(|c: char| c <= ' ');
+    if s.is_empty() {
+        return Err("empty String".to_string());
+    }
+    let unsigned = s.strip_prefix(|c| c == '+' || c == '-').unwrap_or(s);
+    if unsigned == "NaN" || unsigned == "Infinity" {
+        return Ok(Literal::Decimal(s));
+    }
+    if let Some(hex) = unsigned
+        .strip_prefix("0x")
+        .or_else(|| unsigned.strip_prefix("0X"))
+    {
+        return HexLiteral::parse(hex, s.starts_with('-'))
+            .map(Literal::Hexadecimal)
+            .ok_or_else(|| for_input_string(s));
+    }
+
+    let number = unsigned
+        .strip_suffix(|c| "fFdD".contains(c))
+        .unwrap_or(unsigned);
+    let (mantissa, exponent) = match number.find(['e', 'E']) {
+        Some(i) => (&number[..i], Some(&number[i + 1..])),
+        None => (number, None),
+    };
+    let (integer, fraction) = match mantissa.find('.') {
+        Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+        None => (mantissa, ""),
+    };
+    if fraction.contains('.') {
+        return Err("multiple points".to_string());
+    }
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let is_valid_exponent = |e: &str| {
+        let e = e.strip_prefix(|c| c == '+' || c == '-').unwrap_or(e);
+        !e.is_empty() && is_digits(e)
+    };
+    if (integer.is_empty() && fraction.is_empty())
+        || !is_digits(integer)
+        || !is_digits(fraction)
+        || !exponent.is_none_or(is_valid_exponent)
+    {
+        return Err(for_input_string(s));
+    }
+    Ok(Literal::Decimal(
+        &s[..s.len() - (unsigned.len() - number.len())],
+    ))
+}
+
+fn for_input_string(s: &str) -> String {
+    format!("For input string: \"{}\"", s)
+}
+
+// A positive number as 0.d1d2d3... * 10^exponent
+struct Decimal {
+    digits: Vec<u8>,
+    exponent: i32,
+}
+
+impl Decimal {
+    // ``fract_bits`` is a normalized significand with its hidden bit, and the number is
+    // ``fract_bits`` * 2^(``bin_exp`` - 52)
+    fn new(bin_exp: i32, fract_bits: u64, significant_bits: i32) -> Decimal {
+        let tail_zeros = fract_bits.trailing_zeros() as i32;
+        // The number of bits to the right of the binary point after the significant ones
+        let fract_bits_count = EXP_SHIFT + 1 - tail_zeros;
+        let tiny_bits = cmp::max(0, fract_bits_count - bin_exp - 1);
+
+        // Integers that fit in a long
+        if (MIN_SMALL_BIN_EXP..=MAX_SMALL_BIN_EXP).contains(&bin_exp) && tiny_bits == 0 {
+            let insignificant = if bin_exp > significant_bits {
+                insignificant_digits_for_pow2(bin_exp - significant_bits - 1)
+            } else {
+                0
+            };
+            let value = if bin_exp >= EXP_SHIFT {
+                fract_bits << (bin_exp - EXP_SHIFT)
+            } else {
+                fract_bits >> (EXP_SHIFT - bin_exp)
+            };
+            return Decimal::from_integer(value, insignificant);
+        }
+
+        let mut dec_exp = estimate_dec_exp(fract_bits, bin_exp);
+
+        // The digits are generated from B / S, and M is the half of the distance to the
+        // neighbouring numbers. Each is 2^x2 * 5^x5 (times the significand for B).
+        let b5 = cmp::max(0, -dec_exp);
+        let mut b2 = b5 + tiny_bits + bin_exp;
+        let s5 = cmp::max(0, dec_exp);
+        let mut s2 = s5 + tiny_bits;
+        let m5 = b5;
+        let mut m2 = b2 - significant_bits;
+
+        let fract_bits = fract_bits >> tail_zeros;
+        b2 -= fract_bits_count - 1;
+        let common2factor = cmp::min(b2, s2);
+        b2 -= common2factor;
+        s2 -= common2factor;
+        m2 -= common2factor;
+        // The next smaller number is closer below a power of two
+        if fract_bits_count == 1 {
+            m2 -= 1;
+        }
+        if m2 < 0 {
+            b2 -= m2;
+            s2 -= m2;
+            m2 = 0;
+        }
+
+        let b_bits = fract_bits_count + b2 + n_5_bits(b5);
+        let ten_s_bits = s2 + 1 + n_5_bits(s5 + 1);
+
+        let digits = if b_bits < 32 && ten_s_bits < 32 {
+            let b = (fract_bits as i32)
+                .wrapping_mul(5i32.pow(b5 as u32))
+                .wrapping_shl(b2 as u32);
+            let s = 5i32.pow(s5 as u32) << s2;
+            let m = 5i32.pow(m5 as u32) << m2;
+            Digits::generate(
+                &mut dec_exp,
+                Wrapping32 {
+                    b,
+                    s,
+                    m,
+                    tens: s * 10,
+                },
+            )
+        } else if b_bits < 64 && ten_s_bits < 64 {
+            let b = (fract_bits as i64)
+                .wrapping_mul(5i64.pow(b5 as u32))
+                .wrapping_shl(b2 as u32);
+            let s = 5i64.pow(s5 as u32) << s2;
+            let m = 5i64.pow(m5 as u32) << m2;
+            Digits::generate(
+                &mut dec_exp,
+                Wrapping64 {
+                    b,
+                    s,
+                    m,
+                    tens: s * 10,
+                },
+            )
+        } else {
+            let s = BigNum::pow52(s5, s2);
+            Digits::generate(
+                &mut dec_exp,
+                Big {
+                    b: BigNum::from(fract_bits).mul_pow52(b5, b2),
+                    // M and 10 * S are compared with the remainder multiplied by 10
+                    m: BigNum::pow52(m5 + 1, m2 + 1),
+                    tens: BigNum::pow52(s5 + 1, s2 + 1),
+                    s,
+                },
+            )
+        };
+
+        let mut decimal = Decimal {
+            digits: digits.digits,
+            exponent: dec_exp + 1,
+        };
+        // The last digit is rounded by the stopping condition. Ties are broken to even digits.
+        if digits.high
+            && (!digits.low
+                || digits.low_digit_difference == Ordering::Greater
+                || (digits.low_digit_difference == Ordering::Equal
+                    && decimal.digits.last().unwrap() & 1 != 0))
+        {
+            decimal.round_up();
+        }
+        decimal
+    }
+
+    // ``value`` without its last ``insignificant`` digits, which are rounded
+    fn from_integer(mut value: u64, insignificant: i32) -> Decimal {
+        let mut exponent = 0;
+        if insignificant != 0 {
+            let pow10 = 10u64.pow(insignificant as u32);
+            let residue = value % pow10;
+            value /= pow10;
+            exponent += insignificant;
+            if residue >= pow10 >> 1 {
+                value += 1;
+            }
+        }
+        let mut digits = value.to_string().into_bytes();
+        while digits.len() > 1 && *digits.last().unwrap() == b'0' {
+            digits.pop();
+        }
+        exponent += value.to_string().len() as i32;
+        Decimal { digits, exponent }
+    }
+
+    fn round_up(&mut self) {
+        let mut i = self.digits.len() - 1;
+        while self.digits[i] == b'9' && i > 0 {
+            self.digits[i] = b'0';
+            i -= 1;
+        }
+        if self.digits[i] == b'9' {
+            // Carries out, leaving the zeros
+            self.exponent += 1;
+            self.digits[0] = b'1';
+        } else {
+            self.digits[i] += 1;
+        }
+    }
+
+    // Numbers in [10^-3, 10^7) are written like ``123.45``, and others like ``1.2345E-5``. There's
+    // always a digit after the decimal point.
+    fn to_string(&self, is_negative: bool) -> String {
+        let digits = std::str::from_utf8(&self.digits).unwrap();
+        let exponent = self.exponent;
+        let mut s = if is_negative { "-" } else { "" }.to_string();
+        if exponent > 0 && exponent < 8 {
+            let integer_len = cmp::min(digits.len(), exponent as usize);
+            s.push_str(&digits[..integer_len]);
+            if integer_len < exponent as usize {
+                s.push_str("0".repeat(exponent as usize - integer_len).as_str());
+                s.push_str(".0");
+            } else {
+                s.push('.');
+                s.push_str(if integer_len < digits.len() {
+                    &digits[integer_len..]
+                } else {
+                    "0"
+                });
+            }
+        } else if exponent <= 0 && exponent > -3 {
+            s.push_str("0.");
+            s.push_str("0".repeat(-exponent as usize).as_str());
+            s.push_str(digits);
+        } else {
+            s.push_str(&digits[..1]);
+            s.push('.');
+            s.push_str(if digits.len() > 1 { &digits[1..] } else { "0" });
+            s.push('E');
+            s.push_str((exponent - 1).to_string().as_str());
+        }
+        s
+    }
+}
+
+// An estimate of floor(log10(``fract_bits`` * 2^(``bin_exp`` - 52))), which may be one too large.
+// The constants are the JDK's, which aren't exactly log10(2) and so on.
+#[allow(clippy::approx_constant)]
+fn estimate_dec_exp(fract_bits: u64, bin_exp: i32) -> i32 {
+    let d2 = f64::from_bits(0x3ff0_0000_0000_0000 | (fract_bits & (FRACT_HOB - 1)));
+    let d = (d2 - 1.5) * 0.289529654 + 0.176091259 + bin_exp as f64 * 0.301029995663981;
+    d.floor() as i32
+}
+
+// The number of decimal digits that are certainly insignificant in an integer with ``p2`` more
+// bits than the significand
+fn insignificant_digits_for_pow2(p2: i32) -> i32 {
+    if p2 > 1 && p2 < 64 {
+        // floor(log10(2^p2))
+        ((1u64 << p2) as f64).log10().floor() as i32
+    } else {
+        0
+    }
+}
+
+fn n_5_bits(i: i32) -> i32 {
+    if (i as usize) < N_5_BITS.len() {
+        N_5_BITS[i as usize]
+    } else {
+        i * 3
+    }
+}
+
+struct Digits {
+    digits: Vec<u8>,
+    low: bool,
+    high: bool,
+    // The remainder (2 * B) compared with 10 * S
+    low_digit_difference: Ordering,
+}
+
+// The state of the digit generation, in one of the three kinds of arithmetic
+trait Generator {
+    // Returns the next digit, and whether the digits are close enough to the number from
+    // below (low) and above (high)
+    fn next_digit(&mut self, first: bool) -> (u8, bool, bool);
+    fn low_digit_difference(&self) -> Ordering;
+}
+
+impl Digits {
+    fn generate(dec_exp: &mut i32, mut generator: impl Generator) -> Digits {
+        let mut digits = vec![];
+        let (q, mut low, mut high) = generator.next_digit(true);
+        if q == 0 && !high {
+            // The estimate of the exponent was too large
+            *dec_exp -= 1;
+        } else {
+            digits.push(b'0' + q);
+        }
+        // E-form always has a digit after the decimal point. (The JDK checks the exponent as
+        // below, so 1.0E7 through 9.9E7 can have a single digit.)
+        if *dec_exp < -3 || *dec_exp >= 8 {
+            low = false;
+            high = false;
+        }
+        while !low && !high {
+            let (q, l, h) = generator.next_digit(false);
+            low = l;
+            high = h;
+            digits.push(b'0' + q);
+        }
+        Digits {
+            digits,
+            low,
+            high,
+            low_digit_difference: if low && high {
+                generator.low_digit_difference()
+            } else {
+                Ordering::Equal
+            },
+        }
+    }
+}
+
+macro_rules! wrapping_generator {
+    ($name:ident, $ty:ty) => {
+        struct $name {
+            b: $ty,
+            s: $ty,
+            m: $ty,
+            tens: $ty,
+        }
+
+        impl Generator for $name {
+            fn next_digit(&mut self, first: bool) -> (u8, bool, bool) {
+                let q = self.b / self.s;
+                self.b = (self.b % self.s).wrapping_mul(10);
+                self.m = self.m.wrapping_mul(10);
+                if first || self.m > 0 {
+                    (
+                        q as u8,
+                        self.b < self.m,
+                        self.b.wrapping_add(self.m) > self.tens,
+                    )
+                } else {
+                    // M overflowed, so it's certainly larger than B
+                    (q as u8, true, true)
+                }
+            }
+
+            fn low_digit_difference(&self) -> Ordering {
+                self.b.wrapping_shl(1).wrapping_sub(self.tens).cmp(&0)
+            }
+        }
+    };
+}
+
+wrapping_generator!(Wrapping32, i32);
+wrapping_generator!(Wrapping64, i64);
+
+struct Big {
+    b: BigNum,
+    s: BigNum,
+    m: BigNum,
+    tens: BigNum,
+}
+
+impl Generator for Big {
+    fn next_digit(&mut self, first: bool) -> (u8, bool, bool) {
+        let mut q = 0;
+        while self.b >= self.s {
+            self.b.sub_assign(&self.s);
+            q += 1;
+        }
+        self.b.mul_small(10);
+        if !first {
+            self.m.mul_small(10);
+        }
+        let low = self.b < self.m;
+        let mut sum = self.b.clone();
+        sum.add_assign(&self.m);
+        // Unlike the other cases, an equal sum counts as high
+        (q, low, sum >= self.tens)
+    }
+
+    fn low_digit_difference(&self) -> Ordering {
+        let mut b = self.b.clone();
+        b.mul_small(2);
+        b.cmp(&self.tens)
+    }
+}
+
+// A minimal unsigned big integer of little-endian 32-bit limbs
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct BigNum(Vec<u32>);
+
+impl BigNum {
+    fn from(value: u64) -> BigNum {
+        let mut n = BigNum(vec![value as u32, (value >> 32) as u32]);
+        n.trim();
+        n
+    }
+
+    // 5^p5 * 2^p2
+    fn pow52(p5: i32, p2: i32) -> BigNum {
+        BigNum::from(1).mul_pow52(p5, p2)
+    }
+
+    fn mul_pow52(mut self, p5: i32, p2: i32) -> BigNum {
+        for _ in 0..p5 {
+            self.mul_small(5);
+        }
+        for _ in 0..p2 {
+            self.mul_small(2);
+        }
+        self
+    }
+
+    fn mul_small(&mut self, x: u32) {
+        let mut carry = 0u64;
+        for limb in self.0.iter_mut() {
+            let v = *limb as u64 * x as u64 + carry;
+            *limb = v as u32;
+            carry = v >> 32;
+        }
+        if carry != 0 {
+            self.0.push(carry as u32);
+        }
+    }
+
+    fn add_assign(&mut self, other: &BigNum) {
+        let mut carry = 0u64;
+        for i in 0..cmp::max(self.0.len(), other.0.len()) {
+            if i == self.0.len() {
+                self.0.push(0);
+            }
+            let v = self.0[i] as u64 + *other.0.get(i).unwrap_or(&0) as u64 + carry;
+            self.0[i] = v as u32;
+            carry = v >> 32;
+        }
+        if carry != 0 {
+            self.0.push(carry as u32);
+        }
+    }
+
+    // ``other`` must not be larger
+    fn sub_assign(&mut self, other: &BigNum) {
+        let mut borrow = 0i64;
+        for i in 0..self.0.len() {
+            let v = self.0[i] as i64 - *other.0.get(i).unwrap_or(&0) as i64 - borrow;
+            self.0[i] = v as u32;
+            borrow = if v < 0 { 1 } else { 0 };
+        }
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+    }
+}
+
+impl Ord for BigNum {
+    fn cmp(&self, other: &BigNum) -> Ordering {
+        self.0
+            .len()
+            .cmp(&other.0.len())
+            .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigNum {
+    fn partial_cmp(&self, other: &BigNum) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
@@ -2,59 +2,128 @@ use super::super::class::{class::Class, classheap::ClassHeap};
 use super::super::gc::{gc::GcType, gc::GC};
 use super::frame::{AType, Array, ObjectBody};
 use std::slice::Iter;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// The state of the xorshift generator of identity hash codes
+static HASH_CODE_STATE: AtomicU32 = AtomicU32::new(0x2545_f491);
 
 #[derive(Clone, Debug)]
 pub struct ObjectHeap {
     pub gc: GC,
 }
 
+impl Default for ObjectHeap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ObjectHeap {
     pub fn new() -> ObjectHeap {
         ObjectHeap { gc: GC::new() }
     }
 
-    pub fn create_object(&mut self, class: GcType<Class>) -> u64 {
+    /// # Safety
+    ///
+    /// ``class`` must point to a valid class.
+    pub unsafe fn create_object(&mut self, class: GcType<Class>) -> u64 {
         let class_field_count = unsafe { &*class }.get_object_field_count();
         let obj = self.gc.alloc(ObjectBody {
             class,
             variables: vec![0; class_field_count],
+            hash_code: 0,
         });
 
         obj as u64
     }
 
-    pub fn create_string_object(&mut self, string: String, classheap: GcType<ClassHeap>) -> u64 {
+    /// # Safety
+    ///
+    /// ``classheap`` must point to a valid class heap in which ``java/lang/String`` is loaded.
+    pub unsafe fn create_string_object(
+        &mut self,
+        string: String,
+        classheap: GcType<ClassHeap>,
+    ) -> u64 {
+        let chars: Vec<u16> = string.encode_utf16().collect();
+        self.create_string_object_from_chars(&chars, classheap)
+    }
+
+    /// Creates a ``String`` from UTF-16 code units
+    ///
+    /// # Safety
+    ///
+    /// ``classheap`` must point to a valid class heap in which ``java/lang/String`` is loaded.
+    pub unsafe fn create_string_object_from_chars(
+        &mut self,
+        chars: &[u16],
+        classheap: GcType<ClassHeap>,
+    ) -> u64 {
         let class = unsafe { &*classheap }
             .get_class("java/lang/String")
             .unwrap();
         let object = self.create_object(class);
 
-        unsafe { &mut *(object as GcType<ObjectBody>) }.variables[0] =
-            self.gc.alloc(Array::new(AType::Char, 0, Some(string))) as u64;
+        let mut value = Array::new(AType::Char, chars.len());
+        for (i, c) in chars.iter().enumerate() {
+            value.store(i as isize, *c);
+        }
+        unsafe { &mut *(object as GcType<ObjectBody>) }.variables[0] = self.gc.alloc(value) as u64;
 
         object
     }
 
+    /// Returns the identity hash code of an object or an array, assigning one the first time. It's
+    /// kept in the object rather than derived from the address, which may change if objects are
+    /// moved.
+    pub fn identity_hash_code(&mut self, object: u64) -> i32 {
+        let hash_code = if self.gc.is_array(object) {
+            &mut unsafe { &mut *(object as GcType<Array>) }.hash_code
+        } else {
+            &mut unsafe { &mut *(object as GcType<ObjectBody>) }.hash_code
+        };
+        if *hash_code == 0 {
+            *hash_code = next_hash_code();
+        }
+        *hash_code
+    }
+
+    /// Creates a shallow copy of an object or an array, which has its own identity hash code
+    pub fn clone_object(&mut self, object: u64) -> u64 {
+        if self.gc.is_array(object) {
+            let array = unsafe { &*(object as GcType<Array>) };
+            self.gc.alloc(Array {
+                hash_code: 0,
+                ..array.clone()
+            }) as u64
+        } else {
+            let body = unsafe { &*(object as GcType<ObjectBody>) };
+            self.gc.alloc(ObjectBody {
+                hash_code: 0,
+                ..body.clone()
+            }) as u64
+        }
+    }
+
     pub fn create_array(&mut self, atype: AType, size: usize) -> u64 {
-        self.gc.alloc(Array::new(atype, size, None)) as u64
+        self.gc.alloc(Array::new(atype, size)) as u64
     }
 
     pub fn create_obj_array(&mut self, class: GcType<Class>, size: usize) -> u64 {
-        self.gc.alloc(Array::new(AType::Class(class), size, None)) as u64
+        self.gc.alloc(Array::new(AType::Class(class), size)) as u64
     }
 
     pub fn create_multi_array(&mut self, atype: AType, mut counts: Iter<usize>) -> u64 {
         let count = *counts.next().unwrap();
 
         if atype.get_multi_dimensions() <= 1 {
-            return self.gc.alloc(Array::new(
-                atype.get_multi_element_type().clone(),
-                count,
-                None,
-            )) as u64;
+            return self
+                .gc
+                .alloc(Array::new(atype.get_multi_element_type().clone(), count))
+                as u64;
         }
 
-        let mut array = Array::new(atype.clone(), count, None);
+        let mut array = Array::new(atype.clone(), count);
 
         for i in 0..count {
             array.store(
@@ -66,3 +135,24 @@ impl ObjectHeap {
         self.gc.alloc(array) as u64
     }
 }
+
+// Returns a non-zero 31-bit pseudo random number (Marsaglia's xorshift)
+fn next_hash_code() -> i32 {
+    let mut state = HASH_CODE_STATE.load(Ordering::Relaxed);
+    loop {
+        let mut next = state;
+        next ^= next << 13;
+        next ^= next >> 17;
+        next ^= next << 5;
+        match HASH_CODE_STATE.compare_exchange_weak(
+            state,
+            next,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        ) {
+            Ok(_) if next & 0x7fff_ffff != 0 => return (next & 0x7fff_ffff) as i32,
+            Ok(_) => state = next,
+            Err(current) => state = current,
+        }
+    }
+}
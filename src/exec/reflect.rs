@@ -0,0 +1,640 @@
+// ``java.lang.reflect``: ``Method`` and ``Field`` objects made from the metadata of classes
+// https://docs.oracle.com/javase/8/docs/api/java/lang/reflect/package-summary.html
+
+use super::super::class::class::Class;
+use super::super::class::classfile::{field::FieldInfo, method::access_flags, method::MethodInfo};
+use super::super::gc::gc::GcType;
+use super::frame::{Array, ObjectBody};
+use super::lambda::{class_name, is_reference, parse_descriptor};
+use super::method_handle::{self, get_field, set_field};
+use super::mirror;
+use super::vm::VM;
+
+// The flags ``getModifiers()`` returns (``Modifier.classModifiers()`` and so on)
+const CLASS_MODIFIERS: u16 = 0x0611;
+const METHOD_MODIFIERS: u16 = 0x0d3f;
+const FIELD_MODIFIERS: u16 = 0x00df;
+
+// ``ACC_ENUM`` in the access flags of the fields of enum constants
+const ACC_ENUM: u16 = 0x4000;
+
+// ``getModifiers()`` of primitive and array types: ``public final abstract``
+const BUILTIN_TYPE_MODIFIERS: u16 = 0x0411;
+
+/// ``Class.forName``. Throws ``ClassNotFoundException`` and returns null if there is no such
+/// class.
+pub fn for_name(vm: &mut VM, name: &str) -> u64 {
+    let internal_name = name.replace(".", "/");
+    let element = internal_name.trim_start_matches('[');
+    let exists = if element.len() == internal_name.len() {
+        class_exists(vm, element)
+    } else if element.starts_with('L') && element.ends_with(';') {
+        class_exists(vm, &element[1..element.len() - 1])
+    } else {
+        element.len() == 1 && element != "V" && mirror::is_primitive(element)
+    };
+    if !exists {
+        vm.throw_exception("java/lang/ClassNotFoundException", Some(name));
+        return 0;
+    }
+    mirror::of_class_name(vm, internal_name.as_str())
+}
+
+/// Whether the class of ``name`` is loaded or can be found in the class path
+pub fn class_exists(vm: &VM, name: &str) -> bool {
+    unsafe { &*vm.classheap }.get_class(name).is_some() || vm.find_class_file(name).is_some()
+}
+
+/// ``Class.getModifiers``
+pub fn class_modifiers(vm: &mut VM, mirror: u64) -> i32 {
+    match mirror::class(vm, mirror) {
+        Some(class) => (unsafe { &*class }.classfile.access_flags & CLASS_MODIFIERS) as i32,
+        None => BUILTIN_TYPE_MODIFIERS as i32,
+    }
+}
+
+/// ``Class.getSuperclass``. Returns null for ``Object``, interfaces and primitive types.
+pub fn superclass(vm: &mut VM, mirror: u64) -> u64 {
+    let descriptor = mirror::descriptor(mirror);
+    if descriptor.starts_with('[') {
+        return mirror::of_class_name(vm, "java/lang/Object");
+    }
+    if mirror::is_interface(vm, descriptor.as_str()) {
+        return 0;
+    }
+    let super_class_name = mirror::class(vm, mirror)
+        .and_then(|class| unsafe { &*class }.get_super_class_name().cloned());
+    match super_class_name {
+        Some(name) => mirror::of_class_name(vm, name.as_str()),
+        None => 0,
+    }
+}
+
+/// ``Class.getInterfaces``
+pub fn interfaces(vm: &mut VM, mirror: u64) -> u64 {
+    let names: Vec<String> = match mirror::class(vm, mirror) {
+        Some(class) => unsafe { &*class }
+            .get_interface_names()
+            .into_iter()
+            .cloned()
+            .collect(),
+        None => vec![],
+    };
+    let mirrors: Vec<u64> = names
+        .iter()
+        .map(|name| mirror::of_class_name(vm, name))
+        .collect();
+    new_array(vm, "java/lang/Class", &mirrors)
+}
+
+/// ``Class.isInstance``
+pub fn is_instance(vm: &mut VM, mirror: u64, object: u64) -> bool {
+    let descriptor = mirror::descriptor(mirror);
+    object != 0
+        && is_reference(descriptor.as_str())
+        && vm.is_instance_of(object, class_name(descriptor.as_str()))
+}
+
+/// ``Class.newInstance``. Throws ``InstantiationException`` and returns null if the class can't
+/// be instantiated with a constructor taking no arguments.
+pub fn new_instance(vm: &mut VM, mirror: u64) -> u64 {
+    let init = mirror::class(vm, mirror).and_then(|class| {
+        let flags = unsafe { &*class }.classfile.access_flags;
+        if flags & (mirror::ACC_INTERFACE | access_flags::ACC_PACC_ABSTRACT) != 0 {
+            return None;
+        }
+        unsafe { &*class }
+            .get_method("<init>", "()V")
+            .filter(|(init_class, _)| *init_class == class)
+    });
+    let (class, init) = match init {
+        Some(init) => init,
+        None => {
+            let name = mirror::name_of_descriptor(mirror::descriptor(mirror).as_str());
+            vm.throw_exception("java/lang/InstantiationException", Some(name.as_str()));
+            return 0;
+        }
+    };
+
+    let object = unsafe { (*vm.objectheap).create_object(class) };
+    vm.invoke_method(class, init, &[object]);
+    if vm.exception.is_some() {
+        return 0;
+    }
+    object
+}
+
+/// ``Class.getDeclaredMethods``. Constructors and static initializers are not included.
+pub fn declared_methods(vm: &mut VM, mirror: u64) -> u64 {
+    let methods = match mirror::class(vm, mirror) {
+        Some(class) => unsafe { &*class }
+            .classfile
+            .methods
+            .iter()
+            .filter(|method| !method_name(class, method).starts_with('<'))
+            .map(|method| (class, method.clone()))
+            .collect(),
+        None => vec![],
+    };
+    new_method_array(vm, methods)
+}
+
+/// ``Class.getDeclaredFields``
+pub fn declared_fields(vm: &mut VM, mirror: u64) -> u64 {
+    let fields = match mirror::class(vm, mirror) {
+        Some(class) => unsafe { &*class }
+            .classfile
+            .fields
+            .iter()
+            .map(|field| (class, field.clone()))
+            .collect(),
+        None => vec![],
+    };
+    new_field_array(vm, fields)
+}
+
+/// ``Class.getDeclaredMethod`` and ``getMethod``. ``getMethod`` (``public_only``) searches the
+/// public methods of the super classes too. Throws ``NoSuchMethodException`` and returns null if
+/// the method is not found.
+pub fn find_method(
+    vm: &mut VM,
+    mirror: u64,
+    name: &str,
+    parameter_types: GcType<Array>,
+    public_only: bool,
+) -> u64 {
+    let params: Vec<String> = elements_of(parameter_types as u64)
+        .into_iter()
+        .map(mirror::descriptor)
+        .collect();
+    let prefix = format!("({})", params.concat());
+
+    let mut class = mirror::class(vm, mirror);
+    while let Some(cur_class) = class {
+        let method = unsafe { &*cur_class }
+            .classfile
+            .methods
+            .iter()
+            .find(|method| {
+                method_name(cur_class, method) == name
+                    && method_descriptor(cur_class, method).starts_with(prefix.as_str())
+                    && (!public_only || method.check_access_flags(access_flags::ACC_PUBLIC))
+            })
+            .cloned();
+        if let Some(method) = method {
+            resolve_method_types(vm, cur_class, &method);
+            return new_method(vm, cur_class, &method);
+        }
+        class = if public_only {
+            unsafe { &*cur_class }.get_super_class()
+        } else {
+            None
+        };
+    }
+
+    let params: Vec<String> = params
+        .iter()
+        .map(|param| mirror::type_name_of_descriptor(param))
+        .collect();
+    let message = format!(
+        "{}.{}({})",
+        mirror::name_of_descriptor(mirror::descriptor(mirror).as_str()),
+        name,
+        params.join(", ")
+    );
+    vm.throw_exception("java/lang/NoSuchMethodException", Some(message.as_str()));
+    0
+}
+
+/// ``Class.getDeclaredField`` and ``getField``. ``getField`` (``public_only``) searches the
+/// public fields of the super classes too. Throws ``NoSuchFieldException`` and returns null if the
+/// field is not found.
+pub fn find_field(vm: &mut VM, mirror: u64, name: &str, public_only: bool) -> u64 {
+    let mut class = mirror::class(vm, mirror);
+    while let Some(cur_class) = class {
+        let field = unsafe { &*cur_class }
+            .classfile
+            .fields
+            .iter()
+            .find(|field| {
+                field_name(cur_class, field) == name
+                    && (!public_only || field.access_flags & access_flags::ACC_PUBLIC != 0)
+            })
+            .cloned();
+        if let Some(field) = field {
+            mirror::of_descriptor(vm, field_descriptor(cur_class, &field));
+            return new_field(vm, cur_class, &field);
+        }
+        class = if public_only {
+            unsafe { &*cur_class }.get_super_class()
+        } else {
+            None
+        };
+    }
+
+    vm.throw_exception("java/lang/NoSuchFieldException", Some(name));
+    0
+}
+
+/// ``Enum.valueOf``. Throws ``IllegalArgumentException`` and returns null if the enum type has no
+/// constant of ``name``.
+pub fn enum_constant(vm: &mut VM, enum_type: u64, name: &str) -> u64 {
+    let class = mirror::class(vm, enum_type);
+    let constant = class.and_then(|class| {
+        let is_constant =
+            unsafe { &*class }.classfile.fields.iter().any(|field| {
+                field.access_flags & ACC_ENUM != 0 && field_name(class, field) == name
+            });
+        if is_constant {
+            unsafe { &*class }.get_static_variable(name)
+        } else {
+            None
+        }
+    });
+    match constant {
+        Some(constant) => constant,
+        None => {
+            let message = format!(
+                "No enum constant {}.{}",
+                mirror::name_of_descriptor(mirror::descriptor(enum_type).as_str()),
+                name
+            );
+            vm.throw_exception("java/lang/IllegalArgumentException", Some(message.as_str()));
+            0
+        }
+    }
+}
+
+/// ``Method.invoke``. Primitive arguments are unboxed and the returned value is boxed. An
+/// exception thrown by the method is wrapped in ``InvocationTargetException``. Returns null if an
+/// exception is thrown.
+pub fn invoke(vm: &mut VM, method: u64, object: u64, args: GcType<Array>) -> u64 {
+    let class_descriptor = mirror::descriptor(get_field(method, "clazz"));
+    let name = string(get_field(method, "name"));
+    let params: Vec<String> = elements_of(get_field(method, "parameterTypes"))
+        .into_iter()
+        .map(mirror::descriptor)
+        .collect();
+    let ret = mirror::descriptor(get_field(method, "returnType"));
+    let modifiers = get_field(method, "modifiers") as u16;
+    let is_static = modifiers & access_flags::ACC_PACC_STATIC != 0;
+    let descriptor = format!("({}){}", params.concat(), ret);
+    let class = vm.load_class(class_name(class_descriptor.as_str()));
+
+    if !is_static && !check_receiver(vm, object, class_descriptor.as_str()) {
+        return 0;
+    }
+    let args = elements_of(args as u64);
+    if args.len() != params.len() {
+        vm.throw_exception(
+            "java/lang/IllegalArgumentException",
+            Some("wrong number of arguments"),
+        );
+        return 0;
+    }
+
+    let mut slots = if is_static { vec![] } else { vec![object] };
+    for (arg, param) in args.iter().zip(params.iter()) {
+        match unwrap_value(vm, *arg, param) {
+            Some(arg) => slots.push(arg),
+            None => return 0,
+        }
+        if param == "J" || param == "D" {
+            slots.push(0);
+        }
+    }
+
+    // Dispatch virtual methods on the class of the receiver
+    let virtual_method = if is_static
+        || modifiers & access_flags::ACC_PACC_PRIVATE != 0
+        || unsafe { &*vm.objectheap }.gc.is_array(object)
+    {
+        None
+    } else {
+        unsafe { &*(*(object as GcType<ObjectBody>)).class }
+            .get_method(name.as_str(), descriptor.as_str())
+    };
+    let (method_class, method) = virtual_method
+        .or_else(|| unsafe { &*class }.get_method(name.as_str(), descriptor.as_str()))
+        .unwrap();
+    let result = vm.invoke_method(method_class, method, &slots);
+
+    if let Some(target) = vm.exception.take() {
+        vm.throw_exception("java/lang/reflect/InvocationTargetException", None);
+        set_field(vm.exception.unwrap(), "target", target);
+        return 0;
+    }
+    if ret == "V" {
+        return 0;
+    }
+    method_handle::convert(vm, result, ret.as_str(), "Ljava/lang/Object;").unwrap_or(0)
+}
+
+/// ``Field.get``. A primitive value is boxed. Returns null if an exception is thrown.
+pub fn get(vm: &mut VM, field: u64, object: u64) -> u64 {
+    let (class, name, ty, is_static) = field_of(vm, field);
+    let value = if is_static {
+        unsafe { &*class }
+            .get_static_variable(name.as_str())
+            .unwrap()
+    } else {
+        let class_descriptor = format!("L{};", unsafe { &*class }.get_name().unwrap());
+        if !check_receiver(vm, object, class_descriptor.as_str()) {
+            return 0;
+        }
+        let (id, _) = *unsafe { &*class }
+            .get_numbered_field_info(name.as_str())
+            .unwrap();
+        unsafe { &*(object as GcType<ObjectBody>) }.variables[id]
+    };
+    method_handle::convert(vm, value, ty.as_str(), "Ljava/lang/Object;").unwrap_or(0)
+}
+
+/// ``Field.set``. A primitive value is unboxed.
+pub fn set(vm: &mut VM, field: u64, object: u64, value: u64) {
+    let (class, name, ty, is_static) = field_of(vm, field);
+    if !is_static {
+        let class_descriptor = format!("L{};", unsafe { &*class }.get_name().unwrap());
+        if !check_receiver(vm, object, class_descriptor.as_str()) {
+            return;
+        }
+    }
+    let value = match unwrap_value(vm, value, ty.as_str()) {
+        Some(value) => value,
+        None => return,
+    };
+    if is_static {
+        unsafe { &mut *class }.put_static_variable(name.as_str(), value);
+    } else {
+        let (id, _) = *unsafe { &*class }
+            .get_numbered_field_info(name.as_str())
+            .unwrap();
+        unsafe { &mut *(object as GcType<ObjectBody>) }.variables[id] = value;
+    }
+}
+
+/// Formats a ``Method`` as ``Method.toString()`` does, like ``public static int Foo.bar(int,long)``
+pub fn method_to_string(method: u64) -> String {
+    let class = mirror::descriptor(get_field(method, "clazz"));
+    let params: Vec<String> = elements_of(get_field(method, "parameterTypes"))
+        .into_iter()
+        .map(|param| mirror::type_name_of_descriptor(mirror::descriptor(param).as_str()))
+        .collect();
+    format!(
+        "{}{} {}.{}({})",
+        modifiers_prefix(get_field(method, "modifiers") as u16),
+        mirror::type_name_of_descriptor(
+            mirror::descriptor(get_field(method, "returnType")).as_str()
+        ),
+        mirror::name_of_descriptor(class.as_str()),
+        string(get_field(method, "name")),
+        params.join(",")
+    )
+}
+
+/// Formats a ``Field`` as ``Field.toString()`` does, like ``private int Foo.count``
+pub fn field_to_string(field: u64) -> String {
+    let class = mirror::descriptor(get_field(field, "clazz"));
+    format!(
+        "{}{} {}.{}",
+        modifiers_prefix(get_field(field, "modifiers") as u16),
+        mirror::type_name_of_descriptor(mirror::descriptor(get_field(field, "type")).as_str()),
+        mirror::name_of_descriptor(class.as_str()),
+        string(get_field(field, "name"))
+    )
+}
+
+// Modifiers in the order of ``Modifier.toString``, followed by a space
+fn modifiers_prefix(modifiers: u16) -> String {
+    static NAMES: [(u16, &str); 11] = [
+        (0x0001, "public"),
+        (0x0004, "protected"),
+        (0x0002, "private"),
+        (0x0400, "abstract"),
+        (0x0008, "static"),
+        (0x0010, "final"),
+        (0x0080, "transient"),
+        (0x0040, "volatile"),
+        (0x0020, "synchronized"),
+        (0x0100, "native"),
+        (0x0800, "strictfp"),
+    ];
+    NAMES
+        .iter()
+        .filter(|(flag, _)| modifiers & flag != 0)
+        .map(|(_, name)| format!("{} ", name))
+        .collect()
+}
+
+// Throws an exception and returns false if ``object`` is null or not an instance of the type of
+// ``class_descriptor``
+fn check_receiver(vm: &mut VM, object: u64, class_descriptor: &str) -> bool {
+    if object == 0 {
+        vm.throw_exception("java/lang/NullPointerException", None);
+        return false;
+    }
+    if !vm.is_instance_of(object, class_name(class_descriptor)) {
+        vm.throw_exception(
+            "java/lang/IllegalArgumentException",
+            Some("object is not an instance of declaring class"),
+        );
+        return false;
+    }
+    true
+}
+
+// Unboxes and widens ``value`` if ``ty`` is primitive, or checks that it's an instance of ``ty``.
+// Throws ``IllegalArgumentException`` and returns None if the type doesn't match.
+fn unwrap_value(vm: &mut VM, value: u64, ty: &str) -> Option<u64> {
+    if is_reference(ty) {
+        if value == 0 || vm.is_instance_of(value, class_name(ty)) {
+            return Some(value);
+        }
+    } else if value != 0 {
+        let wrapper = format!("L{};", vm.class_name_of(value).replace(".", "/"));
+        if let Some(prim) = method_handle::primitive_of_wrapper(wrapper.as_str()) {
+            if method_handle::widens(prim, ty) {
+                return method_handle::convert(vm, value, wrapper.as_str(), ty);
+            }
+        }
+    }
+    vm.throw_exception(
+        "java/lang/IllegalArgumentException",
+        Some("argument type mismatch"),
+    );
+    None
+}
+
+/// Returns the declaring class and the ``MethodInfo`` of a ``Method``
+pub fn method_info_of(vm: &mut VM, method: u64) -> (GcType<Class>, MethodInfo) {
+    let class = mirror::class(vm, get_field(method, "clazz")).unwrap();
+    let name = string(get_field(method, "name"));
+    let params: Vec<String> = elements_of(get_field(method, "parameterTypes"))
+        .into_iter()
+        .map(mirror::descriptor)
+        .collect();
+    let descriptor = format!(
+        "({}){}",
+        params.concat(),
+        mirror::descriptor(get_field(method, "returnType"))
+    );
+    let method = unsafe { &*class }
+        .classfile
+        .methods
+        .iter()
+        .find(|method| {
+            method_name(class, method) == name && method_descriptor(class, method) == descriptor
+        })
+        .cloned()
+        .unwrap();
+    (class, method)
+}
+
+/// Returns the declaring class and the ``FieldInfo`` of a ``Field``
+pub fn field_info_of(vm: &mut VM, field: u64) -> (GcType<Class>, FieldInfo) {
+    let (class, name, _, _) = field_of(vm, field);
+    let field = unsafe { &*class }
+        .classfile
+        .fields
+        .iter()
+        .find(|field| field_name(class, field) == name)
+        .cloned()
+        .unwrap();
+    (class, field)
+}
+
+// Returns the declaring class, the name, the type and whether it's static of a ``Field``
+fn field_of(vm: &mut VM, field: u64) -> (GcType<Class>, String, String, bool) {
+    let class = mirror::class(vm, get_field(field, "clazz")).unwrap();
+    let name = string(get_field(field, "name"));
+    let ty = mirror::descriptor(get_field(field, "type"));
+    let is_static = get_field(field, "modifiers") as u16 & access_flags::ACC_PACC_STATIC != 0;
+    (class, name, ty, is_static)
+}
+
+fn new_method_array(vm: &mut VM, methods: Vec<(GcType<Class>, MethodInfo)>) -> u64 {
+    for (class, method) in &methods {
+        resolve_method_types(vm, *class, method);
+    }
+    let methods: Vec<u64> = methods
+        .iter()
+        .map(|(class, method)| new_method(vm, *class, method))
+        .collect();
+    new_array(vm, "java/lang/reflect/Method", &methods)
+}
+
+fn new_field_array(vm: &mut VM, fields: Vec<(GcType<Class>, FieldInfo)>) -> u64 {
+    for (class, field) in &fields {
+        mirror::of_descriptor(vm, field_descriptor(*class, field));
+    }
+    let fields: Vec<u64> = fields
+        .iter()
+        .map(|(class, field)| new_field(vm, *class, field))
+        .collect();
+    new_array(vm, "java/lang/reflect/Field", &fields)
+}
+
+// Creates the mirrors of the types in the descriptor of ``method`` in advance. Loading their
+// classes may run Java code (and GC), which must not happen while reflection objects are only
+// referred to from Rust.
+fn resolve_method_types(vm: &mut VM, class: GcType<Class>, method: &MethodInfo) {
+    let (params, ret) = parse_descriptor(method_descriptor(class, method));
+    for ty in params.into_iter().chain(Some(ret)) {
+        mirror::of_descriptor(vm, ty);
+    }
+}
+
+fn new_method(vm: &mut VM, class: GcType<Class>, method: &MethodInfo) -> u64 {
+    let (params, ret) = parse_descriptor(method_descriptor(class, method));
+    let params: Vec<u64> = params
+        .into_iter()
+        .map(|param| mirror::of_descriptor(vm, param))
+        .collect();
+    let clazz = mirror::of_class_name(vm, unsafe { &*class }.get_name().unwrap());
+    let return_type = mirror::of_descriptor(vm, ret);
+    let parameter_types = new_array(vm, "java/lang/Class", &params);
+    let name = unsafe {
+        (*vm.objectheap).create_string_object(method_name(class, method).to_string(), vm.classheap)
+    };
+
+    let method_class = vm.load_class("java/lang/reflect/Method");
+    let object = unsafe { (*vm.objectheap).create_object(method_class) };
+    set_field(object, "clazz", clazz);
+    set_field(object, "name", name);
+    set_field(object, "returnType", return_type);
+    set_field(object, "parameterTypes", parameter_types);
+    set_field(
+        object,
+        "modifiers",
+        (method.access_flags & METHOD_MODIFIERS) as u64,
+    );
+    object
+}
+
+fn new_field(vm: &mut VM, class: GcType<Class>, field: &FieldInfo) -> u64 {
+    let clazz = mirror::of_class_name(vm, unsafe { &*class }.get_name().unwrap());
+    let ty = mirror::of_descriptor(vm, field_descriptor(class, field));
+    let name = unsafe {
+        (*vm.objectheap).create_string_object(field_name(class, field).to_string(), vm.classheap)
+    };
+
+    let field_class = vm.load_class("java/lang/reflect/Field");
+    let object = unsafe { (*vm.objectheap).create_object(field_class) };
+    set_field(object, "clazz", clazz);
+    set_field(object, "name", name);
+    set_field(object, "type", ty);
+    set_field(
+        object,
+        "modifiers",
+        (field.access_flags & FIELD_MODIFIERS) as u64,
+    );
+    object
+}
+
+fn new_array(vm: &mut VM, class_name: &str, elements: &[u64]) -> u64 {
+    let class = vm.load_class(class_name);
+    let array = unsafe { &mut *vm.objectheap }.create_obj_array(class, elements.len());
+    for (i, element) in elements.iter().enumerate() {
+        unsafe { &mut *(array as GcType<Array>) }.store(i as isize, *element);
+    }
+    array
+}
+
+// The elements of an array of references. Null is taken as an empty array.
+fn elements_of(array: u64) -> Vec<u64> {
+    match unsafe { (array as GcType<Array>).as_ref() } {
+        Some(array) => (0..array.get_length())
+            .map(|i| array.at::<u64>(i as isize))
+            .collect(),
+        None => vec![],
+    }
+}
+
+fn method_name(class: GcType<Class>, method: &MethodInfo) -> &str {
+    unsafe { &*class }
+        .get_utf8_from_const_pool(method.name_index as usize)
+        .unwrap()
+}
+
+fn method_descriptor(class: GcType<Class>, method: &MethodInfo) -> &str {
+    unsafe { &*class }
+        .get_utf8_from_const_pool(method.descriptor_index as usize)
+        .unwrap()
+}
+
+fn field_name(class: GcType<Class>, field: &FieldInfo) -> &str {
+    unsafe { &*class }
+        .get_utf8_from_const_pool(field.name_index as usize)
+        .unwrap()
+}
+
+fn field_descriptor(class: GcType<Class>, field: &FieldInfo) -> &str {
+    unsafe { &*class }
+        .get_utf8_from_const_pool(field.descriptor_index as usize)
+        .unwrap()
+}
+
+fn string(object: u64) -> String {
+    unsafe { &*(object as GcType<ObjectBody>) }
+        .get_string()
+}
@@ -0,0 +1,227 @@
+// Parts of ``java.lang.String`` that are easier to write in Rust than in Java
+
+use super::super::gc::gc::GcType;
+use super::frame::Array;
+use super::vm::VM;
+use regex::Regex;
+
+/// ``String.split(regex, limit)``. Returns null with ``PatternSyntaxException`` thrown if
+/// ``regex`` is malformed or uses a construct that isn't supported (see ``translate``).
+pub fn split(vm: &mut VM, string: &str, regex: &str, limit: i32) -> u64 {
+    let matches: Vec<(usize, usize)> = match literal_of(regex) {
+        Some(literal) => string
+            .match_indices(literal)
+            .map(|(start, m)| (start, start + m.len()))
+            .collect(),
+        None => match translate(regex).and_then(|translated| {
+            Regex::new(translated.as_str()).map_err(|err| (err.to_string(), None))
+        }) {
+            Ok(regex) => regex
+                .find_iter(string)
+                .map(|m| (m.start(), m.end()))
+                .collect(),
+            Err((description, index)) => {
+                // Formatted like ``PatternSyntaxException.getMessage``. The errors of the crate
+                // already show the pattern.
+                let message = match index {
+                    Some(index) => format!(
+                        "{} near index {}\n{}\n{}^",
+                        description,
+                        index,
+                        regex,
+                        " ".repeat(index)
+                    ),
+                    None => description,
+                };
+                vm.throw_exception(
+                    "java/util/regex/PatternSyntaxException",
+                    Some(message.as_str()),
+                );
+                return 0;
+            }
+        },
+    };
+
+    // Follows ``Pattern.split``: at most ``limit`` pieces if it's positive, and trailing empty
+    // strings are removed if it's zero
+    let mut pieces = vec![];
+    let mut index = 0;
+    for (start, end) in matches {
+        if limit <= 0 || pieces.len() < limit as usize - 1 {
+            // A zero-width match at the beginning never produces an empty leading piece
+            if index == 0 && end == 0 {
+                continue;
+            }
+            pieces.push(&string[index..start]);
+            index = end;
+        } else {
+            break;
+        }
+    }
+    if index == 0 {
+        pieces = vec![string];
+    } else {
+        pieces.push(&string[index..]);
+        if limit == 0 {
+            while pieces.last() == Some(&"") {
+                pieces.pop();
+            }
+        }
+    }
+
+    let class = vm.load_class("java/lang/String");
+    let objectheap = unsafe { &mut *vm.objectheap };
+    let array = objectheap.create_obj_array(class, pieces.len());
+    for (i, piece) in pieces.iter().enumerate() {
+        let piece = unsafe { objectheap.create_string_object(piece.to_string(), vm.classheap) };
+        unsafe { &mut *(array as GcType<Array>) }.store(i as isize, piece);
+    }
+    array
+}
+
+// Like ``String.split`` in the JDK, a regex of one character that isn't special (or escaped) is
+// matched literally without compiling it
+fn literal_of(regex: &str) -> Option<char> {
+    let mut chars = regex.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(c), None, _) if !".$|()[{^?*+\\".contains(c) => Some(c),
+        (Some('\\'), Some(c), None) if !c.is_ascii_alphanumeric() => Some(c),
+        _ => None,
+    }
+}
+
+const HORIZONTAL_WHITESPACE: &str =
+    "[ \\t\\xA0\\x{1680}\\x{180E}\\x{2000}-\\x{200A}\\x{202F}\\x{205F}\\x{3000}]";
+const VERTICAL_WHITESPACE: &str = "[\\n\\x0B\\f\\r\\x{85}\\x{2028}\\x{2029}]";
+
+/// Translates a ``java.util.regex.Pattern`` into the syntax of the ``regex`` crate, whose ``\d``,
+/// ``\w`` and ``\s`` are Unicode-aware, whose ``\v`` is only a vertical tab and which has no
+/// ``\Q...\E``. Lookaround, back references, atomic groups and possessive quantifiers can't be
+/// expressed, so they are rejected with a description and the index (in chars) where they start.
+fn translate(pattern: &str) -> Result<String, (String, Option<usize>)> {
+    let unsupported = |construct: &str, index: usize| {
+        Err((format!("{} are not supported", construct), Some(index)))
+    };
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut translated = String::new();
+    // The nesting depth of character classes
+    let mut class_depth = 0;
+    // Whether the last thing translated was a quantifier, which possessive ``+`` may follow
+    let mut after_quantifier = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let quantifier = class_depth == 0 && (c == '*' || c == '+' || c == '?' || c == '{');
+        if after_quantifier && c == '+' {
+            return unsupported("Possessive quantifiers", i);
+        }
+        // ``?`` after a quantifier makes it lazy, and isn't a quantifier itself
+        if after_quantifier && c == '?' {
+            translated.push(c);
+            after_quantifier = false;
+            i += 1;
+            continue;
+        }
+        after_quantifier = false;
+        match c {
+            '\\' => {
+                let escaped = match chars.get(i + 1) {
+                    Some(&escaped) => escaped,
+                    None => return Err(("Unexpected internal error".to_string(), Some(i + 1))),
+                };
+                i += 2;
+                match escaped {
+                    'Q' => {
+                        let end = (i..chars.len())
+                            .find(|&j| chars[j] == '\\' && chars.get(j + 1) == Some(&'E'))
+                            .unwrap_or(chars.len());
+                        let quoted: String = chars[i..end].iter().collect();
+                        translated += regex::escape(quoted.as_str()).as_str();
+                        i = end + 2;
+                    }
+                    '1'..='9' | 'k' => return unsupported("Back references", i - 2),
+                    'd' => translated += "[0-9]",
+                    'D' => translated += "[^0-9]",
+                    'w' => translated += "[a-zA-Z_0-9]",
+                    'W' => translated += "[^a-zA-Z_0-9]",
+                    's' => translated += "[ \\t\\n\\x0B\\f\\r]",
+                    'S' => translated += "[^ \\t\\n\\x0B\\f\\r]",
+                    'h' => translated += HORIZONTAL_WHITESPACE,
+                    'H' => translated += HORIZONTAL_WHITESPACE.replacen('[', "[^", 1).as_str(),
+                    'v' => translated += VERTICAL_WHITESPACE,
+                    'V' => translated += VERTICAL_WHITESPACE.replacen('[', "[^", 1).as_str(),
+                    'e' => translated += "\\x1B",
+                    'p' | 'P' if chars.get(i) == Some(&'{') => {
+                        let end = (i..chars.len())
+                            .find(|&j| chars[j] == '}')
+                            .map_or(chars.len(), |j| j + 1);
+                        translated.push('\\');
+                        translated.extend(&chars[i - 1..end]);
+                        i = end;
+                    }
+                    // Java allows any character that isn't a letter or a digit to be escaped
+                    escaped if !escaped.is_alphanumeric() => {
+                        translated += regex::escape(escaped.to_string().as_str()).as_str()
+                    }
+                    escaped => {
+                        translated.push('\\');
+                        translated.push(escaped);
+                    }
+                }
+                continue;
+            }
+            '[' => {
+                class_depth += 1;
+                translated.push(c);
+                // A ``]`` right after the opening bracket (and ``^``) is a literal
+                if chars.get(i + 1) == Some(&'^') {
+                    translated.push('^');
+                    i += 1;
+                }
+                if chars.get(i + 1) == Some(&']') {
+                    translated += "\\]";
+                    i += 1;
+                }
+            }
+            ']' if class_depth > 0 => {
+                class_depth -= 1;
+                translated.push(c);
+            }
+            '(' if class_depth == 0 && chars.get(i + 1) == Some(&'?') => {
+                let rest: String = chars[i + 2..].iter().take(2).collect();
+                if rest.starts_with('=') || rest.starts_with('!') {
+                    return unsupported("Lookahead groups", i);
+                }
+                if rest == "<=" || rest == "<!" {
+                    return unsupported("Lookbehind groups", i);
+                }
+                if rest.starts_with('>') {
+                    return unsupported("Atomic groups", i);
+                }
+                // ``U`` means something else to the crate, and ``d`` isn't known
+                let flags = chars[i + 2..]
+                    .iter()
+                    .take_while(|c| c.is_alphabetic() || **c == '-');
+                if flags.clone().any(|&c| c == 'U' || c == 'd') {
+                    return unsupported("UNICODE_CHARACTER_CLASS and UNIX_LINES flags", i);
+                }
+                translated.push(c);
+            }
+            '{' if quantifier => {
+                // A counted repetition like ``{2,3}``. Anything else is left to the crate.
+                let end = (i..chars.len()).find(|&j| chars[j] == '}');
+                match end {
+                    Some(end) => {
+                        translated.extend(&chars[i..=end]);
+                        i = end;
+                    }
+                    None => translated.push(c),
+                }
+            }
+            c => translated.push(c),
+        }
+        after_quantifier = quantifier;
+        i += 1;
+    }
+    Ok(translated)
+}
@@ -0,0 +1,191 @@
+// ``java.lang.StringBuilder``. Its characters are the first ``count`` elements of ``value``, which
+// is replaced with a larger array when they don't fit.
+
+use super::super::gc::gc::GcType;
+use super::frame::{AType, Array, ObjectBody};
+use super::vm::VM;
+use std::cmp;
+use std::ptr;
+
+// The indices of the fields of ``StringBuilder``
+const VALUE: usize = 0;
+const COUNT: usize = 1;
+
+const NULL: &[u16] = &['n' as u16, 'u' as u16, 'l' as u16, 'l' as u16];
+
+/// Returns the characters in ``builder``
+pub fn chars<'a>(builder: GcType<ObjectBody>) -> &'a [u16] {
+    let body = unsafe { &*builder };
+    let value = unsafe { &*(body.variables[VALUE] as GcType<Array>) };
+    &value.as_chars()[..body.variables[COUNT] as usize]
+}
+
+/// Returns the characters of a ``String``, or ``"null"``
+pub fn chars_of_string<'a>(string: GcType<ObjectBody>) -> &'a [u16] {
+    match unsafe { string.as_ref() } {
+        Some(string) => string.get_string_chars(),
+        None => NULL,
+    }
+}
+
+pub fn append(vm: &mut VM, builder: GcType<ObjectBody>, s: &[u16]) -> GcType<ObjectBody> {
+    let count = chars(builder).len();
+    replace(vm, builder, count, count, s);
+    builder
+}
+
+pub fn append_str(vm: &mut VM, builder: GcType<ObjectBody>, s: &str) -> GcType<ObjectBody> {
+    let s: Vec<u16> = s.encode_utf16().collect();
+    append(vm, builder, &s)
+}
+
+/// Inserts ``s`` before ``offset``. Returns null with ``StringIndexOutOfBoundsException`` thrown
+/// if ``offset`` is out of range.
+pub fn insert(
+    vm: &mut VM,
+    builder: GcType<ObjectBody>,
+    offset: i32,
+    s: &[u16],
+) -> GcType<ObjectBody> {
+    if offset < 0 || offset as usize > chars(builder).len() {
+        throw_index_out_of_bounds(vm, offset);
+        return ptr::null_mut();
+    }
+    replace(vm, builder, offset as usize, offset as usize, s);
+    builder
+}
+
+pub fn insert_str(
+    vm: &mut VM,
+    builder: GcType<ObjectBody>,
+    offset: i32,
+    s: &str,
+) -> GcType<ObjectBody> {
+    let s: Vec<u16> = s.encode_utf16().collect();
+    insert(vm, builder, offset, &s)
+}
+
+/// Removes the characters in ``start..end``. ``end`` may be past the last character.
+pub fn delete(
+    vm: &mut VM,
+    builder: GcType<ObjectBody>,
+    start: i32,
+    end: i32,
+) -> GcType<ObjectBody> {
+    let count = chars(builder).len();
+    let end = cmp::min(end as i64, count as i64) as i32;
+    if start < 0 {
+        throw_index_out_of_bounds(vm, start);
+        return ptr::null_mut();
+    }
+    if start > end {
+        vm.throw_exception("java/lang/StringIndexOutOfBoundsException", None);
+        return ptr::null_mut();
+    }
+    replace(vm, builder, start as usize, end as usize, &[]);
+    builder
+}
+
+pub fn delete_char_at(vm: &mut VM, builder: GcType<ObjectBody>, index: i32) -> GcType<ObjectBody> {
+    if !check_index(vm, builder, index) {
+        return ptr::null_mut();
+    }
+    replace(vm, builder, index as usize, index as usize + 1, &[]);
+    builder
+}
+
+/// Reverses the characters. Surrogate pairs stay in order, as in the JDK.
+pub fn reverse(vm: &mut VM, builder: GcType<ObjectBody>) -> GcType<ObjectBody> {
+    let mut reversed: Vec<u16> = chars(builder).iter().rev().cloned().collect();
+    let mut i = 0;
+    while i + 1 < reversed.len() {
+        if is_low_surrogate(reversed[i]) && is_high_surrogate(reversed[i + 1]) {
+            reversed.swap(i, i + 1);
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+    let count = reversed.len();
+    replace(vm, builder, 0, count, &reversed);
+    builder
+}
+
+/// Truncates the characters, or pads them with ``'\u0000'``
+pub fn set_length(vm: &mut VM, builder: GcType<ObjectBody>, new_length: i32) {
+    if new_length < 0 {
+        throw_index_out_of_bounds(vm, new_length);
+        return;
+    }
+    let count = chars(builder).len();
+    let new_length = new_length as usize;
+    if new_length < count {
+        replace(vm, builder, new_length, count, &[]);
+    } else {
+        replace(vm, builder, count, count, &vec![0; new_length - count]);
+    }
+}
+
+pub fn char_at(vm: &mut VM, builder: GcType<ObjectBody>, index: i32) -> u16 {
+    if !check_index(vm, builder, index) {
+        return 0;
+    }
+    chars(builder)[index as usize]
+}
+
+pub fn set_char_at(vm: &mut VM, builder: GcType<ObjectBody>, index: i32, c: u16) {
+    if check_index(vm, builder, index) {
+        replace(vm, builder, index as usize, index as usize + 1, &[c]);
+    }
+}
+
+/// ``StringBuilder.toString()`` creates a new ``String`` every time
+pub fn to_string(vm: &mut VM, builder: GcType<ObjectBody>) -> GcType<ObjectBody> {
+    let string =
+        unsafe { (*vm.objectheap).create_string_object_from_chars(chars(builder), vm.classheap) };
+    string as GcType<ObjectBody>
+}
+
+// Replaces the characters in ``start..end`` with ``s``, making ``value`` larger if needed
+fn replace(vm: &mut VM, builder: GcType<ObjectBody>, start: usize, end: usize, s: &[u16]) {
+    let old = chars(builder);
+    let mut new = Vec::with_capacity(old.len() - (end - start) + s.len());
+    new.extend_from_slice(&old[..start]);
+    new.extend_from_slice(s);
+    new.extend_from_slice(&old[end..]);
+
+    let body = unsafe { &mut *builder };
+    let capacity = unsafe { &*(body.variables[VALUE] as GcType<Array>) }.get_length();
+    if new.len() > capacity {
+        let capacity = cmp::max(capacity * 2 + 2, new.len());
+        body.variables[VALUE] = unsafe { &mut *vm.objectheap }.create_array(AType::Char, capacity);
+    }
+    let value = unsafe { &mut *(body.variables[VALUE] as GcType<Array>) };
+    for (i, c) in new.iter().enumerate() {
+        value.store(i as isize, *c);
+    }
+    body.variables[COUNT] = new.len() as u64;
+}
+
+fn check_index(vm: &mut VM, builder: GcType<ObjectBody>, index: i32) -> bool {
+    let in_range = index >= 0 && (index as usize) < chars(builder).len();
+    if !in_range {
+        throw_index_out_of_bounds(vm, index);
+    }
+    in_range
+}
+
+fn throw_index_out_of_bounds(vm: &mut VM, index: i32) {
+    vm.throw_exception(
+        "java/lang/StringIndexOutOfBoundsException",
+        Some(format!("String index out of range: {}", index).as_str()),
+    );
+}
+
+fn is_high_surrogate(c: u16) -> bool {
+    (0xd800..0xdc00).contains(&c)
+}
+
+fn is_low_surrogate(c: u16) -> bool {
+    (0xdc00..0xe000).contains(&c)
+}
@@ -0,0 +1,213 @@
+// ``StringConcatFactory``: string concatenation compiled to ``invokedynamic`` since JDK 9
+// https://docs.oracle.com/javase/9/docs/api/java/lang/invoke/StringConcatFactory.html
+
+use super::super::gc::gc::GcType;
+use super::call_site::{BootstrapArgument, CallSite};
+use super::frame::ObjectBody;
+use super::number;
+use super::vm::{u2d, u2f, VM};
+
+// Tags in the recipe of ``makeConcatWithConstants``
+const TAG_ARG: char = '\u{1}';
+const TAG_CONST: char = '\u{2}';
+
+/// A piece of a concatenation, specialized for the types of the arguments when the call site is
+/// linked
+#[derive(Debug)]
+pub enum ConcatPart {
+    Literal(String),
+    // The argument starting at the slot (counted from the first argument)
+    Argument(ArgumentType, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgumentType {
+    Int,
+    Long,
+    Float,
+    Double,
+    Boolean,
+    Char,
+    String,
+    Object,
+}
+
+/// ``StringConcatFactory.makeConcatWithConstants`` and ``makeConcat``. The arguments of the call
+/// site are the values to concatenate.
+pub fn make_concat(
+    name: &str,
+    descriptor: &str,
+    args: &[BootstrapArgument],
+) -> Result<CallSite, String> {
+    let mut types = vec![];
+    let mut slots = 0;
+    let mut chars = descriptor[1..descriptor.find(')').unwrap()].chars();
+    while let Some(c) = chars.next() {
+        let ty = match c {
+            'J' => ArgumentType::Long,
+            'F' => ArgumentType::Float,
+            'D' => ArgumentType::Double,
+            'Z' => ArgumentType::Boolean,
+            'C' => ArgumentType::Char,
+            'L' => {
+                let name: String = chars.by_ref().take_while(|c| *c != ';').collect();
+                if name == "java/lang/String" {
+                    ArgumentType::String
+                } else {
+                    ArgumentType::Object
+                }
+            }
+            '[' => {
+                let element = chars.by_ref().find(|c| *c != '[').unwrap();
+                if element == 'L' {
+                    chars.by_ref().find(|c| *c == ';');
+                }
+                ArgumentType::Object
+            }
+            _ => ArgumentType::Int,
+        };
+        types.push((ty, slots));
+        slots += if ty == ArgumentType::Long || ty == ArgumentType::Double {
+            2
+        } else {
+            1
+        };
+    }
+
+    // ``makeConcat`` has no recipe and concatenates all the arguments
+    let recipe = match (name, args.first()) {
+        (_, Some(BootstrapArgument::String(recipe))) => recipe.clone(),
+        ("makeConcat", None) => TAG_ARG.to_string().repeat(types.len()),
+        _ => return Err("invalid arguments for StringConcatFactory".to_string()),
+    };
+    let mut constants = args.iter().skip(1);
+    let mut types = types.into_iter();
+
+    let mut parts = vec![];
+    let mut literal = String::new();
+    for c in recipe.chars() {
+        match c {
+            TAG_ARG => {
+                let (ty, slot) = types
+                    .next()
+                    .ok_or_else(|| format!("mismatched number of concat arguments: {}", recipe))?;
+                if !literal.is_empty() {
+                    parts.push(ConcatPart::Literal(literal.split_off(0)));
+                }
+                parts.push(ConcatPart::Argument(ty, slot));
+            }
+            TAG_CONST => match constants.next() {
+                Some(BootstrapArgument::String(s)) => literal.push_str(s),
+                Some(BootstrapArgument::Int(i)) => literal.push_str(format!("{}", i).as_str()),
+                Some(BootstrapArgument::Long(l)) => literal.push_str(format!("{}", l).as_str()),
+                Some(BootstrapArgument::Float(f)) => {
+                    literal.push_str(number::float_to_string(*f).as_str())
+                }
+                Some(BootstrapArgument::Double(d)) => {
+                    literal.push_str(number::double_to_string(*d).as_str())
+                }
+                _ => return Err(format!("missing concat constant: {}", recipe)),
+            },
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(ConcatPart::Literal(literal));
+    }
+
+    Ok(CallSite::Concat { parts, slots })
+}
+
+/// Concatenates the arguments laid out as slots of the operand stack into UTF-16 code units.
+/// Returns None if ``toString`` of an argument throws an exception.
+pub fn concat(vm: &mut VM, parts: &[ConcatPart], args: &[u64]) -> Option<Vec<u16>> {
+    let mut chars = vec![];
+    for part in parts {
+        let arg = match part {
+            ConcatPart::Literal(literal) => {
+                chars.extend(literal.encode_utf16());
+                continue;
+            }
+            ConcatPart::Argument(ty, slot) => (*ty, args[*slot]),
+        };
+        let string = match arg {
+            (ArgumentType::Int, arg) => format!("{}", arg as i32),
+            (ArgumentType::Long, arg) => format!("{}", arg as i64),
+            (ArgumentType::Float, arg) => number::float_to_string(u2f(arg)),
+            (ArgumentType::Double, arg) => number::double_to_string(u2d(arg)),
+            (ArgumentType::Boolean, arg) => (if arg != 0 { "true" } else { "false" }).to_string(),
+            // A lone surrogate is kept as it is, so that it can pair with the next argument
+            (ArgumentType::Char, arg) => {
+                chars.push(arg as u16);
+                continue;
+            }
+            (ArgumentType::String, arg) | (ArgumentType::Object, arg) => {
+                chars.extend_from_slice(object_to_chars(vm, arg)?.as_slice());
+                continue;
+            }
+        };
+        chars.extend(string.encode_utf16());
+    }
+    Some(chars)
+}
+
+/// Runs a concatenation with its arguments on top of the operand stack
+pub fn invoke(vm: &mut VM, parts: &[ConcatPart], slots: usize) {
+    let sp = vm.frame_stack.last().unwrap().sp - slots;
+    let args = vm.stack[vm.bp + sp..vm.bp + sp + slots].to_vec();
+    let chars = match concat(vm, parts, &args) {
+        Some(chars) => chars,
+        None => return,
+    };
+
+    let string = unsafe { (*vm.objectheap).create_string_object_from_chars(&chars, vm.classheap) };
+    vm.stack[vm.bp + sp] = string;
+    vm.frame_stack.last_mut().unwrap().sp = sp + 1;
+}
+
+/// Converts an object to a string as ``String.valueOf(Object)`` does. Returns None if
+/// ``toString`` throws an exception.
+pub fn object_to_string(vm: &mut VM, object: u64) -> Option<String> {
+    object_to_chars(vm, object).map(|chars| String::from_utf16_lossy(&chars))
+}
+
+/// Like ``object_to_string``, but returns the UTF-16 code units, which may be unpaired surrogates
+pub fn object_to_chars(vm: &mut VM, object: u64) -> Option<Vec<u16>> {
+    if object == 0 {
+        return Some("null".encode_utf16().collect());
+    }
+
+    let string = if is_string(vm, object) {
+        object
+    } else {
+        // Arrays have only the methods of ``Object``
+        let class = if unsafe { &*vm.objectheap }.gc.is_array(object) {
+            vm.load_class("java/lang/Object")
+        } else {
+            unsafe { &*(object as GcType<ObjectBody>) }.class
+        };
+        let (class, method) = unsafe { &*class }
+            .get_method("toString", "()Ljava/lang/String;")
+            .unwrap();
+        let string = vm.invoke_method(class, method, &[object]);
+        if vm.exception.is_some() {
+            return None;
+        }
+        if string == 0 {
+            return Some("null".encode_utf16().collect());
+        }
+        string
+    };
+    Some(
+        unsafe { &*(string as GcType<ObjectBody>) }
+            .get_string_chars()
+            .to_vec(),
+    )
+}
+
+fn is_string(vm: &VM, object: u64) -> bool {
+    !unsafe { &*vm.objectheap }.gc.is_array(object)
+        && unsafe { &*(*(object as GcType<ObjectBody>)).class }
+            .get_name()
+            .is_some_and(|name| name == "java/lang/String")
+}
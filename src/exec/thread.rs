@@ -0,0 +1,428 @@
+use super::super::class::class::Class;
+use super::super::gc::gc::GcType;
+use super::frame::{Frame, ObjectBody};
+use super::vm::VM;
+use rustc_hash::FxHashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const MAIN_THREAD_ID: usize = 0;
+
+// Java threads run on OS threads, but only the thread holding the global VM lock executes
+// bytecode. Neither the heaps nor the JIT are thread-safe, so the lock is handed over at safepoints
+// (backward branches) and whenever a thread blocks (sleep, join, monitors and Object.wait).
+#[derive(Debug)]
+pub struct ThreadManager {
+    lock: Mutex<LockState>,
+    cond: Condvar,
+    waiting: AtomicUsize,
+    next_id: usize,
+    // The fields below are protected by the global VM lock.
+    pub threads: FxHashMap<usize, JavaThread>,
+    pub monitors: FxHashMap<u64, Monitor>,
+    // The native threads started, which touch the lock even after their Java threads die
+    handles: Vec<thread::JoinHandle<()>>,
+}
+
+#[derive(Debug)]
+struct LockState {
+    owner: Option<usize>,
+    generation: u64,
+}
+
+#[derive(Debug)]
+pub struct JavaThread {
+    pub vm: GcType<VM>,
+    pub object: u64,
+    pub alive: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    pub owner: Option<usize>,
+    pub count: usize,
+    pub wait_set: Vec<usize>,
+}
+
+struct SendPtr<T>(*mut T);
+
+unsafe impl<T> Send for SendPtr<T> {}
+
+impl Default for ThreadManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ThreadManager {
+    pub fn new() -> Self {
+        let mut threads = FxHashMap::default();
+        threads.insert(
+            MAIN_THREAD_ID,
+            JavaThread {
+                vm: 0 as GcType<VM>,
+                object: 0,
+                alive: true,
+            },
+        );
+        ThreadManager {
+            // The main thread owns the lock from the beginning.
+            lock: Mutex::new(LockState {
+                owner: Some(MAIN_THREAD_ID),
+                generation: 0,
+            }),
+            cond: Condvar::new(),
+            waiting: AtomicUsize::new(0),
+            next_id: MAIN_THREAD_ID + 1,
+            threads,
+            monitors: FxHashMap::default(),
+            handles: vec![],
+        }
+    }
+
+    pub fn acquire(&self, tid: usize) {
+        let mut state = self.lock.lock().unwrap();
+        self.waiting.fetch_add(1, Ordering::SeqCst);
+        while state.owner.is_some() {
+            state = self.cond.wait(state).unwrap();
+        }
+        self.waiting.fetch_sub(1, Ordering::SeqCst);
+        state.owner = Some(tid);
+    }
+
+    pub fn release(&self) {
+        let mut state = self.lock.lock().unwrap();
+        state.owner = None;
+        state.generation += 1;
+        self.cond.notify_all();
+    }
+
+    /// Gives up the lock and sleeps until another thread has run or ``deadline`` has passed.
+    /// Callers must check again whatever they were waiting for.
+    pub fn block(&self, tid: usize, deadline: Option<Instant>) {
+        let mut state = self.lock.lock().unwrap();
+        state.owner = None;
+        state.generation += 1;
+        let generation = state.generation;
+        self.cond.notify_all();
+
+        self.waiting.fetch_add(1, Ordering::SeqCst);
+        loop {
+            let timed_out = deadline.is_some_and(|d| Instant::now() >= d);
+            if state.owner.is_none() && (state.generation != generation || timed_out) {
+                break;
+            }
+            state = match deadline {
+                Some(d) if !timed_out => {
+                    self.cond.wait_timeout(state, d - Instant::now()).unwrap().0
+                }
+                _ => self.cond.wait(state).unwrap(),
+            };
+        }
+        self.waiting.fetch_sub(1, Ordering::SeqCst);
+        state.owner = Some(tid);
+    }
+
+    /// Called at safepoints. Hands the lock over only if some other thread wants it.
+    pub fn yield_now(&self, tid: usize) {
+        if self.waiting.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        self.block(tid, None)
+    }
+
+    pub fn sleep(&self, tid: usize, duration: Duration) {
+        self.release();
+        thread::sleep(duration);
+        self.acquire(tid);
+    }
+
+    pub fn register(&mut self, tid: usize, vm: GcType<VM>, object: u64) {
+        self.threads.insert(
+            tid,
+            JavaThread {
+                vm,
+                object,
+                alive: true,
+            },
+        );
+    }
+
+    pub fn new_thread_id(&mut self) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    pub fn find_thread_by_object(&self, object: u64) -> Option<usize> {
+        self.threads
+            .iter()
+            .find(|(_, thread)| thread.object == object)
+            .map(|(id, _)| *id)
+    }
+
+    pub fn is_alive(&self, tid: usize) -> bool {
+        self.threads.get(&tid).is_some_and(|thread| thread.alive)
+    }
+}
+
+// Monitors
+
+impl ThreadManager {
+    pub fn monitor_enter(&mut self, tid: usize, object: u64) {
+        loop {
+            {
+                let monitor = self.monitors.entry(object).or_insert(Monitor {
+                    owner: None,
+                    count: 0,
+                    wait_set: vec![],
+                });
+                match monitor.owner {
+                    None => {
+                        monitor.owner = Some(tid);
+                        monitor.count = 1;
+                        return;
+                    }
+                    Some(owner) if owner == tid => {
+                        monitor.count += 1;
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+            self.block(tid, None);
+        }
+    }
+
+    /// Returns false if ``tid`` doesn't own the monitor.
+    pub fn monitor_exit(&mut self, tid: usize, object: u64) -> bool {
+        let remove = match self.monitors.get_mut(&object) {
+            Some(monitor) if monitor.owner == Some(tid) => {
+                monitor.count -= 1;
+                if monitor.count == 0 {
+                    monitor.owner = None;
+                }
+                monitor.owner.is_none() && monitor.wait_set.is_empty()
+            }
+            _ => return false,
+        };
+        if remove {
+            self.monitors.remove(&object);
+        }
+        true
+    }
+
+    /// Implements ``Object.wait``. Returns false if ``tid`` doesn't own the monitor.
+    pub fn wait(&mut self, tid: usize, object: u64, timeout: Option<Duration>) -> bool {
+        let count = match self.monitors.get_mut(&object) {
+            Some(monitor) if monitor.owner == Some(tid) => {
+                let count = monitor.count;
+                monitor.owner = None;
+                monitor.count = 0;
+                monitor.wait_set.push(tid);
+                count
+            }
+            _ => return false,
+        };
+
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let notified = self
+                .monitors
+                .get(&object)
+                .is_none_or(|monitor| !monitor.wait_set.contains(&tid));
+            let timed_out = deadline.is_some_and(|d| Instant::now() >= d);
+            if notified || timed_out {
+                break;
+            }
+            self.block(tid, deadline);
+        }
+
+        if let Some(monitor) = self.monitors.get_mut(&object) {
+            monitor.wait_set.retain(|waiter| *waiter != tid);
+        }
+        self.monitor_enter(tid, object);
+        self.monitors.get_mut(&object).unwrap().count = count;
+        true
+    }
+
+    /// Implements ``Object.notify`` and ``Object.notifyAll``. Returns false if ``tid`` doesn't own
+    /// the monitor.
+    pub fn notify(&mut self, tid: usize, object: u64, all: bool) -> bool {
+        match self.monitors.get_mut(&object) {
+            Some(monitor) if monitor.owner == Some(tid) => {
+                if all {
+                    monitor.wait_set.clear();
+                } else if !monitor.wait_set.is_empty() {
+                    monitor.wait_set.remove(0);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+// java.lang.Thread
+
+impl VM {
+    pub fn start_thread(&mut self, thread_obj: u64) {
+        let thread_manager = unsafe { &mut *self.thread_manager };
+
+        if thread_manager.find_thread_by_object(thread_obj).is_some() {
+            self.throw_exception("java/lang/IllegalThreadStateException", None);
+            return;
+        }
+
+        // The thread starting another thread must be visible to the GC from now on.
+        let self_object = thread_manager.threads[&self.thread_id].object;
+        thread_manager.register(self.thread_id, self as *mut VM, self_object);
+
+        let class = unsafe { &*(thread_obj as GcType<ObjectBody>) }.class;
+        let (class, method) = unsafe { &*class }.get_method("run", "()V").unwrap();
+
+        let tid = thread_manager.new_thread_id();
+        let mut vm = VM {
+            classheap: self.classheap,
+            objectheap: self.objectheap,
+            runtime_env: self.runtime_env,
+            thread_manager: self.thread_manager,
+            thread_id: tid,
+            exception: None,
+            frame_stack: vec![Frame::new()],
+            stack: unsafe { &*self.runtime_env }.new_operand_stack(),
+            bp: 0,
+            jni_local_frames: vec![],
+        };
+        vm.stack[0] = thread_obj;
+        vm.frame_stack[0].class = Some(class);
+        vm.frame_stack[0].sp = method.code.as_ref().unwrap().max_locals as usize;
+        vm.frame_stack[0].method_info = method;
+
+        let vm = Box::into_raw(Box::new(vm));
+        thread_manager.register(tid, vm, thread_obj);
+
+        let vm = SendPtr(vm);
+        let mut builder = thread::Builder::new();
+        if let Some(stack_size) = unsafe { &*self.runtime_env }.stack_size {
+            builder = builder.stack_size(stack_size);
+        }
+        let handle = builder
+            .spawn(move || {
+                let vm = vm;
+                let vm = unsafe { &mut *vm.0 };
+                let thread_manager = unsafe { &mut *vm.thread_manager };
+
+                thread_manager.acquire(vm.thread_id);
+                vm.run();
+                if vm.exception.is_some() {
+                    let name = vm.get_thread_name(thread_obj);
+                    vm.report_uncaught_exception(name.as_str());
+                }
+
+                let thread = thread_manager.threads.get_mut(&vm.thread_id).unwrap();
+                thread.alive = false;
+                thread.vm = 0 as GcType<VM>;
+                drop(unsafe { Box::from_raw(vm as *mut VM) });
+                thread_manager.release();
+            })
+            .expect("Could not create a native thread");
+        thread_manager.handles.push(handle);
+    }
+
+    pub fn join_thread(&mut self, thread_obj: u64, timeout: Option<Duration>) {
+        let thread_manager = unsafe { &mut *self.thread_manager };
+        let tid = match thread_manager.find_thread_by_object(thread_obj) {
+            Some(tid) => tid,
+            None => return,
+        };
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        while thread_manager.is_alive(tid) {
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                break;
+            }
+            thread_manager.block(self.thread_id, deadline);
+        }
+    }
+
+    /// Waits for all the other threads to terminate. Called when ``main`` returns.
+    pub fn join_all_threads(&mut self) {
+        let thread_manager = unsafe { &mut *self.thread_manager };
+        let tid = self.thread_id;
+        while thread_manager
+            .threads
+            .iter()
+            .any(|(id, thread)| *id != tid && thread.alive)
+        {
+            thread_manager.block(tid, None);
+        }
+        for handle in thread_manager.handles.drain(..) {
+            handle.join().ok();
+        }
+    }
+
+    pub fn get_current_thread_object(&mut self) -> u64 {
+        let object = unsafe { &*self.thread_manager }.threads[&self.thread_id].object;
+        if object != 0 {
+            return object;
+        }
+
+        // The main thread's ``Thread`` object is created on demand.
+        let class = self.load_class("java/lang/Thread");
+        let objectheap = unsafe { &mut *self.objectheap };
+        let object = unsafe { objectheap.create_object(class) };
+        let name = unsafe { objectheap.create_string_object("main".to_string(), self.classheap) };
+        set_field(class, object, "name", name);
+
+        let thread_manager = unsafe { &mut *self.thread_manager };
+        let vm = thread_manager.threads[&self.thread_id].vm;
+        thread_manager.register(self.thread_id, vm, object);
+        object
+    }
+
+    pub fn get_thread_name(&self, thread_obj: u64) -> String {
+        let class = unsafe { &*(thread_obj as GcType<ObjectBody>) }.class;
+        let (id, _) = *unsafe { &*class }.get_numbered_field_info("name").unwrap();
+        let name = unsafe { &*(thread_obj as GcType<ObjectBody>) }.variables[id];
+        unsafe { &*(name as GcType<ObjectBody>) }
+            .get_string()
+    }
+
+    pub fn monitor_enter(&mut self, object: u64) {
+        unsafe { &mut *self.thread_manager }.monitor_enter(self.thread_id, object)
+    }
+
+    pub fn monitor_exit(&mut self, object: u64) {
+        if !unsafe { &mut *self.thread_manager }.monitor_exit(self.thread_id, object) {
+            self.throw_exception(
+                "java/lang/IllegalMonitorStateException",
+                Some("current thread is not owner"),
+            );
+        }
+    }
+
+    pub fn monitor_wait(&mut self, object: u64, timeout: Option<Duration>) {
+        if !unsafe { &mut *self.thread_manager }.wait(self.thread_id, object, timeout) {
+            self.throw_exception(
+                "java/lang/IllegalMonitorStateException",
+                Some("current thread is not owner"),
+            );
+        }
+    }
+
+    pub fn monitor_notify(&mut self, object: u64, all: bool) {
+        if !unsafe { &mut *self.thread_manager }.notify(self.thread_id, object, all) {
+            self.throw_exception(
+                "java/lang/IllegalMonitorStateException",
+                Some("current thread is not owner"),
+            );
+        }
+    }
+}
+
+fn set_field(class: GcType<Class>, object: u64, name: &str, val: u64) {
+    let (id, _) = *unsafe { &*class }.get_numbered_field_info(name).unwrap();
+    unsafe { &mut *(object as GcType<ObjectBody>) }.variables[id] = val;
+}
@@ -3,14 +3,21 @@ use super::super::class::classfile::constant::Constant;
 use super::super::class::classfile::{method, method::MethodInfo};
 use super::super::class::classheap::ClassHeap;
 use super::super::gc::gc::GcType;
+use super::call_site;
 use super::cfg::CFGMaker;
 use super::frame::{AType, Array, Frame, ObjectBody, VariableType};
+use super::jni::{self, JniState};
+use super::method_handle;
+use super::mirror;
 use super::native_functions;
+use super::native_registry::{self, NativeRegistry};
 use super::objectheap::ObjectHeap;
+use super::thread::{ThreadManager, MAIN_THREAD_ID};
 use super::{jit, jit::JIT};
 use ansi_term::Colour;
 use rustc_hash::FxHashMap;
-use std::mem::transmute;
+use std::mem;
+use std::path::Path;
 
 #[macro_export]
 macro_rules! fld { ($a:path, $b:expr, $( $arg:ident ),*) => {{
@@ -32,10 +39,54 @@ macro_rules! expect {
     }};
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct RuntimeEnvironment {
     pub classheap: GcType<ClassHeap>,
     pub objectheap: GcType<ObjectHeap>,
+    pub thread_manager: GcType<ThreadManager>,
+    // Directories searched for class files in order
+    pub classpath: Vec<String>,
+    // Directories searched for native libraries by ``System.loadLibrary``
+    pub library_path: Vec<String>,
+    pub natives: NativeRegistry,
+    pub jni: JniState,
+    // Returned by ``System.getProperty``
+    pub system_properties: FxHashMap<String, String>,
+    // The size in bytes of the stack of each thread. ``None`` uses the default.
+    pub stack_size: Option<usize>,
+    pub jit_mode: jit::JitMode,
+    pub verbose: Vec<Verbose>,
+    // Interned ``MethodType`` objects keyed by their descriptors
+    pub method_types: FxHashMap<String, u64>,
+    // Shared by all the threads, which use it only while holding the VM lock. ``None`` until the
+    // first VM is created.
+    pub jit: Option<JIT>,
+}
+
+/// Events reported to the standard output, like ``-verbose`` of ``java``
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verbose {
+    Class,
+    Gc,
+    Jit,
+}
+
+impl RuntimeEnvironment {
+    pub fn is_verbose(&self, kind: Verbose) -> bool {
+        self.verbose.contains(&kind)
+    }
+
+    /// Creates the operand stack of a thread, 1024 slots unless ``stack_size`` is given.
+    pub fn new_operand_stack(&self) -> Vec<u64> {
+        let len = self
+            .stack_size
+            .map_or(1024, |size| size / mem::size_of::<u64>());
+        vec![0; len]
+    }
+
+    pub fn jit(&mut self) -> &mut JIT {
+        self.jit.as_mut().unwrap()
+    }
 }
 
 #[derive(Debug)]
@@ -43,36 +94,67 @@ pub struct VM {
     pub classheap: GcType<ClassHeap>,
     pub objectheap: GcType<ObjectHeap>,
     pub runtime_env: GcType<RuntimeEnvironment>,
+    pub thread_manager: GcType<ThreadManager>,
+    pub thread_id: usize,
+    // A thrown exception not caught yet
+    pub exception: Option<u64>,
     pub frame_stack: Vec<Frame>,
     pub stack: Vec<u64>,
     pub bp: usize,
-    pub jit: JIT,
+    // Local references created by JNI functions in the running native methods
+    pub jni_local_frames: Vec<Vec<u64>>,
 }
 
 impl VM {
-    pub fn new(classheap: GcType<ClassHeap>, objectheap: GcType<ObjectHeap>) -> Self {
+    /// # Safety
+    ///
+    /// ``classheap`` and ``objectheap`` must point to valid heaps that outlive the VM.
+    pub unsafe fn new(classheap: GcType<ClassHeap>, objectheap: GcType<ObjectHeap>) -> Self {
+        let thread_manager = unsafe { &mut *objectheap }.gc.alloc(ThreadManager::new());
         let runtime_env = unsafe { &mut *objectheap }.gc.alloc(RuntimeEnvironment {
             objectheap,
             classheap,
+            thread_manager,
+            classpath: vec!["./examples".to_string()],
+            library_path: vec![],
+            natives: {
+                let mut natives = NativeRegistry::new();
+                native_functions::register_builtin_natives(&mut natives);
+                natives
+            },
+            jni: JniState::default(),
+            system_properties: FxHashMap::default(),
+            stack_size: None,
+            jit_mode: jit::JitMode::Mixed,
+            verbose: vec![],
+            method_types: FxHashMap::default(),
+            jit: None,
         });
+        unsafe { &mut *runtime_env }.jit = Some(unsafe { JIT::new(runtime_env) });
         VM {
             classheap,
             objectheap,
             runtime_env,
+            thread_manager,
+            thread_id: MAIN_THREAD_ID,
+            exception: None,
             frame_stack: {
                 let mut frame_stack = Vec::with_capacity(128);
                 frame_stack.push(Frame::new());
                 frame_stack
             },
-            stack: vec![0; 1024],
+            stack: unsafe { &*runtime_env }.new_operand_stack(),
             bp: 0,
-            jit: unsafe { JIT::new(runtime_env) },
+            jni_local_frames: vec![],
         }
     }
 }
 
 impl VM {
     pub fn run(&mut self) -> Inst::Code {
+        // JIT-compiled code calls native methods with the VM
+        native_registry::set_current_vm(self);
+
         let frame_stack_len = self.frame_stack.len();
         let frame = &mut self.frame_stack[frame_stack_len - 1];
 
@@ -98,9 +180,13 @@ impl VM {
                     continue;
                 }
 
+                // Backward branches are safepoints where other threads may run
+                unsafe { &*self.thread_manager }.yield_now(self.thread_id);
+
                 jit_info_mgr.inc_count_of_loop_exec($start, $end);
 
-                let can_jit = jit_info_mgr.loop_executed_enough_times($start);
+                let jit_mode = unsafe { &*self.runtime_env }.jit_mode;
+                let can_jit = jit_info_mgr.loop_executed_enough_times($start, jit_mode);
                 if !can_jit {
                     $failed;
                     continue;
@@ -116,14 +202,26 @@ impl VM {
                         exec_info.clone()
                     }
                     none => unsafe {
-                        let mut blocks = CFGMaker::new().make(&code, $start, $end);
                         let class = $frame.class.unwrap();
-                        match self.jit.compile_loop(class, &mut blocks) {
-                            Ok(exec_info) => {
+                        let compiled = match CFGMaker::new().make(&code, $start, $end) {
+                            Some(mut blocks) => (*self.runtime_env)
+                                .jit()
+                                .compile_loop(class, &mut blocks)
+                                .ok(),
+                            None => None,
+                        };
+                        match compiled {
+                            Some(exec_info) => {
+                                report_jit_compiled(
+                                    &*self.runtime_env,
+                                    &*class,
+                                    &$frame.method_info,
+                                    Some($start),
+                                );
                                 *none = Some(exec_info.clone());
                                 exec_info
                             }
-                            Err(_) => {
+                            None => {
                                 *none = Some(jit::LoopJITExecInfo {
                                     local_variables: FxHashMap::default(),
                                     func: 0,
@@ -136,19 +234,34 @@ impl VM {
                     },
                 };
 
+                let gc = &mut unsafe { &mut *self.objectheap }.gc;
+                gc.enter_compiled_code();
                 $frame.pc = unsafe {
-                    self.jit
+                    (*self.runtime_env)
+                        .jit()
                         .run_loop(&mut self.stack, self.bp, &exec_info)
                         .unwrap()
                 };
+                gc.leave_compiled_code();
+
+                // The compiled loop exits at the instruction that threw an exception
+                if self.exception.is_some() {
+                    let pc = $frame.pc;
+                    if !self.catch_exception(pc) {
+                        return Inst::athrow;
+                    }
+                    continue;
+                }
             };
         }
 
         loop {
             let frame = &mut self.frame_stack[frame_stack_len - 1];
-            let cur_code = code[frame.pc as usize];
+            let cur_pc = frame.pc;
+            let cur_code = code[frame.pc];
 
             match cur_code {
+                Inst::nop => frame.pc += 1,
                 Inst::aconst_null => {
                     self.stack[self.bp + frame.sp] = 0;
                     frame.sp += 1;
@@ -166,25 +279,47 @@ impl VM {
                     frame.sp += 1;
                     frame.pc += 1;
                 }
+                Inst::lconst_0 | Inst::lconst_1 => {
+                    self.stack[self.bp + frame.sp] = (cur_code - Inst::lconst_0) as u64;
+                    frame.sp += 2;
+                    frame.pc += 1;
+                }
+                Inst::fconst_0 | Inst::fconst_1 | Inst::fconst_2 => {
+                    self.stack[self.bp + frame.sp] = f2u((cur_code - Inst::fconst_0) as f32);
+                    frame.sp += 1;
+                    frame.pc += 1;
+                }
                 Inst::dconst_0 | Inst::dconst_1 => {
                     self.stack[self.bp + frame.sp] = d2u(cur_code as f64 - Inst::dconst_0 as f64);
                     frame.sp += 2;
                     frame.pc += 1;
                 }
                 Inst::dstore => {
-                    let index = code[frame.pc as usize + 1] as usize;
+                    let index = code[frame.pc + 1] as usize;
+                    self.stack[self.bp + index] = self.stack[self.bp + frame.sp - 2];
+                    frame.sp -= 2;
+                    frame.pc += 2;
+                }
+                Inst::lstore => {
+                    let index = code[frame.pc + 1] as usize;
                     self.stack[self.bp + index] = self.stack[self.bp + frame.sp - 2];
                     frame.sp -= 2;
                     frame.pc += 2;
                 }
+                Inst::fstore => {
+                    let index = code[frame.pc + 1] as usize;
+                    self.stack[self.bp + index] = self.stack[self.bp + frame.sp - 1];
+                    frame.sp -= 1;
+                    frame.pc += 2;
+                }
                 Inst::astore => {
-                    let index = code[frame.pc as usize + 1] as usize;
+                    let index = code[frame.pc + 1] as usize;
                     self.stack[self.bp + index] = self.stack[self.bp + frame.sp - 1];
                     frame.sp -= 1;
                     frame.pc += 2;
                 }
                 Inst::istore => {
-                    let index = code[frame.pc as usize + 1] as usize;
+                    let index = code[frame.pc + 1] as usize;
                     self.stack[self.bp + index] = self.stack[self.bp + frame.sp - 1];
                     frame.sp -= 1;
                     frame.pc += 2;
@@ -201,69 +336,68 @@ impl VM {
                     frame.sp += 1;
                     frame.pc += 1;
                 }
-                Inst::dload_0 | Inst::dload_1 | Inst::dload_2 | Inst::dload_3 => {
+                Inst::lload_0 | Inst::lload_1 | Inst::lload_2 | Inst::lload_3 => {
                     self.stack[self.bp + frame.sp] =
-                        self.stack[self.bp + cur_code as usize - Inst::dload_0 as usize];
+                        self.stack[self.bp + cur_code as usize - Inst::lload_0 as usize];
                     frame.sp += 2;
                     frame.pc += 1;
                 }
-                Inst::baload => {
-                    let arrayref = self.stack[self.bp + frame.sp - 2] as GcType<Array>;
-                    let index = self.stack[self.bp + frame.sp - 1] as isize;
-                    self.stack[self.bp + frame.sp - 2] = unsafe { &*arrayref }.at::<u8>(index);
-                    frame.sp -= 1;
-                    frame.pc += 1;
-                }
-                Inst::iaload => {
-                    let arrayref = self.stack[self.bp + frame.sp - 2] as GcType<Array>;
-                    let index = self.stack[self.bp + frame.sp - 1] as isize;
-                    self.stack[self.bp + frame.sp - 2] = unsafe { &*arrayref }.at::<u32>(index);
-                    frame.sp -= 1;
-                    frame.pc += 1;
-                }
-                Inst::aaload => {
-                    let arrayref = self.stack[self.bp + frame.sp - 2] as GcType<Array>;
-                    let index = self.stack[self.bp + frame.sp - 1] as isize;
-                    self.stack[self.bp + frame.sp - 2] = unsafe { &*arrayref }.at::<u64>(index);
-                    frame.sp -= 1;
+                Inst::fload_0 | Inst::fload_1 | Inst::fload_2 | Inst::fload_3 => {
+                    self.stack[self.bp + frame.sp] =
+                        self.stack[self.bp + cur_code as usize - Inst::fload_0 as usize];
+                    frame.sp += 1;
                     frame.pc += 1;
                 }
-                Inst::daload => {
-                    let arrayref = self.stack[self.bp + frame.sp - 2] as GcType<Array>;
-                    let index = self.stack[self.bp + frame.sp - 1] as isize;
-                    self.stack[self.bp + frame.sp - 2] = unsafe { &*arrayref }.at::<u64>(index);
+                Inst::dload_0 | Inst::dload_1 | Inst::dload_2 | Inst::dload_3 => {
+                    self.stack[self.bp + frame.sp] =
+                        self.stack[self.bp + cur_code as usize - Inst::dload_0 as usize];
+                    frame.sp += 2;
                     frame.pc += 1;
                 }
+                Inst::baload
+                | Inst::caload
+                | Inst::saload
+                | Inst::iaload
+                | Inst::aaload
+                | Inst::laload
+                | Inst::faload
+                | Inst::daload => self.run_array_load(cur_code),
                 Inst::sipush => {
                     let val = ((code[frame.pc + 1] as i16) << 8) + code[frame.pc + 2] as i16;
                     self.stack[self.bp + frame.sp] = val as u64;
                     frame.sp += 1;
                     frame.pc += 3;
                 }
-                Inst::ldc => {
-                    let index = code[frame.pc + 1] as usize;
-                    let val = match unsafe { &*frame.class.unwrap() }.classfile.constant_pool[index]
-                    {
+                Inst::ldc | Inst::ldc_w => {
+                    let index = if cur_code == Inst::ldc_w {
+                        ((code[frame.pc + 1] as usize) << 8) + code[frame.pc + 2] as usize
+                    } else {
+                        code[frame.pc + 1] as usize
+                    };
+                    let class = frame.class.unwrap();
+                    let val = match unsafe { &*class }.classfile.constant_pool[index] {
                         Constant::IntegerInfo { i } => i as u64,
-                        Constant::FloatInfo { f } => unsafe { transmute::<f32, u32>(f) as u64 },
-                        Constant::String { string_index } => unsafe { &mut *frame.class.unwrap() }
-                            .get_java_string_utf8_from_const_pool(
+                        Constant::FloatInfo { f } => f2u(f),
+                        Constant::String { string_index } => unsafe {
+                            (*class).get_java_string_utf8_from_const_pool(
                                 self.objectheap,
                                 string_index as usize,
                             )
-                            .unwrap()
-                            as u64,
-                        _ => unimplemented!(),
+                        }
+                        .unwrap(),
+                        _ => self.load_constant(class, index),
                     };
+                    let frame = &mut self.frame_stack[frame_stack_len - 1];
                     self.stack[self.bp + frame.sp] = val;
                     frame.sp += 1;
-                    frame.pc += 2;
+                    frame.pc += if cur_code == Inst::ldc_w { 3 } else { 2 };
                 }
                 Inst::ldc2_w => {
                     let index = ((code[frame.pc + 1] as usize) << 8) + code[frame.pc + 2] as usize;
                     let val = match unsafe { &*frame.class.unwrap() }.classfile.constant_pool[index]
                     {
-                        Constant::DoubleInfo { f } => unsafe { transmute::<f64, u64>(f) },
+                        Constant::DoubleInfo { f } => f.to_bits(),
+                        Constant::LongInfo { i } => i as u64,
                         _ => unimplemented!(),
                     };
                     self.stack[self.bp + frame.sp] = val;
@@ -282,6 +416,18 @@ impl VM {
                     frame.sp += 2;
                     frame.pc += 2;
                 }
+                Inst::lload => {
+                    let index = code[frame.pc + 1] as usize;
+                    self.stack[self.bp + frame.sp] = self.stack[self.bp + index];
+                    frame.sp += 2;
+                    frame.pc += 2;
+                }
+                Inst::fload => {
+                    let index = code[frame.pc + 1] as usize;
+                    self.stack[self.bp + frame.sp] = self.stack[self.bp + index];
+                    frame.sp += 1;
+                    frame.pc += 2;
+                }
                 Inst::iload => {
                     let index = code[frame.pc + 1] as usize;
                     self.stack[self.bp + frame.sp] = self.stack[self.bp + index];
@@ -300,53 +446,55 @@ impl VM {
                     frame.sp -= 2;
                     frame.pc += 1;
                 }
-                Inst::astore_0 | Inst::astore_1 | Inst::astore_2 | Inst::astore_3 => {
-                    self.stack[self.bp + (cur_code as usize - Inst::astore_0 as usize)] =
-                        self.stack[self.bp + frame.sp - 1];
-                    frame.sp -= 1;
-                    frame.pc += 1;
-                }
-                Inst::bastore => {
-                    let arrayref = self.stack[self.bp + frame.sp - 3] as GcType<Array>;
-                    let index = self.stack[self.bp + frame.sp - 2] as isize;
-                    let value = self.stack[self.bp + frame.sp - 1] as u8;
-                    unsafe { &mut *arrayref }.store(index, value);
-                    frame.sp -= 3;
-                    frame.pc += 1;
-                }
-                Inst::iastore => {
-                    let arrayref = self.stack[self.bp + frame.sp - 3] as GcType<Array>;
-                    let index = self.stack[self.bp + frame.sp - 2] as isize;
-                    let value = self.stack[self.bp + frame.sp - 1] as u32;
-                    unsafe { &mut *arrayref }.store(index, value);
-                    frame.sp -= 3;
+                Inst::lstore_0 | Inst::lstore_1 | Inst::lstore_2 | Inst::lstore_3 => {
+                    self.stack[self.bp + (cur_code as usize - Inst::lstore_0 as usize)] =
+                        self.stack[self.bp + frame.sp - 2];
+                    frame.sp -= 2;
                     frame.pc += 1;
                 }
-                Inst::aastore => {
-                    let arrayref = self.stack[self.bp + frame.sp - 3] as GcType<Array>;
-                    let index = self.stack[self.bp + frame.sp - 2] as isize;
-                    let value = self.stack[self.bp + frame.sp - 1] as u64;
-                    unsafe { &mut *arrayref }.store(index, value);
-                    frame.sp -= 3;
+                Inst::fstore_0 | Inst::fstore_1 | Inst::fstore_2 | Inst::fstore_3 => {
+                    self.stack[self.bp + (cur_code as usize - Inst::fstore_0 as usize)] =
+                        self.stack[self.bp + frame.sp - 1];
+                    frame.sp -= 1;
                     frame.pc += 1;
                 }
-                Inst::dastore => {
-                    let arrayref = self.stack[self.bp + frame.sp - 4] as GcType<Array>;
-                    let index = self.stack[self.bp + frame.sp - 3] as isize;
-                    let value = self.stack[self.bp + frame.sp - 2] as u64;
-                    unsafe { &mut *arrayref }.store(index, value);
-                    frame.sp -= 4;
+                Inst::astore_0 | Inst::astore_1 | Inst::astore_2 | Inst::astore_3 => {
+                    self.stack[self.bp + (cur_code as usize - Inst::astore_0 as usize)] =
+                        self.stack[self.bp + frame.sp - 1];
+                    frame.sp -= 1;
                     frame.pc += 1;
                 }
+                Inst::aastore => self.run_aastore(),
+                Inst::bastore
+                | Inst::castore
+                | Inst::sastore
+                | Inst::iastore
+                | Inst::lastore
+                | Inst::fastore
+                | Inst::dastore => self.run_array_store(cur_code),
                 Inst::bipush => {
-                    self.stack[self.bp + frame.sp] = code[frame.pc + 1] as u64;
+                    self.stack[self.bp + frame.sp] = code[frame.pc + 1] as i8 as u64;
                     frame.sp += 1;
                     frame.pc += 2;
                 }
                 Inst::iadd => {
-                    self.stack[self.bp + frame.sp - 2] = (self.stack[self.bp + frame.sp - 2] as i32
-                        + self.stack[self.bp + frame.sp - 1] as i32)
-                        as u64;
+                    let val2 = self.stack[self.bp + frame.sp - 1] as i32;
+                    let val1 = self.stack[self.bp + frame.sp - 2] as i32;
+                    self.stack[self.bp + frame.sp - 2] = val1.wrapping_add(val2) as u64;
+                    frame.sp -= 1;
+                    frame.pc += 1;
+                }
+                Inst::ladd => {
+                    let val2 = self.stack[self.bp + frame.sp - 2] as i64;
+                    let val1 = self.stack[self.bp + frame.sp - 4] as i64;
+                    self.stack[self.bp + frame.sp - 4] = val1.wrapping_add(val2) as u64;
+                    frame.sp -= 2;
+                    frame.pc += 1;
+                }
+                Inst::fadd => {
+                    self.stack[self.bp + frame.sp - 2] =
+                        f2u(u2f(self.stack[self.bp + frame.sp - 2])
+                            + u2f(self.stack[self.bp + frame.sp - 1]));
                     frame.sp -= 1;
                     frame.pc += 1;
                 }
@@ -358,10 +506,23 @@ impl VM {
                     frame.pc += 1;
                 }
                 Inst::isub => {
-                    self.stack[self.bp + frame.sp - 2] = ((self.stack[self.bp + frame.sp - 2]
-                        as i32)
-                        - (self.stack[self.bp + frame.sp - 1] as i32))
-                        as u64;
+                    let val2 = self.stack[self.bp + frame.sp - 1] as i32;
+                    let val1 = self.stack[self.bp + frame.sp - 2] as i32;
+                    self.stack[self.bp + frame.sp - 2] = val1.wrapping_sub(val2) as u64;
+                    frame.sp -= 1;
+                    frame.pc += 1;
+                }
+                Inst::lsub => {
+                    let val2 = self.stack[self.bp + frame.sp - 2] as i64;
+                    let val1 = self.stack[self.bp + frame.sp - 4] as i64;
+                    self.stack[self.bp + frame.sp - 4] = val1.wrapping_sub(val2) as u64;
+                    frame.sp -= 2;
+                    frame.pc += 1;
+                }
+                Inst::fsub => {
+                    self.stack[self.bp + frame.sp - 2] =
+                        f2u(u2f(self.stack[self.bp + frame.sp - 2])
+                            - u2f(self.stack[self.bp + frame.sp - 1]));
                     frame.sp -= 1;
                     frame.pc += 1;
                 }
@@ -373,18 +534,45 @@ impl VM {
                     frame.pc += 1;
                 }
                 Inst::imul => {
-                    self.stack[self.bp + frame.sp - 2] = ((self.stack[self.bp + frame.sp - 2]
-                        as i32)
-                        * (self.stack[self.bp + frame.sp - 1] as i32))
-                        as u64;
+                    let val2 = self.stack[self.bp + frame.sp - 1] as i32;
+                    let val1 = self.stack[self.bp + frame.sp - 2] as i32;
+                    self.stack[self.bp + frame.sp - 2] = val1.wrapping_mul(val2) as u64;
                     frame.sp -= 1;
                     frame.pc += 1;
                 }
+                Inst::lmul => {
+                    let val2 = self.stack[self.bp + frame.sp - 2] as i64;
+                    let val1 = self.stack[self.bp + frame.sp - 4] as i64;
+                    self.stack[self.bp + frame.sp - 4] = val1.wrapping_mul(val2) as u64;
+                    frame.sp -= 2;
+                    frame.pc += 1;
+                }
                 Inst::idiv => {
-                    self.stack[self.bp + frame.sp - 2] = ((self.stack[self.bp + frame.sp - 2]
-                        as i32)
-                        / (self.stack[self.bp + frame.sp - 1] as i32))
-                        as u64;
+                    let val2 = self.stack[self.bp + frame.sp - 1] as i32;
+                    let val1 = self.stack[self.bp + frame.sp - 2] as i32;
+                    if val2 == 0 {
+                        self.throw_exception("java/lang/ArithmeticException", Some("/ by zero"));
+                    } else {
+                        self.stack[self.bp + frame.sp - 2] = val1.wrapping_div(val2) as u64;
+                        frame.sp -= 1;
+                        frame.pc += 1;
+                    }
+                }
+                Inst::ldiv => {
+                    let val2 = self.stack[self.bp + frame.sp - 2] as i64;
+                    let val1 = self.stack[self.bp + frame.sp - 4] as i64;
+                    if val2 == 0 {
+                        self.throw_exception("java/lang/ArithmeticException", Some("/ by zero"));
+                    } else {
+                        self.stack[self.bp + frame.sp - 4] = val1.wrapping_div(val2) as u64;
+                        frame.sp -= 2;
+                        frame.pc += 1;
+                    }
+                }
+                Inst::fmul => {
+                    self.stack[self.bp + frame.sp - 2] =
+                        f2u(u2f(self.stack[self.bp + frame.sp - 2])
+                            * u2f(self.stack[self.bp + frame.sp - 1]));
                     frame.sp -= 1;
                     frame.pc += 1;
                 }
@@ -395,6 +583,13 @@ impl VM {
                     frame.sp -= 2;
                     frame.pc += 1;
                 }
+                Inst::fdiv => {
+                    self.stack[self.bp + frame.sp - 2] =
+                        f2u(u2f(self.stack[self.bp + frame.sp - 2])
+                            / u2f(self.stack[self.bp + frame.sp - 1]));
+                    frame.sp -= 1;
+                    frame.pc += 1;
+                }
                 Inst::ddiv => {
                     self.stack[self.bp + frame.sp - 4] =
                         d2u(u2d(self.stack[self.bp + frame.sp - 4])
@@ -403,29 +598,100 @@ impl VM {
                     frame.pc += 1;
                 }
                 Inst::irem => {
-                    self.stack[self.bp + frame.sp - 2] = (self.stack[self.bp + frame.sp - 2] as i32
-                        % self.stack[self.bp + frame.sp - 1] as i32)
-                        as u64;
+                    let val2 = self.stack[self.bp + frame.sp - 1] as i32;
+                    let val1 = self.stack[self.bp + frame.sp - 2] as i32;
+                    if val2 == 0 {
+                        self.throw_exception("java/lang/ArithmeticException", Some("/ by zero"));
+                    } else {
+                        self.stack[self.bp + frame.sp - 2] = val1.wrapping_rem(val2) as u64;
+                        frame.sp -= 1;
+                        frame.pc += 1;
+                    }
+                }
+                Inst::lrem => {
+                    let val2 = self.stack[self.bp + frame.sp - 2] as i64;
+                    let val1 = self.stack[self.bp + frame.sp - 4] as i64;
+                    if val2 == 0 {
+                        self.throw_exception("java/lang/ArithmeticException", Some("/ by zero"));
+                    } else {
+                        self.stack[self.bp + frame.sp - 4] = val1.wrapping_rem(val2) as u64;
+                        frame.sp -= 2;
+                        frame.pc += 1;
+                    }
+                }
+                Inst::lneg => {
+                    self.stack[self.bp + frame.sp - 2] =
+                        (self.stack[self.bp + frame.sp - 2] as i64).wrapping_neg() as u64;
+                    frame.pc += 1;
+                }
+                Inst::frem => {
+                    self.stack[self.bp + frame.sp - 2] =
+                        f2u(u2f(self.stack[self.bp + frame.sp - 2])
+                            % u2f(self.stack[self.bp + frame.sp - 1]));
                     frame.sp -= 1;
                     frame.pc += 1;
                 }
+                Inst::fneg => {
+                    self.stack[self.bp + frame.sp - 1] =
+                        f2u(-u2f(self.stack[self.bp + frame.sp - 1]));
+                    frame.pc += 1;
+                }
+                Inst::drem => {
+                    self.stack[self.bp + frame.sp - 4] =
+                        d2u(u2d(self.stack[self.bp + frame.sp - 4])
+                            % u2d(self.stack[self.bp + frame.sp - 2]));
+                    frame.sp -= 2;
+                    frame.pc += 1;
+                }
+                Inst::ineg => {
+                    self.stack[self.bp + frame.sp - 1] =
+                        (self.stack[self.bp + frame.sp - 1] as i32).wrapping_neg() as u64;
+                    frame.pc += 1;
+                }
                 Inst::dneg => {
                     self.stack[self.bp + frame.sp - 2] =
                         d2u(-u2d(self.stack[self.bp + frame.sp - 2]));
                     frame.pc += 1;
                 }
                 Inst::ishl => {
-                    self.stack[self.bp + frame.sp - 2] = ((self.stack[self.bp + frame.sp - 2]
-                        as i32)
-                        << self.stack[self.bp + frame.sp - 1] as i32)
-                        as u64;
+                    let val2 = self.stack[self.bp + frame.sp - 1] as u32 & 0x1f;
+                    let val1 = self.stack[self.bp + frame.sp - 2] as i32;
+                    self.stack[self.bp + frame.sp - 2] = val1.wrapping_shl(val2) as u64;
+                    frame.sp -= 1;
+                    frame.pc += 1;
+                }
+                Inst::lshl => {
+                    let val2 = self.stack[self.bp + frame.sp - 1] as u32 & 0x3f;
+                    let val1 = self.stack[self.bp + frame.sp - 3] as i64;
+                    self.stack[self.bp + frame.sp - 3] = val1.wrapping_shl(val2) as u64;
                     frame.sp -= 1;
                     frame.pc += 1;
                 }
                 Inst::ishr => {
-                    self.stack[self.bp + frame.sp - 2] = (self.stack[self.bp + frame.sp - 2] as i32
-                        >> self.stack[self.bp + frame.sp - 1] as i32)
-                        as u64;
+                    let val2 = self.stack[self.bp + frame.sp - 1] as u32 & 0x1f;
+                    let val1 = self.stack[self.bp + frame.sp - 2] as i32;
+                    self.stack[self.bp + frame.sp - 2] = val1.wrapping_shr(val2) as u64;
+                    frame.sp -= 1;
+                    frame.pc += 1;
+                }
+                Inst::iushr => {
+                    let val2 = self.stack[self.bp + frame.sp - 1] as u32 & 0x1f;
+                    let val1 = self.stack[self.bp + frame.sp - 2] as u32;
+                    self.stack[self.bp + frame.sp - 2] = (val1 >> val2) as i32 as u64;
+                    frame.sp -= 1;
+                    frame.pc += 1;
+                }
+                Inst::lshr => {
+                    let val2 = self.stack[self.bp + frame.sp - 1] as u32 & 0x3f;
+                    let val1 = self.stack[self.bp + frame.sp - 3] as i64;
+                    self.stack[self.bp + frame.sp - 3] = val1.wrapping_shr(val2) as u64;
+                    frame.sp -= 1;
+                    frame.pc += 1;
+                }
+                Inst::lushr => {
+                    let val2 = self.stack[self.bp + frame.sp - 1] as u32 & 0x3f;
+                    let val1 = self.stack[self.bp + frame.sp - 3] as i64;
+                    self.stack[self.bp + frame.sp - 3] = (val1 as u64).wrapping_shr(val2);
                     frame.sp -= 1;
                     frame.pc += 1;
                 }
@@ -436,6 +702,27 @@ impl VM {
                     frame.sp -= 1;
                     frame.pc += 1;
                 }
+                Inst::land => {
+                    let val2 = self.stack[self.bp + frame.sp - 2] as i64;
+                    let val1 = self.stack[self.bp + frame.sp - 4] as i64;
+                    self.stack[self.bp + frame.sp - 4] = (val1 & val2) as u64;
+                    frame.sp -= 2;
+                    frame.pc += 1;
+                }
+                Inst::lor => {
+                    let val2 = self.stack[self.bp + frame.sp - 2] as i64;
+                    let val1 = self.stack[self.bp + frame.sp - 4] as i64;
+                    self.stack[self.bp + frame.sp - 4] = (val1 | val2) as u64;
+                    frame.sp -= 2;
+                    frame.pc += 1;
+                }
+                Inst::ior => {
+                    self.stack[self.bp + frame.sp - 2] = (self.stack[self.bp + frame.sp - 2] as i32
+                        | self.stack[self.bp + frame.sp - 1] as i32)
+                        as u64;
+                    frame.sp -= 1;
+                    frame.pc += 1;
+                }
                 Inst::ixor => {
                     self.stack[self.bp + frame.sp - 2] = (self.stack[self.bp + frame.sp - 2] as i32
                         ^ self.stack[self.bp + frame.sp - 1] as i32)
@@ -443,15 +730,69 @@ impl VM {
                     frame.sp -= 1;
                     frame.pc += 1;
                 }
+                Inst::lxor => {
+                    let val2 = self.stack[self.bp + frame.sp - 2] as i64;
+                    let val1 = self.stack[self.bp + frame.sp - 4] as i64;
+                    self.stack[self.bp + frame.sp - 4] = (val1 ^ val2) as u64;
+                    frame.sp -= 2;
+                    frame.pc += 1;
+                }
                 Inst::iinc => {
                     let index = code[frame.pc + 1] as usize;
-                    let const_ = code[frame.pc + 2];
-                    self.stack[self.bp + index] += const_ as u64;
+                    let const_ = code[frame.pc + 2] as i8 as i32;
+                    self.stack[self.bp + index] =
+                        (self.stack[self.bp + index] as i32).wrapping_add(const_) as u64;
                     frame.pc += 3;
                 }
+                Inst::wide => self.run_wide(),
+                Inst::i2l => {
+                    self.stack[self.bp + frame.sp - 1] =
+                        self.stack[self.bp + frame.sp - 1] as i32 as i64 as u64;
+                    frame.sp += 1;
+                    frame.pc += 1;
+                }
+                Inst::i2f => {
+                    self.stack[self.bp + frame.sp - 1] =
+                        f2u(self.stack[self.bp + frame.sp - 1] as i32 as f32);
+                    frame.pc += 1;
+                }
                 Inst::i2d => {
                     self.stack[self.bp + frame.sp - 1] =
-                        d2u(self.stack[self.bp + frame.sp - 1] as f64);
+                        d2u(self.stack[self.bp + frame.sp - 1] as i32 as f64);
+                    frame.sp += 1;
+                    frame.pc += 1;
+                }
+                Inst::l2i => {
+                    self.stack[self.bp + frame.sp - 2] =
+                        self.stack[self.bp + frame.sp - 2] as i32 as u64;
+                    frame.sp -= 1;
+                    frame.pc += 1;
+                }
+                Inst::l2d => {
+                    self.stack[self.bp + frame.sp - 2] =
+                        d2u(self.stack[self.bp + frame.sp - 2] as i64 as f64);
+                    frame.pc += 1;
+                }
+                Inst::l2f => {
+                    self.stack[self.bp + frame.sp - 2] =
+                        f2u(self.stack[self.bp + frame.sp - 2] as i64 as f32);
+                    frame.sp -= 1;
+                    frame.pc += 1;
+                }
+                Inst::f2i => {
+                    self.stack[self.bp + frame.sp - 1] =
+                        u2f(self.stack[self.bp + frame.sp - 1]) as i32 as u64;
+                    frame.pc += 1;
+                }
+                Inst::f2l => {
+                    self.stack[self.bp + frame.sp - 1] =
+                        u2f(self.stack[self.bp + frame.sp - 1]) as i64 as u64;
+                    frame.sp += 1;
+                    frame.pc += 1;
+                }
+                Inst::f2d => {
+                    self.stack[self.bp + frame.sp - 1] =
+                        d2u(u2f(self.stack[self.bp + frame.sp - 1]) as f64);
                     frame.sp += 1;
                     frame.pc += 1;
                 }
@@ -461,14 +802,37 @@ impl VM {
                     frame.sp -= 1;
                     frame.pc += 1;
                 }
+                Inst::d2l => {
+                    self.stack[self.bp + frame.sp - 2] =
+                        u2d(self.stack[self.bp + frame.sp - 2]) as i64 as u64;
+                    frame.pc += 1;
+                }
+                Inst::d2f => {
+                    self.stack[self.bp + frame.sp - 2] =
+                        f2u(u2d(self.stack[self.bp + frame.sp - 2]) as f32);
+                    frame.sp -= 1;
+                    frame.pc += 1;
+                }
+                Inst::i2b => {
+                    self.stack[self.bp + frame.sp - 1] =
+                        (self.stack[self.bp + frame.sp - 1] as i8) as u64;
+                    frame.pc += 1;
+                }
+                Inst::i2c => {
+                    self.stack[self.bp + frame.sp - 1] =
+                        (self.stack[self.bp + frame.sp - 1] as u16) as u64;
+                    frame.pc += 1;
+                }
                 Inst::i2s => {
                     self.stack[self.bp + frame.sp - 1] =
                         (self.stack[self.bp + frame.sp - 1] as i16) as u64;
                     frame.pc += 1;
                 }
-                Inst::invokestatic | Inst::invokespecial | Inst::invokevirtual => {
-                    self.run_invoke_static(cur_code)
-                }
+                Inst::invokestatic
+                | Inst::invokespecial
+                | Inst::invokevirtual
+                | Inst::invokeinterface => self.run_invoke_static(cur_code),
+                Inst::invokedynamic => self.run_invoke_dynamic(),
                 Inst::new => self.run_new(),
                 Inst::newarray => self.run_new_array(),
                 Inst::anewarray => self.run_new_obj_array(),
@@ -481,11 +845,43 @@ impl VM {
                     frame.sp += 1;
                     frame.pc += 1;
                 }
+                Inst::swap => {
+                    self.stack
+                        .swap(self.bp + frame.sp - 1, self.bp + frame.sp - 2);
+                    frame.pc += 1;
+                }
+                Inst::dup_x2 => {
+                    let val1 = self.stack[self.bp + frame.sp - 1];
+                    let val2 = self.stack[self.bp + frame.sp - 2];
+                    let val3 = self.stack[self.bp + frame.sp - 3];
+                    frame.sp -= 3;
+                    self.stack[self.bp + frame.sp] = val1;
+                    self.stack[self.bp + frame.sp + 1] = val3;
+                    self.stack[self.bp + frame.sp + 2] = val2;
+                    self.stack[self.bp + frame.sp + 3] = val1;
+                    frame.sp += 4;
+                    frame.pc += 1;
+                }
+                Inst::dup2_x2 => {
+                    let val1 = self.stack[self.bp + frame.sp - 1];
+                    let val2 = self.stack[self.bp + frame.sp - 2];
+                    let val3 = self.stack[self.bp + frame.sp - 3];
+                    let val4 = self.stack[self.bp + frame.sp - 4];
+                    frame.sp -= 4;
+                    self.stack[self.bp + frame.sp] = val2;
+                    self.stack[self.bp + frame.sp + 1] = val1;
+                    self.stack[self.bp + frame.sp + 2] = val4;
+                    self.stack[self.bp + frame.sp + 3] = val3;
+                    self.stack[self.bp + frame.sp + 4] = val2;
+                    self.stack[self.bp + frame.sp + 5] = val1;
+                    frame.sp += 6;
+                    frame.pc += 1;
+                }
                 Inst::dup_x1 => {
                     let val1 = self.stack[self.bp + frame.sp - 1];
                     let val2 = self.stack[self.bp + frame.sp - 2];
                     frame.sp -= 2;
-                    self.stack[self.bp + frame.sp + 0] = val1;
+                    self.stack[self.bp + frame.sp] = val1;
                     self.stack[self.bp + frame.sp + 1] = val2;
                     self.stack[self.bp + frame.sp + 2] = val1;
                     frame.sp += 3;
@@ -498,7 +894,7 @@ impl VM {
                     self.stack[self.bp + frame.sp - 3] = val2;
                     self.stack[self.bp + frame.sp - 2] = val1;
                     self.stack[self.bp + frame.sp - 1] = val3;
-                    self.stack[self.bp + frame.sp + 0] = val2;
+                    self.stack[self.bp + frame.sp] = val2;
                     self.stack[self.bp + frame.sp + 1] = val1;
                     frame.sp += 2;
                     frame.pc += 1;
@@ -506,7 +902,7 @@ impl VM {
                 Inst::dup2 => {
                     let val1 = self.stack[self.bp + frame.sp - 1];
                     let val2 = self.stack[self.bp + frame.sp - 2];
-                    self.stack[self.bp + frame.sp + 0] = val2;
+                    self.stack[self.bp + frame.sp] = val2;
                     self.stack[self.bp + frame.sp + 1] = val1;
                     frame.sp += 2;
                     frame.pc += 1;
@@ -516,6 +912,50 @@ impl VM {
                     let dst = (frame.pc as isize + branch as isize) as usize;
                     loop_jit!(frame, dst < frame.pc, dst, frame.pc + 3, frame.pc = dst);
                 }
+                // The return address pushed by ``jsr`` is the pc of the following instruction
+                Inst::jsr => {
+                    let branch = ((code[frame.pc + 1] as i16) << 8) + code[frame.pc + 2] as i16;
+                    self.stack[self.bp + frame.sp] = (frame.pc + 3) as u64;
+                    frame.sp += 1;
+                    frame.pc = (frame.pc as isize + branch as isize) as usize;
+                }
+                Inst::jsr_w => {
+                    let branch = read_i32(code, frame.pc + 1);
+                    self.stack[self.bp + frame.sp] = (frame.pc + 5) as u64;
+                    frame.sp += 1;
+                    frame.pc = (frame.pc as isize + branch as isize) as usize;
+                }
+                Inst::ret => {
+                    let index = code[frame.pc + 1] as usize;
+                    frame.pc = self.stack[self.bp + index] as usize;
+                }
+                Inst::tableswitch | Inst::lookupswitch => {
+                    let key = self.stack[self.bp + frame.sp - 1] as i32;
+                    frame.sp -= 1;
+                    let offset = SwitchTable::lookup(code, frame.pc, key);
+                    frame.pc = (frame.pc as isize + offset) as usize;
+                }
+                Inst::lcmp => {
+                    let val2 = self.stack[self.bp + frame.sp - 2] as i64;
+                    let val1 = self.stack[self.bp + frame.sp - 4] as i64;
+                    frame.sp -= 4;
+                    self.stack[self.bp + frame.sp] = val1.cmp(&val2) as i64 as u64;
+                    frame.sp += 1;
+                    frame.pc += 1;
+                }
+                Inst::fcmpl | Inst::fcmpg => {
+                    let val2 = u2f(self.stack[self.bp + frame.sp - 1]);
+                    let val1 = u2f(self.stack[self.bp + frame.sp - 2]);
+                    frame.sp -= 2;
+                    self.stack[self.bp + frame.sp] = match val1.partial_cmp(&val2) {
+                        Some(ordering) => ordering as i64 as u64,
+                        // NaN
+                        None if cur_code == Inst::fcmpg => 1,
+                        None => (0 - 1) as u64,
+                    };
+                    frame.sp += 1;
+                    frame.pc += 1;
+                }
                 Inst::dcmpl | Inst::dcmpg => {
                     let val2 = u2d(self.stack[self.bp + frame.sp - 2]);
                     let val1 = u2d(self.stack[self.bp + frame.sp - 4]);
@@ -621,6 +1061,23 @@ impl VM {
                         }
                     );
                 }
+                Inst::ifgt => {
+                    let branch = ((code[frame.pc + 1] as i16) << 8) + code[frame.pc + 2] as i16;
+                    let val = self.stack[self.bp + frame.sp - 1] as i32;
+                    frame.sp -= 1;
+                    let dst = (frame.pc as isize + branch as isize) as usize;
+                    loop_jit!(
+                        frame,
+                        dst < frame.pc,
+                        dst,
+                        frame.pc + 3,
+                        if val > 0 {
+                            frame.pc = dst;
+                        } else {
+                            frame.pc += 3;
+                        }
+                    );
+                }
                 Inst::ifnull => {
                     let branch = ((code[frame.pc + 1] as i16) << 8) + code[frame.pc + 2] as i16;
                     let val = self.stack[self.bp + frame.sp - 1] as GcType<u64>;
@@ -631,7 +1088,7 @@ impl VM {
                         dst < frame.pc,
                         dst,
                         frame.pc + 3,
-                        if val == 0 as *mut u64 {
+                        if val.is_null() {
                             frame.pc = dst;
                         } else {
                             frame.pc += 3;
@@ -648,7 +1105,7 @@ impl VM {
                         dst < frame.pc,
                         dst,
                         frame.pc + 3,
-                        if val != 0 as *mut u64 {
+                        if !val.is_null() {
                             frame.pc = dst;
                         } else {
                             frame.pc += 3;
@@ -737,14 +1194,32 @@ impl VM {
                         }
                     );
                 }
-                Inst::if_acmpne => {
+                Inst::if_icmple => {
+                    let branch = ((code[frame.pc + 1] as i16) << 8) + code[frame.pc + 2] as i16;
+                    let val2 = self.stack[self.bp + frame.sp - 1] as i32;
+                    let val1 = self.stack[self.bp + frame.sp - 2] as i32;
+                    frame.sp -= 2;
+                    let dst = (frame.pc as isize + branch as isize) as usize;
+                    loop_jit!(
+                        frame,
+                        dst < frame.pc,
+                        dst,
+                        frame.pc + 3,
+                        if val1 <= val2 {
+                            frame.pc = dst;
+                        } else {
+                            frame.pc += 3;
+                        }
+                    );
+                }
+                Inst::if_acmpeq | Inst::if_acmpne => {
                     let branch = ((code[frame.pc + 1] as i16) << 8) + code[frame.pc + 2] as i16;
                     let val2 = self.stack[self.bp + frame.sp - 1] as GcType<u64>;
                     let val1 = self.stack[self.bp + frame.sp - 2] as GcType<u64>;
                     frame.sp -= 2;
                     let dst = (frame.pc as isize + branch as isize) as usize;
                     loop_jit!(frame, dst < frame.pc, dst, frame.pc + 3, {
-                        if val1 != val2 {
+                        if (val1 == val2) == (cur_code == Inst::if_acmpeq) {
                             frame.pc = dst
                         } else {
                             frame.pc += 3;
@@ -755,6 +1230,14 @@ impl VM {
                     self.stack[self.bp] = self.stack[self.bp + frame.sp - 1];
                     return Inst::ireturn;
                 }
+                Inst::lreturn => {
+                    self.stack[self.bp] = self.stack[self.bp + frame.sp - 2];
+                    return Inst::lreturn;
+                }
+                Inst::freturn => {
+                    self.stack[self.bp] = self.stack[self.bp + frame.sp - 1];
+                    return Inst::freturn;
+                }
                 Inst::areturn => {
                     self.stack[self.bp] = self.stack[self.bp + frame.sp - 1];
                     return Inst::areturn;
@@ -775,29 +1258,109 @@ impl VM {
                 Inst::getfield2_quick => self.run_get_field2_quick(),
                 Inst::putfield2_quick => self.run_put_field2_quick(),
                 Inst::monitorenter => {
-                    // TODO: Implement
+                    let objectref = self.stack[self.bp + frame.sp - 1];
+                    self.monitor_enter(objectref);
+                    let frame = &mut self.frame_stack[frame_stack_len - 1];
                     frame.sp -= 1;
                     frame.pc += 1;
                 }
-                Inst::arraylength => {
+                Inst::monitorexit => {
                     let objectref = self.stack[self.bp + frame.sp - 1];
-                    let array = unsafe { &mut *(objectref as GcType<Array>) };
-                    self.stack[self.bp + frame.sp - 1] = array.get_length() as u64;
+                    frame.sp -= 1;
                     frame.pc += 1;
+                    self.monitor_exit(objectref);
                 }
-                Inst::checkcast => {
-                    // TODO: Implement
-                    frame.pc += 3;
+                Inst::athrow => {
+                    self.exception = Some(self.stack[self.bp + frame.sp - 1]);
+                }
+                Inst::arraylength => {
+                    let objectref = self.stack[self.bp + frame.sp - 1];
+                    if objectref == 0 {
+                        self.throw_exception("java/lang/NullPointerException", None);
+                    } else {
+                        let array = unsafe { &mut *(objectref as GcType<Array>) };
+                        self.stack[self.bp + frame.sp - 1] = array.get_length() as u64;
+                        frame.pc += 1;
+                    }
                 }
+                Inst::checkcast | Inst::instanceof => self.run_type_check(cur_code),
                 Inst::multianearray => self.run_multianewarray(),
                 e => unimplemented!("{}", e),
             }
+
+            if self.exception.is_some() && !self.catch_exception(cur_pc) {
+                return Inst::athrow;
+            }
+        }
+    }
+
+    fn catch_exception(&mut self, pc: usize) -> bool {
+        let exception = self.exception.unwrap();
+        let exception_class = unsafe { &*(*(exception as GcType<ObjectBody>)).class };
+        let frame_stack_len = self.frame_stack.len();
+        let frame = &mut self.frame_stack[frame_stack_len - 1];
+        let frame_class = unsafe { &*frame.class.unwrap() };
+        let (max_locals, handler_pc) = {
+            let code = frame.method_info.code.as_ref().unwrap();
+            let handler = code.exception_table.iter().find(|entry| {
+                if !(entry.start_pc as usize <= pc && pc < entry.end_pc as usize) {
+                    return false;
+                }
+                // catch_type == 0 means any exception (used to implement ``finally``)
+                if entry.catch_type == 0 {
+                    return true;
+                }
+                let name_index = fld!(
+                    Constant::ClassInfo,
+                    &frame_class.classfile.constant_pool[entry.catch_type as usize],
+                    name_index
+                );
+                let name = frame_class.classfile.constant_pool[name_index]
+                    .get_utf8()
+                    .unwrap();
+                exception_class.is_subclass_of(name)
+            });
+            match handler {
+                Some(entry) => (code.max_locals as usize, entry.handler_pc as usize),
+                None => return false,
+            }
+        };
+
+        frame.sp = max_locals;
+        self.stack[self.bp + frame.sp] = exception;
+        frame.sp += 1;
+        frame.pc = handler_pc;
+        self.exception = None;
+        true
+    }
+
+    pub fn throw_exception(&mut self, class_name: &str, message: Option<&str>) {
+        let class = self.load_class(class_name);
+        let objectheap = unsafe { &mut *self.objectheap };
+        let exception = unsafe { objectheap.create_object(class) };
+        if let Some(message) = message {
+            let (id, _) = *unsafe { &*class }
+                .get_numbered_field_info("detailMessage")
+                .unwrap();
+            unsafe { &mut *(exception as GcType<ObjectBody>) }.variables[id] =
+                unsafe { objectheap.create_string_object(message.to_string(), self.classheap) };
         }
+        self.exception = Some(exception);
+    }
+
+    pub fn report_uncaught_exception(&mut self, thread_name: &str) {
+        let exception = self.exception.take().unwrap();
+        eprintln!(
+            "Exception in thread \"{}\" {}",
+            thread_name,
+            describe_exception(exception)
+        );
     }
 
     fn run_native_method(&mut self) {
         let frame = self.frame_stack.last_mut().unwrap();
-        let frame_class = unsafe { &*frame.class.unwrap() };
+        let class = frame.class.unwrap();
+        let frame_class = unsafe { &*class };
         let class_name = frame_class.get_name().unwrap();
         let method_name = frame_class
             .get_utf8_from_const_pool(frame.method_info.name_index as usize)
@@ -806,116 +1369,35 @@ impl VM {
             .get_utf8_from_const_pool(frame.method_info.descriptor_index as usize)
             .unwrap();
         let signature = format!("{}.{}:{}", class_name, method_name, descriptor);
-        let objectheap = unsafe { &mut *self.objectheap };
+        let has_this = !frame
+            .method_info
+            .check_access_flags(method::access_flags::ACC_PACC_STATIC);
 
-        match signature.as_str() {
-            "java/io/PrintStream.println:(Ljava/lang/Object;)V" => {
-                println!("{}", self.stack[self.bp + 1]);
-            }
-            "java/io/PrintStream.println:(I)V" => {
-                native_functions::java_io_printstream_println_i_v(
-                    self.runtime_env,
-                    self.stack[self.bp] as GcType<ObjectBody>,
-                    self.stack[self.bp + 1] as i32,
-                );
-            }
-            "java/io/PrintStream.println:(D)V" => {
-                println!("{}", u2d(self.stack[self.bp + 1]));
-            }
-            "java/io/PrintStream.println:(Z)V" => {
-                println!(
-                    "{}",
-                    if self.stack[self.bp + 1] == 0 {
-                        false
-                    } else {
-                        true
+        let native = match unsafe { &*self.runtime_env }
+            .natives
+            .get(signature.as_str())
+            .cloned()
+        {
+            Some(native) => native,
+            None => {
+                let is_static = !has_this;
+                match jni::resolve_native_method(self, class, method_name, descriptor, is_static) {
+                    Some(native) => native,
+                    None => {
+                        let message = format!("{}.{}{}", class_name, method_name, descriptor);
+                        self.throw_exception(
+                            "java/lang/UnsatisfiedLinkError",
+                            Some(message.as_str()),
+                        );
+                        return;
                     }
-                );
-            }
-            "java/io/PrintStream.println:(Ljava/lang/String;)V" => {
-                native_functions::java_io_printstream_println_string_v(
-                    self.runtime_env,
-                    self.stack[self.bp + 0] as GcType<ObjectBody>,
-                    self.stack[self.bp + 1] as GcType<ObjectBody>,
-                );
-            }
-            "java/io/PrintStream.print:(Ljava/lang/String;)V" => {
-                native_functions::java_io_printstream_print_string_v(
-                    self.runtime_env,
-                    self.stack[self.bp + 0] as GcType<ObjectBody>,
-                    self.stack[self.bp + 1] as GcType<ObjectBody>,
-                );
-            }
-            "java/lang/String.valueOf:(I)Ljava/lang/String;" => {
-                let i = self.stack[self.bp + 0] as i32;
-                self.stack[self.bp + 0] =
-                    objectheap.create_string_object(format!("{}", i), self.classheap);
-            }
-            "java/lang/StringBuilder.append:(Ljava/lang/String;)Ljava/lang/StringBuilder;" => {
-                self.stack[self.bp] =
-                    native_functions::java_lang_stringbuilder_append_string_stringbuilder(
-                        self.runtime_env,
-                        self.stack[self.bp + 0] as GcType<ObjectBody>,
-                        self.stack[self.bp + frame.sp - 1] as GcType<ObjectBody>,
-                    ) as u64;
-            }
-            "java/lang/StringBuilder.append:(I)Ljava/lang/StringBuilder;" => {
-                self.stack[self.bp] =
-                    native_functions::java_lang_stringbuilder_append_i_stringbuilder(
-                        self.runtime_env,
-                        self.stack[self.bp + 0] as GcType<ObjectBody>,
-                        self.stack[self.bp + frame.sp - 1] as i32,
-                    ) as u64;
-            }
-            "java/lang/StringBuilder.toString:()Ljava/lang/String;" => {
-                let s = native_functions::java_lang_stringbuilder_tostring_string(
-                    self.runtime_env,
-                    self.stack[self.bp + 0] as GcType<ObjectBody>,
-                );
-                self.stack[self.bp + 0] = s as u64;
-            }
-            "java/lang/Math.random:()D" => {
-                self.stack[self.bp + 0] =
-                    d2u(native_functions::java_lang_math_random_d(self.runtime_env))
-            }
-            "java/lang/Math.sin:(D)D" => {
-                self.stack[self.bp + 0] = d2u(native_functions::java_lang_math_sin_d_d(
-                    self.runtime_env,
-                    u2d(self.stack[self.bp + 0]),
-                ))
-            }
-            "java/lang/Math.cos:(D)D" => {
-                self.stack[self.bp + 0] = d2u(native_functions::java_lang_math_cos_d_d(
-                    self.runtime_env,
-                    u2d(self.stack[self.bp + 0]),
-                ))
-            }
-            "java/lang/Math.tan:(D)D" => {
-                self.stack[self.bp + 0] = d2u(native_functions::java_lang_math_tan_d_d(
-                    self.runtime_env,
-                    u2d(self.stack[self.bp + 0]),
-                ))
-            }
-            "java/lang/Math.sqrt:(D)D" => {
-                self.stack[self.bp + 0] = d2u(native_functions::java_lang_math_sqrt_d_d(
-                    self.runtime_env,
-                    u2d(self.stack[self.bp + 0]),
-                ))
-            }
-            "java/lang/Math.pow:(DD)D" => {
-                self.stack[self.bp + 0] = d2u(native_functions::java_lang_math_pow_dd_d(
-                    self.runtime_env,
-                    u2d(self.stack[self.bp + 0]),
-                    u2d(self.stack[self.bp + 2]),
-                ))
-            }
-            "java/lang/Math.abs:(D)D" => {
-                self.stack[self.bp + 0] = d2u(native_functions::java_lang_math_abs_d_d(
-                    self.runtime_env,
-                    u2d(self.stack[self.bp + 0]),
-                ))
+                }
             }
-            e => panic!("{:?}", e),
+        };
+        let args = self.stack[self.bp..self.bp + native.slots_count(has_this)].to_vec();
+        let ret = native.call(self, has_this, &args);
+        if !descriptor.ends_with(")V") {
+            self.stack[self.bp] = ret;
         }
     }
 
@@ -951,7 +1433,7 @@ impl VM {
         assert!(id <= 0xff);
 
         let code = unsafe { &mut *frame.method_info.code.as_mut().unwrap().code };
-        code[frame.pc + 0] = match ty {
+        code[frame.pc] = match ty {
             VariableType::Double | VariableType::Long => Inst::getfield2_quick,
             _ => Inst::getfield_quick,
         };
@@ -1001,7 +1483,7 @@ impl VM {
         assert!(id <= 0xff);
 
         let code = unsafe { &mut *frame.method_info.code.as_mut().unwrap().code };
-        code[frame.pc + 0] = match ty {
+        code[frame.pc] = match ty {
             VariableType::Double | VariableType::Long => Inst::putfield2_quick,
             _ => Inst::putfield_quick,
         };
@@ -1108,16 +1590,24 @@ impl VM {
             .get_utf8()
             .unwrap();
         let class = self.load_class(class_name);
-        let name_index = fld!(
+        let (name_index, descriptor_index) = fld!(
             Constant::NameAndTypeInfo,
             &frame_class.classfile.constant_pool[name_and_type_index],
-            name_index
+            name_index,
+            descriptor_index
         );
-        let name = frame_class.classfile.constant_pool[name_index as usize]
+        let name = frame_class.classfile.constant_pool[name_index]
             .get_utf8()
             .unwrap();
-
-        // TODO: ``descriptor`` will be necessary to verify the field's type.
+        let descriptor = frame_class.classfile.constant_pool[descriptor_index]
+            .get_utf8()
+            .unwrap();
+        // long and double take two slots
+        let slots = if descriptor == "J" || descriptor == "D" {
+            2
+        } else {
+            1
+        };
 
         let object = unsafe { &*class }
             .get_static_variable(name.as_str())
@@ -1126,7 +1616,7 @@ impl VM {
         let frame = &mut self.frame_stack[frame_stack_len - 1];
         self.stack[self.bp + frame.sp] = object;
         frame.pc += 3;
-        frame.sp += 1;
+        frame.sp += slots;
     }
 
     fn run_put_static(&mut self) {
@@ -1159,20 +1649,28 @@ impl VM {
             .get_utf8()
             .unwrap();
         let class = self.load_class(class_name);
-        let name_index = fld!(
+        let (name_index, descriptor_index) = fld!(
             Constant::NameAndTypeInfo,
             &frame_class.classfile.constant_pool[name_and_type_index],
-            name_index
+            name_index,
+            descriptor_index
         );
-        let name = frame_class.classfile.constant_pool[name_index as usize]
+        let name = frame_class.classfile.constant_pool[name_index]
             .get_utf8()
             .unwrap();
-
-        // TODO: ``descriptor`` will be necessary to verify the field's type.
+        let descriptor = frame_class.classfile.constant_pool[descriptor_index]
+            .get_utf8()
+            .unwrap();
+        // long and double take two slots
+        let slots = if descriptor == "J" || descriptor == "D" {
+            2
+        } else {
+            1
+        };
 
         let frame = &mut self.frame_stack[frame_stack_len - 1];
-        let val = self.stack[self.bp + frame.sp - 1];
-        frame.sp -= 1;
+        let val = self.stack[self.bp + frame.sp - slots];
+        frame.sp -= slots;
         frame.pc += 3;
 
         unsafe { &mut *class }.put_static_variable(name.as_str(), val)
@@ -1192,14 +1690,20 @@ impl VM {
                 .unwrap()
                 .read_u16_from_code(frame.pc + 1)
         };
-        frame!().pc += 3;
-
-        let (class_index, name_and_type_index) = fld!(
-            Constant::MethodrefInfo,
-            &frame_class.classfile.constant_pool[mref_index],
-            class_index,
-            name_and_type_index
-        );
+        frame!().pc += if instr == Inst::invokeinterface { 5 } else { 3 };
+
+        let (class_index, name_and_type_index) =
+            match &frame_class.classfile.constant_pool[mref_index] {
+                Constant::MethodrefInfo {
+                    class_index,
+                    name_and_type_index,
+                }
+                | Constant::InterfaceMethodrefInfo {
+                    class_index,
+                    name_and_type_index,
+                } => (*class_index as usize, *name_and_type_index as usize),
+                _ => panic!(),
+            };
         let name_index = fld!(
             Constant::ClassInfo,
             &frame_class.classfile.constant_pool[class_index],
@@ -1208,7 +1712,12 @@ impl VM {
         let class_name = frame_class.classfile.constant_pool[name_index as usize]
             .get_utf8()
             .unwrap();
-        let class = self.load_class(class_name);
+        // Array types like ``[I`` have only the methods of ``Object``
+        let class = if class_name.starts_with('[') {
+            self.load_class("java/lang/Object")
+        } else {
+            self.load_class(class_name)
+        };
         let (name_index, descriptor_index) = fld!(
             Constant::NameAndTypeInfo,
             &frame_class.classfile.constant_pool[name_and_type_index],
@@ -1222,10 +1731,56 @@ impl VM {
         let descriptor = frame_class.classfile.constant_pool[descriptor_index]
             .get_utf8()
             .unwrap();
-        let (virtual_class, exec_method) = unsafe { &*class }.get_method(name, descriptor).unwrap();
+
+        // Signature polymorphic methods take the arguments as typed at the call site
+        if class_name == "java/lang/invoke/MethodHandle"
+            && (name == "invokeExact" || name == "invoke")
+        {
+            method_handle::invoke(self, descriptor.as_str(), name == "invokeExact");
+            return;
+        }
+
         let params_num =
             count_params(descriptor.as_str()) + if instr == Inst::invokestatic { 0 } else { 1 };
-        let former_sp = frame!().sp as usize;
+        let former_sp = frame!().sp;
+        let receiver = if instr == Inst::invokestatic {
+            0
+        } else {
+            self.stack[self.bp + former_sp - params_num]
+        };
+        if instr != Inst::invokestatic && receiver == 0 {
+            self.throw_exception("java/lang/NullPointerException", None);
+            return;
+        }
+
+        // Dispatch virtual methods on the class of the receiver. Arrays have only the methods of
+        // ``Object``.
+        let (virtual_class, exec_method) = if (instr == Inst::invokevirtual
+            || instr == Inst::invokeinterface)
+            && receiver != 0
+            && !unsafe { &*self.objectheap }.gc.is_array(receiver)
+        {
+            let receiver_class = unsafe { &*(receiver as GcType<ObjectBody>) }.class;
+            unsafe { &*receiver_class }
+                .get_method(name, descriptor)
+                .or_else(|| unsafe { &*class }.get_method(name, descriptor))
+                .unwrap()
+        } else {
+            unsafe { &*class }.get_method(name, descriptor).unwrap()
+        };
+
+        let monitor = if exec_method.check_access_flags(method::access_flags::ACC_PACC_SYNCHRONIZED)
+        {
+            let object = if instr == Inst::invokestatic {
+                virtual_class as u64
+            } else {
+                receiver
+            };
+            self.monitor_enter(object);
+            Some(object)
+        } else {
+            None
+        };
 
         if instr != Inst::invokespecial {
             if let Some(sp) = unsafe {
@@ -1237,6 +1792,9 @@ impl VM {
                 )
             } {
                 frame!().sp = sp;
+                if let Some(object) = monitor {
+                    self.monitor_exit(object);
+                }
                 return;
             }
         }
@@ -1272,7 +1830,11 @@ impl VM {
         self.bp -= bp_offset;
         self.frame_stack.pop();
 
-        let mut frame = frame!();
+        if let Some(object) = monitor {
+            self.monitor_exit(object);
+        }
+
+        let frame = frame!();
         frame.sp -= params_num;
 
         if !descriptor.ends_with(")V") {
@@ -1285,6 +1847,50 @@ impl VM {
         }
     }
 
+    /// Loads a ``CONSTANT_Class``, ``CONSTANT_MethodType`` or ``CONSTANT_MethodHandle`` by ``ldc``
+    fn load_constant(&mut self, class: GcType<Class>, index: usize) -> u64 {
+        let constant_pool = &unsafe { &*class }.classfile.constant_pool;
+        match constant_pool[index] {
+            Constant::ClassInfo { name_index } => {
+                let name = constant_pool[name_index as usize].get_utf8().unwrap();
+                mirror::of_class_name(self, name)
+            }
+            Constant::MethodTypeInfo { descriptor_index } => {
+                let descriptor = constant_pool[descriptor_index as usize].get_utf8().unwrap();
+                method_handle::method_type(self, descriptor)
+            }
+            Constant::MethodHandleInfo { .. } => method_handle::load_constant(self, class, index),
+            _ => unimplemented!(),
+        }
+    }
+
+    fn run_invoke_dynamic(&mut self) {
+        let frame = self.frame_stack.last_mut().unwrap();
+        let class = frame.class.unwrap();
+        let index = frame
+            .method_info
+            .code
+            .as_ref()
+            .unwrap()
+            .read_u16_from_code(frame.pc + 1);
+        frame.pc += 5;
+
+        // Call sites are linked the first time they are run
+        let call_site = match unsafe { &*class }.call_sites.get(&index) {
+            Some(call_site) => call_site.clone(),
+            None => match call_site::link(self, class, index) {
+                Some(call_site) => {
+                    unsafe { &mut *class }
+                        .call_sites
+                        .insert(index, call_site.clone());
+                    call_site
+                }
+                None => return,
+            },
+        };
+        call_site.invoke(self);
+    }
+
     unsafe fn run_jit_compiled_func(
         &mut self,
         exec_method: &MethodInfo,
@@ -1299,7 +1905,7 @@ impl VM {
 
         jit_info_mgr.inc_count_of_func_exec();
 
-        if !jit_info_mgr.func_executed_enough_times() {
+        if !jit_info_mgr.func_executed_enough_times((&*self.runtime_env).jit_mode) {
             return None;
         }
 
@@ -1313,9 +1919,15 @@ impl VM {
             Some(exec_info) => exec_info.clone(),
             none => {
                 let code = &*exec_method.code.as_ref().unwrap().code;
-                let mut blocks = CFGMaker::new().make(code, 0, code.len());
+                let mut blocks = match CFGMaker::new().make(code, 0, code.len()) {
+                    Some(blocks) => blocks,
+                    None => {
+                        *none = Some(jit::FuncJITExecInfo::cant_compile());
+                        return None;
+                    }
+                };
 
-                match self.jit.compile_func(
+                match (&mut *self.runtime_env).jit().compile_func(
                     (
                         exec_method.name_index as usize,
                         exec_method.descriptor_index as usize,
@@ -1326,6 +1938,7 @@ impl VM {
                     exec_method.check_access_flags(method::access_flags::ACC_PACC_STATIC),
                 ) {
                     Ok(exec_info) => {
+                        report_jit_compiled(&*self.runtime_env, &*class, exec_method, None);
                         *none = Some(exec_info.clone());
                         exec_info.clone()
                     }
@@ -1337,11 +1950,12 @@ impl VM {
             }
         };
 
-        if let Some(sp) = self.jit.run_func(&mut self.stack, self.bp, sp, &exec_info) {
-            return Some(sp);
-        }
-
-        None
+        let jit = (&mut *self.runtime_env).jit();
+        let gc = &mut (&mut *self.objectheap).gc;
+        gc.enter_compiled_code();
+        let sp = jit.run_func(&mut self.stack, self.bp, sp, &exec_info);
+        gc.leave_compiled_code();
+        sp
     }
 
     fn run_new_array(&mut self) {
@@ -1356,15 +1970,32 @@ impl VM {
                 .read_u8_from_code(frame.pc + 1);
             AType::to_atype(atype)
         };
-        frame.pc += 2;
-
-        let size = self.stack[self.bp + frame.sp - 1] as usize;
-        self.stack[self.bp + frame.sp - 1] =
-            unsafe { &mut *self.objectheap }.create_array(atype, size);
+        let sp = frame.sp;
+        let size = match self.array_size(self.stack[self.bp + sp - 1]) {
+            Some(size) => size,
+            None => return,
+        };
+        self.stack[self.bp + sp - 1] = unsafe { &mut *self.objectheap }.create_array(atype, size);
+        self.frame_stack[frame_stack_len - 1].pc += 2;
 
         unsafe { &mut *self.objectheap }.gc.mark_and_sweep(self);
     }
 
+    /// Returns the int ``count`` as the length of a new array, or throws
+    /// ``NegativeArraySizeException`` if it's negative
+    fn array_size(&mut self, count: u64) -> Option<usize> {
+        let count = count as i32;
+        if count < 0 {
+            let message = count.to_string();
+            self.throw_exception(
+                "java/lang/NegativeArraySizeException",
+                Some(message.as_str()),
+            );
+            return None;
+        }
+        Some(count as usize)
+    }
+
     fn run_new_obj_array(&mut self) {
         let frame_stack_len = self.frame_stack.len();
         let (frame_class, class_index) = {
@@ -1385,17 +2016,273 @@ impl VM {
         let class_name = frame_class.classfile.constant_pool[name_index]
             .get_utf8()
             .unwrap();
-        let class = self.load_class(class_name);
+        // Elements may be arrays, e.g. ``new int[n][]``
+        let atype = if class_name.starts_with('[') {
+            self.resolve_class_name(format!("[{}", class_name).as_str())
+        } else {
+            AType::Class(self.load_class(class_name))
+        };
 
-        let frame = &mut self.frame_stack[frame_stack_len - 1];
-        let size = self.stack[self.bp + frame.sp - 1] as usize;
-        self.stack[self.bp + frame.sp - 1] =
-            unsafe { &mut *self.objectheap }.create_obj_array(class, size);
-        frame.pc += 3;
+        let sp = self.frame_stack[frame_stack_len - 1].sp;
+        let size = match self.array_size(self.stack[self.bp + sp - 1]) {
+            Some(size) => size,
+            None => return,
+        };
+        self.stack[self.bp + sp - 1] = unsafe { &mut *self.objectheap }.create_array(atype, size);
+        self.frame_stack[frame_stack_len - 1].pc += 3;
 
         unsafe { &mut *self.objectheap }.gc.mark_and_sweep(self);
     }
 
+    // ``wide`` extends the local variable index of the following instruction to 16 bits, and the
+    // constant of ``iinc`` as well
+    fn run_wide(&mut self) {
+        let frame_stack_len = self.frame_stack.len();
+        let frame = &mut self.frame_stack[frame_stack_len - 1];
+        let code = unsafe { &*frame.method_info.code.as_ref().unwrap().code };
+        let inst = code[frame.pc + 1];
+        let index = ((code[frame.pc + 2] as usize) << 8) + code[frame.pc + 3] as usize;
+
+        match inst {
+            Inst::iload | Inst::fload | Inst::aload => {
+                self.stack[self.bp + frame.sp] = self.stack[self.bp + index];
+                frame.sp += 1;
+            }
+            Inst::lload | Inst::dload => {
+                self.stack[self.bp + frame.sp] = self.stack[self.bp + index];
+                frame.sp += 2;
+            }
+            Inst::istore | Inst::fstore | Inst::astore => {
+                self.stack[self.bp + index] = self.stack[self.bp + frame.sp - 1];
+                frame.sp -= 1;
+            }
+            Inst::lstore | Inst::dstore => {
+                self.stack[self.bp + index] = self.stack[self.bp + frame.sp - 2];
+                frame.sp -= 2;
+            }
+            Inst::iinc => {
+                let const_ = ((code[frame.pc + 4] as i16) << 8) + code[frame.pc + 5] as i16;
+                self.stack[self.bp + index] =
+                    (self.stack[self.bp + index] as i32).wrapping_add(const_ as i32) as u64;
+            }
+            Inst::ret => {
+                frame.pc = self.stack[self.bp + index] as usize;
+                return;
+            }
+            e => unimplemented!("wide {}", e),
+        }
+
+        frame.pc += if inst == Inst::iinc { 6 } else { 4 };
+    }
+
+    /// Returns the array ``arrayref`` if ``index`` is in its bounds. Otherwise throws
+    /// ``NullPointerException`` or ``ArrayIndexOutOfBoundsException`` and returns None.
+    pub fn checked_array(&mut self, arrayref: u64, index: u64) -> Option<&'static mut Array> {
+        if arrayref == 0 {
+            self.throw_exception("java/lang/NullPointerException", None);
+            return None;
+        }
+        let array = unsafe { &mut *(arrayref as GcType<Array>) };
+        let index = index as i32;
+        if index < 0 || index as usize >= array.get_length() {
+            let message = format!(
+                "Index {} out of bounds for length {}",
+                index,
+                array.get_length()
+            );
+            self.throw_exception(
+                "java/lang/ArrayIndexOutOfBoundsException",
+                Some(message.as_str()),
+            );
+            return None;
+        }
+        Some(array)
+    }
+
+    fn run_array_load(&mut self, inst: Inst::Code) {
+        let frame_stack_len = self.frame_stack.len();
+        let sp = self.frame_stack[frame_stack_len - 1].sp;
+        let arrayref = self.stack[self.bp + sp - 2];
+        let index = self.stack[self.bp + sp - 1];
+        let array = match self.checked_array(arrayref, index) {
+            Some(array) => array,
+            None => return,
+        };
+
+        let index = index as i32 as isize;
+        self.stack[self.bp + sp - 2] = match inst {
+            Inst::baload => array.at::<u8>(index) as i8 as u64,
+            Inst::caload => array.at::<u16>(index),
+            Inst::saload => array.at::<u16>(index) as i16 as u64,
+            Inst::iaload | Inst::faload => array.at::<u32>(index),
+            _ => array.at::<u64>(index),
+        };
+        let frame = &mut self.frame_stack[frame_stack_len - 1];
+        // long and double take two slots, so they replace the index too
+        if inst != Inst::laload && inst != Inst::daload {
+            frame.sp -= 1;
+        }
+        frame.pc += 1;
+    }
+
+    fn run_array_store(&mut self, inst: Inst::Code) {
+        let frame_stack_len = self.frame_stack.len();
+        let sp = self.frame_stack[frame_stack_len - 1].sp;
+        let operands = if inst == Inst::lastore || inst == Inst::dastore {
+            4
+        } else {
+            3
+        };
+        let arrayref = self.stack[self.bp + sp - operands];
+        let index = self.stack[self.bp + sp - operands + 1];
+        let value = self.stack[self.bp + sp - operands + 2];
+        let array = match self.checked_array(arrayref, index) {
+            Some(array) => array,
+            None => return,
+        };
+
+        let index = index as i32 as isize;
+        match inst {
+            Inst::bastore => array.store(index, value as u8),
+            Inst::castore | Inst::sastore => array.store(index, value as u16),
+            Inst::iastore | Inst::fastore => array.store(index, value as u32),
+            _ => array.store(index, value),
+        }
+        let frame = &mut self.frame_stack[frame_stack_len - 1];
+        frame.sp -= operands;
+        frame.pc += 1;
+    }
+
+    fn run_aastore(&mut self) {
+        let frame_stack_len = self.frame_stack.len();
+        let sp = self.frame_stack[frame_stack_len - 1].sp;
+        let arrayref = self.stack[self.bp + sp - 3];
+        let index = self.stack[self.bp + sp - 2];
+        let value = self.stack[self.bp + sp - 1];
+        let array = match self.checked_array(arrayref, index) {
+            Some(array) => array,
+            None => return,
+        };
+        let index = index as i32 as isize;
+
+        if value != 0 {
+            let (element, dimensions) = component_type(&array.atype);
+            let (value_element, value_dimensions) = self.reference_type(value);
+            if !is_assignable(&value_element, value_dimensions, element, dimensions) {
+                let name = type_name(&value_element, value_dimensions);
+                self.throw_exception("java/lang/ArrayStoreException", Some(name.as_str()));
+                return;
+            }
+        }
+
+        array.store(index, value);
+        let frame = &mut self.frame_stack[frame_stack_len - 1];
+        frame.sp -= 3;
+        frame.pc += 1;
+    }
+
+    fn run_type_check(&mut self, inst: Inst::Code) {
+        let frame_stack_len = self.frame_stack.len();
+        let (frame_class, class_index, sp) = {
+            let frame = &self.frame_stack[frame_stack_len - 1];
+            let frame_class = unsafe { &*frame.class.unwrap() };
+            let code = unsafe { &*frame.method_info.code.as_ref().unwrap().code };
+            let class_index = ((code[frame.pc + 1] as usize) << 8) + code[frame.pc + 2] as usize;
+            (frame_class, class_index, frame.sp)
+        };
+
+        let name_index = fld!(
+            Constant::ClassInfo,
+            &frame_class.classfile.constant_pool[class_index],
+            name_index
+        );
+        let class_name = frame_class.classfile.constant_pool[name_index]
+            .get_utf8()
+            .unwrap();
+
+        let objectref = self.stack[self.bp + sp - 1];
+        if inst == Inst::instanceof {
+            self.stack[self.bp + sp - 1] =
+                (objectref != 0 && self.is_instance_of(objectref, class_name)) as u64;
+        } else if !self.check_cast(objectref, class_name) {
+            return;
+        }
+
+        self.frame_stack[frame_stack_len - 1].pc += 3;
+    }
+
+    /// Returns true if ``objectref`` is an instance of ``name``, which is a class name or an array
+    /// descriptor as in ``CONSTANT_Class``. ``objectref`` must not be null.
+    /// Classes aren't loaded or initialized for the check. No object is an instance of a class
+    /// that isn't loaded yet, since an object's class is loaded with its super types.
+    pub fn is_instance_of(&mut self, objectref: u64, name: &str) -> bool {
+        let (element, dimensions) = self.reference_type(objectref);
+        let target_dimensions = name.len() - name.trim_start_matches('[').len();
+        let element_name = match &name[target_dimensions..] {
+            element_name if target_dimensions == 0 => Some(element_name),
+            element_name if element_name.starts_with('L') => {
+                Some(&element_name[1..element_name.len() - 1])
+            }
+            _ => None,
+        };
+        if let Some(element_name) = element_name {
+            if unsafe { &*self.classheap }
+                .get_class(element_name)
+                .is_none()
+            {
+                return dimensions > target_dimensions && is_array_super_type(element_name);
+            }
+        }
+
+        let target = if name.starts_with('[') {
+            self.resolve_class_name(name)
+        } else {
+            AType::Class(self.load_class(name))
+        };
+        let (target_element, target_dimensions) = match &target {
+            AType::Multi {
+                element_type,
+                dimensions,
+            } => (&**element_type, *dimensions),
+            class => (class, 0),
+        };
+        is_assignable(&element, dimensions, target_element, target_dimensions)
+    }
+
+    /// Throws ``ClassCastException`` and returns false if non-null ``objectref`` is not an
+    /// instance of ``name``
+    pub fn check_cast(&mut self, objectref: u64, name: &str) -> bool {
+        if objectref == 0 || self.is_instance_of(objectref, name) {
+            return true;
+        }
+        let (element, dimensions) = self.reference_type(objectref);
+        let message = format!(
+            "{} cannot be cast to {}",
+            type_name(&element, dimensions),
+            name.replace("/", ".")
+        );
+        self.throw_exception("java/lang/ClassCastException", Some(message.as_str()));
+        false
+    }
+
+    // Returns the innermost element type and the number of array dimensions of ``objectref``
+    fn reference_type(&self, objectref: u64) -> (AType, usize) {
+        if unsafe { &*self.objectheap }.gc.is_array(objectref) {
+            let array = unsafe { &*(objectref as GcType<Array>) };
+            let (element, dimensions) = component_type(&array.atype);
+            (element.clone(), dimensions + 1)
+        } else {
+            let object = unsafe { &*(objectref as GcType<ObjectBody>) };
+            (AType::Class(object.class), 0)
+        }
+    }
+
+    /// Returns the name of the class of ``objectref`` as ``getClass().getName()`` does
+    pub fn class_name_of(&self, objectref: u64) -> String {
+        let (element, dimensions) = self.reference_type(objectref);
+        type_name(&element, dimensions)
+    }
+
     fn run_multianewarray(&mut self) {
         let frame_stack_len = self.frame_stack.len();
         let (frame_class, class_index, dimensions) = {
@@ -1416,11 +2303,15 @@ impl VM {
             .unwrap();
         let atype = self.resolve_class_name(class_name);
 
-        let frame = &mut self.frame_stack[frame_stack_len - 1];
+        let sp = self.frame_stack[frame_stack_len - 1].sp;
         let mut counts = vec![];
         for i in 0..dimensions {
-            counts.push(self.stack[self.bp + frame.sp - dimensions + i] as usize);
+            match self.array_size(self.stack[self.bp + sp - dimensions + i]) {
+                Some(count) => counts.push(count),
+                None => return,
+            }
         }
+        let frame = &mut self.frame_stack[frame_stack_len - 1];
         frame.sp -= dimensions;
         self.stack[self.bp + frame.sp] =
             unsafe { &mut *self.objectheap }.create_multi_array(atype, counts.iter());
@@ -1430,7 +2321,8 @@ impl VM {
         unsafe { &mut *self.objectheap }.gc.mark_and_sweep(self);
     }
 
-    fn resolve_class_name(&mut self, name: &str) -> AType {
+    /// Returns the ``AType`` of the elements of an array whose type is ``[`` followed by ``name``
+    pub fn resolve_class_name(&mut self, name: &str) -> AType {
         match name.chars().nth(0).unwrap() {
             '[' => match self.resolve_class_name(&name[1..]) {
                 AType::Multi {
@@ -1445,10 +2337,11 @@ impl VM {
                     dimensions: 1,
                 },
             },
-            'L' => AType::Class(self.load_class(&name[1..])),
+            'L' => AType::Class(self.load_class(&name[1..name.len() - 1])),
             'B' => AType::Byte,
             'C' => AType::Char,
             'I' => AType::Int,
+            'S' => AType::Short,
             'Z' => AType::Boolean,
             'F' => AType::Float,
             'J' => AType::Long,
@@ -1478,7 +2371,7 @@ impl VM {
             .get_utf8()
             .unwrap();
         let class = self.load_class(class_name);
-        let object = unsafe { &mut *self.objectheap }.create_object(class);
+        let object = unsafe { (*self.objectheap).create_object(class) };
 
         let frame = &mut self.frame_stack[frame_stack_len - 1];
         self.stack[self.bp + frame.sp] = object;
@@ -1495,56 +2388,229 @@ impl VM {
             return class;
         }
 
-        let filename = format!("./examples/{}.class", class_name);
+        let filename = self
+            .find_class_file(class_name)
+            .unwrap_or_else(|| format!("{}.class", class_name));
         self.load_class_by_file_name(filename.as_str())
     }
 
+    pub fn find_class_file(&self, class_name: &str) -> Option<String> {
+        unsafe { &*self.runtime_env }
+            .classpath
+            .iter()
+            .map(|path| format!("{}/{}.class", path, class_name))
+            .find(|filename| Path::new(filename).is_file())
+    }
+
     pub fn load_class_by_file_name(&mut self, file_name: &str) -> GcType<Class> {
         let class_ptr = unsafe { &mut *self.objectheap }.gc.alloc(Class::new());
 
         unsafe { (*class_ptr).classheap = Some(self.classheap) };
 
         expect!(
-            unsafe { &mut *self.classheap }.load_class(file_name, class_ptr),
+            unsafe { (*self.classheap).load_class(file_name, class_ptr) },
             format!("Could not load class file '{}'", file_name)
         );
 
-        let object = unsafe { &mut *self.objectheap }.create_object(class_ptr);
+        unsafe { self.define_class(class_ptr, file_name) }
+    }
+
+    /// Adds a class read from ``source`` to the class heap, loads the classes it depends on and
+    /// initializes it
+    ///
+    /// # Safety
+    ///
+    /// ``class_ptr`` must point to a parsed class allocated by the GC of this VM.
+    pub unsafe fn define_class(&mut self, class_ptr: GcType<Class>, source: &str) -> GcType<Class> {
+        unsafe { (*self.classheap).add_class(class_ptr) };
+
+        // Fields are numbered after those of the super class
+        if let Some(super_class_name) = unsafe { &*class_ptr }.get_super_class_name().cloned() {
+            self.load_class(super_class_name.as_str());
+        }
+        // Interfaces are needed to check subtypes
+        let interface_names: Vec<String> = unsafe { &*class_ptr }
+            .get_interface_names()
+            .into_iter()
+            .cloned()
+            .collect();
+        for name in interface_names {
+            self.load_class(name.as_str());
+        }
+        unsafe { &mut *class_ptr }.number_fields();
+
+        if unsafe { &*self.runtime_env }.is_verbose(Verbose::Class) {
+            println!(
+                "[Loaded {} from {}]",
+                unsafe { &*class_ptr }.get_name().unwrap().replace("/", "."),
+                source
+            );
+        }
+
+        // Initialization with ``static { ... }``. ``<clinit>`` of super classes has already been
+        // run when they were loaded.
+        if let Some((class, method)) = unsafe { &*class_ptr }
+            .get_method("<clinit>", "()V")
+            .filter(|(class, _)| *class == class_ptr)
+        {
+            self.invoke_method(class, method, &[]);
+        }
+
+        class_ptr
+    }
+
+    /// Runs ``f`` with ``slots`` pushed on the operand stack of the current frame, which GC
+    /// traces. Keeps references that only native code holds alive while ``f`` may run Java code.
+    pub fn with_roots<R>(&mut self, slots: &[u64], f: impl FnOnce(&mut VM) -> R) -> R {
+        let sp = self.frame_stack.last().unwrap().sp;
+        self.stack[self.bp + sp..self.bp + sp + slots.len()].copy_from_slice(slots);
+        self.frame_stack.last_mut().unwrap().sp = sp + slots.len();
+        let ret = f(self);
+        self.frame_stack.last_mut().unwrap().sp = sp;
+        ret
+    }
 
+    /// Runs ``method`` on top of the current frame. ``args`` are laid out as on the operand stack
+    /// (including ``this`` for instance methods). Returns the first slot of the returned value.
+    pub fn invoke_method(&mut self, class: GcType<Class>, method: MethodInfo, args: &[u64]) -> u64 {
         let cur_sp = self.frame_stack.last().unwrap().sp;
         let save_bp = self.bp;
 
         self.bp = save_bp + cur_sp;
-        self.stack[self.bp] = object;
-
-        // TODO: Support initializer whose descriptor is not '()V'
-        if let Some((class, method)) = unsafe { &*class_ptr }.get_method("<init>", "()V") {
-            let mut frame = Frame::new();
-            frame.class = Some(class);
-            frame.method_info = method;
-            frame.sp = frame.method_info.code.as_ref().unwrap().max_locals as usize;
-
-            self.frame_stack.push(frame);
-            self.bp = save_bp + cur_sp;
-            self.run();
-            self.frame_stack.pop();
+        self.stack[self.bp..self.bp + args.len()].copy_from_slice(args);
+
+        let mut frame = Frame::new();
+        frame.class = Some(class);
+        frame.sp = if method.check_access_flags(method::access_flags::ACC_PACC_NATIVE) {
+            args.len()
+        } else {
+            method.code.as_ref().unwrap().max_locals as usize
+        };
+        frame.method_info = method;
+
+        self.frame_stack.push(frame);
+        self.run();
+        self.frame_stack.pop();
+
+        let ret = self.stack[self.bp];
+        self.bp = save_bp;
+        ret
+    }
+}
+
+/// Formats an exception like ``java.lang.IllegalStateException: message``
+// Splits the ``atype`` of an array into the innermost element type and the number of array
+// dimensions of its elements
+fn component_type(atype: &AType) -> (&AType, usize) {
+    match atype {
+        AType::Multi {
+            element_type,
+            dimensions,
+        } => (element_type, dimensions - 1),
+        element => (element, 0),
+    }
+}
+
+// Returns true if a reference to ``element`` wrapped in ``dimensions`` arrays can be assigned to
+// one of ``target_element`` wrapped in ``target_dimensions`` arrays
+fn is_assignable(
+    element: &AType,
+    dimensions: usize,
+    target_element: &AType,
+    target_dimensions: usize,
+) -> bool {
+    if dimensions == target_dimensions {
+        return match (element, target_element) {
+            (AType::Class(class), AType::Class(target)) => {
+                let target_name = unsafe { &**target }.get_name().unwrap();
+                unsafe { &**class }.is_subclass_of(target_name)
+            }
+            (AType::Class(_), _) | (_, AType::Class(_)) => false,
+            (element, target_element) => element.to_num() == target_element.to_num(),
+        };
+    }
+
+    // Every array is an ``Object``, a ``Cloneable`` and a ``Serializable``
+    dimensions > target_dimensions
+        && match target_element {
+            AType::Class(target) => is_array_super_type(unsafe { &**target }.get_name().unwrap()),
+            _ => false,
         }
+}
+
+// Returns true if ``name`` is a class that every array is an instance of
+fn is_array_super_type(name: &str) -> bool {
+    matches!(
+        name,
+        "java/lang/Object" | "java/lang/Cloneable" | "java/io/Serializable"
+    )
+}
 
-        // Initialization with ``static { ... }``
-        if let Some((class, method)) = unsafe { &*class_ptr }.get_method("<clinit>", "()V") {
-            let mut frame = Frame::new();
-            frame.class = Some(class);
-            frame.method_info = method;
-            frame.sp = frame.method_info.code.as_ref().unwrap().max_locals as usize;
-            self.frame_stack.push(frame);
-            self.bp = save_bp + cur_sp;
-            self.run();
-            self.frame_stack.pop();
+// Returns the name of a type as ``Class.getName()`` does
+fn type_name(element: &AType, dimensions: usize) -> String {
+    let element = match element {
+        AType::Class(class) => {
+            let name = unsafe { &**class }.get_name().unwrap().replace("/", ".");
+            if dimensions == 0 {
+                return name;
+            }
+            format!("L{};", name)
         }
+        AType::Boolean => "Z".to_string(),
+        AType::Char => "C".to_string(),
+        AType::Float => "F".to_string(),
+        AType::Double => "D".to_string(),
+        AType::Byte => "B".to_string(),
+        AType::Short => "S".to_string(),
+        AType::Int => "I".to_string(),
+        AType::Long => "J".to_string(),
+        AType::Multi { .. } => unreachable!(),
+    };
+    format!("{}{}", "[".repeat(dimensions), element)
+}
 
-        self.bp = save_bp;
+pub fn describe_exception(exception: u64) -> String {
+    let exception = unsafe { &*(exception as GcType<ObjectBody>) };
+    let class = unsafe { &*exception.class };
+    let class_name = class.get_name().unwrap().replace("/", ".");
+    let message = class
+        .get_numbered_field_info("detailMessage")
+        .map(|(id, _)| exception.variables[*id])
+        .unwrap_or(0);
+    if message == 0 {
+        class_name
+    } else {
+        format!(
+            "{}: {}",
+            class_name,
+            unsafe { &*(message as GcType<ObjectBody>) }.get_string()
+        )
+    }
+}
 
-        class_ptr
+// Reports a method (or a loop starting at ``loop_start`` in it) compiled with ``-verbose:jit``
+fn report_jit_compiled(
+    runtime_env: &RuntimeEnvironment,
+    class: &Class,
+    method: &MethodInfo,
+    loop_start: Option<usize>,
+) {
+    if !runtime_env.is_verbose(Verbose::Jit) {
+        return;
+    }
+    let signature = format!(
+        "{}.{}:{}",
+        class.get_name().unwrap().replace("/", "."),
+        class
+            .get_utf8_from_const_pool(method.name_index as usize)
+            .unwrap(),
+        class
+            .get_utf8_from_const_pool(method.descriptor_index as usize)
+            .unwrap()
+    );
+    match loop_start {
+        Some(start) => println!("[JIT compiled loop at {} in {}]", start, signature),
+        None => println!("[JIT compiled {}]", signature),
     }
 }
 
@@ -1552,6 +2618,10 @@ fn count_params(descriptor: &str) -> usize {
     let mut count = 0usize;
     let mut i = 1;
     while i < descriptor.len() {
+        // An array is a reference whatever its element type is
+        while descriptor.chars().nth(i).unwrap() == '[' {
+            i += 1
+        }
         if descriptor.chars().nth(i).unwrap() == 'L' {
             while descriptor.chars().nth(i).unwrap() != ';' {
                 i += 1
@@ -1560,7 +2630,9 @@ fn count_params(descriptor: &str) -> usize {
         if descriptor.chars().nth(i).unwrap() == ')' {
             break;
         }
-        if descriptor.chars().nth(i).unwrap() == 'J' || descriptor.chars().nth(i).unwrap() == 'D' {
+        if (descriptor.chars().nth(i).unwrap() == 'J' || descriptor.chars().nth(i).unwrap() == 'D')
+            && descriptor.chars().nth(i - 1).unwrap() != '['
+        {
             count += 1;
         }
         count += 1;
@@ -1571,12 +2643,128 @@ fn count_params(descriptor: &str) -> usize {
 
 #[inline]
 pub fn d2u(f: f64) -> u64 {
-    unsafe { transmute::<f64, u64>(f) }
+    f.to_bits()
 }
 
 #[inline]
 pub fn u2d(u: u64) -> f64 {
-    unsafe { transmute::<u64, f64>(u) }
+    f64::from_bits(u)
+}
+
+// A float occupies the low 32 bits of a slot
+#[inline]
+pub fn f2u(f: f32) -> u64 {
+    f.to_bits() as u64
+}
+
+#[inline]
+pub fn u2f(u: u64) -> f32 {
+    f32::from_bits(u as u32)
+}
+
+/// Operands of ``tableswitch`` and ``lookupswitch``. Offsets are relative to the switch itself.
+#[derive(Debug, Clone)]
+pub struct SwitchTable {
+    pub default: isize,
+    pub cases: Vec<(i32, isize)>,
+    pub size: usize,
+}
+
+impl SwitchTable {
+    /// Reads the switch at ``code[pc]``. ``start`` is the position of ``code[0]`` in the method.
+    pub fn read(code: &[Inst::Code], pc: usize, start: usize) -> Self {
+        let operands = Self::operands(pc, start);
+        let default = read_i32(code, operands) as isize;
+        let (cases, end) = match code[pc] {
+            Inst::tableswitch => {
+                let low = read_i32(code, operands + 4);
+                let high = read_i32(code, operands + 8);
+                let cases: Vec<(i32, isize)> = (low..=high)
+                    .enumerate()
+                    .map(|(i, key)| (key, read_i32(code, operands + 12 + i * 4) as isize))
+                    .collect();
+                let end = operands + 12 + cases.len() * 4;
+                (cases, end)
+            }
+            Inst::lookupswitch => {
+                let npairs = read_i32(code, operands + 4) as usize;
+                let pairs = operands + 8;
+                let cases = (0..npairs)
+                    .map(|i| {
+                        let key = read_i32(code, pairs + i * 8);
+                        (key, read_i32(code, pairs + i * 8 + 4) as isize)
+                    })
+                    .collect();
+                (cases, pairs + npairs * 8)
+            }
+            _ => unreachable!(),
+        };
+        SwitchTable {
+            default,
+            cases,
+            size: end - pc,
+        }
+    }
+
+    /// Returns the offset the switch at ``code[pc]`` jumps by for ``key`` without reading the
+    /// whole table. ``code`` must be the entire code of the method.
+    pub fn lookup(code: &[Inst::Code], pc: usize, key: i32) -> isize {
+        let operands = Self::operands(pc, 0);
+        let offset = match code[pc] {
+            Inst::tableswitch => {
+                let low = read_i32(code, operands + 4);
+                let high = read_i32(code, operands + 8);
+                if low <= key && key <= high {
+                    let i = (key as i64 - low as i64) as usize;
+                    read_i32(code, operands + 12 + i * 4)
+                } else {
+                    read_i32(code, operands)
+                }
+            }
+            Inst::lookupswitch => {
+                // The pairs are sorted by key
+                let pairs = operands + 8;
+                let (mut lo, mut hi) = (0, read_i32(code, operands + 4) as usize);
+                loop {
+                    if lo >= hi {
+                        break read_i32(code, operands);
+                    }
+                    let mid = (lo + hi) / 2;
+                    let mid_key = read_i32(code, pairs + mid * 8);
+                    if mid_key == key {
+                        break read_i32(code, pairs + mid * 8 + 4);
+                    } else if mid_key < key {
+                        lo = mid + 1;
+                    } else {
+                        hi = mid;
+                    }
+                }
+            }
+            _ => unreachable!(),
+        };
+        offset as isize
+    }
+
+    /// Returns the positions the switch at ``pc`` may jump to, the default one first.
+    pub fn destinations(&self, pc: usize) -> Vec<usize> {
+        let mut destinations = vec![(pc as isize + self.default) as usize];
+        for (_, offset) in &self.cases {
+            let dst = (pc as isize + offset) as usize;
+            if !destinations.contains(&dst) {
+                destinations.push(dst);
+            }
+        }
+        destinations
+    }
+
+    // Operands begin at the first 4-byte boundary after the opcode
+    fn operands(pc: usize, start: usize) -> usize {
+        pc + 1 + (3 - (start + pc) % 4)
+    }
+}
+
+fn read_i32(code: &[Inst::Code], i: usize) -> i32 {
+    i32::from_be_bytes([code[i], code[i + 1], code[i + 2], code[i + 3]])
 }
 
 #[rustfmt::skip]
@@ -1584,6 +2772,7 @@ pub fn u2d(u: u64) -> f64 {
 #[allow(non_snake_case)]
 pub mod Inst {
     pub type Code = u8;
+    pub const nop:          u8 = 0;
     pub const aconst_null:  u8 = 1;
     pub const iconst_m1:    u8 = 2;
     pub const iconst_0:     u8 = 3;
@@ -1592,33 +2781,63 @@ pub mod Inst {
     pub const iconst_3:     u8 = 6;
     pub const iconst_4:     u8 = 7;
     pub const iconst_5:     u8 = 8;
+    pub const lconst_0:     u8 = 9;
+    pub const lconst_1:     u8 = 10;
+    pub const fconst_0:     u8 = 11;
+    pub const fconst_1:     u8 = 12;
+    pub const fconst_2:     u8 = 13;
     pub const dconst_0:     u8 = 14;
     pub const dconst_1:     u8 = 15;
     pub const bipush:       u8 = 16;
     pub const sipush:       u8 = 17;
     pub const ldc:          u8 = 18;
+    pub const ldc_w:        u8 = 19;
     pub const ldc2_w:       u8 = 20;
     pub const iload:        u8 = 21;
+    pub const lload:        u8 = 22;
+    pub const fload:        u8 = 23;
     pub const dload:        u8 = 24;
     pub const aload_0:      u8 = 42;
     pub const aload_1:      u8 = 43;
     pub const aload_2:      u8 = 44;
     pub const aload_3:      u8 = 45;
+    pub const caload:       u8 = 52;
+    pub const saload:       u8 = 53;
     pub const istore:       u8 = 54;
+    pub const lstore:       u8 = 55;
+    pub const fstore:       u8 = 56;
     pub const istore_0:     u8 = 59;
     pub const istore_1:     u8 = 60;
     pub const istore_2:     u8 = 61;
     pub const istore_3:     u8 = 62;
+    pub const lstore_0:     u8 = 63;
+    pub const lstore_1:     u8 = 64;
+    pub const lstore_2:     u8 = 65;
+    pub const lstore_3:     u8 = 66;
+    pub const fstore_0:     u8 = 67;
+    pub const fstore_1:     u8 = 68;
+    pub const fstore_2:     u8 = 69;
+    pub const fstore_3:     u8 = 70;
     pub const aload:        u8 = 25;
     pub const iload_0:      u8 = 26;
     pub const iload_1:      u8 = 27;
     pub const iload_2:      u8 = 28;
     pub const iload_3:      u8 = 29;
+    pub const lload_0:      u8 = 30;
+    pub const lload_1:      u8 = 31;
+    pub const lload_2:      u8 = 32;
+    pub const lload_3:      u8 = 33;
+    pub const fload_0:      u8 = 34;
+    pub const fload_1:      u8 = 35;
+    pub const fload_2:      u8 = 36;
+    pub const fload_3:      u8 = 37;
     pub const dload_0:      u8 = 38;
     pub const dload_1:      u8 = 39;
     pub const dload_2:      u8 = 40;
     pub const dload_3:      u8 = 41;
     pub const iaload:       u8 = 46;
+    pub const laload:       u8 = 47;
+    pub const faload:       u8 = 48;
     pub const daload:       u8 = 49;
     pub const aaload:       u8 = 50;
     pub const baload:       u8 = 51;
@@ -1633,48 +2852,101 @@ pub mod Inst {
     pub const astore_2:     u8 = 77;
     pub const astore_3:     u8 = 78;
     pub const iastore:      u8 = 79;
+    pub const lastore:      u8 = 80;
+    pub const fastore:      u8 = 81;
     pub const dastore:      u8 = 82;
     pub const aastore:      u8 = 83;
     pub const bastore:      u8 = 84;
+    pub const castore:      u8 = 85;
+    pub const sastore:      u8 = 86;
     pub const pop:          u8 = 87;
     pub const pop2:         u8 = 88;
     pub const dup:          u8 = 89;
     pub const dup_x1:       u8 = 90;
+    pub const dup_x2:       u8 = 91;
     pub const dup2:         u8 = 92;
     pub const dup2_x1:      u8 = 93;
+    pub const dup2_x2:      u8 = 94;
+    pub const swap:         u8 = 95;
     pub const iadd:         u8 = 96;
+    pub const ladd:         u8 = 97;
+    pub const fadd:         u8 = 98;
     pub const dadd:         u8 = 99;
     pub const isub:         u8 = 100;
+    pub const lsub:         u8 = 101;
+    pub const fsub:         u8 = 102;
     pub const dsub:         u8 = 103;
     pub const imul:         u8 = 104;
+    pub const lmul:         u8 = 105;
+    pub const fmul:         u8 = 106;
     pub const dmul:         u8 = 107;
     pub const idiv:         u8 = 108;
+    pub const ldiv:         u8 = 109;
+    pub const fdiv:         u8 = 110;
     pub const ddiv:         u8 = 111;
     pub const irem:         u8 = 112;
+    pub const lrem:         u8 = 113;
+    pub const frem:         u8 = 114;
+    pub const drem:         u8 = 115;
+    pub const ineg:         u8 = 116;
+    pub const lneg:         u8 = 117;
+    pub const fneg:         u8 = 118;
     pub const dneg:         u8 = 119;
     pub const ishl:         u8 = 120;
+    pub const lshl:         u8 = 121;
     pub const ishr:         u8 = 122;
+    pub const lshr:         u8 = 123;
+    pub const iushr:        u8 = 124;
+    pub const lushr:        u8 = 125;
     pub const iand:         u8 = 126;
+    pub const land:         u8 = 127;
+    pub const ior:          u8 = 128;
+    pub const lor:          u8 = 129;
     pub const ixor:         u8 = 130;
+    pub const lxor:         u8 = 131;
     pub const iinc:         u8 = 132;
+    pub const i2l:          u8 = 133;
+    pub const i2f:          u8 = 134;
     pub const i2d:          u8 = 135;
+    pub const l2i:          u8 = 136;
+    pub const l2f:          u8 = 137;
+    pub const l2d:          u8 = 138;
+    pub const f2i:          u8 = 139;
+    pub const f2l:          u8 = 140;
+    pub const f2d:          u8 = 141;
     pub const d2i:          u8 = 142;
+    pub const d2l:          u8 = 143;
+    pub const d2f:          u8 = 144;
+    pub const i2b:          u8 = 145;
+    pub const i2c:          u8 = 146;
     pub const i2s:          u8 = 147;
+    pub const lcmp:         u8 = 148;
+    pub const fcmpl:        u8 = 149;
+    pub const fcmpg:        u8 = 150;
     pub const dcmpl:        u8 = 151;
     pub const dcmpg:        u8 = 152;
     pub const ifeq:         u8 = 153;
     pub const ifne:         u8 = 154;
     pub const iflt:         u8 = 155;
     pub const ifge:         u8 = 156;
+    pub const ifgt:         u8 = 157;
     pub const ifle:         u8 = 158;
     pub const if_icmpeq:    u8 = 159;
     pub const if_icmpne:    u8 = 160;
+    pub const if_icmplt:    u8 = 161;
     pub const if_icmpge:    u8 = 162;
     pub const if_icmpgt:    u8 = 163;
-    pub const if_icmplt:    u8 = 164;
+    pub const if_icmple:    u8 = 164;
+    pub const if_acmpeq:    u8 = 165;
     pub const if_acmpne:    u8 = 166;
     pub const goto:         u8 = 167;
+    pub const jsr:          u8 = 168;
+    pub const ret:          u8 = 169;
+    pub const tableswitch:  u8 = 170;
+    pub const lookupswitch: u8 = 171;
     pub const ireturn:      u8 = 172;
+    pub const lreturn:      u8 = 173;
+    pub const freturn:      u8 = 174;
     pub const dreturn:      u8 = 175;
     pub const areturn:      u8 = 176;
     pub const return_:      u8 = 177;
@@ -1685,15 +2957,22 @@ pub mod Inst {
     pub const invokevirtual:u8 = 182;
     pub const invokespecial:u8 = 183;
     pub const invokestatic: u8 = 184;
+    pub const invokeinterface:u8 = 185;
+    pub const invokedynamic:u8 = 186;
     pub const new:          u8 = 187;
     pub const newarray:     u8 = 188;
     pub const anewarray:    u8 = 189;
     pub const arraylength:  u8 = 190;
+    pub const athrow:       u8 = 191;
     pub const checkcast:    u8 = 192;
+    pub const instanceof:   u8 = 193;
     pub const monitorenter: u8 = 194;
+    pub const monitorexit:  u8 = 195;
+    pub const wide:         u8 = 196;
     pub const multianearray:u8 = 197;
     pub const ifnull:       u8 = 198;
     pub const ifnonnull:    u8 = 199;
+    pub const jsr_w:        u8 = 201;
     // Quick opcodes (faster)
     pub const getfield_quick: u8 = 204;
     pub const putfield_quick: u8 = 205;
@@ -1709,15 +2988,36 @@ pub mod Inst {
                 | astore_3 | iaload | aaload | daload | baload | iastore | aastore | dastore | bastore
                 | iadd | isub | imul | irem | iand | idiv
                 | dadd | dsub | dmul | ddiv | dneg | i2d | i2s | pop | pop2 | dcmpl | dcmpg | dup
-                | ireturn | dreturn | areturn | return_ | monitorenter | aconst_null | arraylength 
-                | ishl | ishr | ixor | dup_x1 | d2i | dup2 | dup2_x1 => 1,
-            dstore | astore | istore | ldc | aload | dload | iload | bipush | newarray => 2,
+                | ireturn | dreturn | areturn | return_ | monitorenter | monitorexit | aconst_null | arraylength 
+                | athrow | ishl | ishr | ixor | dup_x1 | d2i | dup2 | dup2_x1
+                | lconst_0 | lconst_1 | lload_0 | lload_1 | lload_2 | lload_3 | lstore_0 | lstore_1
+                | lstore_2 | lstore_3 | laload | lastore | ladd | lsub | lmul | ldiv | lrem | lneg
+                | lshl | lshr | lushr | land | lor | lxor | i2l | l2i | l2d | d2l | lcmp | lreturn
+                | fconst_0 | fconst_1 | fconst_2 | fload_0 | fload_1 | fload_2 | fload_3 | fstore_0
+                | fstore_1 | fstore_2 | fstore_3 | faload | fastore | fadd | fsub | fmul | fdiv | frem
+                | fneg | i2f | l2f | f2i | f2l | f2d | d2f | fcmpl | fcmpg | freturn
+                | nop | caload | saload | castore | sastore | dup_x2 | dup2_x2 | swap | drem | ineg
+                | iushr | ior | i2b | i2c => 1,
+            dstore | astore | istore | ldc | aload | dload | iload | lload | lstore | fload | fstore
+                | bipush | newarray | ret => 2,
             sipush | ldc2_w | iinc | invokestatic | invokespecial | invokevirtual | new | anewarray 
-                | goto | ifeq | iflt | ifne | ifle | ifge | if_icmpne | if_icmpge | if_icmpgt | if_icmpeq | if_acmpne | if_icmplt |
-                ifnull | ifnonnull | checkcast |
+                | goto | jsr | ldc_w | if_acmpeq | ifeq | iflt | ifne | ifle | ifge | ifgt | if_icmpne | if_icmpge | if_icmpgt | if_icmpeq | if_acmpne | if_icmplt | if_icmple |
+                ifnull | ifnonnull | checkcast | instanceof |
                 getstatic | putstatic | getfield | putfield | getfield_quick | putfield_quick | getfield2_quick | putfield2_quick => 3, 
             multianearray => 4,
+            invokeinterface | invokedynamic | jsr_w => 5,
             e => unimplemented!("{}", e),
         }
     }
+
+    // ``start`` is the position of ``code[0]`` in the method, which the operands of the switches
+    // are aligned against
+    pub fn get_inst_size_at(code: &[Code], pc: usize, start: usize) -> usize {
+        match code[pc] {
+            tableswitch | lookupswitch => super::SwitchTable::read(code, pc, start).size,
+            wide if code[pc + 1] == iinc => 6,
+            wide => 4,
+            inst => get_inst_size(inst),
+        }
+    }
 }
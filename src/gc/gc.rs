@@ -3,10 +3,12 @@
 use super::super::class::{class::Class, classfile::constant::Constant, classheap::ClassHeap};
 use super::super::exec::{
     frame::{AType, Array, Frame, ObjectBody},
-    vm::{RuntimeEnvironment, VM},
+    thread::ThreadManager,
+    vm::{RuntimeEnvironment, Verbose, VM},
 };
 use rustc_hash::FxHashMap;
 use std::mem;
+use std::time::Instant;
 
 pub type GcType<T> = *mut T;
 
@@ -26,6 +28,7 @@ enum GcTargetType {
     ClassHeap,
     ObjectHeap,
     RuntimeEnvironment,
+    ThreadManager,
     Unknown,
 }
 
@@ -45,6 +48,7 @@ impl GcTargetInfo {
                 s if s.ends_with("ClassHeap") => GcTargetType::ClassHeap,
                 s if s.ends_with("ObjectHeap") => GcTargetType::ObjectHeap,
                 s if s.ends_with("RuntimeEnvironment") => GcTargetType::RuntimeEnvironment,
+                s if s.ends_with("ThreadManager") => GcTargetType::ThreadManager,
                 _ => GcTargetType::Unknown,
             },
             state: GcState::Unmarked,
@@ -56,7 +60,19 @@ impl GcTargetInfo {
 pub struct GC {
     allocated_memory: GcStateMap,
     allocated_memory_size_in_byte: usize,
+    // Objects are collected once more memory than this is allocated
+    threshold_in_byte: usize,
     gc_disabled: bool,
+    // The number of compiled methods and loops running in any thread. The references they hold
+    // are in registers and native stack frames, which aren't traced, so nothing is collected
+    // until they all return.
+    compiled_code_running: usize,
+}
+
+impl Default for GC {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GC {
@@ -64,48 +80,56 @@ impl GC {
         GC {
             allocated_memory: GcStateMap::default(),
             allocated_memory_size_in_byte: 0,
+            threshold_in_byte: 10 * 1024 * 1024,
             gc_disabled: false,
+            compiled_code_running: 0,
         }
     }
 
+    /// Sets the size of the heap that triggers collection (``-Xmx``). It's 10 MiB by default.
+    pub fn set_threshold(&mut self, size_in_byte: usize) {
+        self.threshold_in_byte = size_in_byte;
+    }
+
     pub fn alloc<T>(&mut self, val: T) -> GcType<T> {
         let size = mem::size_of_val(&val);
         let ptr = Box::into_raw(Box::new(val));
-        let info = GcTargetInfo::new_unmarked(unsafe { std::intrinsics::type_name::<T>() });
+        let info = GcTargetInfo::new_unmarked(std::any::type_name::<T>());
         self.allocated_memory_size_in_byte += size;
         self.allocated_memory.insert(ptr as *mut u64, info);
         ptr
     }
 
     pub fn mark_and_sweep(&mut self, vm: &VM) {
-        if self.gc_disabled {
+        if self.gc_disabled || self.compiled_code_running > 0 {
             return;
         }
 
-        let over10mib_allocated = self.allocated_memory_size_in_byte > 10 * 1024 * 1024;
-        if !over10mib_allocated {
+        let over_threshold = self.allocated_memory_size_in_byte > self.threshold_in_byte;
+        if !over_threshold {
             return;
         }
 
+        let verbose = unsafe { &*vm.runtime_env }.is_verbose(Verbose::Gc);
+        let start = Instant::now();
+        let size_before = self.allocated_memory_size_in_byte;
+
         let mut m = GcStateMap::default();
-        self.trace(&vm, &mut m);
+        self.trace(vm, &mut m);
         self.free(&m);
-    }
 
-    fn trace(&mut self, vm: &VM, m: &mut GcStateMap) {
-        trace_ptr(&mut self.allocated_memory, m, vm.runtime_env as *mut u64);
-        trace_ptr(&mut self.allocated_memory, m, vm.classheap as *mut u64);
-        trace_ptr(&mut self.allocated_memory, m, vm.objectheap as *mut u64);
-
-        // trace frame stack
-        for frame in &vm.frame_stack {
-            frame.trace(&mut self.allocated_memory, m);
+        if verbose {
+            println!(
+                "[GC {}K->{}K, {:.7} secs]",
+                size_before / 1024,
+                self.allocated_memory_size_in_byte / 1024,
+                start.elapsed().as_secs_f64()
+            );
         }
+    }
 
-        // trace variable stack
-        for val in &vm.stack {
-            trace_ptr(&mut self.allocated_memory, m, *val as *mut u64);
-        }
+    fn trace(&mut self, vm: &VM, m: &mut GcStateMap) {
+        vm.trace(&mut self.allocated_memory, m);
     }
 
     fn free(&mut self, m: &GcStateMap) {
@@ -114,7 +138,7 @@ impl GC {
         self.allocated_memory.retain(|p, info| {
             let is_marked = m
                 .get(p)
-                .and_then(|info| Some(info.state == GcState::Marked))
+                .map(|info| info.state == GcState::Marked)
                 .unwrap_or(false);
             if !is_marked {
                 let released_size = free_ptr(*p, info);
@@ -128,6 +152,59 @@ impl GC {
         }
     }
 
+    /// Frees everything allocated, including the heaps and the runtime environment, which are
+    /// never collected.
+    ///
+    /// # Safety
+    ///
+    /// Nothing allocated by this GC may be used afterward.
+    pub unsafe fn free_all(&mut self) {
+        for (ptr, info) in mem::take(&mut self.allocated_memory) {
+            match info.ty {
+                GcTargetType::ClassHeap => drop(Box::from_raw(ptr as *mut ClassHeap)),
+                GcTargetType::RuntimeEnvironment => {
+                    drop(Box::from_raw(ptr as *mut RuntimeEnvironment))
+                }
+                GcTargetType::ThreadManager => drop(Box::from_raw(ptr as *mut ThreadManager)),
+                _ => {
+                    free_ptr(ptr, &info);
+                }
+            }
+        }
+        self.allocated_memory_size_in_byte = 0;
+    }
+
+    /// Returns true if ``ptr`` is an object (not an array) allocated by this GC
+    pub fn is_object(&self, ptr: u64) -> bool {
+        matches!(
+            self.allocated_memory.get(&(ptr as *mut u64)),
+            Some(GcTargetInfo {
+                ty: GcTargetType::Object,
+                ..
+            })
+        )
+    }
+
+    pub fn is_array(&self, ptr: u64) -> bool {
+        matches!(
+            self.allocated_memory.get(&(ptr as *mut u64)),
+            Some(GcTargetInfo {
+                ty: GcTargetType::Array,
+                ..
+            })
+        )
+    }
+
+    /// Called before running compiled code, which may give the VM lock to other threads or call
+    /// back into the interpreter
+    pub fn enter_compiled_code(&mut self) {
+        self.compiled_code_running += 1;
+    }
+
+    pub fn leave_compiled_code(&mut self) {
+        self.compiled_code_running -= 1;
+    }
+
     pub fn enable(&mut self) {
         self.gc_disabled = false;
     }
@@ -137,6 +214,36 @@ impl GC {
     }
 }
 
+impl VM {
+    fn trace(&self, allocated: &mut GcStateMap, m: &mut GcStateMap) {
+        trace_ptr(allocated, m, self.runtime_env as *mut u64);
+        trace_ptr(allocated, m, self.classheap as *mut u64);
+        trace_ptr(allocated, m, self.objectheap as *mut u64);
+        // Other threads are traced from here
+        trace_ptr(allocated, m, self.thread_manager as *mut u64);
+
+        // trace frame stack
+        for frame in &self.frame_stack {
+            frame.trace(allocated, m);
+        }
+
+        // trace variable stack
+        for val in &self.stack {
+            trace_ptr(allocated, m, *val as *mut u64);
+        }
+
+        if let Some(exception) = self.exception {
+            trace_ptr(allocated, m, exception as *mut u64);
+        }
+
+        for frame in &self.jni_local_frames {
+            for object in frame {
+                trace_ptr(allocated, m, *object as *mut u64);
+            }
+        }
+    }
+}
+
 impl Frame {
     fn trace(&self, allocated: &mut GcStateMap, m: &mut GcStateMap) {
         if let Some(class) = self.class {
@@ -151,20 +258,22 @@ impl Class {
             .iter()
             .for_each(|(_, v)| trace_ptr(allocated, traced, *v as *mut u64));
         for constant in &self.classfile.constant_pool {
-            match constant {
-                Constant::Utf8 { java_string, .. } => {
-                    if let Some(java_string) = java_string {
-                        trace_ptr(allocated, traced, *java_string as *mut u64);
-                    }
-                }
-                _ => {}
+            if let Constant::Utf8 {
+                java_string: Some(java_string),
+                ..
+            } = constant
+            {
+                trace_ptr(allocated, traced, *java_string as *mut u64);
             }
         }
+        for method_handle in self.method_handles.values() {
+            trace_ptr(allocated, traced, *method_handle as *mut u64);
+        }
     }
 }
 
 fn trace_ptr(allocated: &mut GcStateMap, m: &mut GcStateMap, ptr: *mut u64) {
-    if ptr == 0 as *mut u64 {
+    if ptr.is_null() {
         return;
     }
 
@@ -172,7 +281,7 @@ fn trace_ptr(allocated: &mut GcStateMap, m: &mut GcStateMap, ptr: *mut u64) {
         return;
     }
 
-    let mut info = if let Some(info) = allocated.get(&ptr).map(|x| *x) {
+    let mut info = if let Some(info) = allocated.get(&ptr).copied() {
         info
     } else {
         return;
@@ -187,14 +296,11 @@ fn trace_ptr(allocated: &mut GcStateMap, m: &mut GcStateMap, ptr: *mut u64) {
         GcTargetType::Array => {
             // TODO: FIX
             let ary = unsafe { &*(ptr as *mut Array) };
-            match ary.atype {
-                AType::Class(_) => {
-                    let len = ary.get_length();
-                    for i in 0..len {
-                        trace_ptr(allocated, m, ary.at::<u64>(i as isize) as *mut u64);
-                    }
+            if let AType::Class(_) = ary.atype {
+                let len = ary.get_length();
+                for i in 0..len {
+                    trace_ptr(allocated, m, ary.at::<u64>(i as isize) as *mut u64);
                 }
-                _ => {}
             }
         }
         GcTargetType::Object => {
@@ -210,15 +316,38 @@ fn trace_ptr(allocated: &mut GcStateMap, m: &mut GcStateMap, ptr: *mut u64) {
         }
         GcTargetType::ClassHeap => {
             let classheap = unsafe { &*(ptr as *mut ClassHeap) };
-            for (_, class_ptr) in &classheap.class_map {
+            for class_ptr in classheap.class_map.values() {
                 trace_ptr(allocated, m, *class_ptr as *mut u64);
             }
+            for mirror in classheap.mirrors.values() {
+                trace_ptr(allocated, m, *mirror as *mut u64);
+            }
+            // Interned strings are never collected (see ``ClassHeap::strings``)
+            for string in classheap.strings.values() {
+                trace_ptr(allocated, m, *string as *mut u64);
+            }
         }
         GcTargetType::ObjectHeap => {}
         GcTargetType::RuntimeEnvironment => {
             let renv = unsafe { &*(ptr as *mut RuntimeEnvironment) };
             trace_ptr(allocated, m, renv.classheap as *mut u64);
             trace_ptr(allocated, m, renv.objectheap as *mut u64);
+            trace_ptr(allocated, m, renv.thread_manager as *mut u64);
+            for object in renv.jni.global_refs.keys() {
+                trace_ptr(allocated, m, *object as *mut u64);
+            }
+            for method_type in renv.method_types.values() {
+                trace_ptr(allocated, m, *method_type as *mut u64);
+            }
+        }
+        GcTargetType::ThreadManager => {
+            let thread_manager = unsafe { &*(ptr as *mut ThreadManager) };
+            for thread in thread_manager.threads.values() {
+                trace_ptr(allocated, m, thread.object as *mut u64);
+                if thread.alive && !thread.vm.is_null() {
+                    unsafe { &*thread.vm }.trace(allocated, m);
+                }
+            }
         }
         GcTargetType::Unknown => panic!(),
     };
@@ -234,6 +363,7 @@ fn free_ptr(ptr: *mut u64, info: &GcTargetInfo) -> usize {
         GcTargetType::ClassHeap
         | GcTargetType::ObjectHeap
         | GcTargetType::RuntimeEnvironment
+        | GcTargetType::ThreadManager
         | GcTargetType::Unknown => 0,
     }
 }
@@ -0,0 +1,310 @@
+// C API to embed ferrugo in programs not written in Rust. The declarations are in
+// ``include/ferrugo.h``, generated by ``cbindgen --config cbindgen.toml -o include/ferrugo.h``.
+
+use super::jvm::{Error, Jvm};
+use super::marshal::{IntoJava, JavaValue};
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::{ptr, slice};
+
+/// A Java virtual machine
+pub struct FerrugoVm {
+    jvm: Jvm,
+    // The description of the last error
+    error: Option<CString>,
+}
+
+/// The result of a function
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FerrugoStatus {
+    Ok = 0,
+    ClassNotFound,
+    MethodNotFound,
+    InvalidArguments,
+    InvalidReturnType,
+    /// An exception was thrown and not caught by Java code
+    UncaughtException,
+    /// A string passed is null or not valid UTF-8
+    InvalidString,
+    /// The VM failed unexpectedly. It must not be used any longer.
+    InternalError,
+}
+
+/// The type of a ``FerrugoValue``
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FerrugoType {
+    Void = 0,
+    Int,
+    Long,
+    Float,
+    Double,
+    Boolean,
+    /// ``java.lang.String``. A null pointer is ``null``.
+    String,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union FerrugoValueData {
+    pub int_value: i32,
+    pub long_value: i64,
+    pub float_value: f32,
+    pub double_value: f64,
+    pub boolean_value: bool,
+    pub string_value: *mut c_char,
+}
+
+/// An argument passed to or a value returned from Java. A returned string is owned by the caller
+/// and must be freed with ``ferrugo_string_free``.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct FerrugoValue {
+    pub ty: FerrugoType,
+    pub data: FerrugoValueData,
+}
+
+/// Creates a VM. The class library is searched for in ``./examples``.
+#[no_mangle]
+pub extern "C" fn ferrugo_vm_new() -> *mut FerrugoVm {
+    Box::into_raw(Box::new(FerrugoVm {
+        jvm: Jvm::builder().build(),
+        error: None,
+    }))
+}
+
+/// Destroys a VM after waiting for the Java threads to terminate.
+///
+/// # Safety
+///
+/// ``vm`` must be null or a VM returned by ``ferrugo_vm_new`` and not destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ferrugo_vm_destroy(vm: *mut FerrugoVm) {
+    if !vm.is_null() {
+        drop(Box::from_raw(vm));
+    }
+}
+
+/// Sets the directories to search for class files, separated by ``:``.
+///
+/// # Safety
+///
+/// ``vm`` must be a VM returned by ``ferrugo_vm_new`` and not destroyed yet, and ``classpath`` must
+/// be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ferrugo_vm_set_classpath(
+    vm: *mut FerrugoVm,
+    classpath: *const c_char,
+) -> FerrugoStatus {
+    let vm = &mut *vm;
+    vm.run(|jvm| {
+        let classpath = to_str(classpath)?;
+        jvm.set_classpath(
+            classpath
+                .split(':')
+                .filter(|path| !path.is_empty())
+                .collect(),
+        );
+        Ok(())
+    })
+}
+
+/// Loads a class in the classpath (e.g. ``pkg/Main``).
+///
+/// # Safety
+///
+/// ``vm`` must be a VM returned by ``ferrugo_vm_new`` and not destroyed yet, and ``class_name``
+/// must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ferrugo_vm_load_class(
+    vm: *mut FerrugoVm,
+    class_name: *const c_char,
+) -> FerrugoStatus {
+    let vm = &mut *vm;
+    vm.run(|jvm| Ok(jvm.load_class_by_name(to_str(class_name)?)?))
+}
+
+/// Loads a class file not in the classpath. The name of the class is stored in ``class_name``
+/// unless it's null.
+///
+/// # Safety
+///
+/// ``vm`` must be a VM returned by ``ferrugo_vm_new`` and not destroyed yet, and ``file_name`` must
+/// be null or point to a NUL-terminated string. ``class_name`` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ferrugo_vm_load_class_file(
+    vm: *mut FerrugoVm,
+    file_name: *const c_char,
+    class_name: *mut *mut c_char,
+) -> FerrugoStatus {
+    let vm = &mut *vm;
+    vm.run(|jvm| {
+        let name = jvm.load_class_file(to_str(file_name)?)?;
+        if !class_name.is_null() {
+            *class_name = CString::new(name).unwrap().into_raw();
+        }
+        Ok(())
+    })
+}
+
+/// Calls a static method. The returned value is stored in ``ret`` unless it's null.
+///
+/// # Safety
+///
+/// ``vm`` must be a VM returned by ``ferrugo_vm_new`` and not destroyed yet. The names and the
+/// descriptor must be null or point to NUL-terminated strings, ``args`` must point to ``args_len``
+/// values unless ``args_len`` is 0, and ``ret`` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ferrugo_vm_invoke_static(
+    vm: *mut FerrugoVm,
+    class_name: *const c_char,
+    method_name: *const c_char,
+    descriptor: *const c_char,
+    args: *const FerrugoValue,
+    args_len: usize,
+    ret: *mut FerrugoValue,
+) -> FerrugoStatus {
+    let vm = &mut *vm;
+    vm.run(|jvm| {
+        let (class_name, method_name) = (to_str(class_name)?, to_str(method_name)?);
+        let descriptor = to_str(descriptor)?;
+        let args = if args_len == 0 {
+            vec![]
+        } else {
+            slice::from_raw_parts(args, args_len)
+                .iter()
+                .map(|arg| to_java(arg))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        let args: Vec<&dyn IntoJava> = args.iter().map(|arg| &**arg).collect();
+
+        let value = if descriptor.ends_with(")Ljava/lang/String;") {
+            let string: Option<String> =
+                jvm.invoke_static(class_name, method_name, descriptor, &args)?;
+            FerrugoValue {
+                ty: FerrugoType::String,
+                data: FerrugoValueData {
+                    string_value: string.map_or(ptr::null_mut(), |string| {
+                        CString::new(string).unwrap().into_raw()
+                    }),
+                },
+            }
+        } else {
+            let value: JavaValue = jvm.invoke_static(class_name, method_name, descriptor, &args)?;
+            from_java(value, descriptor)?
+        };
+        if !ret.is_null() {
+            *ret = value;
+        } else if value.ty == FerrugoType::String {
+            ferrugo_string_free(value.data.string_value);
+        }
+        Ok(())
+    })
+}
+
+/// Returns the description of the last error, such as ``java.lang.IllegalStateException:
+/// message`` for an uncaught exception, or null. It's valid until the next call with ``vm``.
+///
+/// # Safety
+///
+/// ``vm`` must be a VM returned by ``ferrugo_vm_new`` and not destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ferrugo_vm_last_error(vm: *const FerrugoVm) -> *const c_char {
+    (&*vm)
+        .error
+        .as_ref()
+        .map_or(ptr::null(), |error| error.as_ptr())
+}
+
+/// Frees a string returned by ferrugo.
+///
+/// # Safety
+///
+/// ``string`` must be null or a string returned by ferrugo and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn ferrugo_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+enum CallError {
+    Jvm(Error),
+    InvalidString,
+}
+
+impl From<Error> for CallError {
+    fn from(error: Error) -> Self {
+        CallError::Jvm(error)
+    }
+}
+
+impl FerrugoVm {
+    // Runs ``f`` and records the error if any. Panics don't unwind into C.
+    fn run<F: FnOnce(&mut Jvm) -> Result<(), CallError>>(&mut self, f: F) -> FerrugoStatus {
+        let jvm = &mut self.jvm;
+        let (status, error) = match panic::catch_unwind(AssertUnwindSafe(|| f(jvm))) {
+            Ok(Ok(())) => (FerrugoStatus::Ok, None),
+            Ok(Err(CallError::InvalidString)) => (
+                FerrugoStatus::InvalidString,
+                Some("A string is null or not valid UTF-8".to_string()),
+            ),
+            Ok(Err(CallError::Jvm(error))) => {
+                let status = match error {
+                    Error::ClassNotFound(_) => FerrugoStatus::ClassNotFound,
+                    Error::MethodNotFound(_) => FerrugoStatus::MethodNotFound,
+                    Error::InvalidArguments(_) => FerrugoStatus::InvalidArguments,
+                    Error::InvalidReturnType(_) => FerrugoStatus::InvalidReturnType,
+                    Error::UncaughtException(_) => FerrugoStatus::UncaughtException,
+                };
+                (status, Some(error.to_string()))
+            }
+            Err(_) => (
+                FerrugoStatus::InternalError,
+                Some("The VM panicked".to_string()),
+            ),
+        };
+        self.error = error.map(|error| CString::new(error.replace('\0', " ")).unwrap());
+        status
+    }
+}
+
+unsafe fn to_str<'a>(string: *const c_char) -> Result<&'a str, CallError> {
+    if string.is_null() {
+        return Err(CallError::InvalidString);
+    }
+    CStr::from_ptr(string)
+        .to_str()
+        .map_err(|_| CallError::InvalidString)
+}
+
+unsafe fn to_java(value: &FerrugoValue) -> Result<Box<dyn IntoJava>, CallError> {
+    Ok(match value.ty {
+        FerrugoType::Void => Box::new(JavaValue::Void),
+        FerrugoType::Int => Box::new(value.data.int_value),
+        FerrugoType::Long => Box::new(value.data.long_value),
+        FerrugoType::Float => Box::new(value.data.float_value),
+        FerrugoType::Double => Box::new(value.data.double_value),
+        FerrugoType::Boolean => Box::new(value.data.boolean_value),
+        FerrugoType::String if value.data.string_value.is_null() => Box::new(None::<String>),
+        FerrugoType::String => Box::new(to_str(value.data.string_value)?.to_string()),
+    })
+}
+
+fn from_java(value: JavaValue, descriptor: &str) -> Result<FerrugoValue, CallError> {
+    let (ty, data) = match value {
+        JavaValue::Void => (FerrugoType::Void, FerrugoValueData { long_value: 0 }),
+        JavaValue::Int(i) => (FerrugoType::Int, FerrugoValueData { int_value: i }),
+        JavaValue::Long(l) => (FerrugoType::Long, FerrugoValueData { long_value: l }),
+        JavaValue::Float(f) => (FerrugoType::Float, FerrugoValueData { float_value: f }),
+        JavaValue::Double(d) => (FerrugoType::Double, FerrugoValueData { double_value: d }),
+        JavaValue::Boolean(b) => (FerrugoType::Boolean, FerrugoValueData { boolean_value: b }),
+        // Only strings can be returned to C
+        JavaValue::Object(_) => {
+            return Err(Error::InvalidReturnType(descriptor.to_string()).into());
+        }
+    };
+    Ok(FerrugoValue { ty, data })
+}
@@ -0,0 +1,292 @@
+// Safe API to embed ferrugo in Rust programs
+
+use super::super::class::{class::Class, classfile::method, classheap::ClassHeap};
+use super::super::exec::{
+    jit::JitMode,
+    native_registry::{IntoNativeFunction, NativeRegistry},
+    objectheap::ObjectHeap,
+    vm::{describe_exception, Verbose, VM},
+};
+use super::super::gc::gc::GcType;
+use super::marshal::{self, FromJava, IntoJava};
+use std::env;
+use std::fmt;
+use std::path::{Path, MAIN_SEPARATOR};
+
+/// The separator of the directories in a classpath, ``:`` (``;`` on Windows) like ``java``
+pub const PATH_SEPARATOR: &str = if cfg!(windows) { ";" } else { ":" };
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    ClassNotFound(String),
+    MethodNotFound(String),
+    InvalidArguments(String),
+    InvalidReturnType(String),
+    // The description of an exception thrown and not caught by Java code
+    UncaughtException(String),
+}
+
+pub struct JvmBuilder {
+    classpath: Vec<String>,
+    library_path: Vec<String>,
+    natives: NativeRegistry,
+    system_properties: Vec<(String, String)>,
+    gc_threshold: Option<usize>,
+    stack_size: Option<usize>,
+    jit_mode: JitMode,
+    verbose: Vec<Verbose>,
+}
+
+/// A Java virtual machine. The thread calling ``invoke_static`` runs as the main thread.
+///
+/// References returned to Rust (``JavaValue::Object``) are not roots of the GC. They must not be
+/// used after Java code runs again unless they are reachable from Java.
+///
+/// ```ignore
+/// let mut jvm = Jvm::builder().classpath("./classes").build();
+/// let n: i32 = jvm.invoke_static("pkg/Main", "compute", "(I)I", &[&10])?;
+/// ```
+pub struct Jvm {
+    vm: Box<VM>,
+    objectheap: GcType<ObjectHeap>,
+    // Directories searched after those given by users, where the class library lives
+    system_classpath: Vec<String>,
+}
+
+impl JvmBuilder {
+    /// Adds a directory to search for class files. Directories are searched in the order they
+    /// are added, followed by ``./examples`` where the class library lives.
+    pub fn classpath<S: Into<String>>(mut self, path: S) -> Self {
+        self.classpath.push(path.into());
+        self
+    }
+
+    /// Adds a directory to search for native libraries loaded by ``System.loadLibrary``. The
+    /// directories the dynamic linker searches are tried after them.
+    pub fn library_path<S: Into<String>>(mut self, path: S) -> Self {
+        self.library_path.push(path.into());
+        self
+    }
+
+    /// Registers a native method. See ``Jvm::register_native``.
+    pub fn native<Args, F: IntoNativeFunction<Args>>(mut self, signature: &str, func: F) -> Self {
+        self.natives.register(signature, func);
+        self
+    }
+
+    /// Sets a system property returned by ``System.getProperty``, like ``-Dkey=value``.
+    pub fn system_property<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.system_properties.push((key.into(), value.into()));
+        self
+    }
+
+    /// Sets the size of the heap in bytes that triggers garbage collection, which ``-Xmx`` sets.
+    /// Unlike ``-Xmx`` of ``java``, the heap may grow beyond it and ``OutOfMemoryError`` is never
+    /// thrown.
+    pub fn gc_threshold(mut self, size: usize) -> Self {
+        self.gc_threshold = Some(size);
+        self
+    }
+
+    /// Sets the size in bytes of the stack of each thread, like ``-Xss``. It limits both the
+    /// operand stack and the native stack the interpreter runs on, except for the thread calling
+    /// ``invoke_static`` whose native stack is not created by the VM.
+    pub fn stack_size(mut self, size: usize) -> Self {
+        self.stack_size = Some(size);
+        self
+    }
+
+    /// Sets when methods are compiled. See ``JitMode``.
+    pub fn jit_mode(mut self, mode: JitMode) -> Self {
+        self.jit_mode = mode;
+        self
+    }
+
+    /// Reports the events of ``kind`` to the standard output, like ``-verbose``.
+    pub fn verbose(mut self, kind: Verbose) -> Self {
+        if !self.verbose.contains(&kind) {
+            self.verbose.push(kind);
+        }
+        self
+    }
+
+    pub fn build(self) -> Jvm {
+        let objectheap = Box::into_raw(Box::new(ObjectHeap::new()));
+        if let Some(size) = self.gc_threshold {
+            unsafe { &mut *objectheap }.gc.set_threshold(size);
+        }
+        let classheap = unsafe { &mut *objectheap }.gc.alloc(ClassHeap::new());
+
+        let mut vm = Box::new(unsafe { VM::new(classheap, objectheap) });
+        let runtime_env = unsafe { &mut *vm.runtime_env };
+        let system_classpath = runtime_env.classpath.clone();
+        runtime_env.system_properties = default_system_properties(&self.classpath)
+            .into_iter()
+            .chain(self.system_properties)
+            .collect();
+        runtime_env.classpath = self
+            .classpath
+            .into_iter()
+            .chain(runtime_env.classpath.drain(..))
+            .collect();
+        runtime_env.library_path = self.library_path;
+        runtime_env.natives.extend(self.natives);
+        runtime_env.stack_size = self.stack_size;
+        runtime_env.jit_mode = self.jit_mode;
+        runtime_env.verbose = self.verbose;
+        vm.stack = runtime_env.new_operand_stack();
+        vm.load_class("java/lang/String");
+
+        Jvm {
+            vm,
+            objectheap,
+            system_classpath,
+        }
+    }
+}
+
+impl Jvm {
+    pub fn builder() -> JvmBuilder {
+        JvmBuilder {
+            classpath: vec![],
+            library_path: vec![],
+            natives: NativeRegistry::new(),
+            system_properties: vec![],
+            gc_threshold: None,
+            stack_size: None,
+            jit_mode: JitMode::Mixed,
+            verbose: vec![],
+        }
+    }
+
+    /// Registers ``func`` as the native method of ``signature`` (e.g. ``pkg/Main.add:(II)I``),
+    /// replacing a builtin one if any. ``func`` takes the VM followed by the arguments, with
+    /// ``this`` first for an instance method.
+    ///
+    /// ```ignore
+    /// jvm.register_native("pkg/Main.add:(II)I", |_: &mut VM, x: i32, y: i32| x + y);
+    /// ```
+    pub fn register_native<Args, F: IntoNativeFunction<Args>>(&mut self, signature: &str, func: F) {
+        unsafe { &mut *self.vm.runtime_env }
+            .natives
+            .register(signature, func);
+    }
+
+    /// Replaces the directories to search for class files. Classes already loaded are kept.
+    pub fn set_classpath<S: Into<String>>(&mut self, paths: Vec<S>) {
+        unsafe { &mut *self.vm.runtime_env }.classpath = paths
+            .into_iter()
+            .map(|path| path.into())
+            .chain(self.system_classpath.iter().cloned())
+            .collect();
+    }
+
+    /// Loads a class in the classpath and runs its static initializer unless it's loaded.
+    pub fn load_class_by_name(&mut self, class_name: &str) -> Result<(), Error> {
+        self.load_class(class_name)?;
+        if let Some(exception) = self.vm.exception.take() {
+            return Err(Error::UncaughtException(describe_exception(exception)));
+        }
+        Ok(())
+    }
+
+    /// Loads a class file not in the classpath and returns the name of the class.
+    pub fn load_class_file(&mut self, file_name: &str) -> Result<String, Error> {
+        if !Path::new(file_name).is_file() {
+            return Err(Error::ClassNotFound(file_name.to_string()));
+        }
+        let class = self.vm.load_class_by_file_name(file_name);
+        Ok(unsafe { &*class }.get_name().unwrap().clone())
+    }
+
+    /// Calls a static method. ``class_name`` is in the internal form (e.g. ``pkg/Main``).
+    pub fn invoke_static<R: FromJava>(
+        &mut self,
+        class_name: &str,
+        method_name: &str,
+        descriptor: &str,
+        args: &[&dyn IntoJava],
+    ) -> Result<R, Error> {
+        let class = self.load_class(class_name)?;
+        let (class, method) = unsafe { &*class }
+            .get_method(method_name, descriptor)
+            .filter(|(_, method)| method.check_access_flags(method::access_flags::ACC_PACC_STATIC))
+            .ok_or_else(|| {
+                Error::MethodNotFound(format!("{}.{}:{}", class_name, method_name, descriptor))
+            })?;
+
+        let args: Vec<_> = args.iter().map(|arg| arg.to_java(&mut self.vm)).collect();
+        let slots = marshal::to_slots(&mut self.vm, descriptor, &args)
+            .ok_or_else(|| Error::InvalidArguments(descriptor.to_string()))?;
+        let ret = self.vm.invoke_method(class, method, &slots);
+
+        if let Some(exception) = self.vm.exception.take() {
+            return Err(Error::UncaughtException(describe_exception(exception)));
+        }
+
+        R::from_java(marshal::from_slot(descriptor, ret), &self.vm)
+            .ok_or_else(|| Error::InvalidReturnType(descriptor.to_string()))
+    }
+
+    fn load_class(&mut self, class_name: &str) -> Result<GcType<Class>, Error> {
+        if unsafe { &*self.vm.classheap }
+            .get_class(class_name)
+            .is_none()
+            && self.vm.find_class_file(class_name).is_none()
+        {
+            return Err(Error::ClassNotFound(class_name.to_string()));
+        }
+        Ok(self.vm.load_class(class_name))
+    }
+}
+
+impl Drop for Jvm {
+    fn drop(&mut self) {
+        // Like the java launcher, wait for the other threads to terminate
+        self.vm.join_all_threads();
+        let mut objectheap = unsafe { Box::from_raw(self.objectheap) };
+        unsafe { objectheap.gc.free_all() };
+    }
+}
+
+// The properties every JVM provides
+fn default_system_properties(classpath: &[String]) -> Vec<(String, String)> {
+    let line_separator = if cfg!(windows) { "\r\n" } else { "\n" };
+    let user_dir = env::current_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_default();
+    vec![
+        ("java.vm.name", "Ferrugo".to_string()),
+        ("java.vm.version", env!("CARGO_PKG_VERSION").to_string()),
+        ("java.class.path", classpath.join(PATH_SEPARATOR)),
+        ("os.name", env::consts::OS.to_string()),
+        ("os.arch", env::consts::ARCH.to_string()),
+        ("file.separator", MAIN_SEPARATOR.to_string()),
+        ("path.separator", PATH_SEPARATOR.to_string()),
+        ("line.separator", line_separator.to_string()),
+        ("user.dir", user_dir),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value))
+    .collect()
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::ClassNotFound(name) => write!(f, "Could not find class '{}'", name),
+            Error::MethodNotFound(name) => write!(f, "Could not find static method '{}'", name),
+            Error::InvalidArguments(descriptor) => {
+                write!(f, "Arguments do not match the descriptor '{}'", descriptor)
+            }
+            Error::InvalidReturnType(descriptor) => {
+                write!(
+                    f,
+                    "Return type does not match the descriptor '{}'",
+                    descriptor
+                )
+            }
+            Error::UncaughtException(description) => write!(f, "{}", description),
+        }
+    }
+}
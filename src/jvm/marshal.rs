@@ -0,0 +1,408 @@
+// Conversion between Rust values and Java values
+
+use super::super::exec::{
+    frame::{AType, Array, ObjectBody},
+    vm::{d2u, f2u, u2d, u2f, VM},
+};
+use super::super::gc::gc::GcType;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JavaValue {
+    Void,
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Boolean(bool),
+    // A reference to an object or an array. 0 means null.
+    Object(u64),
+}
+
+/// Rust values that can be passed to Java. Strings and arrays are allocated in the Java heap.
+pub trait IntoJava {
+    fn to_java(&self, vm: &mut VM) -> JavaValue;
+}
+
+/// Rust values that can be made from Java values. Returns None if the types don't match.
+pub trait FromJava: Sized {
+    fn from_java(value: JavaValue, vm: &VM) -> Option<Self>;
+}
+
+/// Rust values that can be elements of Java arrays
+pub trait ArrayElement {
+    fn atype(vm: &mut VM) -> AType;
+}
+
+/// Rust values that are elements of arrays of references, which may be null
+pub trait ReferenceElement: ArrayElement {}
+
+macro_rules! impl_primitive {
+    ($ty:ty, $variant:ident, $atype:expr) => {
+        impl IntoJava for $ty {
+            fn to_java(&self, _vm: &mut VM) -> JavaValue {
+                JavaValue::$variant((*self).into())
+            }
+        }
+
+        impl FromJava for $ty {
+            fn from_java(value: JavaValue, _vm: &VM) -> Option<Self> {
+                match value {
+                    JavaValue::$variant(v) => Some(v as $ty),
+                    _ => None,
+                }
+            }
+        }
+
+        impl ArrayElement for $ty {
+            fn atype(_vm: &mut VM) -> AType {
+                $atype
+            }
+        }
+    };
+}
+
+impl_primitive!(i8, Int, AType::Byte);
+impl_primitive!(i16, Int, AType::Short);
+impl_primitive!(u16, Int, AType::Char);
+impl_primitive!(i32, Int, AType::Int);
+impl_primitive!(i64, Long, AType::Long);
+impl_primitive!(f32, Float, AType::Float);
+impl_primitive!(f64, Double, AType::Double);
+impl_primitive!(bool, Boolean, AType::Boolean);
+
+impl IntoJava for JavaValue {
+    fn to_java(&self, _vm: &mut VM) -> JavaValue {
+        *self
+    }
+}
+
+impl FromJava for JavaValue {
+    fn from_java(value: JavaValue, _vm: &VM) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl IntoJava for () {
+    fn to_java(&self, _vm: &mut VM) -> JavaValue {
+        JavaValue::Void
+    }
+}
+
+impl FromJava for () {
+    fn from_java(value: JavaValue, _vm: &VM) -> Option<Self> {
+        match value {
+            JavaValue::Void => Some(()),
+            _ => None,
+        }
+    }
+}
+
+// Raw references for native methods implemented in the VM. Null is a null pointer.
+macro_rules! impl_reference {
+    ($ty:ty) => {
+        impl IntoJava for GcType<$ty> {
+            fn to_java(&self, _vm: &mut VM) -> JavaValue {
+                JavaValue::Object(*self as u64)
+            }
+        }
+
+        impl FromJava for GcType<$ty> {
+            fn from_java(value: JavaValue, _vm: &VM) -> Option<Self> {
+                match value {
+                    JavaValue::Object(o) => Some(o as GcType<$ty>),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_reference!(ObjectBody);
+impl_reference!(Array);
+
+impl IntoJava for str {
+    fn to_java(&self, vm: &mut VM) -> JavaValue {
+        let objectheap = unsafe { &mut *vm.objectheap };
+        JavaValue::Object(unsafe {
+            objectheap.create_string_object(self.to_string(), vm.classheap)
+        })
+    }
+}
+
+impl IntoJava for String {
+    fn to_java(&self, vm: &mut VM) -> JavaValue {
+        self.as_str().to_java(vm)
+    }
+}
+
+impl FromJava for String {
+    fn from_java(value: JavaValue, vm: &VM) -> Option<Self> {
+        match value {
+            JavaValue::Object(string) if is_string(vm, string) => {
+                Some(unsafe { &*(string as GcType<ObjectBody>) }.get_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl ArrayElement for String {
+    fn atype(vm: &mut VM) -> AType {
+        AType::Class(vm.load_class("java/lang/String"))
+    }
+}
+
+impl ReferenceElement for String {}
+
+impl ArrayElement for &str {
+    fn atype(vm: &mut VM) -> AType {
+        String::atype(vm)
+    }
+}
+
+impl ReferenceElement for &str {}
+
+// None is null
+impl<T: IntoJava> IntoJava for Option<T> {
+    fn to_java(&self, vm: &mut VM) -> JavaValue {
+        match self {
+            Some(val) => val.to_java(vm),
+            None => JavaValue::Object(0),
+        }
+    }
+}
+
+impl<T: FromJava> FromJava for Option<T> {
+    fn from_java(value: JavaValue, vm: &VM) -> Option<Self> {
+        match value {
+            JavaValue::Object(0) => Some(None),
+            value => T::from_java(value, vm).map(Some),
+        }
+    }
+}
+
+// Only arrays of references can hold null
+impl<T: ReferenceElement> ArrayElement for Option<T> {
+    fn atype(vm: &mut VM) -> AType {
+        T::atype(vm)
+    }
+}
+
+impl<T: ReferenceElement> ReferenceElement for Option<T> {}
+
+impl<T: IntoJava + ArrayElement> IntoJava for [T] {
+    fn to_java(&self, vm: &mut VM) -> JavaValue {
+        let atype = T::atype(vm);
+        let array = unsafe { &mut *vm.objectheap }.create_array(atype.clone(), self.len());
+        for (i, elem) in self.iter().enumerate() {
+            let elem = elem.to_java(vm);
+            store_element(unsafe { &mut *(array as GcType<Array>) }, i, elem);
+        }
+        JavaValue::Object(array)
+    }
+}
+
+impl<T: IntoJava + ArrayElement> IntoJava for Vec<T> {
+    fn to_java(&self, vm: &mut VM) -> JavaValue {
+        self.as_slice().to_java(vm)
+    }
+}
+
+impl<T: FromJava> FromJava for Vec<T> {
+    fn from_java(value: JavaValue, vm: &VM) -> Option<Self> {
+        let array = match value {
+            JavaValue::Object(array) if unsafe { &*vm.objectheap }.gc.is_array(array) => unsafe {
+                &*(array as GcType<Array>)
+            },
+            _ => return None,
+        };
+        (0..array.get_length())
+            .map(|i| T::from_java(load_element(array, i), vm))
+            .collect()
+    }
+}
+
+impl<T: IntoJava + ?Sized> IntoJava for &T {
+    fn to_java(&self, vm: &mut VM) -> JavaValue {
+        (**self).to_java(vm)
+    }
+}
+
+fn store_element(array: &mut Array, index: usize, elem: JavaValue) {
+    let index = index as isize;
+    match (&array.atype, elem) {
+        (AType::Byte, JavaValue::Int(i)) => array.store(index, i as i8),
+        (AType::Short, JavaValue::Int(i)) | (AType::Char, JavaValue::Int(i)) => {
+            array.store(index, i as i16)
+        }
+        (AType::Int, JavaValue::Int(i)) => array.store(index, i),
+        (AType::Boolean, JavaValue::Boolean(b)) => array.store(index, b as u8),
+        (AType::Long, JavaValue::Long(l)) => array.store(index, l),
+        (AType::Float, JavaValue::Float(f)) => array.store(index, f2u(f) as u32),
+        (AType::Double, JavaValue::Double(d)) => array.store(index, d2u(d)),
+        (AType::Class(_), JavaValue::Object(o)) | (AType::Multi { .. }, JavaValue::Object(o)) => {
+            array.store(index, o)
+        }
+        (atype, elem) => panic!("{:?} can't be stored in {:?} array", elem, atype),
+    }
+}
+
+fn load_element(array: &Array, index: usize) -> JavaValue {
+    let index = index as isize;
+    match array.atype {
+        AType::Byte => JavaValue::Int(array.at::<u8>(index) as i8 as i32),
+        AType::Short => JavaValue::Int(array.at::<u16>(index) as i16 as i32),
+        AType::Char => JavaValue::Int(array.at::<u16>(index) as i32),
+        AType::Int => JavaValue::Int(array.at::<u32>(index) as i32),
+        AType::Boolean => JavaValue::Boolean(array.at::<u8>(index) != 0),
+        AType::Long => JavaValue::Long(array.at::<u64>(index) as i64),
+        AType::Float => JavaValue::Float(u2f(array.at::<u32>(index))),
+        AType::Double => JavaValue::Double(u2d(array.at::<u64>(index))),
+        _ => JavaValue::Object(array.at::<u64>(index)),
+    }
+}
+
+/// Lays out ``args`` as slots of the operand stack according to the parameters of
+/// ``descriptor``. Returns None if they don't match, including references to objects that aren't
+/// instances of the parameter types.
+pub fn to_slots(vm: &mut VM, descriptor: &str, args: &[JavaValue]) -> Option<Vec<u64>> {
+    let params = param_descriptors(descriptor);
+    if params.len() != args.len() {
+        return None;
+    }
+
+    let mut slots = vec![];
+    for (param, arg) in params.iter().zip(args) {
+        let ty = param.chars().next().unwrap();
+        match (ty, arg) {
+            ('I', JavaValue::Int(i))
+            | ('S', JavaValue::Int(i))
+            | ('B', JavaValue::Int(i))
+            | ('C', JavaValue::Int(i)) => slots.push(*i as u64),
+            ('Z', JavaValue::Boolean(b)) => slots.push(*b as u64),
+            ('F', JavaValue::Float(f)) => slots.push(f2u(*f)),
+            ('J', JavaValue::Long(l)) => slots.extend_from_slice(&[*l as u64, 0]),
+            ('D', JavaValue::Double(d)) => slots.extend_from_slice(&[d2u(*d), 0]),
+            ('L', JavaValue::Object(o)) | ('[', JavaValue::Object(o))
+                if *o == 0 || is_instance_of(vm, *o, param) =>
+            {
+                slots.push(*o)
+            }
+            _ => return None,
+        }
+    }
+    Some(slots)
+}
+
+/// Converts the value returned by a method of ``descriptor``
+pub fn from_slot(descriptor: &str, slot: u64) -> JavaValue {
+    match descriptor.chars().skip_while(|c| *c != ')').nth(1).unwrap() {
+        'V' => JavaValue::Void,
+        'Z' => JavaValue::Boolean(slot != 0),
+        'J' => JavaValue::Long(slot as i64),
+        'F' => JavaValue::Float(u2f(slot)),
+        'D' => JavaValue::Double(u2d(slot)),
+        'L' | '[' => JavaValue::Object(slot),
+        _ => JavaValue::Int(slot as i32),
+    }
+}
+
+/// Converts the arguments of a method of ``descriptor`` laid out as slots of the operand stack.
+/// ``this`` comes first if ``has_this``.
+pub fn from_slots(descriptor: &str, has_this: bool, slots: &[u64]) -> Vec<JavaValue> {
+    let mut args = vec![];
+    let mut slots = slots.iter();
+    if has_this {
+        args.push(JavaValue::Object(*slots.next().unwrap()));
+    }
+    for ty in param_types(descriptor) {
+        let slot = *slots.next().unwrap();
+        args.push(match ty {
+            'Z' => JavaValue::Boolean(slot != 0),
+            'J' => JavaValue::Long(slot as i64),
+            'F' => JavaValue::Float(u2f(slot)),
+            'D' => JavaValue::Double(u2d(slot)),
+            'L' => JavaValue::Object(slot),
+            _ => JavaValue::Int(slot as i32),
+        });
+        if ty == 'J' || ty == 'D' {
+            slots.next();
+        }
+    }
+    args
+}
+
+/// Converts a value into a slot of the operand stack
+pub fn to_slot(value: JavaValue) -> u64 {
+    match value {
+        JavaValue::Void => 0,
+        JavaValue::Int(i) => i as u64,
+        JavaValue::Long(l) => l as u64,
+        JavaValue::Float(f) => f2u(f),
+        JavaValue::Double(d) => d2u(d),
+        JavaValue::Boolean(b) => b as u64,
+        JavaValue::Object(o) => o,
+    }
+}
+
+/// Returns the number of slots the parameters of ``descriptor`` occupy
+pub fn params_slots_count(descriptor: &str) -> usize {
+    param_types(descriptor)
+        .iter()
+        .map(|ty| if *ty == 'J' || *ty == 'D' { 2 } else { 1 })
+        .sum()
+}
+
+/// Returns the types of the parameters. Any reference type (including arrays) is 'L'.
+pub fn param_types(descriptor: &str) -> Vec<char> {
+    param_descriptors(descriptor)
+        .iter()
+        .map(|param| match param.chars().next().unwrap() {
+            '[' => 'L',
+            c => c,
+        })
+        .collect()
+}
+
+/// Returns the descriptors of the parameters, like ``I`` or ``[Ljava/lang/String;``
+pub fn param_descriptors(descriptor: &str) -> Vec<&str> {
+    let mut params = vec![];
+    let bytes = descriptor.as_bytes();
+    let mut i = 1;
+    while i < bytes.len() && bytes[i] != b')' {
+        let start = i;
+        while bytes[i] == b'[' {
+            i += 1;
+        }
+        if bytes[i] == b'L' {
+            while bytes[i] != b';' {
+                i += 1;
+            }
+        }
+        i += 1;
+        params.push(&descriptor[start..i]);
+    }
+    params
+}
+
+fn is_string(vm: &VM, object: u64) -> bool {
+    unsafe { &*vm.objectheap }.gc.is_object(object)
+        && unsafe { &*(*(object as GcType<ObjectBody>)).class }
+            .get_name()
+            .is_some_and(|name| name == "java/lang/String")
+}
+
+// Returns true if ``object`` is an object or an array allocated by the VM and an instance of the
+// type ``param`` (a field descriptor)
+fn is_instance_of(vm: &mut VM, object: u64, param: &str) -> bool {
+    let gc = &unsafe { &*vm.objectheap }.gc;
+    if !gc.is_object(object) && !gc.is_array(object) {
+        return false;
+    }
+    let name = if param.starts_with('L') {
+        &param[1..param.len() - 1]
+    } else {
+        param
+    };
+    vm.is_instance_of(object, name)
+}
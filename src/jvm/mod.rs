@@ -0,0 +1,4 @@
+pub mod capi;
+#[allow(clippy::module_inception)]
+pub mod jvm;
+pub mod marshal;
@@ -1,6 +1,5 @@
 #![feature(box_patterns)]
-#![feature(repeat_generic_slice)]
-#![feature(core_intrinsics)]
+#![feature(c_variadic)]
 
 #[macro_use]
 pub mod macros;
@@ -8,8 +7,11 @@ pub mod macros;
 pub mod exec;
 pub mod class;
 pub mod gc;
+pub mod jvm;
 
 extern crate libc;
+extern crate libffi;
 extern crate llvm_sys as llvm;
 extern crate rand;
+extern crate regex;
 extern crate rustc_hash;
@@ -1,88 +1,271 @@
 #[macro_use]
 extern crate ferrugo;
 use ferrugo::class::{class::Class, classheap};
-use ferrugo::exec::objectheap::ObjectHeap;
-use ferrugo::exec::vm::VM;
+use ferrugo::exec::{jit::JitMode, vm::Verbose};
+use ferrugo::jvm::jvm::{Error, Jvm, JvmBuilder, PATH_SEPARATOR};
 
 extern crate clap;
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg, ArgMatches};
 
 extern crate ansi_term;
 use ansi_term::Colour;
 
-const VERSION_STR: &'static str = env!("CARGO_PKG_VERSION");
+use std::{env, process, thread};
+
+const VERSION_STR: &str = env!("CARGO_PKG_VERSION");
 
 fn main() {
-    let app = App::new("Ferrugo")
+    let app_matches = app().get_matches_from(normalize_java_options(env::args()));
+
+    let main_class = match app_matches.value_of("main") {
+        Some(main_class) => main_class.to_string(),
+        None => return,
+    };
+
+    if app_matches.is_present("dump") {
+        show_methods(main_class.as_str());
+        return;
+    }
+
+    let (builder, stack_size) = match jvm_builder(&app_matches) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}: {}", Colour::Red.bold().paint("error"), message);
+            process::exit(1);
+        }
+    };
+    let args: Vec<String> = app_matches
+        .values_of("args")
+        .map_or(vec![], |args| args.map(|arg| arg.to_string()).collect());
+
+    // Like the java launcher, run main on a thread of the requested stack size
+    let status = match stack_size {
+        Some(stack_size) => thread::Builder::new()
+            .name("main".to_string())
+            .stack_size(stack_size)
+            .spawn(move || launch(builder, main_class.as_str(), &args))
+            .expect("Could not create the main thread")
+            .join()
+            .unwrap_or(1),
+        None => launch(builder, main_class.as_str(), &args),
+    };
+    process::exit(status);
+}
+
+fn app() -> App<'static, 'static> {
+    App::new("Ferrugo")
         .version(VERSION_STR)
         .author("uint256_t")
         .about("A JVM Implementation written in Rust")
-        .arg(Arg::with_name("file").help("Input file name").index(1))
+        .usage("ferrugo [options] <mainclass | file.class> [args...]")
+        .setting(AppSettings::TrailingVarArg)
+        .arg(
+            Arg::with_name("main")
+                .help("Main class (e.g. com.foo.Main) or class file")
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("args")
+                .help("Arguments passed to main")
+                .index(2)
+                .multiple(true)
+                .allow_hyphen_values(true),
+        )
+        .arg(
+            Arg::with_name("classpath")
+                .help("Directories to search for class files, separated by ':' (-cp, -classpath)")
+                .long("class-path")
+                .value_name("path")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("property")
+                .help("Sets a system property")
+                .short("D")
+                .value_name("key=value")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("x")
+                .help("Sets a VM option: mx<size>, ss<size>, int, comp or mixed")
+                .short("X")
+                .value_name("option")
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .help("Reports events to the standard output (-verbose:class|gc|jit)")
+                .long("verbose")
+                .value_name("kind")
+                .possible_values(&["class", "gc", "jit"])
+                .multiple(true)
+                .number_of_values(1),
+        )
         .arg(
             Arg::with_name("dump")
                 .help("Dumps methods in the specified classfile")
                 .short("d")
                 .long("dump"),
-        );
-    let app_matches = app.clone().get_matches();
-
-    let filename = match app_matches.value_of("file") {
-        Some(filename) => filename,
-        None => return,
-    };
+        )
+}
 
-    if app_matches.is_present("dump") {
-        show_methods(filename);
-        return;
+// Rewrites the options of ``java`` that clap can't parse (e.g. ``-cp``) into long options. The
+// main class and the arguments following it are kept as they are.
+fn normalize_java_options<I: IntoIterator<Item = String>>(args: I) -> Vec<String> {
+    let mut args = args.into_iter();
+    let mut normalized: Vec<String> = args.next().into_iter().collect();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-cp" | "-classpath" | "--class-path" => {
+                normalized.push("--class-path".to_string());
+                normalized.extend(args.next());
+            }
+            "--" => {
+                normalized.push(arg);
+                normalized.extend(args);
+                break;
+            }
+            "-version" => normalized.push("--version".to_string()),
+            "-help" | "-?" => normalized.push("--help".to_string()),
+            "-verbose" => normalized.push("--verbose=class".to_string()),
+            _ if arg.starts_with("-verbose:") => {
+                normalized.push(format!("--verbose={}", &arg["-verbose:".len()..]))
+            }
+            _ if arg.starts_with('-') => normalized.push(arg),
+            _ => {
+                // Arguments like ``-Dkey=value`` after the main class are not options
+                normalized.push("--".to_string());
+                normalized.push(arg);
+                normalized.extend(args);
+                break;
+            }
+        }
     }
-
-    run_file(filename);
+    normalized
 }
 
-fn run_file(filename: &str) {
-    #[rustfmt::skip]
-    macro_rules! expect { ($expr:expr, $msg:expr) => {{ match $expr {
-        Some(some) => some,
-        None => { eprintln!("{}: {}", Colour::Red.bold().paint("error"), $msg); return }
-    } }}; }
+// Makes a JVM from the options. The stack size is also returned to create the main thread.
+fn jvm_builder(matches: &ArgMatches) -> Result<(JvmBuilder, Option<usize>), String> {
+    let mut builder = Jvm::builder();
+    let mut stack_size = None;
 
-    let objectheap_ptr = Box::into_raw(Box::new(ObjectHeap::new()));
-    let objectheap = unsafe { &mut *objectheap_ptr };
+    // The last -cp wins. ``CLASSPATH`` is used without it, and the current directory without both.
+    let classpath = matches
+        .values_of("classpath")
+        .and_then(|mut paths| paths.next_back())
+        .map(|path| path.to_string())
+        .or_else(|| env::var("CLASSPATH").ok())
+        .unwrap_or_else(|| ".".to_string());
+    for path in classpath
+        .split(PATH_SEPARATOR)
+        .filter(|path| !path.is_empty())
+    {
+        builder = builder.classpath(path);
+    }
 
-    let classheap_ptr = objectheap.gc.alloc(classheap::ClassHeap::new());
-    let classheap = unsafe { &mut *classheap_ptr };
+    for property in matches.values_of("property").into_iter().flatten() {
+        let mut key_value = property.splitn(2, '=');
+        let key = key_value.next().unwrap();
+        if key.is_empty() {
+            return Err(format!("Invalid system property: -D{}", property));
+        }
+        builder = builder.system_property(key, key_value.next().unwrap_or(""));
+    }
 
-    let mut vm = VM::new(classheap, objectheap);
-    vm.load_class("java/lang/String");
+    for option in matches.values_of("x").into_iter().flatten() {
+        builder = match option {
+            "int" => builder.jit_mode(JitMode::Interpreted),
+            "comp" => builder.jit_mode(JitMode::Compiled),
+            "mixed" => builder.jit_mode(JitMode::Mixed),
+            // The heap is not limited. It only triggers garbage collection.
+            _ if option.starts_with("mx") => builder.gc_threshold(
+                parse_size(&option[2..])
+                    .ok_or_else(|| format!("Invalid maximum heap size: -X{}", option))?,
+            ),
+            _ if option.starts_with("ss") => {
+                let size = parse_size(&option[2..])
+                    .ok_or_else(|| format!("Invalid thread stack size: -X{}", option))?;
+                stack_size = Some(size);
+                builder.stack_size(size)
+            }
+            // The heap grows as needed
+            _ if option.starts_with("ms") && parse_size(&option[2..]).is_some() => builder,
+            _ => return Err(format!("Unrecognized option: -X{}", option)),
+        };
+    }
 
-    let class_ptr = vm.load_class_by_file_name(filename);
+    for kind in matches.values_of("verbose").into_iter().flatten() {
+        builder = builder.verbose(match kind {
+            "class" => Verbose::Class,
+            "gc" => Verbose::Gc,
+            _ => Verbose::Jit,
+        });
+    }
 
-    let (class, method) = expect!(
-        unsafe { &*class_ptr }.get_method("main", "([Ljava/lang/String;)V"),
-        "Couldn't find method 'main(String[])'"
-    );
+    Ok((builder, stack_size))
+}
 
-    let object = objectheap.create_object(class_ptr);
+// Parses a size like ``512m`` in bytes. ``k``, ``m`` and ``g`` are accepted as suffixes.
+fn parse_size(size: &str) -> Option<usize> {
+    let (digits, unit) = match size.chars().last()? {
+        'k' | 'K' => (&size[..size.len() - 1], 1 << 10),
+        'm' | 'M' => (&size[..size.len() - 1], 1 << 20),
+        'g' | 'G' => (&size[..size.len() - 1], 1 << 30),
+        _ => (size, 1),
+    };
+    digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(unit))
+        .filter(|&n| n > 0)
+}
 
-    vm.stack[0] = object;
-    vm.frame_stack[0].class = Some(class);
-    vm.frame_stack[0].method_info = method;
-    vm.frame_stack[0].sp = vm.frame_stack[0]
-        .method_info
-        .code
-        .as_ref()
-        .unwrap()
-        .max_locals as usize;
+/// Runs ``main(String[])`` of ``main_class``, a binary name (e.g. ``com.foo.Main``) looked up in
+/// the classpath or a class file. Returns the exit status.
+fn launch(builder: JvmBuilder, main_class: &str, args: &[String]) -> i32 {
+    let mut jvm = builder.build();
+
+    let class_name = if main_class.ends_with(".class") {
+        jvm.load_class_file(main_class)
+    } else {
+        let class_name = main_class.replace(".", "/");
+        jvm.load_class_by_name(class_name.as_str())
+            .map(|_| class_name)
+    };
+    let class_name = match class_name {
+        Ok(class_name) => class_name,
+        Err(Error::UncaughtException(description)) => {
+            eprintln!("Exception in thread \"main\" {}", description);
+            return 1;
+        }
+        Err(e) => {
+            eprintln!("{}: {}", Colour::Red.bold().paint("error"), e);
+            return 1;
+        }
+    };
 
     dprintln!("---- exec output begin ----");
-    vm.run();
+    let status = match jvm.invoke_static::<()>(
+        class_name.as_str(),
+        "main",
+        "([Ljava/lang/String;)V",
+        &[&args],
+    ) {
+        Ok(_) => 0,
+        Err(Error::UncaughtException(description)) => {
+            eprintln!("Exception in thread \"main\" {}", description);
+            1
+        }
+        Err(e) => {
+            eprintln!("{}: {}", Colour::Red.bold().paint("error"), e);
+            1
+        }
+    };
     dprintln!("---- exec output end ------");
-
-    dprintln!("stack trace: {:?}", &vm.stack[0..128]);
-
-    unsafe {
-        Box::from_raw(objectheap_ptr);
-    }
+    status
 }
 
 fn show_methods(filename: &str) {
@@ -107,16 +290,107 @@ fn show_methods(filename: &str) {
         method.code.as_ref().unwrap().dump_bytecode();
     }
 
-    unsafe { Box::from_raw(classheap_ptr) };
+    drop(unsafe { Box::from_raw(classheap_ptr) });
 }
 
 #[test]
-fn run_example() {
-    run_file("examples/Hello.class");
-    run_file("examples/MillerRabin.class");
-    run_file("examples/BigInt.class");
-    run_file("examples/EratosthenesSieve.class");
-    run_file("examples/GameOfLife.class");
+fn pass_args_to_main() {
+    assert_eq!(
+        launch(
+            Jvm::builder(),
+            "examples/Args.class",
+            &["foo".to_string(), "-x".to_string()],
+        ),
+        0
+    );
+
+    let mut jvm = Jvm::builder().build();
+    assert_eq!(
+        jvm.invoke_static::<()>(
+            "Args",
+            "main",
+            "([Ljava/lang/String;)V",
+            &[&vec!["foo", "-x"]]
+        ),
+        Ok(())
+    );
+    assert_eq!(jvm.invoke_static("Args", "argCount", "()I", &[]), Ok(2));
+    assert_eq!(
+        jvm.invoke_static("Args", "lastArg", "()Ljava/lang/String;", &[]),
+        Ok("-x".to_string())
+    );
+}
+
+#[test]
+fn java_launcher() {
+    let command_line = |args: &[&str]| {
+        normalize_java_options(["ferrugo"].iter().chain(args).map(|arg| arg.to_string()))
+    };
+    assert_eq!(
+        command_line(&["-cp", "a:b", "-verbose:gc", "-Xint", "Main", "-cp", "x"]),
+        command_line(&[
+            "--class-path",
+            "a:b",
+            "--verbose=gc",
+            "-Xint",
+            "--",
+            "Main",
+            "-cp",
+            "x"
+        ])
+    );
+
+    let matches = app().get_matches_from(command_line(&[
+        "-classpath",
+        "examples",
+        "-Dgreeting=Hi",
+        "-Dempty",
+        "-Xmx64m",
+        "-Xss4M",
+        "-Xint",
+        "-verbose",
+        "-verbose:jit",
+        "SystemProperties",
+        "-Dnot=option",
+    ]));
+    assert_eq!(matches.value_of("main"), Some("SystemProperties"));
+    assert_eq!(
+        matches.values_of("args").unwrap().collect::<Vec<_>>(),
+        vec!["-Dnot=option"]
+    );
+    let (builder, stack_size) = jvm_builder(&matches).unwrap();
+    assert_eq!(stack_size, Some(4 * 1024 * 1024));
+    assert_eq!(launch(builder, "SystemProperties", &[]), 0);
+
+    let (builder, _) = jvm_builder(&matches).unwrap();
+    let mut jvm = builder.build();
+    let mut get = |key: &str| {
+        jvm.invoke_static::<Option<String>>(
+            "SystemProperties",
+            "get",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            &[&key],
+        )
+    };
+    assert_eq!(get("greeting"), Ok(Some("Hi".to_string())));
+    assert_eq!(get("empty"), Ok(Some("".to_string())));
+    assert_eq!(get("java.class.path"), Ok(Some("examples".to_string())));
+    assert_eq!(get("no.such.property"), Ok(None));
+
+    let matches = app().get_matches_from(command_line(&["-Xcomp", "-Xms16m", "Main"]));
+    assert!(jvm_builder(&matches).is_ok());
+    let matches = app().get_matches_from(command_line(&["-Xfoo", "Main"]));
+    assert_eq!(
+        jvm_builder(&matches).err(),
+        Some("Unrecognized option: -Xfoo".to_string())
+    );
+    assert_eq!(launch(Jvm::builder(), "no.such.Main", &[]), 1);
+
+    assert_eq!(parse_size("512k"), Some(512 * 1024));
+    assert_eq!(parse_size("2g"), Some(2 * 1024 * 1024 * 1024));
+    assert_eq!(parse_size("4096"), Some(4096));
+    assert_eq!(parse_size("m"), None);
+    assert_eq!(parse_size("0"), None);
 }
 
 #[test]
@@ -131,3 +405,454 @@ fn read_classfiles() {
             .unwrap();
     }
 }
+
+#[test]
+fn floating_point_to_string() {
+    use ferrugo::exec::number::{double_to_string, float_to_string};
+    assert_eq!(double_to_string(1.0), "1.0");
+    assert_eq!(double_to_string(-0.0), "-0.0");
+    assert_eq!(double_to_string(0.001), "0.001");
+    assert_eq!(double_to_string(1.0e7), "1.0E7");
+    assert_eq!(double_to_string(1.0e-5), "1.0E-5");
+    assert_eq!(double_to_string(0.1 + 0.2), "0.30000000000000004");
+    assert_eq!(double_to_string(1.0e23), "9.999999999999999E22");
+    assert_eq!(
+        double_to_string(f64::MIN_POSITIVE),
+        "2.2250738585072014E-308"
+    );
+    assert_eq!(double_to_string(f64::NAN), "NaN");
+    assert_eq!(double_to_string(f64::NEG_INFINITY), "-Infinity");
+    assert_eq!(float_to_string(0.1), "0.1");
+    assert_eq!(float_to_string(1.0 / 3.0), "0.33333334");
+    assert_eq!(float_to_string(f32::MAX), "3.4028235E38");
+    assert_eq!(float_to_string(1.0e-45), "1.4E-45");
+}
+
+#[test]
+fn parse_floating_point() {
+    use ferrugo::exec::number::{parse_double, parse_float};
+    assert_eq!(parse_double("3.5"), Ok(3.5));
+    assert_eq!(parse_double(" -1e3d\n"), Ok(-1000.0));
+    assert_eq!(parse_double(".5"), Ok(0.5));
+    assert_eq!(parse_double("1e400"), Ok(f64::INFINITY));
+    assert_eq!(parse_double("-Infinity"), Ok(f64::NEG_INFINITY));
+    assert!(parse_double("NaN").unwrap().is_nan());
+    assert_eq!(parse_float("0.1f"), Ok(0.1));
+    assert_eq!(parse_float("3.4028236e38"), Ok(f32::INFINITY));
+    assert_eq!(parse_double("0x1p4"), Ok(16.0));
+    assert_eq!(parse_double("-0X1.8P1d"), Ok(-3.0));
+    assert_eq!(parse_double("0x.8p-1"), Ok(0.25));
+    assert_eq!(parse_double("0x1p-1074"), Ok(5e-324));
+    assert_eq!(parse_double("0x1p-1075"), Ok(0.0));
+    assert_eq!(parse_double("0x1.8p-1074"), Ok(1e-323));
+    assert_eq!(parse_double("0x1p1024"), Ok(f64::INFINITY));
+    assert_eq!(parse_double("0x1.fffffffffffff7ffp1023"), Ok(f64::MAX));
+    assert_eq!(parse_double("0x1.00000000000008p0"), Ok(1.0));
+    assert_eq!(
+        parse_double("0x1.000000000000080001p0"),
+        Ok(1.0 + f64::EPSILON)
+    );
+    assert_eq!(parse_float("0x1.000001p0f"), Ok(1.0));
+    assert_eq!(parse_float("0x1.0000011p0"), Ok(1.0 + f32::EPSILON));
+    assert_eq!(parse_float("0x1p-149"), Ok(1e-45));
+    assert_eq!(parse_float("0x1.fffffep127"), Ok(f32::MAX));
+    assert_eq!(parse_float("0x1.ffffffp127"), Ok(f32::INFINITY));
+    assert_eq!(parse_double(""), Err("empty String".to_string()));
+    assert_eq!(parse_double("1.2.3"), Err("multiple points".to_string()));
+    assert_eq!(
+        parse_double("infinity"),
+        Err("For input string: \"infinity\"".to_string())
+    );
+    assert_eq!(
+        parse_double("1e"),
+        Err("For input string: \"1e\"".to_string())
+    );
+    assert_eq!(
+        parse_double("0x1.8"),
+        Err("For input string: \"0x1.8\"".to_string())
+    );
+    assert_eq!(
+        parse_float("0xp1"),
+        Err("For input string: \"0xp1\"".to_string())
+    );
+}
+
+#[test]
+fn invoke_static() {
+    let mut jvm = Jvm::builder().build();
+    assert_eq!(jvm.invoke_static("Hello", "fibo", "(I)I", &[&10]), Ok(55));
+    assert_eq!(
+        jvm.invoke_static("Hello", "is_prime", "(I)Z", &[&97]),
+        Ok(true)
+    );
+    assert_eq!(
+        jvm.invoke_static::<i32>("Hello", "fibo", "(I)I", &[&1.0]),
+        Err(Error::InvalidArguments("(I)I".to_string()))
+    );
+    assert_eq!(
+        jvm.invoke_static::<bool>("Hello", "fibo", "(I)I", &[&10]),
+        Err(Error::InvalidReturnType("(I)I".to_string()))
+    );
+    assert_eq!(
+        jvm.invoke_static("Longs", "factorial", "(I)J", &[&20]),
+        Ok(2432902008176640000i64)
+    );
+    assert_eq!(
+        jvm.invoke_static("Longs", "gcd", "(JJ)J", &[&(1i64 << 40), &(6i64 << 32)]),
+        Ok(2i64 << 32)
+    );
+    assert_eq!(
+        jvm.invoke_static("Floats", "lerp", "(FFF)F", &[&1.5f32, &4.25f32, &0.5f32]),
+        Ok(2.875f32)
+    );
+    assert_eq!(
+        jvm.invoke_static("Floats", "iterations", "(FF)I", &[&-0.75f32, &0.25f32]),
+        Ok(13)
+    );
+    assert_eq!(
+        jvm.invoke_static("Switches", "daysInMonth", "(II)I", &[&2, &2000]),
+        Ok(29)
+    );
+    assert_eq!(
+        jvm.invoke_static("Switches", "score", "(I)I", &[&100000]),
+        Ok(100)
+    );
+    for regex in &["a(?=b)", "(?<=a)b", "(?>a)", "(a)\\1", "a++", "a{1,2}+"] {
+        assert_eq!(
+            jvm.invoke_static(
+                "Strings",
+                "splitError",
+                "(Ljava/lang/String;)Ljava/lang/String;",
+                &[regex]
+            ),
+            Ok("java.util.regex.PatternSyntaxException".to_string())
+        );
+    }
+    assert_eq!(
+        jvm.invoke_static::<()>("NoSuchClass", "main", "()V", &[]),
+        Err(Error::ClassNotFound("NoSuchClass".to_string()))
+    );
+
+    assert_eq!(
+        jvm.invoke_static("Embedding", "sum", "([I)I", &[&vec![1, 2, 3]]),
+        Ok(6)
+    );
+    assert_eq!(
+        jvm.invoke_static("Embedding", "range", "(I)[I", &[&3]),
+        Ok(vec![0, 1, 2])
+    );
+    assert_eq!(
+        jvm.invoke_static(
+            "Embedding",
+            "greet",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            &[&"ferrugo"]
+        ),
+        Ok("Hello, ferrugo".to_string())
+    );
+    assert_eq!(
+        jvm.invoke_static(
+            "Embedding",
+            "first",
+            "([Ljava/lang/String;)Ljava/lang/String;",
+            &[&vec!["a", "b"]]
+        ),
+        Ok(Some("a".to_string()))
+    );
+    assert_eq!(
+        jvm.invoke_static::<Option<String>>(
+            "Embedding",
+            "first",
+            "([Ljava/lang/String;)Ljava/lang/String;",
+            &[&Vec::<String>::new()]
+        ),
+        Ok(None)
+    );
+    assert_eq!(
+        jvm.invoke_static(
+            "Embedding",
+            "isNull",
+            "(Ljava/lang/String;)Z",
+            &[&None::<String>]
+        ),
+        Ok(true)
+    );
+    assert_eq!(
+        jvm.invoke_static::<String>("Embedding", "range", "(I)[I", &[&3]),
+        Err(Error::InvalidReturnType("(I)[I".to_string()))
+    );
+    assert_eq!(
+        jvm.invoke_static::<Vec<i32>>(
+            "Embedding",
+            "greet",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            &[&"ferrugo"]
+        ),
+        Err(Error::InvalidReturnType(
+            "(Ljava/lang/String;)Ljava/lang/String;".to_string()
+        ))
+    );
+    assert_eq!(
+        jvm.invoke_static::<i32>("Embedding", "sum", "([I)I", &[&"1, 2"]),
+        Err(Error::InvalidArguments("([I)I".to_string()))
+    );
+    assert_eq!(
+        jvm.invoke_static::<i32>("Embedding", "sum", "([I)I", &[&vec![1i64, 2]]),
+        Err(Error::InvalidArguments("([I)I".to_string()))
+    );
+}
+
+#[test]
+fn register_native() {
+    use ferrugo::exec::vm::VM;
+    let mut jvm = Jvm::builder()
+        .native("Embedding.add:(II)I", |_: &mut VM, x: i32, y: i32| x + y)
+        .build();
+    jvm.register_native(
+        "Embedding.decorate:(Ljava/lang/String;)Ljava/lang/String;",
+        |_: &mut VM, s: String| format!("*{}*", s),
+    );
+    assert_eq!(
+        jvm.invoke_static("Embedding", "addTwice", "(II)I", &[&1, &2]),
+        Ok(5)
+    );
+    assert_eq!(
+        jvm.invoke_static(
+            "Embedding",
+            "shout",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            &[&"hi"]
+        ),
+        Ok("*hi*!".to_string())
+    );
+
+    // Arguments the function can't take throw exceptions
+    assert_eq!(
+        jvm.invoke_static(
+            "Embedding",
+            "tryDecorate",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            &[&None::<String>]
+        ),
+        Ok("NullPointerException".to_string())
+    );
+    jvm.register_native("Embedding.add:(II)I", |_: &mut VM, x: f64, y: f64| {
+        (x + y) as i32
+    });
+    match jvm.invoke_static::<i32>("Embedding", "addTwice", "(II)I", &[&1, &2]) {
+        Err(Error::UncaughtException(description)) => {
+            assert!(description.contains("java.lang.IllegalArgumentException"))
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn jit_calls_replaced_native() {
+    use ferrugo::exec::{jit::JitMode, vm::VM};
+    let mut jvm = Jvm::builder()
+        .native("Embedding.add:(II)I", |_: &mut VM, x: i32, y: i32| x + y)
+        .jit_mode(JitMode::Compiled)
+        .build();
+    assert_eq!(
+        jvm.invoke_static("Embedding", "addTwiceFromJava", "(II)I", &[&1, &2]),
+        Ok(5)
+    );
+
+    // The compiled ``addTwice`` calls the function registered last
+    jvm.register_native("Embedding.add:(II)I", |_: &mut VM, x: i32, y: i32| x * y);
+    assert_eq!(
+        jvm.invoke_static("Embedding", "addTwiceFromJava", "(II)I", &[&1, &2]),
+        Ok(4)
+    );
+}
+
+// The directory containing ``jni.h``: ``$JAVA_HOME/include`` or that of the JDK providing
+// ``javac`` in ``PATH``
+#[cfg(test)]
+fn find_jni_include_dir() -> Option<std::path::PathBuf> {
+    use std::{env, fs, path::PathBuf};
+    let java_home = env::var_os("JAVA_HOME").map(PathBuf::from).or_else(|| {
+        let javac = env::split_paths(&env::var_os("PATH")?)
+            .map(|dir| dir.join("javac"))
+            .find(|javac| javac.is_file())?;
+        Some(
+            fs::canonicalize(javac)
+                .ok()?
+                .parent()?
+                .parent()?
+                .to_path_buf(),
+        )
+    })?;
+    Some(java_home.join("include")).filter(|include| include.join("jni.h").is_file())
+}
+
+#[test]
+fn load_jni_library() {
+    use std::{env, process::Command};
+
+    let include = match find_jni_include_dir() {
+        Some(include) => include,
+        None => return eprintln!("skipped: jni.h not found"),
+    };
+    let out_dir = env::temp_dir().join(format!("ferrugo-jni-{}", std::process::id()));
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let status = Command::new("cc")
+        .args(["-shared", "-fPIC", "-o"])
+        .arg(out_dir.join("libjniexample.so"))
+        .arg(format!("-I{}", include.display()))
+        .arg(format!("-I{}", include.join(env::consts::OS).display()))
+        .arg("examples/jni/jniexample.c")
+        .status();
+    match status {
+        Ok(status) if status.success() => {}
+        _ => return eprintln!("skipped: could not build examples/jni/jniexample.c"),
+    }
+
+    let mut jvm = Jvm::builder()
+        .library_path(out_dir.to_str().unwrap())
+        .build();
+    assert_eq!(
+        jvm.invoke_static("JniExample", "add", "(II)I", &[&1, &2]),
+        Ok(3)
+    );
+    assert_eq!(
+        jvm.invoke_static("JniExample", "hypot", "(DD)D", &[&3.0, &4.0]),
+        Ok(5.0)
+    );
+    assert_eq!(
+        jvm.invoke_static(
+            "JniExample",
+            "greet",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            &[&"ferrugo"]
+        ),
+        Ok("Hello, ferrugo (7)".to_string())
+    );
+    assert_eq!(
+        jvm.invoke_static("JniExample", "sumOfSquares", "(I)I", &[&4]),
+        Ok(14)
+    );
+    assert_eq!(
+        jvm.invoke_static("JniExample", "applyTwice", "(I)I", &[&5]),
+        Ok(20)
+    );
+    assert_eq!(
+        jvm.invoke_static("JniExample", "count", "(I)I", &[&3]),
+        Ok(3)
+    );
+    assert_eq!(
+        jvm.invoke_static("JniExample", "triple", "(I)I", &[&7]),
+        Ok(21)
+    );
+    assert_eq!(
+        jvm.invoke_static(
+            "JniExample",
+            "catchFailure",
+            "(Ljava/lang/String;)Ljava/lang/String;",
+            &[&"bad"]
+        ),
+        Ok("bad".to_string())
+    );
+    assert_eq!(
+        jvm.invoke_static("JniExample", "isMissing", "()Z", &[]),
+        Ok(true)
+    );
+    assert_eq!(
+        jvm.invoke_static("JniExample", "mixAll", "()D", &[]),
+        Ok(136.0)
+    );
+
+    std::fs::remove_dir_all(&out_dir).unwrap();
+}
+
+#[test]
+fn c_api() {
+    use ferrugo::jvm::capi::*;
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+
+    let cstr = |s: &str| CString::new(s).unwrap();
+    unsafe {
+        let vm = ferrugo_vm_new();
+        assert_eq!(
+            ferrugo_vm_set_classpath(vm, cstr("./examples").as_ptr()),
+            FerrugoStatus::Ok
+        );
+        assert_eq!(
+            ferrugo_vm_load_class(vm, cstr("Embedding").as_ptr()),
+            FerrugoStatus::Ok
+        );
+
+        let mut ret = FerrugoValue {
+            ty: FerrugoType::Void,
+            data: FerrugoValueData { long_value: 0 },
+        };
+        let args = [FerrugoValue {
+            ty: FerrugoType::Int,
+            data: FerrugoValueData { int_value: 10 },
+        }];
+        assert_eq!(
+            ferrugo_vm_invoke_static(
+                vm,
+                cstr("Hello").as_ptr(),
+                cstr("fibo").as_ptr(),
+                cstr("(I)I").as_ptr(),
+                args.as_ptr(),
+                args.len(),
+                &mut ret
+            ),
+            FerrugoStatus::Ok
+        );
+        assert_eq!((ret.ty, ret.data.int_value), (FerrugoType::Int, 55));
+
+        let name = cstr("ferrugo");
+        let args = [FerrugoValue {
+            ty: FerrugoType::String,
+            data: FerrugoValueData {
+                string_value: name.as_ptr() as *mut _,
+            },
+        }];
+        assert_eq!(
+            ferrugo_vm_invoke_static(
+                vm,
+                cstr("Embedding").as_ptr(),
+                cstr("greet").as_ptr(),
+                cstr("(Ljava/lang/String;)Ljava/lang/String;").as_ptr(),
+                args.as_ptr(),
+                args.len(),
+                &mut ret
+            ),
+            FerrugoStatus::Ok
+        );
+        assert_eq!(ret.ty, FerrugoType::String);
+        assert_eq!(
+            CStr::from_ptr(ret.data.string_value).to_str(),
+            Ok("Hello, ferrugo")
+        );
+        ferrugo_string_free(ret.data.string_value);
+
+        assert_eq!(
+            ferrugo_vm_invoke_static(
+                vm,
+                cstr("Hello").as_ptr(),
+                cstr("fibo").as_ptr(),
+                cstr("(I)I").as_ptr(),
+                args.as_ptr(),
+                args.len(),
+                ptr::null_mut()
+            ),
+            FerrugoStatus::InvalidArguments
+        );
+        assert_eq!(
+            ferrugo_vm_load_class(vm, cstr("NoSuchClass").as_ptr()),
+            FerrugoStatus::ClassNotFound
+        );
+        assert_eq!(
+            CStr::from_ptr(ferrugo_vm_last_error(vm)).to_str(),
+            Ok("Could not find class 'NoSuchClass'")
+        );
+
+        ferrugo_vm_destroy(vm);
+    }
+}
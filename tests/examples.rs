@@ -0,0 +1,77 @@
+// Runs the examples with the ferrugo binary and compares what they print with the outputs in
+// ``examples/expected``. They're the same as java's, except for Atomics, which java doesn't let use
+// ``sun.misc.Unsafe``, Reflection and Annotations, whose members are in another order, and Strings,
+// which java prints in the platform encoding.
+
+use std::fs;
+use std::process::Command;
+
+// Runs ``class`` interpreted and returns its standard output
+fn run(class: &str) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_ferrugo"))
+        .args(["-Xint", "-cp", "examples", class])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{} exited with {}: {}",
+        class,
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output.stdout
+}
+
+#[test]
+fn run_example() {
+    for class in &[
+        "Hello",
+        "MillerRabin",
+        "BigInt",
+        "EratosthenesSieve",
+        "Threads",
+        "Atomics",
+        "Longs",
+        "Floats",
+        "Switches",
+        "Casts",
+        "Opcodes",
+        "Subroutines",
+        "Lambdas",
+        "Concat",
+        "Handles",
+        "Reflection",
+        "Annotations",
+        "Objects",
+        "Strings",
+        "StringBuilders",
+        "Doubles",
+        "Boxing",
+    ] {
+        let file_name = format!("examples/expected/{}.txt", class);
+        let expected = fs::read(&file_name).unwrap();
+        let output = run(class);
+        if output != expected {
+            let (output, expected) = (
+                String::from_utf8_lossy(&output),
+                String::from_utf8_lossy(&expected),
+            );
+            let line = output
+                .lines()
+                .zip(expected.lines())
+                .position(|(output, expected)| output != expected)
+                .unwrap_or_else(|| output.lines().count().min(expected.lines().count()));
+            panic!(
+                "the output of {} differs from {} at line {}:\n{:?}\nexpected:\n{:?}",
+                class,
+                file_name,
+                line + 1,
+                output.lines().nth(line),
+                expected.lines().nth(line)
+            );
+        }
+    }
+
+    // It prints a random grid
+    run("GameOfLife");
+}